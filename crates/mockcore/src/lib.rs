@@ -29,13 +29,14 @@ use {
   },
   jsonrpc_core::{IoHandler, Value},
   jsonrpc_http_server::{CloseHandle, ServerBuilder},
+  rest::Rest,
   serde::{Deserialize, Serialize},
   server::Server,
   state::State,
   std::{
     collections::{BTreeMap, BTreeSet, HashMap},
     fs,
-    path::PathBuf,
+    path::{Path, PathBuf},
     sync::{Arc, Mutex, MutexGuard},
     thread,
     time::Duration,
@@ -44,6 +45,7 @@ use {
 };
 
 mod api;
+mod rest;
 mod server;
 mod state;
 
@@ -88,6 +90,7 @@ impl Builder {
     io.extend_with(server.to_delegate());
 
     let rpc_server = ServerBuilder::new(io)
+      .request_middleware(Rest::new(state.clone()))
       .threads(1)
       .start_http(&"127.0.0.1:0".parse().unwrap())
       .unwrap();
@@ -211,7 +214,7 @@ impl Handle {
     .unwrap()
   }
 
-  pub fn state(&self) -> MutexGuard<State> {
+  pub fn state(&self) -> MutexGuard<'_, State> {
     self.state.lock().unwrap()
   }
 
@@ -279,6 +282,10 @@ impl Handle {
     self.state().mempool().to_vec()
   }
 
+  pub fn evict(&self, txid: Txid) {
+    self.state().evict(txid);
+  }
+
   pub fn descriptors(&self) -> Vec<String> {
     self.state().descriptors.clone()
   }
@@ -312,6 +319,47 @@ impl Handle {
   pub fn get_locked(&self) -> BTreeSet<OutPoint> {
     self.state().get_locked()
   }
+
+  pub fn write_block_files(
+    &self,
+    blocks_dir: &Path,
+    blocks_per_file: usize,
+    xor_key: Option<[u8; 8]>,
+  ) {
+    let state = self.state();
+
+    fs::create_dir_all(blocks_dir).unwrap();
+
+    if let Some(xor_key) = xor_key {
+      fs::write(blocks_dir.join("xor.dat"), xor_key).unwrap();
+    }
+
+    // blocks are written in block hash order, which, like the order in which
+    // bitcoind receives blocks, does not match block height order
+    let blocks = state.blocks.values().collect::<Vec<&Block>>();
+
+    for (i, chunk) in blocks.chunks(blocks_per_file).enumerate() {
+      let mut file = Vec::new();
+
+      for block in chunk {
+        let block = serialize(*block);
+        file.extend_from_slice(&state.network.magic().to_bytes());
+        file.extend_from_slice(&u32::try_from(block.len()).unwrap().to_le_bytes());
+        file.extend_from_slice(&block);
+      }
+
+      // bitcoind preallocates block files, leaving zeroes after the last block
+      file.extend_from_slice(&[0; 64]);
+
+      if let Some(xor_key) = xor_key {
+        for (i, byte) in file.iter_mut().enumerate() {
+          *byte ^= xor_key[i % xor_key.len()];
+        }
+      }
+
+      fs::write(blocks_dir.join(format!("blk{i:05}.dat")), file).unwrap();
+    }
+  }
 }
 
 impl Drop for Handle {
@@ -0,0 +1,104 @@
+use {
+  super::*,
+  jsonrpc_http_server::{
+    hyper::{Body, Request, Response, StatusCode},
+    RequestMiddleware, RequestMiddlewareAction,
+  },
+  std::str::FromStr,
+};
+
+pub(crate) struct Rest {
+  state: Arc<Mutex<State>>,
+}
+
+impl Rest {
+  pub(crate) fn new(state: Arc<Mutex<State>>) -> Self {
+    Self { state }
+  }
+
+  fn get(&self, path: &str) -> Option<Vec<u8>> {
+    let state = self.state.lock().unwrap();
+
+    if path == "chaininfo.json" {
+      return Some(
+        serde_json::to_vec(&serde_json::json!({
+          "bestblockhash": state.hashes.last().unwrap(),
+          "blocks": state.hashes.len() - 1,
+          "headers": state.hashes.len() - 1,
+        }))
+        .unwrap(),
+      );
+    }
+
+    if let Some(height) = path
+      .strip_prefix("blockhashbyheight/")
+      .and_then(|path| path.strip_suffix(".bin"))
+    {
+      return state
+        .hashes
+        .get(height.parse::<usize>().ok()?)
+        .map(serialize);
+    }
+
+    if let Some(hash) = path
+      .strip_prefix("block/")
+      .and_then(|path| path.strip_suffix(".bin"))
+    {
+      return state
+        .blocks
+        .get(&BlockHash::from_str(hash).ok()?)
+        .map(serialize);
+    }
+
+    if let Some(hash) = path
+      .strip_prefix("headers/")
+      .and_then(|path| path.strip_suffix(".bin?count=1"))
+    {
+      return state
+        .blocks
+        .get(&BlockHash::from_str(hash).ok()?)
+        .map(|block| serialize(&block.header));
+    }
+
+    if let Some(txid) = path
+      .strip_prefix("tx/")
+      .and_then(|path| path.strip_suffix(".bin"))
+    {
+      return state
+        .transactions
+        .get(&Txid::from_str(txid).ok()?)
+        .map(serialize);
+    }
+
+    None
+  }
+}
+
+impl RequestMiddleware for Rest {
+  fn on_request(&self, request: Request<Body>) -> RequestMiddlewareAction {
+    let Some(path) = request
+      .uri()
+      .path_and_query()
+      .and_then(|path| path.as_str().strip_prefix("/rest/"))
+    else {
+      return RequestMiddlewareAction::Proceed {
+        should_continue_on_invalid_cors: false,
+        request,
+      };
+    };
+
+    let response = match self.get(path) {
+      Some(body) => Response::new(Body::from(body)),
+      None => {
+        let mut response = Response::new(Body::empty());
+        *response.status_mut() = StatusCode::NOT_FOUND;
+        response
+      }
+    };
+
+    RequestMiddlewareAction::Respond {
+      should_validate_hosts: false,
+      response: Box::pin(async { Ok(response) }),
+    }
+  }
+}
@@ -265,6 +265,12 @@ impl State {
     &self.mempool
   }
 
+  pub(crate) fn evict(&mut self, txid: Txid) {
+    let len = self.mempool.len();
+    self.mempool.retain(|tx| tx.txid() != txid);
+    assert_eq!(self.mempool.len() + 1, len, "transaction not in mempool");
+  }
+
   pub(crate) fn get_confirmations(&self, tx: &Transaction) -> i32 {
     for (confirmations, hash) in self.hashes.iter().rev().enumerate() {
       if self.blocks.get(hash).unwrap().txdata.contains(tx) {
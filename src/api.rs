@@ -11,6 +11,45 @@ pub use crate::{
   },
 };
 
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct AddressInscriptions {
+  pub ids: Vec<InscriptionId>,
+  pub more: bool,
+  pub page: usize,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct AddressRunes {
+  pub balances: Vec<(SpacedRune, Pile)>,
+  pub more: bool,
+  pub page: usize,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct AddressRuneDelta {
+  pub received: Pile,
+  pub rune: SpacedRune,
+  pub sent: Pile,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct AddressTransaction {
+  pub direction: Direction,
+  pub height: u32,
+  pub inscriptions: Vec<InscriptionId>,
+  pub received: u64,
+  pub runes: Vec<AddressRuneDelta>,
+  pub sent: u64,
+  pub txid: Txid,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct AddressTransactions {
+  pub transactions: Vec<AddressTransaction>,
+  pub more: bool,
+  pub page: usize,
+}
+
 #[derive(Debug, PartialEq, Serialize, Deserialize)]
 pub struct Block {
   pub best_height: u32,
@@ -89,6 +128,35 @@ pub struct ChildInscriptions {
   pub page: usize,
 }
 
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct InscriptionSearch {
+  pub ids: Vec<InscriptionId>,
+  pub next: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct TextSearch {
+  pub query: String,
+  pub results: Vec<TextSearchResult>,
+  pub more: bool,
+  pub page: usize,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize, Clone)]
+pub struct TextSearchResult {
+  pub id: InscriptionId,
+  pub number: i32,
+  pub score: f64,
+  pub snippet: String,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct ContentHashInscriptions {
+  pub ids: Vec<InscriptionId>,
+  pub more: bool,
+  pub page: usize,
+}
+
 #[derive(Debug, PartialEq, Serialize, Deserialize, Clone)]
 pub struct Inscription {
   pub address: Option<String>,
@@ -111,6 +179,31 @@ pub struct Inscription {
   pub value: Option<u64>,
 }
 
+#[derive(Debug, PartialEq, Serialize, Deserialize, Copy, Clone)]
+#[serde(rename_all = "kebab-case")]
+pub enum Direction {
+  Incoming,
+  Outgoing,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct InscriptionHistory {
+  pub transfers: Vec<InscriptionTransfer>,
+  pub more: bool,
+  pub page: usize,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct InscriptionTransfer {
+  pub fee: u64,
+  pub height: u32,
+  pub new_location: SatPoint,
+  pub old_location: SatPoint,
+  pub receiver: Option<String>,
+  pub sender: Option<String>,
+  pub txid: Txid,
+}
+
 #[derive(Debug, PartialEq, Serialize, Deserialize)]
 pub struct InscriptionRecursive {
   pub charms: Vec<Charm>,
@@ -189,6 +282,20 @@ impl Output {
   }
 }
 
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct RuneHolder {
+  pub address: Option<String>,
+  pub amount: u128,
+  pub script_pubkey: ScriptBuf,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct RuneHolders {
+  pub holders: Vec<RuneHolder>,
+  pub more: bool,
+  pub page: usize,
+}
+
 #[derive(Debug, PartialEq, Serialize, Deserialize)]
 pub struct Sat {
   pub block: u32,
@@ -44,6 +44,8 @@ impl Arguments {
       );
     }
 
-    Ok(self.subcommand.run(Settings::load(self.options)?)?)
+    let format = self.options.format;
+
+    Ok(self.subcommand.run(Settings::load(self.options)?, format)?)
   }
 }
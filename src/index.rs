@@ -1,13 +1,20 @@
 use {
   self::{
+    block_source::BlockSource,
     entry::{
-      Entry, HeaderValue, InscriptionEntry, InscriptionEntryValue, InscriptionIdValue,
-      OutPointValue, RuneEntryValue, RuneIdValue, SatPointValue, SatRange, TxOutValue, TxidValue,
+      AddressTransactionEntry, AddressTransactionEntryValue, Entry, HeaderValue, InscriptionEntry,
+      InscriptionEntryValue, InscriptionIdValue, InscriptionProperties, InscriptionPropertiesValue,
+      OutPointValue, RuneEntryValue, RuneIdValue, SatPointValue, SatRange, TransferEntryValue,
+      TxOutValue, TxidValue,
     },
-    event::Event,
+    event::{BlockEventCounts, Event, InscriptionDetails},
+    inscription_search::{InscriptionSearch, InscriptionSearchResults},
     lot::Lot,
-    reorg::Reorg,
-    updater::Updater,
+    mempool::Mempool,
+    metaprotocol::{MetaprotocolIndexer, MetaprotocolReader},
+    reorg::{Reorg, Rollback},
+    undo_log::{LoggedMultimapTable, LoggedTable, UndoLog},
+    updater::{EventOutbox, Updater},
   },
   super::*,
   crate::{
@@ -35,42 +42,74 @@ use {
   },
 };
 
-pub use self::entry::RuneEntry;
+pub use self::entry::{RuneEntry, TransferEntry};
 
+pub mod block_source;
 pub(crate) mod entry;
 pub mod event;
 mod fetcher;
+mod full_text;
+pub mod inscription_search;
 mod lot;
-mod reorg;
+mod mempool;
+pub(crate) mod metaprotocol;
+pub mod reorg;
+pub(crate) mod replay;
 mod rtx;
+pub mod snapshot;
+mod undo_log;
 mod updater;
+pub mod verify;
 
 #[cfg(test)]
 pub(crate) mod testing;
 
-const SCHEMA_VERSION: u64 = 26;
+const SCHEMA_VERSION: u64 = 36;
 
+define_multimap_table! { CONTENT_HASH_TO_SEQUENCE_NUMBER, &[u8; 32], u32 }
+define_multimap_table! { HEIGHT_TO_TRANSFER, u32, (u32, u32) }
+define_multimap_table! { RUNE_BALANCE_TO_HOLDER, (RuneIdValue, u128), &[u8] }
 define_multimap_table! { SATPOINT_TO_SEQUENCE_NUMBER, &SatPointValue, u32 }
 define_multimap_table! { SAT_TO_SEQUENCE_NUMBER, u64, u32 }
 define_multimap_table! { SEQUENCE_NUMBER_TO_CHILDREN, u32, u32 }
+define_multimap_table! { TERM_TO_SEQUENCE_NUMBER, &str, (u32, u32, u32) }
 define_multimap_table! { SCRIPT_PUBKEY_TO_OUTPOINT, &[u8], OutPointValue }
+define_multimap_table! { SCRIPT_PUBKEY_TO_SEQUENCE_NUMBER, &[u8], u32 }
+define_table! { CONTENT_HASH_TO_BODY, &[u8; 32], &[u8] }
 define_table! { CONTENT_TYPE_TO_COUNT, Option<&[u8]>, u64 }
+define_table! { EVENT_ID_TO_EVENT, u64, &[u8] }
+define_table! { FEE_TO_SEQUENCE_NUMBER, (u64, u32), () }
 define_table! { HEIGHT_TO_BLOCK_HEADER, u32, &HeaderValue }
 define_table! { HEIGHT_TO_LAST_SEQUENCE_NUMBER, u32, u32 }
+define_table! { HEIGHT_TO_UNDO_LOG, (u32, &str), &[u8] }
 define_table! { HOME_INSCRIPTIONS, u32, InscriptionIdValue }
 define_table! { INSCRIPTION_ID_TO_SEQUENCE_NUMBER, InscriptionIdValue, u32 }
 define_table! { INSCRIPTION_NUMBER_TO_SEQUENCE_NUMBER, i32, u32 }
+define_table! { METAPROTOCOLS, &str, () }
 define_table! { OUTPOINT_TO_RUNE_BALANCES, &OutPointValue, &[u8] }
+define_table! { OUTPOINT_TO_RUNE_HOLDER, &OutPointValue, &[u8] }
 define_table! { OUTPOINT_TO_SAT_RANGES, &OutPointValue, &[u8] }
+define_table! { OUTPOINT_TO_SPENT_RUNE_BALANCES, &OutPointValue, &[u8] }
 define_table! { OUTPOINT_TO_TXOUT, &OutPointValue, TxOutValue }
+define_table! { PENDING_TRANSACTION_ID_TO_HEIGHT, &TxidValue, u32 }
+define_table! { RUNE_HOLDER_TO_BALANCE, (RuneIdValue, &[u8]), u128 }
+define_table! { RUNE_ID_TO_HOLDER_COUNT, RuneIdValue, u64 }
 define_table! { RUNE_ID_TO_RUNE_ENTRY, RuneIdValue, RuneEntryValue }
 define_table! { RUNE_TO_RUNE_ID, u128, RuneIdValue }
 define_table! { SAT_TO_SATPOINT, u64, &SatPointValue }
+define_table! { SCRIPT_PUBKEY_TO_RUNE_BALANCE, (&[u8], RuneIdValue), u128 }
+define_table! { SCRIPT_PUBKEY_TO_TRANSACTION, (&[u8], u32, u32), AddressTransactionEntryValue }
+define_table! { SEQUENCE_NUMBER_TO_CONTENT, u32, (Option<&[u8; 32]>, &[u8]) }
 define_table! { SEQUENCE_NUMBER_TO_INSCRIPTION_ENTRY, u32, InscriptionEntryValue }
+define_table! { SEQUENCE_NUMBER_TO_INSCRIPTION_PROPERTIES, u32, InscriptionPropertiesValue }
 define_table! { SEQUENCE_NUMBER_TO_RUNE_ID, u32, RuneIdValue }
 define_table! { SEQUENCE_NUMBER_TO_SATPOINT, u32, &SatPointValue }
+define_table! { SEQUENCE_NUMBER_TO_TEXT, u32, &str }
+define_table! { SEQUENCE_NUMBER_TO_TRANSFER, (u32, u32), TransferEntryValue }
+define_table! { SIZE_TO_SEQUENCE_NUMBER, (u64, u32), () }
 define_table! { STATISTIC_TO_COUNT, u64, u64 }
 define_table! { TRANSACTION_ID_TO_RUNE, &TxidValue, u128 }
+define_table! { TRANSACTION_ID_TO_RUNE_MINT, &TxidValue, u128 }
 define_table! { TRANSACTION_ID_TO_TRANSACTION, &TxidValue, &[u8] }
 define_table! { WRITE_TRANSACTION_STARTING_BLOCK_COUNT_TO_TIMESTAMP, u32, u128 }
 
@@ -92,6 +131,15 @@ pub(crate) enum Statistic {
   IndexSpentSats = 13,
   InitialSyncTime = 14,
   IndexAddresses = 15,
+  Events = 16,
+  IndexTransfers = 17,
+  IndexAddressHistory = 18,
+  IndexContent = 19,
+  IndexFullText = 20,
+  FullTextTerms = 21,
+  EventConsumer = 22,
+  ReorgRollback = 23,
+  IndexSearch = 24,
 }
 
 impl Statistic {
@@ -106,6 +154,23 @@ impl From<Statistic> for u64 {
   }
 }
 
+// events are removed from the outbox once they are consumed, so only one kind
+// of consumer may use an index's outbox
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum EventConsumer {
+  Feed = 1,
+  Sink = 2,
+}
+
+impl EventConsumer {
+  fn command(self) -> &'static str {
+    match self {
+      Self::Feed => "`ord server --events`",
+      Self::Sink => "`ord index publish`",
+    }
+  }
+}
+
 #[derive(Serialize)]
 pub struct Info {
   blocks_indexed: u32,
@@ -183,20 +248,30 @@ impl<T> BitcoinCoreRpcResultExt<T> for Result<T, bitcoincore_rpc::Error> {
 }
 
 pub struct Index {
+  block_source: Arc<dyn BlockSource>,
   pub(crate) client: Client,
   database: Database,
   durability: redb::Durability,
+  event_outbox: bool,
   event_sender: Option<tokio::sync::mpsc::Sender<Event>>,
   first_inscription_height: u32,
   genesis_block_coinbase_transaction: Transaction,
   genesis_block_coinbase_txid: Txid,
   height_limit: Option<u32>,
+  index_address_history: bool,
   index_addresses: bool,
+  index_content: bool,
+  index_full_text: bool,
   index_runes: bool,
   index_sats: bool,
+  index_search: bool,
   index_spent_sats: bool,
   index_transactions: bool,
+  index_transfers: bool,
+  mempool: Option<Mutex<Mempool>>,
+  metaprotocols: Vec<&'static dyn MetaprotocolIndexer>,
   path: PathBuf,
+  reorg_rollback: Rollback,
   settings: Settings,
   started: DateTime<Utc>,
   unrecoverably_reorged: AtomicBool,
@@ -210,9 +285,25 @@ impl Index {
   pub fn open_with_event_sender(
     settings: &Settings,
     event_sender: Option<tokio::sync::mpsc::Sender<Event>>,
+  ) -> Result<Self> {
+    Index::open_inner(settings, event_sender.is_some(), event_sender)
+  }
+
+  pub fn open_with_event_outbox(settings: &Settings, consumer: EventConsumer) -> Result<Self> {
+    let index = Index::open_inner(settings, true, None)?;
+    index.claim_event_outbox(consumer)?;
+    Ok(index)
+  }
+
+  fn open_inner(
+    settings: &Settings,
+    event_outbox: bool,
+    event_sender: Option<tokio::sync::mpsc::Sender<Event>>,
   ) -> Result<Self> {
     let client = settings.bitcoin_rpc_client(None)?;
 
+    let block_source = block_source::open(settings)?;
+
     let path = settings.index().to_owned();
 
     let data_dir = path.parent().unwrap();
@@ -296,27 +387,64 @@ impl Index {
 
         tx.set_durability(durability);
 
+        tx.open_multimap_table(CONTENT_HASH_TO_SEQUENCE_NUMBER)?;
+        tx.open_multimap_table(HEIGHT_TO_TRANSFER)?;
+        tx.open_multimap_table(RUNE_BALANCE_TO_HOLDER)?;
         tx.open_multimap_table(SATPOINT_TO_SEQUENCE_NUMBER)?;
         tx.open_multimap_table(SAT_TO_SEQUENCE_NUMBER)?;
         tx.open_multimap_table(SCRIPT_PUBKEY_TO_OUTPOINT)?;
+        tx.open_multimap_table(SCRIPT_PUBKEY_TO_SEQUENCE_NUMBER)?;
         tx.open_multimap_table(SEQUENCE_NUMBER_TO_CHILDREN)?;
+        tx.open_multimap_table(TERM_TO_SEQUENCE_NUMBER)?;
+        tx.open_table(CONTENT_HASH_TO_BODY)?;
         tx.open_table(CONTENT_TYPE_TO_COUNT)?;
+        tx.open_table(EVENT_ID_TO_EVENT)?;
+        tx.open_table(FEE_TO_SEQUENCE_NUMBER)?;
         tx.open_table(HEIGHT_TO_BLOCK_HEADER)?;
         tx.open_table(HEIGHT_TO_LAST_SEQUENCE_NUMBER)?;
+        tx.open_table(HEIGHT_TO_UNDO_LOG)?;
         tx.open_table(HOME_INSCRIPTIONS)?;
         tx.open_table(INSCRIPTION_ID_TO_SEQUENCE_NUMBER)?;
         tx.open_table(INSCRIPTION_NUMBER_TO_SEQUENCE_NUMBER)?;
         tx.open_table(OUTPOINT_TO_RUNE_BALANCES)?;
+        tx.open_table(OUTPOINT_TO_RUNE_HOLDER)?;
+        tx.open_table(OUTPOINT_TO_SPENT_RUNE_BALANCES)?;
         tx.open_table(OUTPOINT_TO_TXOUT)?;
+        tx.open_table(PENDING_TRANSACTION_ID_TO_HEIGHT)?;
+        tx.open_table(RUNE_HOLDER_TO_BALANCE)?;
+        tx.open_table(RUNE_ID_TO_HOLDER_COUNT)?;
         tx.open_table(RUNE_ID_TO_RUNE_ENTRY)?;
         tx.open_table(RUNE_TO_RUNE_ID)?;
         tx.open_table(SAT_TO_SATPOINT)?;
+        tx.open_table(SCRIPT_PUBKEY_TO_RUNE_BALANCE)?;
+        tx.open_table(SCRIPT_PUBKEY_TO_TRANSACTION)?;
+        tx.open_table(SEQUENCE_NUMBER_TO_CONTENT)?;
         tx.open_table(SEQUENCE_NUMBER_TO_INSCRIPTION_ENTRY)?;
+        tx.open_table(SEQUENCE_NUMBER_TO_INSCRIPTION_PROPERTIES)?;
         tx.open_table(SEQUENCE_NUMBER_TO_RUNE_ID)?;
         tx.open_table(SEQUENCE_NUMBER_TO_SATPOINT)?;
+        tx.open_table(SEQUENCE_NUMBER_TO_TEXT)?;
+        tx.open_table(SEQUENCE_NUMBER_TO_TRANSFER)?;
+        tx.open_table(SIZE_TO_SEQUENCE_NUMBER)?;
         tx.open_table(TRANSACTION_ID_TO_RUNE)?;
+        tx.open_table(TRANSACTION_ID_TO_RUNE_MINT)?;
         tx.open_table(WRITE_TRANSACTION_STARTING_BLOCK_COUNT_TO_TIMESTAMP)?;
 
+        {
+          let mut metaprotocols = tx.open_table(METAPROTOCOLS)?;
+
+          for name in settings.index_metaprotocols() {
+            let indexer = metaprotocol::indexer(name)
+              .ok_or_else(|| anyhow!("unknown metaprotocol `{name}`"))?;
+
+            for table in indexer.tables() {
+              tx.open_table(TableDefinition::<&[u8], &[u8]>::new(table))?;
+            }
+
+            metaprotocols.insert(name.as_str(), ())?;
+          }
+        }
+
         {
           let mut outpoint_to_sat_ranges = tx.open_table(OUTPOINT_TO_SAT_RANGES)?;
           let mut statistics = tx.open_table(STATISTIC_TO_COUNT)?;
@@ -325,12 +453,30 @@ impl Index {
             outpoint_to_sat_ranges.insert(&OutPoint::null().store(), [].as_slice())?;
           }
 
+          Self::set_statistic(
+            &mut statistics,
+            Statistic::IndexAddressHistory,
+            u64::from(settings.index_address_history()),
+          )?;
+
           Self::set_statistic(
             &mut statistics,
             Statistic::IndexAddresses,
             u64::from(settings.index_addresses()),
           )?;
 
+          Self::set_statistic(
+            &mut statistics,
+            Statistic::IndexContent,
+            u64::from(settings.index_content()),
+          )?;
+
+          Self::set_statistic(
+            &mut statistics,
+            Statistic::IndexFullText,
+            u64::from(settings.index_full_text()),
+          )?;
+
           Self::set_statistic(
             &mut statistics,
             Statistic::IndexRunes,
@@ -343,6 +489,12 @@ impl Index {
             u64::from(settings.index_sats() || settings.index_spent_sats()),
           )?;
 
+          Self::set_statistic(
+            &mut statistics,
+            Statistic::IndexSearch,
+            u64::from(settings.index_search()),
+          )?;
+
           Self::set_statistic(
             &mut statistics,
             Statistic::IndexSpentSats,
@@ -355,6 +507,18 @@ impl Index {
             u64::from(settings.index_transactions()),
           )?;
 
+          Self::set_statistic(
+            &mut statistics,
+            Statistic::IndexTransfers,
+            u64::from(settings.index_transfers()),
+          )?;
+
+          Self::set_statistic(
+            &mut statistics,
+            Statistic::ReorgRollback,
+            settings.reorg_rollback().unwrap_or_default() as u64,
+          )?;
+
           Self::set_statistic(&mut statistics, Statistic::Schema, SCHEMA_VERSION)?;
         }
 
@@ -409,41 +573,99 @@ impl Index {
       Err(error) => bail!("failed to open index: {error}"),
     };
 
+    let event_outbox_claimed;
+    let index_address_history;
     let index_addresses;
+    let index_content;
+    let index_full_text;
     let index_runes;
+    let metaprotocols;
+    let reorg_rollback;
     let index_sats;
+    let index_search;
     let index_spent_sats;
     let index_transactions;
+    let index_transfers;
 
     {
       let tx = database.begin_read()?;
       let statistics = tx.open_table(STATISTIC_TO_COUNT)?;
+      event_outbox_claimed = Self::is_statistic_set(&statistics, Statistic::EventConsumer)?;
+      index_address_history = Self::is_statistic_set(&statistics, Statistic::IndexAddressHistory)?;
       index_addresses = Self::is_statistic_set(&statistics, Statistic::IndexAddresses)?;
+      index_content = Self::is_statistic_set(&statistics, Statistic::IndexContent)?;
+      index_full_text = Self::is_statistic_set(&statistics, Statistic::IndexFullText)?;
       index_runes = Self::is_statistic_set(&statistics, Statistic::IndexRunes)?;
       index_sats = Self::is_statistic_set(&statistics, Statistic::IndexSats)?;
+      index_search = Self::is_statistic_set(&statistics, Statistic::IndexSearch)?;
       index_spent_sats = Self::is_statistic_set(&statistics, Statistic::IndexSpentSats)?;
       index_transactions = Self::is_statistic_set(&statistics, Statistic::IndexTransactions)?;
+      index_transfers = Self::is_statistic_set(&statistics, Statistic::IndexTransfers)?;
+
+      // savepoints and undo logs are only written in the index's own rollback
+      // mode, so the other mode would have nothing to roll back to
+      reorg_rollback = Rollback::from_statistic(
+        statistics
+          .get(&Statistic::ReorgRollback.key())?
+          .map(|rollback| rollback.value())
+          .unwrap_or_default(),
+      )?;
+
+      if let Some(requested) = settings.reorg_rollback() {
+        if requested != reorg_rollback {
+          bail!(
+            "index at `{}` was built with `--reorg-rollback {reorg_rollback}`, and cannot be opened with `--reorg-rollback {requested}`",
+            path.display(),
+          );
+        }
+      }
+
+      metaprotocols = tx
+        .open_table(METAPROTOCOLS)?
+        .iter()?
+        .map(|entry| {
+          let name = entry?.0.value().to_string();
+          metaprotocol::indexer(&name)
+            .ok_or_else(|| anyhow!("index references unknown metaprotocol `{name}`"))
+        })
+        .collect::<Result<Vec<&'static dyn MetaprotocolIndexer>>>()?;
     }
 
+    // once an outbox has been claimed, every process that updates the index
+    // must record events, or its consumer would see a gap
+    let event_outbox = event_outbox || event_outbox_claimed;
+
     let genesis_block_coinbase_transaction =
       settings.chain().genesis_block().coinbase().unwrap().clone();
 
     Ok(Self {
       genesis_block_coinbase_txid: genesis_block_coinbase_transaction.txid(),
+      block_source,
       client,
       database,
       durability,
+      event_outbox,
       event_sender,
       first_inscription_height: settings.first_inscription_height(),
       genesis_block_coinbase_transaction,
       height_limit: settings.height_limit(),
+      index_address_history,
       index_addresses,
+      index_content,
+      index_full_text,
       index_runes,
       index_sats,
+      index_search,
       index_spent_sats,
       index_transactions,
+      index_transfers,
+      mempool: (event_outbox && settings.mempool_events())
+        .then(|| Mempool::new(settings).map(Mutex::new))
+        .transpose()?,
+      metaprotocols,
       settings: settings.clone(),
       path,
+      reorg_rollback,
       started: Utc::now(),
       unrecoverably_reorged: AtomicBool::new(false),
     })
@@ -465,6 +687,22 @@ impl Index {
     )
   }
 
+  pub fn has_address_history_index(&self) -> bool {
+    self.index_address_history
+  }
+
+  pub fn has_content_index(&self) -> bool {
+    self.index_content
+  }
+
+  pub fn has_full_text_index(&self) -> bool {
+    self.index_full_text
+  }
+
+  pub(crate) fn metaprotocols(&self) -> &[&'static dyn MetaprotocolIndexer] {
+    &self.metaprotocols
+  }
+
   pub fn has_address_index(&self) -> bool {
     self.index_addresses
   }
@@ -477,6 +715,14 @@ impl Index {
     self.index_sats
   }
 
+  pub fn has_search_index(&self) -> bool {
+    self.index_search
+  }
+
+  pub fn has_transfer_index(&self) -> bool {
+    self.index_transfers
+  }
+
   pub fn status(&self) -> Result<StatusHtml> {
     let rtx = self.database.begin_read()?;
 
@@ -625,6 +871,8 @@ impl Index {
   }
 
   pub fn update(&self) -> Result {
+    self.dispatch_events()?;
+
     loop {
       let wtx = self.begin_write()?;
 
@@ -642,18 +890,22 @@ impl Index {
         outputs_traversed: 0,
         range_cache: HashMap::new(),
         sat_ranges_since_flush: 0,
+        undo_log: UndoLog::default(),
+        undo_log_height: None,
       };
 
       match updater.update_index(wtx) {
-        Ok(ok) => return Ok(ok),
+        Ok(()) => return self.update_mempool(),
         Err(err) => {
           log::info!("{}", err.to_string());
 
           match err.downcast_ref() {
             Some(&reorg::Error::Recoverable { height, depth }) => {
+              metrics::REORGS.with_label_values(&["recovered"]).inc();
               Reorg::handle_reorg(self, height, depth)?;
             }
             Some(&reorg::Error::Unrecoverable) => {
+              metrics::REORGS.with_label_values(&["unrecoverable"]).inc();
               self
                 .unrecoverably_reorged
                 .store(true, atomic::Ordering::Relaxed);
@@ -666,6 +918,121 @@ impl Index {
     }
   }
 
+  fn update_mempool(&self) -> Result {
+    let Some(mempool) = &self.mempool else {
+      return Ok(());
+    };
+
+    mempool.lock().unwrap().update(self)
+  }
+
+  pub fn pending_events(&self, limit: usize) -> Result<Vec<(u64, Event)>> {
+    self.events_from(0, limit)
+  }
+
+  pub(crate) fn events_from(&self, event_id: u64, limit: usize) -> Result<Vec<(u64, Event)>> {
+    self
+      .database
+      .begin_read()?
+      .open_table(EVENT_ID_TO_EVENT)?
+      .range(event_id..)?
+      .take(limit)
+      .map(|result| {
+        let (id, event) = result?;
+        Ok((id.value(), serde_json::from_slice(event.value())?))
+      })
+      .collect()
+  }
+
+  pub(crate) fn event_outbox_len(&self) -> Result<u64> {
+    Ok(
+      self
+        .database
+        .begin_read()?
+        .open_table(EVENT_ID_TO_EVENT)?
+        .len()?,
+    )
+  }
+
+  pub(crate) fn next_event_id(&self) -> Result<u64> {
+    Ok(
+      self
+        .database
+        .begin_read()?
+        .open_table(STATISTIC_TO_COUNT)?
+        .get(&Statistic::Events.key())?
+        .map(|count| count.value())
+        .unwrap_or_default(),
+    )
+  }
+
+  fn claim_event_outbox(&self, consumer: EventConsumer) -> Result {
+    let wtx = self.begin_write()?;
+
+    {
+      let mut statistics = wtx.open_table(STATISTIC_TO_COUNT)?;
+
+      let claimed = statistics
+        .get(&Statistic::EventConsumer.key())?
+        .map(|count| count.value());
+
+      match claimed {
+        None => Self::set_statistic(&mut statistics, Statistic::EventConsumer, consumer as u64)?,
+        Some(claimed) if claimed == consumer as u64 => {}
+        Some(_) => {
+          let other = match consumer {
+            EventConsumer::Feed => EventConsumer::Sink,
+            EventConsumer::Sink => EventConsumer::Feed,
+          };
+
+          bail!(
+            "index event outbox is consumed by {}, and cannot also be consumed by {}",
+            other.command(),
+            consumer.command(),
+          );
+        }
+      }
+    }
+
+    wtx.commit()?;
+
+    Ok(())
+  }
+
+  pub fn mark_events_delivered(&self, event_id: u64) -> Result {
+    let wtx = self.begin_write()?;
+
+    wtx
+      .open_table(EVENT_ID_TO_EVENT)?
+      .retain_in(..=event_id, |_, _| false)?;
+
+    wtx.commit()?;
+
+    Ok(())
+  }
+
+  fn dispatch_events(&self) -> Result {
+    let Some(sender) = &self.event_sender else {
+      return Ok(());
+    };
+
+    loop {
+      let events = self.pending_events(1000)?;
+
+      let Some((last, _)) = events.last() else {
+        return Ok(());
+      };
+
+      let last = *last;
+
+      for (_, event) in events {
+        sender.blocking_send(event)?;
+      }
+
+      self.mark_events_delivered(last)?;
+    }
+  }
+
   pub fn export(&self, filename: &String, include_addresses: bool) -> Result {
     let mut writer = BufWriter::new(fs::File::create(filename)?);
     let rtx = self.database.begin_read()?;
@@ -961,6 +1328,60 @@ impl Index {
     Ok((entries, more))
   }
 
+  pub fn get_rune_holder_count(&self, id: RuneId) -> Result<u64> {
+    Ok(
+      self
+        .database
+        .begin_read()?
+        .open_table(RUNE_ID_TO_HOLDER_COUNT)?
+        .get(&id.store())?
+        .map(|count| count.value())
+        .unwrap_or_default(),
+    )
+  }
+
+  pub fn get_rune_holders_paginated(
+    &self,
+    id: RuneId,
+    page_size: usize,
+    page_index: usize,
+  ) -> Result<(Vec<(ScriptBuf, u128)>, bool)> {
+    let mut holders = Vec::new();
+
+    for result in self
+      .database
+      .begin_read()?
+      .open_multimap_table(RUNE_BALANCE_TO_HOLDER)?
+      .range((id.store(), 0)..=(id.store(), u128::MAX))?
+      .rev()
+    {
+      let (balance, scripts) = result?;
+      let (_id, balance) = balance.value();
+
+      for script in scripts {
+        holders.push((ScriptBuf::from_bytes(script?.value().to_vec()), balance));
+      }
+
+      if holders.len() > page_index.saturating_add(1).saturating_mul(page_size) {
+        break;
+      }
+    }
+
+    let mut holders = holders
+      .into_iter()
+      .skip(page_index.saturating_mul(page_size))
+      .take(page_size.saturating_add(1))
+      .collect::<Vec<(ScriptBuf, u128)>>();
+
+    let more = holders.len() > page_size;
+
+    if more {
+      holders.pop();
+    }
+
+    Ok((holders, more))
+  }
+
   pub fn encode_rune_balance(id: RuneId, balance: u128, buffer: &mut Vec<u8>) {
     varint::encode_to_vec(id.block.into(), buffer);
     varint::encode_to_vec(id.tx.into(), buffer);
@@ -1457,6 +1878,12 @@ impl Index {
       return Ok(None);
     }
 
+    if self.index_content {
+      if let Some(inscription) = self.get_inscription_content(inscription_id)? {
+        return Ok(Some(inscription));
+      }
+    }
+
     Ok(self.get_transaction(inscription_id.txid)?.and_then(|tx| {
       ParsedEnvelope::from_transaction(&tx)
         .into_iter()
@@ -1465,6 +1892,113 @@ impl Index {
     }))
   }
 
+  fn get_inscription_content(&self, inscription_id: InscriptionId) -> Result<Option<Inscription>> {
+    let rtx = self.database.begin_read()?;
+
+    let Some(sequence_number) = rtx
+      .open_table(INSCRIPTION_ID_TO_SEQUENCE_NUMBER)?
+      .get(&inscription_id.store())?
+      .map(|guard| guard.value())
+    else {
+      return Ok(None);
+    };
+
+    let sequence_number_to_content = rtx.open_table(SEQUENCE_NUMBER_TO_CONTENT)?;
+
+    let Some(content) = sequence_number_to_content.get(sequence_number)? else {
+      return Ok(None);
+    };
+
+    let (hash, inscription) = content.value();
+
+    let mut inscription: Inscription = ciborium::from_reader(inscription)?;
+
+    if let Some(hash) = hash {
+      inscription.body = Some(
+        rtx
+          .open_table(CONTENT_HASH_TO_BODY)?
+          .get(hash)?
+          .ok_or_else(|| anyhow!("missing body for content hash {}", hex::encode(hash)))?
+          .value()
+          .into(),
+      );
+    }
+
+    Ok(Some(inscription))
+  }
+
+  pub fn get_inscriptions_by_content_hash_paginated(
+    &self,
+    hash: [u8; 32],
+    page_size: usize,
+    page_index: usize,
+  ) -> Result<(Vec<InscriptionId>, bool)> {
+    let rtx = self.database.begin_read()?;
+
+    let sequence_number_to_entry = rtx.open_table(SEQUENCE_NUMBER_TO_INSCRIPTION_ENTRY)?;
+
+    let mut ids = rtx
+      .open_multimap_table(CONTENT_HASH_TO_SEQUENCE_NUMBER)?
+      .get(&hash)?
+      .skip(page_index.saturating_mul(page_size))
+      .take(page_size.saturating_add(1))
+      .map(|result| {
+        result
+          .and_then(|sequence_number| {
+            sequence_number_to_entry
+              .get(sequence_number.value())
+              .map(|entry| InscriptionEntry::load(entry.unwrap().value()).id)
+          })
+          .map_err(|err| err.into())
+      })
+      .collect::<Result<Vec<InscriptionId>>>()?;
+
+    let more = ids.len() > page_size;
+
+    if more {
+      ids.pop();
+    }
+
+    Ok((ids, more))
+  }
+
+  pub fn search_full_text(
+    &self,
+    query: &str,
+    page_size: usize,
+    page_index: usize,
+  ) -> Result<(Vec<api::TextSearchResult>, bool)> {
+    full_text::search(self, query, page_size, page_index)
+  }
+
+  /// Returns `None` if this index has no `protocol` metaprotocol index, and
+  /// `Some(None)` if the route found nothing.
+  pub(crate) fn metaprotocol_route(
+    &self,
+    protocol: &str,
+    path: &[&str],
+  ) -> Result<Option<Option<serde_json::Value>>> {
+    let Some(indexer) = self
+      .metaprotocols
+      .iter()
+      .find(|indexer| indexer.name() == protocol)
+    else {
+      return Ok(None);
+    };
+
+    let reader =
+      MetaprotocolReader::new(self.settings.chain(), self.database.begin_read()?, *indexer);
+
+    Ok(Some(indexer.route(&reader, path)?))
+  }
+
+  pub fn search_inscriptions(
+    &self,
+    search: &InscriptionSearch,
+  ) -> Result<InscriptionSearchResults> {
+    search.run(self)
+  }
+
   pub fn inscription_count(&self, txid: Txid) -> Result<u32> {
     let start = InscriptionId { index: 0, txid };
 
@@ -2233,42 +2767,188 @@ impl Index {
       .collect()
   }
 
-  pub(crate) fn get_aggregated_rune_balances_for_outputs(
+  pub fn get_address_transactions_paginated(
     &self,
-    outputs: &Vec<OutPoint>,
-  ) -> Result<Vec<(SpacedRune, Decimal, Option<char>)>> {
-    let mut runes = BTreeMap::new();
+    address: &Address,
+    page_size: usize,
+    page_index: usize,
+  ) -> Result<(Vec<api::AddressTransaction>, bool)> {
+    let rtx = self.database.begin_read()?;
 
-    for output in outputs {
-      let rune_balances = self.get_rune_balances_for_output(*output)?;
-
-      for (spaced_rune, pile) in rune_balances {
-        runes
-          .entry(spaced_rune)
-          .and_modify(|(decimal, _symbol): &mut (Decimal, Option<char>)| {
-            assert_eq!(decimal.scale, pile.divisibility);
-            decimal.value += pile.amount;
-          })
-          .or_insert((
-            Decimal {
-              value: pile.amount,
-              scale: pile.divisibility,
-            },
-            pile.symbol,
-          ));
-      }
-    }
+    let rune_id_to_rune_entry = rtx.open_table(RUNE_ID_TO_RUNE_ENTRY)?;
 
-    Ok(
-      runes
-        .into_iter()
-        .map(|(spaced_rune, (decimal, symbol))| (spaced_rune, decimal, symbol))
-        .collect(),
-    )
-  }
+    let script_pubkey = address.script_pubkey();
 
-  pub(crate) fn get_sat_balances_for_outputs(&self, outputs: &Vec<OutPoint>) -> Result<u64> {
-    let outpoint_to_txout = self.database.begin_read()?.open_table(OUTPOINT_TO_TXOUT)?;
+    let mut transactions = rtx
+      .open_table(SCRIPT_PUBKEY_TO_TRANSACTION)?
+      .range((script_pubkey.as_bytes(), 0, 0)..=(script_pubkey.as_bytes(), u32::MAX, u32::MAX))?
+      .rev()
+      .skip(page_index.saturating_mul(page_size))
+      .take(page_size.saturating_add(1))
+      .map(|result| {
+        let entry = AddressTransactionEntry::load(result?.1.value());
+
+        let runes = entry
+          .runes
+          .into_iter()
+          .map(|(id, received, sent)| {
+            let rune = RuneEntry::load(rune_id_to_rune_entry.get(id.store())?.unwrap().value());
+
+            let pile = |amount| Pile {
+              amount,
+              divisibility: rune.divisibility,
+              symbol: rune.symbol,
+            };
+
+            Ok(api::AddressRuneDelta {
+              received: pile(received),
+              rune: rune.spaced_rune,
+              sent: pile(sent),
+            })
+          })
+          .collect::<Result<Vec<api::AddressRuneDelta>>>()?;
+
+        Ok(api::AddressTransaction {
+          direction: if entry.sent > 0 {
+            api::Direction::Outgoing
+          } else {
+            api::Direction::Incoming
+          },
+          height: entry.height,
+          inscriptions: entry.inscriptions,
+          received: entry.received,
+          runes,
+          sent: entry.sent,
+          txid: entry.txid,
+        })
+      })
+      .collect::<Result<Vec<api::AddressTransaction>>>()?;
+
+    let more = transactions.len() > page_size;
+
+    if more {
+      transactions.pop();
+    }
+
+    Ok((transactions, more))
+  }
+
+  pub fn get_inscription_transfers_paginated(
+    &self,
+    inscription_id: InscriptionId,
+    page_size: usize,
+    page_index: usize,
+  ) -> Result<Option<(Vec<TransferEntry>, bool)>> {
+    let rtx = self.database.begin_read()?;
+
+    let Some(sequence_number) = rtx
+      .open_table(INSCRIPTION_ID_TO_SEQUENCE_NUMBER)?
+      .get(&inscription_id.store())?
+      .map(|guard| guard.value())
+    else {
+      return Ok(None);
+    };
+
+    let mut transfers = rtx
+      .open_table(SEQUENCE_NUMBER_TO_TRANSFER)?
+      .range((sequence_number, 0)..=(sequence_number, u32::MAX))?
+      .skip(page_index.saturating_mul(page_size))
+      .take(page_size.saturating_add(1))
+      .map(|result| Ok(TransferEntry::load(result?.1.value())))
+      .collect::<Result<Vec<TransferEntry>>>()?;
+
+    let more = transfers.len() > page_size;
+
+    if more {
+      transfers.pop();
+    }
+
+    Ok(Some((transfers, more)))
+  }
+
+  pub fn get_inscriptions_for_address_paginated(
+    &self,
+    address: &Address,
+    page_size: usize,
+    page_index: usize,
+  ) -> Result<(Vec<InscriptionId>, bool)> {
+    let rtx = self.database.begin_read()?;
+
+    let sequence_number_to_entry = rtx.open_table(SEQUENCE_NUMBER_TO_INSCRIPTION_ENTRY)?;
+
+    let mut inscriptions = rtx
+      .open_multimap_table(SCRIPT_PUBKEY_TO_SEQUENCE_NUMBER)?
+      .get(address.script_pubkey().as_bytes())?
+      .skip(page_index.saturating_mul(page_size))
+      .take(page_size.saturating_add(1))
+      .map(|result| {
+        let sequence_number = result?.value();
+        Ok(
+          InscriptionEntry::load(
+            sequence_number_to_entry
+              .get(sequence_number)?
+              .unwrap()
+              .value(),
+          )
+          .id,
+        )
+      })
+      .collect::<Result<Vec<InscriptionId>>>()?;
+
+    let more = inscriptions.len() > page_size;
+
+    if more {
+      inscriptions.pop();
+    }
+
+    Ok((inscriptions, more))
+  }
+
+  pub fn get_rune_balances_for_address_paginated(
+    &self,
+    address: &Address,
+    page_size: usize,
+    page_index: usize,
+  ) -> Result<(Vec<(SpacedRune, Pile)>, bool)> {
+    let rtx = self.database.begin_read()?;
+
+    let rune_id_to_rune_entry = rtx.open_table(RUNE_ID_TO_RUNE_ENTRY)?;
+
+    let script_pubkey = address.script_pubkey();
+
+    let mut balances = rtx
+      .open_table(SCRIPT_PUBKEY_TO_RUNE_BALANCE)?
+      .range((script_pubkey.as_bytes(), (0, 0))..=(script_pubkey.as_bytes(), (u64::MAX, u32::MAX)))?
+      .skip(page_index.saturating_mul(page_size))
+      .take(page_size.saturating_add(1))
+      .map(|result| {
+        let (key, amount) = result?;
+        let (_script_pubkey, id) = key.value();
+
+        let entry = RuneEntry::load(rune_id_to_rune_entry.get(id)?.unwrap().value());
+
+        Ok((
+          entry.spaced_rune,
+          Pile {
+            amount: amount.value(),
+            divisibility: entry.divisibility,
+            symbol: entry.symbol,
+          },
+        ))
+      })
+      .collect::<Result<Vec<(SpacedRune, Pile)>>>()?;
+
+    let more = balances.len() > page_size;
+
+    if more {
+      balances.pop();
+    }
+
+    Ok((balances, more))
+  }
+
+  pub(crate) fn get_sat_balances_for_outputs(&self, outputs: &Vec<OutPoint>) -> Result<u64> {
+    let outpoint_to_txout = self.database.begin_read()?.open_table(OUTPOINT_TO_TXOUT)?;
 
     let mut acc = 0;
     for output in outputs {
@@ -3451,6 +4131,44 @@ mod tests {
       format!("index at `{}{delimiter}regtest{delimiter}index.redb` appears to have been built with a newer, incompatible version of ord, consider updating ord: index schema {}, ord schema {SCHEMA_VERSION}", path.display(), u64::MAX));
   }
 
+  #[test]
+  fn reorg_rollback_mode_must_match_index() {
+    let tempdir = Context::builder()
+      .args(["--reorg-rollback", "undo-log"])
+      .build()
+      .tempdir;
+
+    let tempdir = Context::builder()
+      .args(["--reorg-rollback", "undo-log"])
+      .tempdir(tempdir)
+      .build()
+      .tempdir;
+
+    let Context {
+      index,
+      core: _core,
+      tempdir,
+    } = Context::builder().tempdir(tempdir).build();
+
+    assert_eq!(index.reorg_rollback, Rollback::UndoLog);
+
+    drop(index);
+
+    let path = tempdir.path().to_owned();
+
+    let delimiter = if cfg!(windows) { '\\' } else { '/' };
+
+    assert_eq!(
+      Context::builder()
+        .args(["--reorg-rollback", "savepoints"])
+        .tempdir(tempdir)
+        .try_build()
+        .err()
+        .unwrap()
+        .to_string(),
+      format!("index at `{}{delimiter}regtest{delimiter}index.redb` was built with `--reorg-rollback undo-log`, and cannot be opened with `--reorg-rollback savepoints`", path.display()));
+  }
+
   #[test]
   fn inscriptions_on_output() {
     for context in Context::configurations() {
@@ -4491,7 +5209,7 @@ mod tests {
             i,
             if i == 1 { 0 } else { 1 },
             0,
-            inscription("text/plain;charset=utf-8", &format!("hello {}", i)).to_witness(),
+            inscription("text/plain;charset=utf-8", format!("hello {}", i)).to_witness(),
           )], // for the first inscription use coinbase, otherwise use the previous tx
           ..default()
         });
@@ -4588,6 +5306,119 @@ mod tests {
     }
   }
 
+  #[test]
+  fn reorg_does_not_redeliver_or_reuse_event_ids() {
+    let mut context = Context::builder().event_outbox().build();
+
+    context.index.set_durability(redb::Durability::Immediate);
+
+    context.mine_blocks(1);
+
+    context.core.broadcast_tx(TransactionTemplate {
+      inputs: &[(1, 0, 0, inscription("text/plain", "hello").to_witness())],
+      ..default()
+    });
+
+    context.mine_blocks(6);
+
+    context.index.mark_events_delivered(16).unwrap();
+
+    context.core.broadcast_tx(TransactionTemplate {
+      inputs: &[(2, 0, 0, inscription("text/plain", "hello").to_witness())],
+      ..default()
+    });
+
+    context.mine_blocks(1);
+
+    assert_eq!(
+      context
+        .index
+        .pending_events(usize::MAX)
+        .unwrap()
+        .into_iter()
+        .map(|(event_id, _)| event_id)
+        .collect::<Vec<u64>>(),
+      [17, 18, 19],
+    );
+
+    context.core.invalidate_tip();
+    context.mine_blocks(2);
+
+    let events = context.index.pending_events(usize::MAX).unwrap();
+
+    assert!(matches!(events[0], (20, Event::BlocksRolledBack { .. })));
+
+    assert!(events
+      .iter()
+      .enumerate()
+      .all(|(i, (event_id, _event))| *event_id == u64::try_from(i).unwrap() + 20));
+  }
+
+  #[test]
+  fn reorg_emits_rollback_events() {
+    let mut context = Context::builder().event_outbox().build();
+
+    context.index.set_durability(redb::Durability::Immediate);
+
+    context.mine_blocks(1);
+
+    let txid = context.core.broadcast_tx(TransactionTemplate {
+      inputs: &[(1, 0, 0, inscription("text/plain", "hello").to_witness())],
+      ..default()
+    });
+
+    context.mine_blocks(7);
+
+    context.index.mark_events_delivered(u64::MAX).unwrap();
+
+    let block_hashes = (0..=8)
+      .map(|height| context.index.block_hash(Some(height)).unwrap().unwrap())
+      .collect::<Vec<BlockHash>>();
+
+    context.core.invalidate_tip();
+    context.mine_blocks(2);
+
+    let mut expected = vec![Event::BlocksRolledBack {
+      block_height: 9,
+      depth: 2,
+      from_height: 2,
+      to_height: 8,
+    }];
+
+    expected.extend((2..=8).rev().map(|height| Event::BlockRetracted {
+      block_hash: block_hashes[height],
+      block_height: height.try_into().unwrap(),
+    }));
+
+    expected.push(Event::InscriptionCreated {
+      block_height: 2,
+      charms: 0,
+      details: None,
+      inscription_id: InscriptionId { txid, index: 0 },
+      location: Some(SatPoint {
+        outpoint: OutPoint { txid, vout: 0 },
+        offset: 0,
+      }),
+      parent_inscription_ids: Vec::new(),
+      sequence_number: 0,
+    });
+
+    assert_eq!(
+      context
+        .index
+        .pending_events(usize::MAX)
+        .unwrap()
+        .into_iter()
+        .map(|(_event_id, event)| event)
+        .filter(|event| !matches!(
+          event,
+          Event::BlockStarted { .. } | Event::BlockCommitted { .. }
+        ))
+        .collect::<Vec<Event>>(),
+      expected,
+    );
+  }
+
   #[test]
   fn recover_from_3_block_deep_and_consecutive_reorg() {
     for mut context in Context::configurations() {
@@ -4713,577 +5544,821 @@ mod tests {
   }
 
   #[test]
-  fn inscription_without_parent_tag_has_no_parent_entry() {
-    for context in Context::configurations() {
-      context.mine_blocks(1);
+  fn content_index_stores_identical_bodies_once() {
+    let context = Context::builder().arg("--index-content").build();
+
+    context.mine_blocks(4);
+
+    let inscriptions = [
+      inscription("text/plain", "foo"),
+      inscription("text/html", "foo"),
+      inscription("text/plain", "bar"),
+      Inscription {
+        content_type: Some("text/plain".into()),
+        metadata: Some(vec![0xA0]),
+        ..default()
+      },
+    ];
+
+    let mut ids = Vec::new();
 
+    for (i, inscription) in inscriptions.iter().enumerate() {
       let txid = context.core.broadcast_tx(TransactionTemplate {
-        inputs: &[(1, 0, 0, inscription("text/plain", "hello").to_witness())],
+        inputs: &[(i + 1, 0, 0, inscription.to_witness())],
         ..default()
       });
 
-      context.mine_blocks(1);
+      ids.push(InscriptionId { txid, index: 0 });
+    }
 
-      let inscription_id = InscriptionId { txid, index: 0 };
+    context.mine_blocks(1);
 
-      assert!(context
-        .index
-        .get_inscription_entry(inscription_id)
-        .unwrap()
-        .unwrap()
-        .parents
-        .is_empty());
-    }
-  }
+    let rtx = context.index.database.begin_read().unwrap();
 
-  #[test]
-  fn inscription_with_parent_tag_without_parent_has_no_parent_entry() {
-    for context in Context::configurations() {
-      context.mine_blocks(1);
+    assert_eq!(
+      rtx.open_table(CONTENT_HASH_TO_BODY).unwrap().len().unwrap(),
+      2
+    );
 
-      let parent_txid = context.core.broadcast_tx(TransactionTemplate {
-        inputs: &[(1, 0, 0, inscription("text/plain", "hello").to_witness())],
-        ..default()
-      });
+    assert_eq!(
+      rtx
+        .open_table(SEQUENCE_NUMBER_TO_CONTENT)
+        .unwrap()
+        .len()
+        .unwrap(),
+      4
+    );
 
-      context.mine_blocks(1);
+    for (id, inscription) in ids.iter().zip(inscriptions) {
+      assert_eq!(
+        context.index.get_inscription_content(*id).unwrap(),
+        Some(inscription),
+      );
+    }
 
-      let parent_inscription_id = InscriptionId {
-        txid: parent_txid,
-        index: 0,
-      };
+    let hash = |body: &str| bitcoin::hashes::sha256::Hash::hash(body.as_bytes()).to_byte_array();
 
-      let txid = context.core.broadcast_tx(TransactionTemplate {
-        inputs: &[(
-          2,
-          0,
-          0,
-          Inscription {
-            content_type: Some("text/plain".into()),
-            body: Some("hello".into()),
-            parents: vec![parent_inscription_id.value()],
-            ..default()
-          }
-          .to_witness(),
-        )],
-        ..default()
-      });
+    assert_eq!(
+      context
+        .index
+        .get_inscriptions_by_content_hash_paginated(hash("foo"), 100, 0)
+        .unwrap(),
+      (vec![ids[0], ids[1]], false),
+    );
 
-      context.mine_blocks(1);
+    assert_eq!(
+      context
+        .index
+        .get_inscriptions_by_content_hash_paginated(hash("foo"), 1, 1)
+        .unwrap(),
+      (vec![ids[1]], false),
+    );
 
-      let inscription_id = InscriptionId { txid, index: 0 };
+    assert_eq!(
+      context
+        .index
+        .get_inscriptions_by_content_hash_paginated(hash("foo"), 100, usize::MAX)
+        .unwrap(),
+      (Vec::new(), false),
+    );
 
-      assert!(context
+    assert_eq!(
+      context
         .index
-        .get_inscription_entry(inscription_id)
-        .unwrap()
-        .unwrap()
-        .parents
-        .is_empty());
-    }
+        .get_inscriptions_by_content_hash_paginated(hash("baz"), 100, 0)
+        .unwrap(),
+      (Vec::new(), false),
+    );
   }
 
   #[test]
-  fn inscription_with_parent_tag_and_parent_has_parent_entry() {
-    for context in Context::configurations() {
-      context.mine_blocks(1);
+  fn content_is_not_stored_without_content_index() {
+    let context = Context::builder().build();
 
-      let parent_txid = context.core.broadcast_tx(TransactionTemplate {
-        inputs: &[(1, 0, 0, inscription("text/plain", "hello").to_witness())],
-        ..default()
-      });
-
-      context.mine_blocks(1);
-
-      let parent_inscription_id = InscriptionId {
-        txid: parent_txid,
-        index: 0,
-      };
-
-      let txid = context.core.broadcast_tx(TransactionTemplate {
-        inputs: &[(
-          2,
-          1,
-          0,
-          Inscription {
-            content_type: Some("text/plain".into()),
-            body: Some("hello".into()),
-            parents: vec![parent_inscription_id.value()],
-            ..default()
-          }
-          .to_witness(),
-        )],
-        ..default()
-      });
-
-      context.mine_blocks(1);
+    context.mine_blocks(1);
 
-      let inscription_id = InscriptionId { txid, index: 0 };
+    context.core.broadcast_tx(TransactionTemplate {
+      inputs: &[(1, 0, 0, inscription("text/plain", "foo").to_witness())],
+      ..default()
+    });
 
-      assert_eq!(
-        context.index.get_parents_by_inscription_id(inscription_id),
-        vec![parent_inscription_id]
-      );
+    context.mine_blocks(1);
 
-      assert_eq!(
-        context
-          .index
-          .get_children_by_inscription_id(parent_inscription_id)
-          .unwrap(),
-        vec![inscription_id]
-      );
-    }
+    assert_eq!(
+      context
+        .index
+        .database
+        .begin_read()
+        .unwrap()
+        .open_table(CONTENT_HASH_TO_BODY)
+        .unwrap()
+        .len()
+        .unwrap(),
+      0
+    );
   }
 
   #[test]
-  fn inscription_with_two_parent_tags_and_parents_has_parent_entries() {
-    for context in Context::configurations() {
-      context.mine_blocks(2);
+  fn search_inscriptions() {
+    let context = Context::builder().arg("--index-search").build();
+
+    context.mine_blocks(4);
+
+    let inscriptions = [
+      (inscription("text/plain", "a"), 300),
+      (inscription("text/html", "bbb"), 100),
+      (
+        Inscription {
+          content_type: Some("text/plain".into()),
+          body: Some("cc".into()),
+          metadata: Some(vec![0xA0]),
+          metaprotocol: Some("brc-20".into()),
+          ..default()
+        },
+        200,
+      ),
+    ];
 
-      let parent_txid_a = context.core.broadcast_tx(TransactionTemplate {
-        inputs: &[(1, 0, 0, inscription("text/plain", "hello").to_witness())],
-        ..default()
-      });
-      let parent_txid_b = context.core.broadcast_tx(TransactionTemplate {
-        inputs: &[(2, 0, 0, inscription("text/plain", "world").to_witness())],
+    let mut ids = Vec::new();
+
+    for (i, (inscription, fee)) in inscriptions.iter().enumerate() {
+      let txid = context.core.broadcast_tx(TransactionTemplate {
+        inputs: &[(i + 1, 0, 0, inscription.to_witness())],
+        fee: *fee,
         ..default()
       });
 
+      ids.push(InscriptionId { txid, index: 0 });
+
       context.mine_blocks(1);
+    }
 
-      let parent_inscription_id_a = InscriptionId {
-        txid: parent_txid_a,
-        index: 0,
-      };
-      let parent_inscription_id_b = InscriptionId {
-        txid: parent_txid_b,
-        index: 0,
-      };
+    let search = |search: InscriptionSearch| context.index.search_inscriptions(&search).unwrap();
 
-      let multi_parent_inscription = Inscription {
+    assert_eq!(
+      search(default()),
+      InscriptionSearchResults {
+        ids: vec![ids[2], ids[1], ids[0]],
+        next: None,
+      }
+    );
+
+    assert_eq!(
+      search(InscriptionSearch {
         content_type: Some("text/plain".into()),
-        body: Some("hello".into()),
-        parents: vec![
-          parent_inscription_id_a.value(),
-          parent_inscription_id_b.value(),
-        ],
+        order: Some(inscription_search::Order::Asc),
         ..default()
-      };
-      let multi_parent_witness = multi_parent_inscription.to_witness();
+      })
+      .ids,
+      [ids[0], ids[2]],
+    );
 
-      let revelation_input = (3, 1, 0, multi_parent_witness);
+    assert_eq!(
+      search(InscriptionSearch {
+        metaprotocol: Some("brc-20".into()),
+        metadata: Some(true),
+        ..default()
+      })
+      .ids,
+      [ids[2]],
+    );
 
-      let parent_b_input = (3, 2, 0, Witness::new());
+    assert_eq!(
+      search(InscriptionSearch {
+        metadata: Some(false),
+        delegate: Some(false),
+        ..default()
+      })
+      .ids,
+      [ids[1], ids[0]],
+    );
 
-      let txid = context.core.broadcast_tx(TransactionTemplate {
-        inputs: &[revelation_input, parent_b_input],
+    assert_eq!(
+      search(InscriptionSearch {
+        min_height: Some(6),
+        max_height: Some(7),
         ..default()
-      });
+      })
+      .ids,
+      [ids[2], ids[1]],
+    );
 
-      context.mine_blocks(1);
+    assert_eq!(
+      search(InscriptionSearch {
+        sort: Some(inscription_search::Sort::Fee),
+        ..default()
+      })
+      .ids,
+      [ids[0], ids[2], ids[1]],
+    );
 
-      let inscription_id = InscriptionId { txid, index: 0 };
+    assert_eq!(
+      search(InscriptionSearch {
+        sort: Some(inscription_search::Sort::Fee),
+        min_fee: Some(150),
+        max_fee: Some(250),
+        ..default()
+      })
+      .ids,
+      [ids[2]],
+    );
 
-      assert_eq!(
-        context.index.get_parents_by_inscription_id(inscription_id),
-        vec![parent_inscription_id_a, parent_inscription_id_b]
-      );
+    assert_eq!(
+      search(InscriptionSearch {
+        sort: Some(inscription_search::Sort::Size),
+        order: Some(inscription_search::Order::Asc),
+        ..default()
+      })
+      .ids,
+      [ids[0], ids[2], ids[1]],
+    );
 
-      assert_eq!(
-        context
-          .index
-          .get_children_by_inscription_id(parent_inscription_id_a)
-          .unwrap(),
-        vec![inscription_id]
-      );
-      assert_eq!(
-        context
-          .index
-          .get_children_by_inscription_id(parent_inscription_id_b)
-          .unwrap(),
-        vec![inscription_id]
-      );
-    }
+    let first = search(InscriptionSearch {
+      sort: Some(inscription_search::Sort::Fee),
+      limit: Some(2),
+      ..default()
+    });
+
+    assert_eq!(first.ids, [ids[0], ids[2]]);
+
+    assert_eq!(
+      search(InscriptionSearch {
+        sort: Some(inscription_search::Sort::Fee),
+        limit: Some(2),
+        cursor: first.next,
+        ..default()
+      }),
+      InscriptionSearchResults {
+        ids: vec![ids[1]],
+        next: None,
+      }
+    );
   }
 
   #[test]
-  fn inscription_with_repeated_parent_tags_and_parents_has_singular_parent_entry() {
-    for context in Context::configurations() {
-      context.mine_blocks(1);
+  fn full_text_search() {
+    let context = Context::builder().arg("--index-full-text").build();
 
-      let parent_txid = context.core.broadcast_tx(TransactionTemplate {
-        inputs: &[(1, 0, 0, inscription("text/plain", "hello").to_witness())],
-        ..default()
-      });
+    context.mine_blocks(5);
 
-      context.mine_blocks(1);
+    let inscriptions = [
+      inscription("text/plain", "hello world"),
+      inscription("text/markdown", "Hello, hello, HELLO!"),
+      inscription("application/json", r#"{"name":"world"}"#),
+      inscription("image/png", "hello"),
+    ];
 
-      let parent_inscription_id = InscriptionId {
-        txid: parent_txid,
-        index: 0,
-      };
+    let mut ids = Vec::new();
 
+    for (i, inscription) in inscriptions.iter().enumerate() {
       let txid = context.core.broadcast_tx(TransactionTemplate {
-        inputs: &[(
-          2,
-          1,
-          0,
-          Inscription {
-            content_type: Some("text/plain".into()),
-            body: Some("hello".into()),
-            parents: vec![parent_inscription_id.value(), parent_inscription_id.value()],
-            ..default()
-          }
-          .to_witness(),
-        )],
+        inputs: &[(i + 1, 0, 0, inscription.to_witness())],
         ..default()
       });
 
+      ids.push(InscriptionId { txid, index: 0 });
+
       context.mine_blocks(1);
+    }
 
-      let inscription_id = InscriptionId { txid, index: 0 };
+    let search = |query, page_size, page_index| {
+      let (results, more) = context
+        .index
+        .search_full_text(query, page_size, page_index)
+        .unwrap();
 
-      assert_eq!(
-        context.index.get_parents_by_inscription_id(inscription_id),
-        vec![parent_inscription_id]
-      );
+      (
+        results
+          .into_iter()
+          .map(|result| (result.id, result.snippet))
+          .collect::<Vec<(InscriptionId, String)>>(),
+        more,
+      )
+    };
 
-      assert_eq!(
-        context
-          .index
-          .get_children_by_inscription_id(parent_inscription_id)
-          .unwrap(),
-        vec![inscription_id]
-      );
-    }
+    assert_eq!(
+      search("HELLO", 100, 0),
+      (
+        vec![
+          (ids[1], "Hello, hello, HELLO!".into()),
+          (ids[0], "hello world".into()),
+        ],
+        false,
+      ),
+    );
+
+    assert_eq!(
+      search("world", 1, 0),
+      (vec![(ids[2], r#"{"name":"world"}"#.into())], true),
+    );
+
+    assert_eq!(
+      search("world", 1, 1),
+      (vec![(ids[0], "hello world".into())], false),
+    );
+
+    assert_eq!(search("goodbye", 100, 0), (Vec::new(), false));
+
+    assert_eq!(search("", 100, 0), (Vec::new(), false));
+
+    assert_eq!(context.index.statistic(Statistic::FullTextTerms), 2 + 3 + 2,);
   }
 
   #[test]
-  fn inscription_with_distinct_parent_tag_encodings_for_same_parent_has_singular_parent_entry() {
-    for context in Context::configurations() {
-      context.mine_blocks(1);
+  fn full_text_is_not_indexed_without_full_text_index() {
+    let context = Context::builder().build();
 
-      let parent_txid = context.core.broadcast_tx(TransactionTemplate {
-        inputs: &[(1, 0, 0, inscription("text/plain", "hello").to_witness())],
-        ..default()
-      });
+    context.mine_blocks(1);
 
-      context.mine_blocks(1);
+    context.core.broadcast_tx(TransactionTemplate {
+      inputs: &[(1, 0, 0, inscription("text/plain", "hello").to_witness())],
+      ..default()
+    });
 
-      let parent_inscription_id = InscriptionId {
-        txid: parent_txid,
-        index: 0,
-      };
+    context.mine_blocks(1);
 
-      let trailing_zero_inscription_id: Vec<u8> = parent_inscription_id
-        .value()
-        .into_iter()
-        .chain(vec![0, 0, 0, 0])
-        .collect();
+    assert!(!context.index.has_full_text_index());
 
-      let txid = context.core.broadcast_tx(TransactionTemplate {
-        inputs: &[(
-          2,
-          1,
-          0,
-          Inscription {
-            content_type: Some("text/plain".into()),
-            body: Some("hello".into()),
-            parents: vec![parent_inscription_id.value(), trailing_zero_inscription_id],
-            ..default()
-          }
-          .to_witness(),
-        )],
-        ..default()
-      });
+    assert_eq!(
+      context.index.search_full_text("hello", 100, 0).unwrap().0,
+      Vec::new(),
+    );
+  }
 
-      context.mine_blocks(1);
+  #[test]
+  fn metaprotocol_token() {
+    let context = Context::builder()
+      .arg("--index-metaprotocol=token")
+      .event_outbox()
+      .build();
 
-      let inscription_id = InscriptionId { txid, index: 0 };
+    context.mine_blocks(3);
 
-      assert_eq!(
-        context.index.get_parents_by_inscription_id(inscription_id),
-        vec![parent_inscription_id]
-      );
-
-      assert_eq!(
-        context
-          .index
-          .get_children_by_inscription_id(parent_inscription_id)
-          .unwrap(),
-        vec![inscription_id]
-      );
-    }
-  }
+    let payloads = [
+      r#"{"p":"token","op":"deploy","tick":"ORDI","max":"21000","lim":"1000"}"#,
+      r#"{"p":"token","op":"mint","tick":"ordi","amt":"1000"}"#,
+      r#"{"p":"token","op":"transfer","tick":"ordi","amt":"400"}"#,
+    ];
 
-  #[test]
-  fn inscription_with_three_parent_tags_and_two_parents_has_two_parent_entries() {
-    for context in Context::configurations() {
-      context.mine_blocks(3);
+    let mut txids = Vec::new();
 
-      let parent_txid_a = context.core.broadcast_tx(TransactionTemplate {
-        inputs: &[(1, 0, 0, inscription("text/plain", "hello").to_witness())],
-        ..default()
-      });
-      let parent_txid_b = context.core.broadcast_tx(TransactionTemplate {
-        inputs: &[(2, 0, 0, inscription("text/plain", "world").to_witness())],
-        ..default()
-      });
-      let parent_txid_c = context.core.broadcast_tx(TransactionTemplate {
-        inputs: &[(3, 0, 0, inscription("text/plain", "wazzup").to_witness())],
+    for (i, payload) in payloads.iter().enumerate() {
+      txids.push(context.core.broadcast_tx(TransactionTemplate {
+        inputs: &[(i + 1, 0, 0, inscription("text/plain", payload).to_witness())],
         ..default()
-      });
+      }));
 
       context.mine_blocks(1);
+    }
 
-      let parent_inscription_id_a = InscriptionId {
-        txid: parent_txid_a,
-        index: 0,
-      };
-      let parent_inscription_id_b = InscriptionId {
-        txid: parent_txid_b,
-        index: 0,
-      };
-      let parent_inscription_id_c = InscriptionId {
-        txid: parent_txid_c,
-        index: 0,
-      };
+    let send = context.core.broadcast_tx(TransactionTemplate {
+      inputs: &[(6, 1, 0, Default::default())],
+      p2tr: true,
+      ..default()
+    });
 
-      let multi_parent_inscription = Inscription {
-        content_type: Some("text/plain".into()),
-        body: Some("hello".into()),
-        parents: vec![
-          parent_inscription_id_a.value(),
-          parent_inscription_id_b.value(),
-          parent_inscription_id_c.value(),
-        ],
-        ..default()
-      };
-      let multi_parent_witness = multi_parent_inscription.to_witness();
+    context.mine_blocks(1);
 
-      let revealing_parent_a_input = (4, 1, 0, multi_parent_witness);
+    let sender = context.core.address(OutPoint {
+      txid: txids[2],
+      vout: 0,
+    });
 
-      let parent_c_input = (4, 3, 0, Witness::new());
+    let receiver = context.core.address(OutPoint {
+      txid: send,
+      vout: 0,
+    });
 
-      let txid = context.core.broadcast_tx(TransactionTemplate {
-        inputs: &[revealing_parent_a_input, parent_c_input],
-        ..default()
-      });
+    let route = |path: &[&str]| {
+      context
+        .index
+        .metaprotocol_route("token", path)
+        .unwrap()
+        .unwrap()
+    };
 
-      context.mine_blocks(1);
+    assert_eq!(
+      route(&["token", "ordi"]).unwrap()["minted"],
+      serde_json::json!("1000"),
+    );
 
-      let inscription_id = InscriptionId { txid, index: 0 };
+    assert_eq!(
+      route(&["balances", &sender.to_string()]),
+      Some(serde_json::json!({
+        "ordi": {
+          "available": "600",
+          "transferable": "0",
+        },
+      })),
+    );
 
-      assert_eq!(
-        context.index.get_parents_by_inscription_id(inscription_id),
-        vec![parent_inscription_id_a, parent_inscription_id_c]
-      );
+    assert_eq!(
+      route(&["balances", &receiver.to_string()]),
+      Some(serde_json::json!({
+        "ordi": {
+          "available": "400",
+          "transferable": "0",
+        },
+      })),
+    );
 
-      assert_eq!(
-        context
-          .index
-          .get_children_by_inscription_id(parent_inscription_id_a)
-          .unwrap(),
-        vec![inscription_id]
-      );
-      assert_eq!(
-        context
-          .index
-          .get_children_by_inscription_id(parent_inscription_id_b)
-          .unwrap(),
-        Vec::new()
-      );
-      assert_eq!(
-        context
-          .index
-          .get_children_by_inscription_id(parent_inscription_id_c)
-          .unwrap(),
-        vec![inscription_id]
-      );
-    }
+    assert_eq!(
+      context
+        .index
+        .pending_events(usize::MAX)
+        .unwrap()
+        .into_iter()
+        .filter_map(|(_, event)| match event {
+          Event::MetaprotocolEvent { kind, protocol, .. } => Some((protocol, kind)),
+          _ => None,
+        })
+        .collect::<Vec<(String, String)>>(),
+      [
+        ("token".to_string(), "deploy".to_string()),
+        ("token".into(), "mint".into()),
+        ("token".into(), "inscribe-transfer".into()),
+        ("token".into(), "transfer".into()),
+      ],
+    );
+
+    assert_eq!(
+      context
+        .index
+        .metaprotocol_route("foo", &["tokens"])
+        .unwrap(),
+      None
+    );
   }
 
   #[test]
-  fn inscription_with_valid_and_malformed_parent_tags_only_lists_valid_entries() {
-    for context in Context::configurations() {
-      context.mine_blocks(3);
+  fn metaprotocol_token_transfer_spent_as_fee_returns_to_sender() {
+    let context = Context::builder().arg("--index-metaprotocol=token").build();
 
-      let parent_txid_a = context.core.broadcast_tx(TransactionTemplate {
-        inputs: &[(1, 0, 0, inscription("text/plain", "hello").to_witness())],
-        ..default()
-      });
-      let parent_txid_b = context.core.broadcast_tx(TransactionTemplate {
-        inputs: &[(2, 0, 0, inscription("text/plain", "world").to_witness())],
-        ..default()
-      });
-      let parent_txid_c = context.core.broadcast_tx(TransactionTemplate {
-        inputs: &[(3, 0, 0, inscription("text/plain", "wazzup").to_witness())],
+    context.mine_blocks(3);
+
+    let payloads = [
+      r#"{"p":"token","op":"deploy","tick":"ORDI","max":"21000","lim":"1000"}"#,
+      r#"{"p":"token","op":"mint","tick":"ordi","amt":"1000"}"#,
+      r#"{"p":"token","op":"transfer","tick":"ordi","amt":"400"}"#,
+    ];
+
+    let mut txids = Vec::new();
+
+    for (i, payload) in payloads.iter().enumerate() {
+      txids.push(context.core.broadcast_tx(TransactionTemplate {
+        inputs: &[(i + 1, 0, 0, inscription("text/plain", payload).to_witness())],
         ..default()
-      });
+      }));
 
       context.mine_blocks(1);
+    }
 
-      let parent_inscription_id_a = InscriptionId {
-        txid: parent_txid_a,
-        index: 0,
-      };
-      let parent_inscription_id_b = InscriptionId {
-        txid: parent_txid_b,
-        index: 0,
-      };
-      let parent_inscription_id_c = InscriptionId {
-        txid: parent_txid_c,
+    context.core.broadcast_tx(TransactionTemplate {
+      inputs: &[(6, 1, 0, Default::default())],
+      fee: 50 * COIN_VALUE,
+      ..default()
+    });
+
+    let coinbase = context.mine_blocks(1)[0].txdata[0].txid();
+
+    context.index.assert_inscription_location(
+      InscriptionId {
+        txid: txids[2],
         index: 0,
-      };
+      },
+      SatPoint {
+        outpoint: OutPoint {
+          txid: coinbase,
+          vout: 0,
+        },
+        offset: 50 * COIN_VALUE,
+      },
+      None,
+    );
 
-      let malformed_inscription_id_b = parent_inscription_id_b
-        .value()
-        .into_iter()
-        .chain(iter::once(0))
-        .collect();
+    let sender = context.core.address(OutPoint {
+      txid: txids[2],
+      vout: 0,
+    });
 
-      let multi_parent_inscription = Inscription {
-        content_type: Some("text/plain".into()),
-        body: Some("hello".into()),
-        parents: vec![
-          parent_inscription_id_a.value(),
-          malformed_inscription_id_b,
-          parent_inscription_id_c.value(),
-        ],
-        ..default()
-      };
-      let multi_parent_witness = multi_parent_inscription.to_witness();
+    assert_eq!(
+      context
+        .index
+        .metaprotocol_route("token", &["balances", &sender.to_string()])
+        .unwrap()
+        .unwrap(),
+      Some(serde_json::json!({
+        "ordi": {
+          "available": "1000",
+          "transferable": "0",
+        },
+      })),
+    );
+  }
 
-      let revealing_parent_a_input = (4, 1, 0, multi_parent_witness);
-      let parent_b_input = (4, 2, 0, Witness::new());
-      let parent_c_input = (4, 3, 0, Witness::new());
+  #[test]
+  fn unknown_metaprotocols_are_rejected() {
+    assert_eq!(
+      Context::builder()
+        .arg("--index-metaprotocol=foo")
+        .try_build()
+        .err()
+        .unwrap()
+        .to_string(),
+      "unknown metaprotocol `foo`",
+    );
+  }
 
-      let txid = context.core.broadcast_tx(TransactionTemplate {
-        inputs: &[revealing_parent_a_input, parent_b_input, parent_c_input],
-        ..default()
-      });
+  #[test]
+  fn search_inscriptions_by_rarity() {
+    let context = Context::builder()
+      .args(["--index-sats", "--index-search"])
+      .build();
 
-      context.mine_blocks(1);
+    context.mine_blocks(1);
 
-      let inscription_id = InscriptionId { txid, index: 0 };
+    let txid = context.core.broadcast_tx(TransactionTemplate {
+      inputs: &[(1, 0, 0, inscription("text/plain", "foo").to_witness())],
+      ..default()
+    });
 
-      assert_eq!(
-        context.index.get_parents_by_inscription_id(inscription_id),
-        vec![parent_inscription_id_a, parent_inscription_id_c]
-      );
+    context.mine_blocks(1);
 
-      assert_eq!(
-        context
-          .index
-          .get_children_by_inscription_id(parent_inscription_id_a)
-          .unwrap(),
-        vec![inscription_id]
-      );
-      assert_eq!(
-        context
-          .index
-          .get_children_by_inscription_id(parent_inscription_id_b)
-          .unwrap(),
-        Vec::new()
-      );
-      assert_eq!(
-        context
-          .index
-          .get_children_by_inscription_id(parent_inscription_id_c)
-          .unwrap(),
-        vec![inscription_id]
-      );
-    }
+    let search = |rarity| {
+      context
+        .index
+        .search_inscriptions(&InscriptionSearch {
+          rarity: Some(DeserializeFromStr(rarity)),
+          ..default()
+        })
+        .unwrap()
+        .ids
+    };
+
+    assert_eq!(search(Rarity::Uncommon), [InscriptionId { txid, index: 0 }]);
+    assert_eq!(search(Rarity::Common), []);
   }
 
   #[test]
-  fn parents_can_be_in_preceding_input() {
-    for context in Context::configurations() {
-      context.mine_blocks(1);
-
-      let parent_txid = context.core.broadcast_tx(TransactionTemplate {
-        inputs: &[(1, 0, 0, inscription("text/plain", "hello").to_witness())],
-        ..default()
-      });
+  fn search_inscriptions_by_parent() {
+    let context = Context::builder().arg("--index-search").build();
 
-      context.mine_blocks(2);
+    context.mine_blocks(1);
 
-      let parent_inscription_id = InscriptionId {
-        txid: parent_txid,
+    let parent_txid = context.core.broadcast_tx(TransactionTemplate {
+      inputs: &[(1, 0, 0, inscription("text/plain", "parent").to_witness())],
+      ..default()
+    });
+
+    let parent = InscriptionId {
+      txid: parent_txid,
+      index: 0,
+    };
+
+    context.mine_blocks(1);
+
+    let child_txid = context.core.broadcast_tx(TransactionTemplate {
+      inputs: &[(
+        2,
+        1,
+        0,
+        Inscription {
+          parents: vec![parent.value()],
+          ..inscription("text/plain", "child")
+        }
+        .to_witness(),
+      )],
+      ..default()
+    });
+
+    context.mine_blocks(1);
+
+    assert_eq!(
+      context
+        .index
+        .search_inscriptions(&InscriptionSearch {
+          parent: Some(DeserializeFromStr(parent)),
+          ..default()
+        })
+        .unwrap()
+        .ids,
+      [InscriptionId {
+        txid: child_txid,
         index: 0,
+      }],
+    );
+  }
+
+  #[test]
+  fn search_inscriptions_requires_indices() {
+    let context = Context::builder().build();
+
+    assert_eq!(
+      InscriptionSearch::default()
+        .validate(&context.index)
+        .unwrap_err(),
+      inscription_search::InvalidSearch::MissingIndex("search"),
+    );
+
+    let context = Context::builder().arg("--index-search").build();
+
+    InscriptionSearch::default()
+      .validate(&context.index)
+      .unwrap();
+
+    assert_eq!(
+      InscriptionSearch {
+        rarity: Some(DeserializeFromStr(Rarity::Uncommon)),
+        ..default()
+      }
+      .validate(&context.index)
+      .unwrap_err(),
+      inscription_search::InvalidSearch::MissingIndex("sat"),
+    );
+  }
+
+  #[test]
+  fn undo_log_rolls_back_to_fork_point() {
+    for args in [
+      &[][..],
+      &["--index-sats"],
+      &[
+        "--index-runes",
+        "--index-addresses",
+        "--index-transfers",
+        "--index-content",
+        "--index-full-text",
+        "--index-metaprotocol=token",
+        "--index-search",
+      ],
+    ] {
+      let context = Context::builder()
+        .args(["--reorg-rollback", "undo-log"])
+        .args(args)
+        .build();
+
+      context.mine_blocks(1);
+
+      let txid = context.core.broadcast_tx(TransactionTemplate {
+        inputs: &[(1, 0, 0, inscription("text/plain", "hello").to_witness())],
+        ..default()
+      });
+
+      let first_id = InscriptionId { txid, index: 0 };
+      let first_location = SatPoint {
+        outpoint: OutPoint { txid, vout: 0 },
+        offset: 0,
       };
 
+      context.mine_blocks(25);
+
+      context.core.broadcast_tx(TransactionTemplate {
+        inputs: &[(2, 1, 0, Default::default())],
+        ..default()
+      });
+
       let txid = context.core.broadcast_tx(TransactionTemplate {
-        inputs: &[
-          (2, 1, 0, Default::default()),
-          (
-            3,
-            0,
-            0,
-            Inscription {
-              content_type: Some("text/plain".into()),
-              body: Some("hello".into()),
-              parents: vec![parent_inscription_id.value()],
-              ..default()
-            }
-            .to_witness(),
-          ),
-        ],
+        inputs: &[(
+          3,
+          0,
+          0,
+          inscription(
+            "text/plain",
+            r#"{"p":"token","op":"deploy","tick":"world","max":"1"}"#,
+          )
+          .to_witness(),
+        )],
         ..default()
       });
 
+      let second_id = InscriptionId { txid, index: 0 };
+
       context.mine_blocks(1);
 
-      let inscription_id = InscriptionId { txid, index: 0 };
+      assert!(context.index.inscription_exists(second_id).unwrap());
 
-      assert_eq!(
-        context.index.get_parents_by_inscription_id(inscription_id),
-        vec![parent_inscription_id]
-      );
+      for _ in 0..20 {
+        context.core.invalidate_tip();
+      }
 
-      assert_eq!(
-        context
+      context.mine_blocks(22);
+
+      assert!(!context.index.inscription_exists(second_id).unwrap());
+
+      context
+        .index
+        .assert_inscription_location(first_id, first_location, Some(50 * COIN_VALUE));
+
+      let fresh = Index::open(
+        &context
           .index
-          .get_children_by_inscription_id(parent_inscription_id)
-          .unwrap(),
-        vec![inscription_id]
-      );
+          .settings
+          .clone()
+          .with_index(context.tempdir.path().join("fresh.redb")),
+      )
+      .unwrap();
+
+      fresh.update().unwrap();
+
+      let table_lengths = |index: &Index| {
+        let rtx = index.database.begin_read().unwrap();
+
+        let mut lengths = BTreeMap::new();
+
+        for table in rtx.list_tables().unwrap() {
+          if [
+            HEIGHT_TO_UNDO_LOG.name(),
+            STATISTIC_TO_COUNT.name(),
+            WRITE_TRANSACTION_STARTING_BLOCK_COUNT_TO_TIMESTAMP.name(),
+          ]
+          .contains(&table.name())
+          {
+            continue;
+          }
+
+          lengths.insert(
+            table.name().to_string(),
+            rtx.open_untyped_table(table).unwrap().len().unwrap(),
+          );
+        }
+
+        for table in rtx.list_multimap_tables().unwrap() {
+          lengths.insert(
+            table.name().to_string(),
+            rtx
+              .open_untyped_multimap_table(table)
+              .unwrap()
+              .len()
+              .unwrap(),
+          );
+        }
+
+        lengths
+      };
+
+      assert_eq!(table_lengths(&context.index), table_lengths(&fresh));
+
+      for statistic in [
+        Statistic::BlessedInscriptions,
+        Statistic::CursedInscriptions,
+        Statistic::LostSats,
+        Statistic::OutputsTraversed,
+        Statistic::Runes,
+        Statistic::SatRanges,
+        Statistic::UnboundInscriptions,
+      ] {
+        assert_eq!(
+          context.index.statistic(statistic),
+          fresh.statistic(statistic),
+        );
+      }
     }
   }
 
   #[test]
-  fn parents_can_be_in_following_input() {
-    for context in Context::configurations() {
-      context.mine_blocks(1);
+  fn undo_logs_are_kept_for_undo_log_depth_blocks() {
+    let context = Context::builder()
+      .args(["--reorg-rollback", "undo-log", "--undo-log-depth", "3"])
+      .build();
 
-      let parent_txid = context.core.broadcast_tx(TransactionTemplate {
-        inputs: &[(1, 0, 0, inscription("text/plain", "hello").to_witness())],
-        ..default()
-      });
+    context.mine_blocks(10);
 
-      context.mine_blocks(2);
+    assert_eq!(
+      UndoLog::blocks(&context.index.begin_read().unwrap(), 11).unwrap(),
+      3
+    );
 
-      let parent_inscription_id = InscriptionId {
-        txid: parent_txid,
-        index: 0,
-      };
+    for _ in 0..3 {
+      context.core.invalidate_tip();
+    }
+
+    context.mine_blocks(4);
+
+    assert_eq!(context.index.block_count().unwrap(), 12);
+
+    for _ in 0..4 {
+      context.core.invalidate_tip();
+    }
+
+    context.mine_blocks_with_update(5, false);
+
+    assert_eq!(
+      context
+        .index
+        .update()
+        .unwrap_err()
+        .downcast_ref::<reorg::Error>(),
+      Some(&reorg::Error::Unrecoverable),
+    );
+  }
+
+  #[test]
+  fn inscription_without_parent_tag_has_no_parent_entry() {
+    for context in Context::configurations() {
+      context.mine_blocks(1);
 
       let txid = context.core.broadcast_tx(TransactionTemplate {
-        inputs: &[
-          (
-            3,
-            0,
-            0,
-            Inscription {
-              content_type: Some("text/plain".into()),
-              body: Some("hello".into()),
-              parents: vec![parent_inscription_id.value()],
-              ..default()
-            }
-            .to_witness(),
-          ),
-          (2, 1, 0, Default::default()),
-        ],
+        inputs: &[(1, 0, 0, inscription("text/plain", "hello").to_witness())],
         ..default()
       });
 
@@ -5291,23 +6366,18 @@ mod tests {
 
       let inscription_id = InscriptionId { txid, index: 0 };
 
-      assert_eq!(
-        context.index.get_parents_by_inscription_id(inscription_id),
-        vec![parent_inscription_id]
-      );
-
-      assert_eq!(
-        context
-          .index
-          .get_children_by_inscription_id(parent_inscription_id)
-          .unwrap(),
-        vec![inscription_id]
-      );
+      assert!(context
+        .index
+        .get_inscription_entry(inscription_id)
+        .unwrap()
+        .unwrap()
+        .parents
+        .is_empty());
     }
   }
 
   #[test]
-  fn inscription_with_invalid_parent_tag_and_parent_has_no_parent_entry() {
+  fn inscription_with_parent_tag_without_parent_has_no_parent_entry() {
     for context in Context::configurations() {
       context.mine_blocks(1);
 
@@ -5326,16 +6396,12 @@ mod tests {
       let txid = context.core.broadcast_tx(TransactionTemplate {
         inputs: &[(
           2,
-          1,
+          0,
           0,
           Inscription {
             content_type: Some("text/plain".into()),
             body: Some("hello".into()),
-            parents: vec![parent_inscription_id
-              .value()
-              .into_iter()
-              .chain(iter::once(0))
-              .collect()],
+            parents: vec![parent_inscription_id.value()],
             ..default()
           }
           .to_witness(),
@@ -5358,19 +6424,35 @@ mod tests {
   }
 
   #[test]
-  fn inscription_with_pointer() {
+  fn inscription_with_parent_tag_and_parent_has_parent_entry() {
     for context in Context::configurations() {
       context.mine_blocks(1);
 
-      let inscription = Inscription {
-        content_type: Some("text/plain".into()),
-        body: Some("hello".into()),
-        pointer: Some(100u64.to_le_bytes().to_vec()),
+      let parent_txid = context.core.broadcast_tx(TransactionTemplate {
+        inputs: &[(1, 0, 0, inscription("text/plain", "hello").to_witness())],
         ..default()
+      });
+
+      context.mine_blocks(1);
+
+      let parent_inscription_id = InscriptionId {
+        txid: parent_txid,
+        index: 0,
       };
 
       let txid = context.core.broadcast_tx(TransactionTemplate {
-        inputs: &[(1, 0, 0, inscription.to_witness())],
+        inputs: &[(
+          2,
+          1,
+          0,
+          Inscription {
+            content_type: Some("text/plain".into()),
+            body: Some("hello".into()),
+            parents: vec![parent_inscription_id.value()],
+            ..default()
+          }
+          .to_witness(),
+        )],
         ..default()
       });
 
@@ -5378,31 +6460,63 @@ mod tests {
 
       let inscription_id = InscriptionId { txid, index: 0 };
 
-      context.index.assert_inscription_location(
-        inscription_id,
-        SatPoint {
-          outpoint: OutPoint { txid, vout: 0 },
-          offset: 100,
-        },
-        Some(50 * COIN_VALUE + 100),
+      assert_eq!(
+        context.index.get_parents_by_inscription_id(inscription_id),
+        vec![parent_inscription_id]
+      );
+
+      assert_eq!(
+        context
+          .index
+          .get_children_by_inscription_id(parent_inscription_id)
+          .unwrap(),
+        vec![inscription_id]
       );
     }
   }
 
   #[test]
-  fn inscription_with_pointer_greater_than_output_value_assigned_default() {
+  fn inscription_with_two_parent_tags_and_parents_has_parent_entries() {
     for context in Context::configurations() {
+      context.mine_blocks(2);
+
+      let parent_txid_a = context.core.broadcast_tx(TransactionTemplate {
+        inputs: &[(1, 0, 0, inscription("text/plain", "hello").to_witness())],
+        ..default()
+      });
+      let parent_txid_b = context.core.broadcast_tx(TransactionTemplate {
+        inputs: &[(2, 0, 0, inscription("text/plain", "world").to_witness())],
+        ..default()
+      });
+
       context.mine_blocks(1);
 
-      let inscription = Inscription {
+      let parent_inscription_id_a = InscriptionId {
+        txid: parent_txid_a,
+        index: 0,
+      };
+      let parent_inscription_id_b = InscriptionId {
+        txid: parent_txid_b,
+        index: 0,
+      };
+
+      let multi_parent_inscription = Inscription {
         content_type: Some("text/plain".into()),
         body: Some("hello".into()),
-        pointer: Some((50 * COIN_VALUE).to_le_bytes().to_vec()),
+        parents: vec![
+          parent_inscription_id_a.value(),
+          parent_inscription_id_b.value(),
+        ],
         ..default()
       };
+      let multi_parent_witness = multi_parent_inscription.to_witness();
+
+      let revelation_input = (3, 1, 0, multi_parent_witness);
+
+      let parent_b_input = (3, 2, 0, Witness::new());
 
       let txid = context.core.broadcast_tx(TransactionTemplate {
-        inputs: &[(1, 0, 0, inscription.to_witness())],
+        inputs: &[revelation_input, parent_b_input],
         ..default()
       });
 
@@ -5410,32 +6524,58 @@ mod tests {
 
       let inscription_id = InscriptionId { txid, index: 0 };
 
-      context.index.assert_inscription_location(
-        inscription_id,
-        SatPoint {
-          outpoint: OutPoint { txid, vout: 0 },
-          offset: 0,
-        },
-        Some(50 * COIN_VALUE),
+      assert_eq!(
+        context.index.get_parents_by_inscription_id(inscription_id),
+        vec![parent_inscription_id_a, parent_inscription_id_b]
+      );
+
+      assert_eq!(
+        context
+          .index
+          .get_children_by_inscription_id(parent_inscription_id_a)
+          .unwrap(),
+        vec![inscription_id]
+      );
+      assert_eq!(
+        context
+          .index
+          .get_children_by_inscription_id(parent_inscription_id_b)
+          .unwrap(),
+        vec![inscription_id]
       );
     }
   }
 
   #[test]
-  fn inscription_with_pointer_into_fee_ignored_and_assigned_default_location() {
+  fn inscription_with_repeated_parent_tags_and_parents_has_singular_parent_entry() {
     for context in Context::configurations() {
       context.mine_blocks(1);
 
-      let inscription = Inscription {
-        content_type: Some("text/plain".into()),
-        body: Some("hello".into()),
-        pointer: Some((25 * COIN_VALUE).to_le_bytes().to_vec()),
+      let parent_txid = context.core.broadcast_tx(TransactionTemplate {
+        inputs: &[(1, 0, 0, inscription("text/plain", "hello").to_witness())],
         ..default()
+      });
+
+      context.mine_blocks(1);
+
+      let parent_inscription_id = InscriptionId {
+        txid: parent_txid,
+        index: 0,
       };
 
       let txid = context.core.broadcast_tx(TransactionTemplate {
-        inputs: &[(1, 0, 0, inscription.to_witness())],
-        fee: 25 * COIN_VALUE,
+        inputs: &[(
+          2,
+          1,
+          0,
+          Inscription {
+            content_type: Some("text/plain".into()),
+            body: Some("hello".into()),
+            parents: vec![parent_inscription_id.value(), parent_inscription_id.value()],
+            ..default()
+          }
+          .to_witness(),
+        )],
         ..default()
       });
 
@@ -5443,31 +6583,57 @@ mod tests {
 
       let inscription_id = InscriptionId { txid, index: 0 };
 
-      context.index.assert_inscription_location(
-        inscription_id,
-        SatPoint {
-          outpoint: OutPoint { txid, vout: 0 },
-          offset: 0,
-        },
-        Some(50 * COIN_VALUE),
+      assert_eq!(
+        context.index.get_parents_by_inscription_id(inscription_id),
+        vec![parent_inscription_id]
+      );
+
+      assert_eq!(
+        context
+          .index
+          .get_children_by_inscription_id(parent_inscription_id)
+          .unwrap(),
+        vec![inscription_id]
       );
     }
   }
 
   #[test]
-  fn inscription_with_pointer_is_cursed() {
+  fn inscription_with_distinct_parent_tag_encodings_for_same_parent_has_singular_parent_entry() {
     for context in Context::configurations() {
       context.mine_blocks(1);
 
-      let inscription = Inscription {
-        content_type: Some("text/plain".into()),
-        body: Some("pointer-child".into()),
-        pointer: Some(0u64.to_le_bytes().to_vec()),
+      let parent_txid = context.core.broadcast_tx(TransactionTemplate {
+        inputs: &[(1, 0, 0, inscription("text/plain", "hello").to_witness())],
         ..default()
+      });
+
+      context.mine_blocks(1);
+
+      let parent_inscription_id = InscriptionId {
+        txid: parent_txid,
+        index: 0,
       };
 
+      let trailing_zero_inscription_id: Vec<u8> = parent_inscription_id
+        .value()
+        .into_iter()
+        .chain(vec![0, 0, 0, 0])
+        .collect();
+
       let txid = context.core.broadcast_tx(TransactionTemplate {
-        inputs: &[(1, 0, 0, inscription.to_witness())],
+        inputs: &[(
+          2,
+          1,
+          0,
+          Inscription {
+            content_type: Some("text/plain".into()),
+            body: Some("hello".into()),
+            parents: vec![parent_inscription_id.value(), trailing_zero_inscription_id],
+            ..default()
+          }
+          .to_witness(),
+        )],
         ..default()
       });
 
@@ -5475,805 +6641,2303 @@ mod tests {
 
       let inscription_id = InscriptionId { txid, index: 0 };
 
-      context.index.assert_inscription_location(
-        inscription_id,
-        SatPoint {
-          outpoint: OutPoint { txid, vout: 0 },
-          offset: 0,
-        },
-        Some(50 * COIN_VALUE),
+      assert_eq!(
+        context.index.get_parents_by_inscription_id(inscription_id),
+        vec![parent_inscription_id]
       );
 
-      assert_eq!(context.index.inscription_number(inscription_id), -1);
+      assert_eq!(
+        context
+          .index
+          .get_children_by_inscription_id(parent_inscription_id)
+          .unwrap(),
+        vec![inscription_id]
+      );
     }
   }
 
   #[test]
-  fn inscription_with_pointer_to_parent_is_cursed_reinscription() {
+  fn inscription_with_three_parent_tags_and_two_parents_has_two_parent_entries() {
     for context in Context::configurations() {
-      context.mine_blocks(1);
+      context.mine_blocks(3);
 
-      let parent_txid = context.core.broadcast_tx(TransactionTemplate {
-        inputs: &[(1, 0, 0, inscription("text/plain", "parent").to_witness())],
+      let parent_txid_a = context.core.broadcast_tx(TransactionTemplate {
+        inputs: &[(1, 0, 0, inscription("text/plain", "hello").to_witness())],
+        ..default()
+      });
+      let parent_txid_b = context.core.broadcast_tx(TransactionTemplate {
+        inputs: &[(2, 0, 0, inscription("text/plain", "world").to_witness())],
+        ..default()
+      });
+      let parent_txid_c = context.core.broadcast_tx(TransactionTemplate {
+        inputs: &[(3, 0, 0, inscription("text/plain", "wazzup").to_witness())],
         ..default()
       });
 
       context.mine_blocks(1);
 
-      let parent_inscription_id = InscriptionId {
-        txid: parent_txid,
+      let parent_inscription_id_a = InscriptionId {
+        txid: parent_txid_a,
+        index: 0,
+      };
+      let parent_inscription_id_b = InscriptionId {
+        txid: parent_txid_b,
+        index: 0,
+      };
+      let parent_inscription_id_c = InscriptionId {
+        txid: parent_txid_c,
         index: 0,
       };
 
-      let child_inscription = Inscription {
+      let multi_parent_inscription = Inscription {
         content_type: Some("text/plain".into()),
-        body: Some("pointer-child".into()),
-        parents: vec![parent_inscription_id.value()],
-        pointer: Some(0u64.to_le_bytes().to_vec()),
+        body: Some("hello".into()),
+        parents: vec![
+          parent_inscription_id_a.value(),
+          parent_inscription_id_b.value(),
+          parent_inscription_id_c.value(),
+        ],
         ..default()
       };
+      let multi_parent_witness = multi_parent_inscription.to_witness();
+
+      let revealing_parent_a_input = (4, 1, 0, multi_parent_witness);
+
+      let parent_c_input = (4, 3, 0, Witness::new());
 
       let txid = context.core.broadcast_tx(TransactionTemplate {
-        inputs: &[(2, 1, 0, child_inscription.to_witness())],
+        inputs: &[revealing_parent_a_input, parent_c_input],
         ..default()
       });
 
       context.mine_blocks(1);
 
-      let child_inscription_id = InscriptionId { txid, index: 0 };
+      let inscription_id = InscriptionId { txid, index: 0 };
 
-      context.index.assert_inscription_location(
-        parent_inscription_id,
-        SatPoint {
-          outpoint: OutPoint { txid, vout: 0 },
-          offset: 0,
-        },
-        Some(50 * COIN_VALUE),
+      assert_eq!(
+        context.index.get_parents_by_inscription_id(inscription_id),
+        vec![parent_inscription_id_a, parent_inscription_id_c]
       );
 
-      context.index.assert_inscription_location(
-        child_inscription_id,
-        SatPoint {
-          outpoint: OutPoint { txid, vout: 0 },
-          offset: 0,
-        },
-        Some(50 * COIN_VALUE),
+      assert_eq!(
+        context
+          .index
+          .get_children_by_inscription_id(parent_inscription_id_a)
+          .unwrap(),
+        vec![inscription_id]
       );
-
-      assert_eq!(context.index.inscription_number(child_inscription_id), -1);
-
       assert_eq!(
         context
           .index
-          .get_parents_by_inscription_id(child_inscription_id),
-        vec![parent_inscription_id]
+          .get_children_by_inscription_id(parent_inscription_id_b)
+          .unwrap(),
+        Vec::new()
       );
-
       assert_eq!(
         context
           .index
-          .get_children_by_inscription_id(parent_inscription_id)
+          .get_children_by_inscription_id(parent_inscription_id_c)
           .unwrap(),
-        vec![child_inscription_id]
+        vec![inscription_id]
       );
     }
   }
 
   #[test]
-  fn inscriptions_in_same_input_with_pointers_to_same_output() {
+  fn inscription_with_valid_and_malformed_parent_tags_only_lists_valid_entries() {
     for context in Context::configurations() {
-      context.mine_blocks(1);
-
-      let builder = script::Builder::new();
+      context.mine_blocks(3);
 
-      let builder = Inscription {
-        pointer: Some(100u64.to_le_bytes().to_vec()),
+      let parent_txid_a = context.core.broadcast_tx(TransactionTemplate {
+        inputs: &[(1, 0, 0, inscription("text/plain", "hello").to_witness())],
         ..default()
-      }
-      .append_reveal_script_to_builder(builder);
-
-      let builder = Inscription {
-        pointer: Some(300_000u64.to_le_bytes().to_vec()),
+      });
+      let parent_txid_b = context.core.broadcast_tx(TransactionTemplate {
+        inputs: &[(2, 0, 0, inscription("text/plain", "world").to_witness())],
         ..default()
-      }
-      .append_reveal_script_to_builder(builder);
+      });
+      let parent_txid_c = context.core.broadcast_tx(TransactionTemplate {
+        inputs: &[(3, 0, 0, inscription("text/plain", "wazzup").to_witness())],
+        ..default()
+      });
 
-      let builder = Inscription {
-        pointer: Some(1_000_000u64.to_le_bytes().to_vec()),
+      context.mine_blocks(1);
+
+      let parent_inscription_id_a = InscriptionId {
+        txid: parent_txid_a,
+        index: 0,
+      };
+      let parent_inscription_id_b = InscriptionId {
+        txid: parent_txid_b,
+        index: 0,
+      };
+      let parent_inscription_id_c = InscriptionId {
+        txid: parent_txid_c,
+        index: 0,
+      };
+
+      let malformed_inscription_id_b = parent_inscription_id_b
+        .value()
+        .into_iter()
+        .chain(iter::once(0))
+        .collect();
+
+      let multi_parent_inscription = Inscription {
+        content_type: Some("text/plain".into()),
+        body: Some("hello".into()),
+        parents: vec![
+          parent_inscription_id_a.value(),
+          malformed_inscription_id_b,
+          parent_inscription_id_c.value(),
+        ],
         ..default()
-      }
-      .append_reveal_script_to_builder(builder);
+      };
+      let multi_parent_witness = multi_parent_inscription.to_witness();
 
-      let witness = Witness::from_slice(&[builder.into_bytes(), Vec::new()]);
+      let revealing_parent_a_input = (4, 1, 0, multi_parent_witness);
+      let parent_b_input = (4, 2, 0, Witness::new());
+      let parent_c_input = (4, 3, 0, Witness::new());
 
       let txid = context.core.broadcast_tx(TransactionTemplate {
-        inputs: &[(1, 0, 0, witness)],
+        inputs: &[revealing_parent_a_input, parent_b_input, parent_c_input],
         ..default()
       });
 
       context.mine_blocks(1);
 
-      let first = InscriptionId { txid, index: 0 };
-      let second = InscriptionId { txid, index: 1 };
-      let third = InscriptionId { txid, index: 2 };
+      let inscription_id = InscriptionId { txid, index: 0 };
 
-      context.index.assert_inscription_location(
-        first,
-        SatPoint {
-          outpoint: OutPoint { txid, vout: 0 },
-          offset: 100,
-        },
-        Some(50 * COIN_VALUE + 100),
+      assert_eq!(
+        context.index.get_parents_by_inscription_id(inscription_id),
+        vec![parent_inscription_id_a, parent_inscription_id_c]
       );
 
-      context.index.assert_inscription_location(
-        second,
-        SatPoint {
-          outpoint: OutPoint { txid, vout: 0 },
-          offset: 300_000,
-        },
-        Some(50 * COIN_VALUE + 300_000),
+      assert_eq!(
+        context
+          .index
+          .get_children_by_inscription_id(parent_inscription_id_a)
+          .unwrap(),
+        vec![inscription_id]
       );
-
-      context.index.assert_inscription_location(
-        third,
-        SatPoint {
-          outpoint: OutPoint { txid, vout: 0 },
-          offset: 1_000_000,
-        },
-        Some(50 * COIN_VALUE + 1_000_000),
+      assert_eq!(
+        context
+          .index
+          .get_children_by_inscription_id(parent_inscription_id_b)
+          .unwrap(),
+        Vec::new()
+      );
+      assert_eq!(
+        context
+          .index
+          .get_children_by_inscription_id(parent_inscription_id_c)
+          .unwrap(),
+        vec![inscription_id]
       );
     }
   }
 
   #[test]
-  fn inscriptions_in_same_input_with_pointers_to_different_outputs() {
+  fn parents_can_be_in_preceding_input() {
     for context in Context::configurations() {
-      context.mine_blocks_with_subsidy(1, 300_000);
+      context.mine_blocks(1);
 
-      let builder = script::Builder::new();
+      let parent_txid = context.core.broadcast_tx(TransactionTemplate {
+        inputs: &[(1, 0, 0, inscription("text/plain", "hello").to_witness())],
+        ..default()
+      });
 
-      let builder = Inscription {
-        pointer: Some(100u64.to_le_bytes().to_vec()),
+      context.mine_blocks(2);
+
+      let parent_inscription_id = InscriptionId {
+        txid: parent_txid,
+        index: 0,
+      };
+
+      let txid = context.core.broadcast_tx(TransactionTemplate {
+        inputs: &[
+          (2, 1, 0, Default::default()),
+          (
+            3,
+            0,
+            0,
+            Inscription {
+              content_type: Some("text/plain".into()),
+              body: Some("hello".into()),
+              parents: vec![parent_inscription_id.value()],
+              ..default()
+            }
+            .to_witness(),
+          ),
+        ],
         ..default()
-      }
-      .append_reveal_script_to_builder(builder);
+      });
 
-      let builder = Inscription {
-        pointer: Some(100_111u64.to_le_bytes().to_vec()),
+      context.mine_blocks(1);
+
+      let inscription_id = InscriptionId { txid, index: 0 };
+
+      assert_eq!(
+        context.index.get_parents_by_inscription_id(inscription_id),
+        vec![parent_inscription_id]
+      );
+
+      assert_eq!(
+        context
+          .index
+          .get_children_by_inscription_id(parent_inscription_id)
+          .unwrap(),
+        vec![inscription_id]
+      );
+    }
+  }
+
+  #[test]
+  fn parents_can_be_in_following_input() {
+    for context in Context::configurations() {
+      context.mine_blocks(1);
+
+      let parent_txid = context.core.broadcast_tx(TransactionTemplate {
+        inputs: &[(1, 0, 0, inscription("text/plain", "hello").to_witness())],
         ..default()
-      }
-      .append_reveal_script_to_builder(builder);
+      });
 
-      let builder = Inscription {
-        pointer: Some(299_999u64.to_le_bytes().to_vec()),
+      context.mine_blocks(2);
+
+      let parent_inscription_id = InscriptionId {
+        txid: parent_txid,
+        index: 0,
+      };
+
+      let txid = context.core.broadcast_tx(TransactionTemplate {
+        inputs: &[
+          (
+            3,
+            0,
+            0,
+            Inscription {
+              content_type: Some("text/plain".into()),
+              body: Some("hello".into()),
+              parents: vec![parent_inscription_id.value()],
+              ..default()
+            }
+            .to_witness(),
+          ),
+          (2, 1, 0, Default::default()),
+        ],
         ..default()
-      }
-      .append_reveal_script_to_builder(builder);
+      });
 
-      let witness = Witness::from_slice(&[builder.into_bytes(), Vec::new()]);
+      context.mine_blocks(1);
+
+      let inscription_id = InscriptionId { txid, index: 0 };
+
+      assert_eq!(
+        context.index.get_parents_by_inscription_id(inscription_id),
+        vec![parent_inscription_id]
+      );
+
+      assert_eq!(
+        context
+          .index
+          .get_children_by_inscription_id(parent_inscription_id)
+          .unwrap(),
+        vec![inscription_id]
+      );
+    }
+  }
+
+  #[test]
+  fn inscription_with_invalid_parent_tag_and_parent_has_no_parent_entry() {
+    for context in Context::configurations() {
+      context.mine_blocks(1);
+
+      let parent_txid = context.core.broadcast_tx(TransactionTemplate {
+        inputs: &[(1, 0, 0, inscription("text/plain", "hello").to_witness())],
+        ..default()
+      });
+
+      context.mine_blocks(1);
+
+      let parent_inscription_id = InscriptionId {
+        txid: parent_txid,
+        index: 0,
+      };
 
       let txid = context.core.broadcast_tx(TransactionTemplate {
-        inputs: &[(1, 0, 0, witness)],
-        outputs: 3,
+        inputs: &[(
+          2,
+          1,
+          0,
+          Inscription {
+            content_type: Some("text/plain".into()),
+            body: Some("hello".into()),
+            parents: vec![parent_inscription_id
+              .value()
+              .into_iter()
+              .chain(iter::once(0))
+              .collect()],
+            ..default()
+          }
+          .to_witness(),
+        )],
         ..default()
       });
 
       context.mine_blocks(1);
 
-      let first = InscriptionId { txid, index: 0 };
-      let second = InscriptionId { txid, index: 1 };
-      let third = InscriptionId { txid, index: 2 };
+      let inscription_id = InscriptionId { txid, index: 0 };
+
+      assert!(context
+        .index
+        .get_inscription_entry(inscription_id)
+        .unwrap()
+        .unwrap()
+        .parents
+        .is_empty());
+    }
+  }
+
+  #[test]
+  fn inscription_with_pointer() {
+    for context in Context::configurations() {
+      context.mine_blocks(1);
+
+      let inscription = Inscription {
+        content_type: Some("text/plain".into()),
+        body: Some("hello".into()),
+        pointer: Some(100u64.to_le_bytes().to_vec()),
+        ..default()
+      };
+
+      let txid = context.core.broadcast_tx(TransactionTemplate {
+        inputs: &[(1, 0, 0, inscription.to_witness())],
+        ..default()
+      });
+
+      context.mine_blocks(1);
+
+      let inscription_id = InscriptionId { txid, index: 0 };
+
+      context.index.assert_inscription_location(
+        inscription_id,
+        SatPoint {
+          outpoint: OutPoint { txid, vout: 0 },
+          offset: 100,
+        },
+        Some(50 * COIN_VALUE + 100),
+      );
+    }
+  }
+
+  #[test]
+  fn inscription_with_pointer_greater_than_output_value_assigned_default() {
+    for context in Context::configurations() {
+      context.mine_blocks(1);
+
+      let inscription = Inscription {
+        content_type: Some("text/plain".into()),
+        body: Some("hello".into()),
+        pointer: Some((50 * COIN_VALUE).to_le_bytes().to_vec()),
+        ..default()
+      };
+
+      let txid = context.core.broadcast_tx(TransactionTemplate {
+        inputs: &[(1, 0, 0, inscription.to_witness())],
+        ..default()
+      });
+
+      context.mine_blocks(1);
+
+      let inscription_id = InscriptionId { txid, index: 0 };
+
+      context.index.assert_inscription_location(
+        inscription_id,
+        SatPoint {
+          outpoint: OutPoint { txid, vout: 0 },
+          offset: 0,
+        },
+        Some(50 * COIN_VALUE),
+      );
+    }
+  }
+
+  #[test]
+  fn inscription_with_pointer_into_fee_ignored_and_assigned_default_location() {
+    for context in Context::configurations() {
+      context.mine_blocks(1);
+
+      let inscription = Inscription {
+        content_type: Some("text/plain".into()),
+        body: Some("hello".into()),
+        pointer: Some((25 * COIN_VALUE).to_le_bytes().to_vec()),
+        ..default()
+      };
+
+      let txid = context.core.broadcast_tx(TransactionTemplate {
+        inputs: &[(1, 0, 0, inscription.to_witness())],
+        fee: 25 * COIN_VALUE,
+        ..default()
+      });
+
+      context.mine_blocks(1);
+
+      let inscription_id = InscriptionId { txid, index: 0 };
+
+      context.index.assert_inscription_location(
+        inscription_id,
+        SatPoint {
+          outpoint: OutPoint { txid, vout: 0 },
+          offset: 0,
+        },
+        Some(50 * COIN_VALUE),
+      );
+    }
+  }
+
+  #[test]
+  fn inscription_with_pointer_is_cursed() {
+    for context in Context::configurations() {
+      context.mine_blocks(1);
+
+      let inscription = Inscription {
+        content_type: Some("text/plain".into()),
+        body: Some("pointer-child".into()),
+        pointer: Some(0u64.to_le_bytes().to_vec()),
+        ..default()
+      };
+
+      let txid = context.core.broadcast_tx(TransactionTemplate {
+        inputs: &[(1, 0, 0, inscription.to_witness())],
+        ..default()
+      });
+
+      context.mine_blocks(1);
+
+      let inscription_id = InscriptionId { txid, index: 0 };
+
+      context.index.assert_inscription_location(
+        inscription_id,
+        SatPoint {
+          outpoint: OutPoint { txid, vout: 0 },
+          offset: 0,
+        },
+        Some(50 * COIN_VALUE),
+      );
+
+      assert_eq!(context.index.inscription_number(inscription_id), -1);
+    }
+  }
+
+  #[test]
+  fn inscription_with_pointer_to_parent_is_cursed_reinscription() {
+    for context in Context::configurations() {
+      context.mine_blocks(1);
+
+      let parent_txid = context.core.broadcast_tx(TransactionTemplate {
+        inputs: &[(1, 0, 0, inscription("text/plain", "parent").to_witness())],
+        ..default()
+      });
+
+      context.mine_blocks(1);
+
+      let parent_inscription_id = InscriptionId {
+        txid: parent_txid,
+        index: 0,
+      };
+
+      let child_inscription = Inscription {
+        content_type: Some("text/plain".into()),
+        body: Some("pointer-child".into()),
+        parents: vec![parent_inscription_id.value()],
+        pointer: Some(0u64.to_le_bytes().to_vec()),
+        ..default()
+      };
+
+      let txid = context.core.broadcast_tx(TransactionTemplate {
+        inputs: &[(2, 1, 0, child_inscription.to_witness())],
+        ..default()
+      });
+
+      context.mine_blocks(1);
+
+      let child_inscription_id = InscriptionId { txid, index: 0 };
+
+      context.index.assert_inscription_location(
+        parent_inscription_id,
+        SatPoint {
+          outpoint: OutPoint { txid, vout: 0 },
+          offset: 0,
+        },
+        Some(50 * COIN_VALUE),
+      );
+
+      context.index.assert_inscription_location(
+        child_inscription_id,
+        SatPoint {
+          outpoint: OutPoint { txid, vout: 0 },
+          offset: 0,
+        },
+        Some(50 * COIN_VALUE),
+      );
+
+      assert_eq!(context.index.inscription_number(child_inscription_id), -1);
+
+      assert_eq!(
+        context
+          .index
+          .get_parents_by_inscription_id(child_inscription_id),
+        vec![parent_inscription_id]
+      );
+
+      assert_eq!(
+        context
+          .index
+          .get_children_by_inscription_id(parent_inscription_id)
+          .unwrap(),
+        vec![child_inscription_id]
+      );
+    }
+  }
+
+  #[test]
+  fn inscriptions_in_same_input_with_pointers_to_same_output() {
+    for context in Context::configurations() {
+      context.mine_blocks(1);
+
+      let builder = script::Builder::new();
+
+      let builder = Inscription {
+        pointer: Some(100u64.to_le_bytes().to_vec()),
+        ..default()
+      }
+      .append_reveal_script_to_builder(builder);
+
+      let builder = Inscription {
+        pointer: Some(300_000u64.to_le_bytes().to_vec()),
+        ..default()
+      }
+      .append_reveal_script_to_builder(builder);
+
+      let builder = Inscription {
+        pointer: Some(1_000_000u64.to_le_bytes().to_vec()),
+        ..default()
+      }
+      .append_reveal_script_to_builder(builder);
+
+      let witness = Witness::from_slice(&[builder.into_bytes(), Vec::new()]);
+
+      let txid = context.core.broadcast_tx(TransactionTemplate {
+        inputs: &[(1, 0, 0, witness)],
+        ..default()
+      });
+
+      context.mine_blocks(1);
+
+      let first = InscriptionId { txid, index: 0 };
+      let second = InscriptionId { txid, index: 1 };
+      let third = InscriptionId { txid, index: 2 };
+
+      context.index.assert_inscription_location(
+        first,
+        SatPoint {
+          outpoint: OutPoint { txid, vout: 0 },
+          offset: 100,
+        },
+        Some(50 * COIN_VALUE + 100),
+      );
+
+      context.index.assert_inscription_location(
+        second,
+        SatPoint {
+          outpoint: OutPoint { txid, vout: 0 },
+          offset: 300_000,
+        },
+        Some(50 * COIN_VALUE + 300_000),
+      );
+
+      context.index.assert_inscription_location(
+        third,
+        SatPoint {
+          outpoint: OutPoint { txid, vout: 0 },
+          offset: 1_000_000,
+        },
+        Some(50 * COIN_VALUE + 1_000_000),
+      );
+    }
+  }
+
+  #[test]
+  fn inscriptions_in_same_input_with_pointers_to_different_outputs() {
+    for context in Context::configurations() {
+      context.mine_blocks_with_subsidy(1, 300_000);
+
+      let builder = script::Builder::new();
+
+      let builder = Inscription {
+        pointer: Some(100u64.to_le_bytes().to_vec()),
+        ..default()
+      }
+      .append_reveal_script_to_builder(builder);
+
+      let builder = Inscription {
+        pointer: Some(100_111u64.to_le_bytes().to_vec()),
+        ..default()
+      }
+      .append_reveal_script_to_builder(builder);
+
+      let builder = Inscription {
+        pointer: Some(299_999u64.to_le_bytes().to_vec()),
+        ..default()
+      }
+      .append_reveal_script_to_builder(builder);
+
+      let witness = Witness::from_slice(&[builder.into_bytes(), Vec::new()]);
+
+      let txid = context.core.broadcast_tx(TransactionTemplate {
+        inputs: &[(1, 0, 0, witness)],
+        outputs: 3,
+        ..default()
+      });
+
+      context.mine_blocks(1);
+
+      let first = InscriptionId { txid, index: 0 };
+      let second = InscriptionId { txid, index: 1 };
+      let third = InscriptionId { txid, index: 2 };
+
+      context.index.assert_inscription_location(
+        first,
+        SatPoint {
+          outpoint: OutPoint { txid, vout: 0 },
+          offset: 100,
+        },
+        Some(50 * COIN_VALUE + 100),
+      );
+
+      context.index.assert_inscription_location(
+        second,
+        SatPoint {
+          outpoint: OutPoint { txid, vout: 1 },
+          offset: 111,
+        },
+        Some(50 * COIN_VALUE + 100_111),
+      );
+
+      context.index.assert_inscription_location(
+        third,
+        SatPoint {
+          outpoint: OutPoint { txid, vout: 2 },
+          offset: 99_999,
+        },
+        Some(50 * COIN_VALUE + 299_999),
+      );
+    }
+  }
+
+  #[test]
+  fn inscriptions_in_different_inputs_with_pointers_to_different_outputs() {
+    for context in Context::configurations() {
+      context.mine_blocks(3);
+
+      let inscription_for_second_output = Inscription {
+        content_type: Some("text/plain".into()),
+        body: Some("hello jupiter".into()),
+        pointer: Some((50 * COIN_VALUE).to_le_bytes().to_vec()),
+        ..default()
+      };
+
+      let inscription_for_third_output = Inscription {
+        content_type: Some("text/plain".into()),
+        body: Some("hello mars".into()),
+        pointer: Some((100 * COIN_VALUE).to_le_bytes().to_vec()),
+        ..default()
+      };
+
+      let inscription_for_first_output = Inscription {
+        content_type: Some("text/plain".into()),
+        body: Some("hello world".into()),
+        pointer: Some(0u64.to_le_bytes().to_vec()),
+        ..default()
+      };
+
+      let txid = context.core.broadcast_tx(TransactionTemplate {
+        inputs: &[
+          (1, 0, 0, inscription_for_second_output.to_witness()),
+          (2, 0, 0, inscription_for_third_output.to_witness()),
+          (3, 0, 0, inscription_for_first_output.to_witness()),
+        ],
+        outputs: 3,
+        ..default()
+      });
+
+      context.mine_blocks(1);
+
+      let inscription_for_second_output = InscriptionId { txid, index: 0 };
+      let inscription_for_third_output = InscriptionId { txid, index: 1 };
+      let inscription_for_first_output = InscriptionId { txid, index: 2 };
+
+      context.index.assert_inscription_location(
+        inscription_for_second_output,
+        SatPoint {
+          outpoint: OutPoint { txid, vout: 1 },
+          offset: 0,
+        },
+        Some(100 * COIN_VALUE),
+      );
+
+      context.index.assert_inscription_location(
+        inscription_for_third_output,
+        SatPoint {
+          outpoint: OutPoint { txid, vout: 2 },
+          offset: 0,
+        },
+        Some(150 * COIN_VALUE),
+      );
+
+      context.index.assert_inscription_location(
+        inscription_for_first_output,
+        SatPoint {
+          outpoint: OutPoint { txid, vout: 0 },
+          offset: 0,
+        },
+        Some(50 * COIN_VALUE),
+      );
+    }
+  }
+
+  #[test]
+  fn inscriptions_in_different_inputs_with_pointers_to_same_output() {
+    for context in Context::configurations() {
+      context.mine_blocks(3);
+
+      let first_inscription = Inscription {
+        content_type: Some("text/plain".into()),
+        body: Some("hello jupiter".into()),
+        ..default()
+      };
+
+      let second_inscription = Inscription {
+        content_type: Some("text/plain".into()),
+        body: Some("hello mars".into()),
+        pointer: Some(1u64.to_le_bytes().to_vec()),
+        ..default()
+      };
+
+      let third_inscription = Inscription {
+        content_type: Some("text/plain".into()),
+        body: Some("hello world".into()),
+        pointer: Some(2u64.to_le_bytes().to_vec()),
+        ..default()
+      };
+
+      let txid = context.core.broadcast_tx(TransactionTemplate {
+        inputs: &[
+          (1, 0, 0, first_inscription.to_witness()),
+          (2, 0, 0, second_inscription.to_witness()),
+          (3, 0, 0, third_inscription.to_witness()),
+        ],
+        outputs: 1,
+        ..default()
+      });
+
+      context.mine_blocks(1);
+
+      let first_inscription_id = InscriptionId { txid, index: 0 };
+      let second_inscription_id = InscriptionId { txid, index: 1 };
+      let third_inscription_id = InscriptionId { txid, index: 2 };
+
+      context.index.assert_inscription_location(
+        first_inscription_id,
+        SatPoint {
+          outpoint: OutPoint { txid, vout: 0 },
+          offset: 0,
+        },
+        Some(50 * COIN_VALUE),
+      );
+
+      context.index.assert_inscription_location(
+        second_inscription_id,
+        SatPoint {
+          outpoint: OutPoint { txid, vout: 0 },
+          offset: 1,
+        },
+        Some(50 * COIN_VALUE + 1),
+      );
+
+      context.index.assert_inscription_location(
+        third_inscription_id,
+        SatPoint {
+          outpoint: OutPoint { txid, vout: 0 },
+          offset: 2,
+        },
+        Some(50 * COIN_VALUE + 2),
+      );
+    }
+  }
+
+  #[test]
+  fn inscriptions_with_pointers_to_same_sat_one_becomes_cursed_reinscriptions() {
+    for context in Context::configurations() {
+      context.mine_blocks(2);
+
+      let inscription = Inscription {
+        content_type: Some("text/plain".into()),
+        body: Some("hello jupiter".into()),
+        ..default()
+      };
+
+      let cursed_reinscription = Inscription {
+        content_type: Some("text/plain".into()),
+        body: Some("hello mars".into()),
+        pointer: Some(0u64.to_le_bytes().to_vec()),
+        ..default()
+      };
+
+      let txid = context.core.broadcast_tx(TransactionTemplate {
+        inputs: &[
+          (1, 0, 0, inscription.to_witness()),
+          (2, 0, 0, cursed_reinscription.to_witness()),
+        ],
+        outputs: 2,
+        ..default()
+      });
+
+      context.mine_blocks(1);
+
+      let inscription_id = InscriptionId { txid, index: 0 };
+      let cursed_reinscription_id = InscriptionId { txid, index: 1 };
+
+      context.index.assert_inscription_location(
+        inscription_id,
+        SatPoint {
+          outpoint: OutPoint { txid, vout: 0 },
+          offset: 0,
+        },
+        Some(50 * COIN_VALUE),
+      );
+
+      context.index.assert_inscription_location(
+        cursed_reinscription_id,
+        SatPoint {
+          outpoint: OutPoint { txid, vout: 0 },
+          offset: 0,
+        },
+        Some(50 * COIN_VALUE),
+      );
+
+      assert_eq!(context.index.inscription_number(inscription_id), 0);
+
+      assert_eq!(
+        context.index.inscription_number(cursed_reinscription_id),
+        -1
+      );
+    }
+  }
+
+  #[test]
+  fn inscribe_into_fee() {
+    for context in Context::configurations() {
+      context.mine_blocks(1);
+
+      let inscription = Inscription::default();
+
+      let txid = context.core.broadcast_tx(TransactionTemplate {
+        inputs: &[(1, 0, 0, inscription.to_witness())],
+        fee: 50 * COIN_VALUE,
+        ..default()
+      });
+
+      let blocks = context.mine_blocks(1);
+
+      let inscription_id = InscriptionId { txid, index: 0 };
+
+      context.index.assert_inscription_location(
+        inscription_id,
+        SatPoint {
+          outpoint: OutPoint {
+            txid: blocks[0].txdata[0].txid(),
+            vout: 0,
+          },
+          offset: 50 * COIN_VALUE,
+        },
+        Some(50 * COIN_VALUE),
+      );
+    }
+  }
+
+  #[test]
+  fn inscribe_into_fee_with_reduced_subsidy() {
+    for context in Context::configurations() {
+      context.mine_blocks(1);
+
+      let inscription = Inscription::default();
+
+      let txid = context.core.broadcast_tx(TransactionTemplate {
+        inputs: &[(1, 0, 0, inscription.to_witness())],
+        fee: 50 * COIN_VALUE,
+        ..default()
+      });
+
+      let blocks = context.mine_blocks_with_subsidy(1, 25 * COIN_VALUE);
+
+      let inscription_id = InscriptionId { txid, index: 0 };
+
+      context.index.assert_inscription_location(
+        inscription_id,
+        SatPoint {
+          outpoint: OutPoint {
+            txid: blocks[0].txdata[0].txid(),
+            vout: 0,
+          },
+          offset: 50 * COIN_VALUE,
+        },
+        Some(50 * COIN_VALUE),
+      );
+    }
+  }
+
+  #[test]
+  fn pre_jubilee_first_reinscription_after_cursed_inscription_is_blessed() {
+    for context in Context::configurations() {
+      context.mine_blocks(1);
+
+      // Before the jubilee, an inscription on a sat using a pushnum opcode is
+      // cursed and not vindicated.
+
+      let script = script::Builder::new()
+        .push_opcode(opcodes::OP_FALSE)
+        .push_opcode(opcodes::all::OP_IF)
+        .push_slice(b"ord")
+        .push_slice([])
+        .push_opcode(opcodes::all::OP_PUSHNUM_1)
+        .push_opcode(opcodes::all::OP_ENDIF)
+        .into_script();
+
+      let witness = Witness::from_slice(&[script.into_bytes(), Vec::new()]);
+
+      let txid = context.core.broadcast_tx(TransactionTemplate {
+        inputs: &[(1, 0, 0, witness)],
+        ..default()
+      });
+
+      let inscription_id = InscriptionId { txid, index: 0 };
+
+      context.mine_blocks(1);
+
+      let entry = context
+        .index
+        .get_inscription_entry(inscription_id)
+        .unwrap()
+        .unwrap();
+
+      assert!(Charm::charms(entry.charms).contains(&Charm::Cursed));
+
+      assert!(!Charm::charms(entry.charms).contains(&Charm::Vindicated));
+
+      let sat = entry.sat;
+
+      assert_eq!(entry.inscription_number, -1);
+
+      // Before the jubilee, reinscription on the same sat is not cursed and
+      // not vindicated.
+
+      let inscription = Inscription::default();
+
+      let txid = context.core.broadcast_tx(TransactionTemplate {
+        inputs: &[(2, 1, 0, inscription.to_witness())],
+        ..default()
+      });
+
+      context.mine_blocks(1);
+
+      let inscription_id = InscriptionId { txid, index: 0 };
+
+      let entry = context
+        .index
+        .get_inscription_entry(inscription_id)
+        .unwrap()
+        .unwrap();
+
+      assert_eq!(entry.inscription_number, 0);
+
+      assert!(!Charm::charms(entry.charms).contains(&Charm::Cursed));
+
+      assert!(!Charm::charms(entry.charms).contains(&Charm::Vindicated));
+
+      assert_eq!(sat, entry.sat);
+
+      // Before the jubilee, a third reinscription on the same sat is cursed
+      // and not vindicated.
+
+      let inscription = Inscription::default();
+
+      let txid = context.core.broadcast_tx(TransactionTemplate {
+        inputs: &[(3, 1, 0, inscription.to_witness())],
+        ..default()
+      });
+
+      context.mine_blocks(1);
+
+      let inscription_id = InscriptionId { txid, index: 0 };
+
+      let entry = context
+        .index
+        .get_inscription_entry(inscription_id)
+        .unwrap()
+        .unwrap();
+
+      assert!(Charm::charms(entry.charms).contains(&Charm::Cursed));
+
+      assert!(!Charm::charms(entry.charms).contains(&Charm::Vindicated));
+
+      assert_eq!(entry.inscription_number, -2);
+
+      assert_eq!(sat, entry.sat);
+    }
+  }
+
+  #[test]
+  fn post_jubilee_first_reinscription_after_vindicated_inscription_not_vindicated() {
+    for context in Context::configurations() {
+      context.mine_blocks(110);
+      // After the jubilee, an inscription on a sat using a pushnum opcode is
+      // vindicated and not cursed.
+
+      let script = script::Builder::new()
+        .push_opcode(opcodes::OP_FALSE)
+        .push_opcode(opcodes::all::OP_IF)
+        .push_slice(b"ord")
+        .push_slice([])
+        .push_opcode(opcodes::all::OP_PUSHNUM_1)
+        .push_opcode(opcodes::all::OP_ENDIF)
+        .into_script();
+
+      let witness = Witness::from_slice(&[script.into_bytes(), Vec::new()]);
+
+      let txid = context.core.broadcast_tx(TransactionTemplate {
+        inputs: &[(1, 0, 0, witness)],
+        ..default()
+      });
+
+      let inscription_id = InscriptionId { txid, index: 0 };
+
+      context.mine_blocks(1);
+
+      let entry = context
+        .index
+        .get_inscription_entry(inscription_id)
+        .unwrap()
+        .unwrap();
+
+      assert!(!Charm::charms(entry.charms).contains(&Charm::Cursed));
+
+      assert!(Charm::charms(entry.charms).contains(&Charm::Vindicated));
+
+      let sat = entry.sat;
+
+      assert_eq!(entry.inscription_number, 0);
+
+      // After the jubilee, a reinscription on the same is not cursed and not
+      // vindicated.
+
+      let inscription = Inscription::default();
+
+      let txid = context.core.broadcast_tx(TransactionTemplate {
+        inputs: &[(111, 1, 0, inscription.to_witness())],
+        ..default()
+      });
+
+      context.mine_blocks(1);
+
+      let inscription_id = InscriptionId { txid, index: 0 };
+
+      let entry = context
+        .index
+        .get_inscription_entry(inscription_id)
+        .unwrap()
+        .unwrap();
+
+      assert!(!Charm::charms(entry.charms).contains(&Charm::Cursed));
+
+      assert!(!Charm::charms(entry.charms).contains(&Charm::Vindicated));
+
+      assert_eq!(entry.inscription_number, 1);
+
+      assert_eq!(sat, entry.sat);
+
+      // After the jubilee, a third reinscription on the same is vindicated and
+      // not cursed.
+
+      let inscription = Inscription::default();
+
+      let txid = context.core.broadcast_tx(TransactionTemplate {
+        inputs: &[(112, 1, 0, inscription.to_witness())],
+        ..default()
+      });
+
+      context.mine_blocks(1);
+
+      let inscription_id = InscriptionId { txid, index: 0 };
+
+      let entry = context
+        .index
+        .get_inscription_entry(inscription_id)
+        .unwrap()
+        .unwrap();
+
+      assert!(!Charm::charms(entry.charms).contains(&Charm::Cursed));
+
+      assert!(Charm::charms(entry.charms).contains(&Charm::Vindicated));
+
+      assert_eq!(entry.inscription_number, 2);
+
+      assert_eq!(sat, entry.sat);
+    }
+  }
+
+  #[test]
+  fn index_spent_sats_retains_spent_sat_range_entries() {
+    let ranges = {
+      let context = Context::builder().arg("--index-sats").build();
+
+      context.mine_blocks(1);
+
+      let outpoint = OutPoint {
+        txid: context.core.tx(1, 0).into(),
+        vout: 0,
+      };
+
+      let ranges = context.index.list(outpoint).unwrap().unwrap();
+
+      assert!(!ranges.is_empty());
+
+      context.core.broadcast_tx(TransactionTemplate {
+        inputs: &[(1, 0, 0, Default::default())],
+        ..default()
+      });
+
+      context.mine_blocks(1);
+
+      assert!(context.index.list(outpoint).unwrap().is_none());
+
+      ranges
+    };
+
+    {
+      let context = Context::builder()
+        .arg("--index-sats")
+        .arg("--index-spent-sats")
+        .build();
+
+      context.mine_blocks(1);
+
+      let outpoint = OutPoint {
+        txid: context.core.tx(1, 0).into(),
+        vout: 0,
+      };
+
+      let unspent_ranges = context.index.list(outpoint).unwrap().unwrap();
+
+      assert!(!unspent_ranges.is_empty());
+
+      assert_eq!(unspent_ranges, ranges);
+
+      context.core.broadcast_tx(TransactionTemplate {
+        inputs: &[(1, 0, 0, Default::default())],
+        ..default()
+      });
+
+      context.mine_blocks(1);
+
+      let spent_ranges = context.index.list(outpoint).unwrap().unwrap();
+
+      assert_eq!(spent_ranges, ranges);
+    }
+  }
+
+  #[test]
+  fn index_spent_sats_implies_index_sats() {
+    let context = Context::builder().arg("--index-spent-sats").build();
+
+    context.mine_blocks(1);
+
+    let outpoint = OutPoint {
+      txid: context.core.tx(1, 0).into(),
+      vout: 0,
+    };
+
+    context.core.broadcast_tx(TransactionTemplate {
+      inputs: &[(1, 0, 0, Default::default())],
+      ..default()
+    });
+
+    context.mine_blocks(1);
+
+    assert!(context.index.list(outpoint).unwrap().is_some());
+  }
+
+  #[test]
+  fn spent_sats_are_retained_after_flush() {
+    let context = Context::builder().arg("--index-spent-sats").build();
+
+    context.mine_blocks(1);
+
+    let txid = context.core.broadcast_tx(TransactionTemplate {
+      inputs: &[(1, 0, 0, Default::default())],
+      ..default()
+    });
+
+    context.mine_blocks_with_update(1, false);
+
+    let outpoint = OutPoint { txid, vout: 0 };
+
+    context.core.broadcast_tx(TransactionTemplate {
+      inputs: &[(2, 1, 0, Default::default())],
+      ..default()
+    });
+
+    context.mine_blocks(1);
+
+    assert!(context.index.list(outpoint).unwrap().is_some());
+  }
+
+  #[test]
+  fn is_output_spent() {
+    let context = Context::builder().build();
+
+    assert!(!context.index.is_output_spent(OutPoint::null()).unwrap());
+    assert!(!context
+      .index
+      .is_output_spent(Chain::Mainnet.genesis_coinbase_outpoint())
+      .unwrap());
+
+    context.mine_blocks(1);
+
+    assert!(!context
+      .index
+      .is_output_spent(OutPoint {
+        txid: context.core.tx(1, 0).txid(),
+        vout: 0,
+      })
+      .unwrap());
+
+    context.core.broadcast_tx(TransactionTemplate {
+      inputs: &[(1, 0, 0, Default::default())],
+      ..default()
+    });
+
+    context.mine_blocks(1);
+
+    assert!(context
+      .index
+      .is_output_spent(OutPoint {
+        txid: context.core.tx(1, 0).txid(),
+        vout: 0,
+      })
+      .unwrap());
+  }
+
+  #[test]
+  fn is_output_in_active_chain() {
+    let context = Context::builder().build();
+
+    assert!(context
+      .index
+      .is_output_in_active_chain(OutPoint::null())
+      .unwrap());
+
+    assert!(context
+      .index
+      .is_output_in_active_chain(Chain::Mainnet.genesis_coinbase_outpoint())
+      .unwrap());
+
+    context.mine_blocks(1);
+
+    assert!(context
+      .index
+      .is_output_in_active_chain(OutPoint {
+        txid: context.core.tx(1, 0).txid(),
+        vout: 0,
+      })
+      .unwrap());
+
+    assert!(!context
+      .index
+      .is_output_in_active_chain(OutPoint {
+        txid: context.core.tx(1, 0).txid(),
+        vout: 1,
+      })
+      .unwrap());
+
+    assert!(!context
+      .index
+      .is_output_in_active_chain(OutPoint {
+        txid: Txid::all_zeros(),
+        vout: 0,
+      })
+      .unwrap());
+  }
+
+  #[test]
+  fn output_addresses_are_updated() {
+    let context = Context::builder()
+      .arg("--index-addresses")
+      .arg("--index-sats")
+      .build();
+
+    context.mine_blocks(2);
+
+    let txid = context.core.broadcast_tx(TransactionTemplate {
+      inputs: &[(1, 0, 0, Witness::new()), (2, 0, 0, Witness::new())],
+      outputs: 2,
+      ..Default::default()
+    });
+
+    context.mine_blocks(1);
+
+    let transaction = context.index.get_transaction(txid).unwrap().unwrap();
+
+    let first_address = context
+      .index
+      .settings
+      .chain()
+      .address_from_script(&transaction.output[0].script_pubkey)
+      .unwrap();
+
+    let first_address_second_output = OutPoint {
+      txid: transaction.txid(),
+      vout: 1,
+    };
+
+    assert_eq!(
+      context.index.get_address_info(&first_address).unwrap(),
+      [
+        OutPoint {
+          txid: transaction.txid(),
+          vout: 0
+        },
+        first_address_second_output
+      ]
+    );
+
+    let txid = context.core.broadcast_tx(TransactionTemplate {
+      inputs: &[(3, 1, 0, Witness::new())],
+      p2tr: true,
+      ..Default::default()
+    });
+
+    context.mine_blocks(1);
+
+    let transaction = context.index.get_transaction(txid).unwrap().unwrap();
+
+    let second_address = context
+      .index
+      .settings
+      .chain()
+      .address_from_script(&transaction.output[0].script_pubkey)
+      .unwrap();
+
+    assert_eq!(
+      context.index.get_address_info(&first_address).unwrap(),
+      [first_address_second_output]
+    );
+
+    assert_eq!(
+      context.index.get_address_info(&second_address).unwrap(),
+      [OutPoint {
+        txid: transaction.txid(),
+        vout: 0
+      }]
+    );
+  }
+
+  #[test]
+  fn address_inscriptions_are_updated() {
+    let context = Context::builder().arg("--index-addresses").build();
+
+    context.mine_blocks(1);
+
+    let txid = context.core.broadcast_tx(TransactionTemplate {
+      inputs: &[(1, 0, 0, inscription("text/plain", "hello").to_witness())],
+      ..default()
+    });
+
+    context.mine_blocks(1);
+
+    let inscription_id = InscriptionId { txid, index: 0 };
+
+    let first_address = context.core.address(OutPoint { txid, vout: 0 });
+
+    assert_eq!(
+      context
+        .index
+        .get_inscriptions_for_address_paginated(&first_address, 100, 0)
+        .unwrap(),
+      (vec![inscription_id], false)
+    );
+
+    let txid = context.core.broadcast_tx(TransactionTemplate {
+      inputs: &[(2, 1, 0, Default::default())],
+      p2tr: true,
+      ..default()
+    });
+
+    context.mine_blocks(1);
 
-      context.index.assert_inscription_location(
-        first,
-        SatPoint {
-          outpoint: OutPoint { txid, vout: 0 },
-          offset: 100,
-        },
-        Some(50 * COIN_VALUE + 100),
-      );
+    let second_address = context.core.address(OutPoint { txid, vout: 0 });
 
-      context.index.assert_inscription_location(
-        second,
-        SatPoint {
-          outpoint: OutPoint { txid, vout: 1 },
-          offset: 111,
-        },
-        Some(50 * COIN_VALUE + 100_111),
-      );
+    assert_eq!(
+      context
+        .index
+        .get_inscriptions_for_address_paginated(&first_address, 100, 0)
+        .unwrap(),
+      (Vec::new(), false)
+    );
 
-      context.index.assert_inscription_location(
-        third,
-        SatPoint {
-          outpoint: OutPoint { txid, vout: 2 },
-          offset: 99_999,
-        },
-        Some(50 * COIN_VALUE + 299_999),
-      );
-    }
+    assert_eq!(
+      context
+        .index
+        .get_inscriptions_for_address_paginated(&second_address, 100, 0)
+        .unwrap(),
+      (vec![inscription_id], false)
+    );
   }
 
   #[test]
-  fn inscriptions_in_different_inputs_with_pointers_to_different_outputs() {
-    for context in Context::configurations() {
-      context.mine_blocks(3);
-
-      let inscription_for_second_output = Inscription {
-        content_type: Some("text/plain".into()),
-        body: Some("hello jupiter".into()),
-        pointer: Some((50 * COIN_VALUE).to_le_bytes().to_vec()),
-        ..default()
-      };
+  fn address_inscriptions_are_paginated() {
+    let context = Context::builder().arg("--index-addresses").build();
 
-      let inscription_for_third_output = Inscription {
-        content_type: Some("text/plain".into()),
-        body: Some("hello mars".into()),
-        pointer: Some((100 * COIN_VALUE).to_le_bytes().to_vec()),
-        ..default()
-      };
+    context.mine_blocks(3);
 
-      let inscription_for_first_output = Inscription {
-        content_type: Some("text/plain".into()),
-        body: Some("hello world".into()),
-        pointer: Some(0u64.to_le_bytes().to_vec()),
-        ..default()
-      };
+    let mut inscription_ids = Vec::new();
 
+    for i in 1..=3 {
       let txid = context.core.broadcast_tx(TransactionTemplate {
-        inputs: &[
-          (1, 0, 0, inscription_for_second_output.to_witness()),
-          (2, 0, 0, inscription_for_third_output.to_witness()),
-          (3, 0, 0, inscription_for_first_output.to_witness()),
-        ],
-        outputs: 3,
+        inputs: &[(i, 0, 0, inscription("text/plain", "hello").to_witness())],
         ..default()
       });
 
       context.mine_blocks(1);
 
-      let inscription_for_second_output = InscriptionId { txid, index: 0 };
-      let inscription_for_third_output = InscriptionId { txid, index: 1 };
-      let inscription_for_first_output = InscriptionId { txid, index: 2 };
+      inscription_ids.push(InscriptionId { txid, index: 0 });
+    }
 
-      context.index.assert_inscription_location(
-        inscription_for_second_output,
-        SatPoint {
-          outpoint: OutPoint { txid, vout: 1 },
-          offset: 0,
-        },
-        Some(100 * COIN_VALUE),
-      );
+    let address = context.core.address(OutPoint {
+      txid: inscription_ids[0].txid,
+      vout: 0,
+    });
 
-      context.index.assert_inscription_location(
-        inscription_for_third_output,
-        SatPoint {
-          outpoint: OutPoint { txid, vout: 2 },
-          offset: 0,
-        },
-        Some(150 * COIN_VALUE),
-      );
+    assert_eq!(
+      context
+        .index
+        .get_inscriptions_for_address_paginated(&address, 2, 0)
+        .unwrap(),
+      (inscription_ids[..2].to_vec(), true)
+    );
 
-      context.index.assert_inscription_location(
-        inscription_for_first_output,
-        SatPoint {
-          outpoint: OutPoint { txid, vout: 0 },
-          offset: 0,
-        },
-        Some(50 * COIN_VALUE),
-      );
-    }
+    assert_eq!(
+      context
+        .index
+        .get_inscriptions_for_address_paginated(&address, 2, 1)
+        .unwrap(),
+      (inscription_ids[2..].to_vec(), false)
+    );
   }
 
   #[test]
-  fn inscriptions_in_different_inputs_with_pointers_to_same_output() {
-    for context in Context::configurations() {
-      context.mine_blocks(3);
+  fn address_inscriptions_are_not_indexed_without_address_index() {
+    let context = Context::builder().build();
 
-      let first_inscription = Inscription {
-        content_type: Some("text/plain".into()),
-        body: Some("hello jupiter".into()),
-        ..default()
-      };
+    context.mine_blocks(1);
 
-      let second_inscription = Inscription {
-        content_type: Some("text/plain".into()),
-        body: Some("hello mars".into()),
-        pointer: Some(1u64.to_le_bytes().to_vec()),
-        ..default()
-      };
+    let txid = context.core.broadcast_tx(TransactionTemplate {
+      inputs: &[(1, 0, 0, inscription("text/plain", "hello").to_witness())],
+      ..default()
+    });
 
-      let third_inscription = Inscription {
-        content_type: Some("text/plain".into()),
-        body: Some("hello world".into()),
-        pointer: Some(2u64.to_le_bytes().to_vec()),
-        ..default()
-      };
+    context.mine_blocks(1);
 
-      let txid = context.core.broadcast_tx(TransactionTemplate {
-        inputs: &[
-          (1, 0, 0, first_inscription.to_witness()),
-          (2, 0, 0, second_inscription.to_witness()),
-          (3, 0, 0, third_inscription.to_witness()),
-        ],
-        outputs: 1,
+    assert_eq!(
+      context
+        .index
+        .get_inscriptions_for_address_paginated(
+          &context.core.address(OutPoint { txid, vout: 0 }),
+          100,
+          0
+        )
+        .unwrap(),
+      (Vec::new(), false)
+    );
+  }
+
+  #[test]
+  fn address_rune_balances_are_updated() {
+    const RUNE: u128 = 99246114928149462;
+
+    let context = Context::builder()
+      .arg("--index-addresses")
+      .arg("--index-runes")
+      .build();
+
+    let (txid, id) = context.etch(
+      Runestone {
+        edicts: vec![Edict {
+          id: RuneId::default(),
+          amount: u128::MAX,
+          output: 0,
+        }],
+        etching: Some(Etching {
+          rune: Some(Rune(RUNE)),
+          premine: Some(u128::MAX),
+          ..default()
+        }),
         ..default()
-      });
+      },
+      1,
+    );
 
-      context.mine_blocks(1);
+    let first_address = context.core.address(OutPoint { txid, vout: 0 });
 
-      let first_inscription_id = InscriptionId { txid, index: 0 };
-      let second_inscription_id = InscriptionId { txid, index: 1 };
-      let third_inscription_id = InscriptionId { txid, index: 2 };
+    let pile = |amount| Pile {
+      amount,
+      divisibility: 0,
+      symbol: None,
+    };
 
-      context.index.assert_inscription_location(
-        first_inscription_id,
-        SatPoint {
-          outpoint: OutPoint { txid, vout: 0 },
-          offset: 0,
-        },
-        Some(50 * COIN_VALUE),
-      );
+    let spaced_rune = SpacedRune {
+      rune: Rune(RUNE),
+      spacers: 0,
+    };
 
-      context.index.assert_inscription_location(
-        second_inscription_id,
-        SatPoint {
-          outpoint: OutPoint { txid, vout: 0 },
-          offset: 1,
-        },
-        Some(50 * COIN_VALUE + 1),
-      );
+    assert_eq!(
+      context
+        .index
+        .get_rune_balances_for_address_paginated(&first_address, 100, 0)
+        .unwrap(),
+      (vec![(spaced_rune, pile(u128::MAX))], false)
+    );
 
-      context.index.assert_inscription_location(
-        third_inscription_id,
-        SatPoint {
-          outpoint: OutPoint { txid, vout: 0 },
-          offset: 2,
-        },
-        Some(50 * COIN_VALUE + 2),
-      );
-    }
-  }
+    let txid = context.core.broadcast_tx(TransactionTemplate {
+      inputs: &[(id.block.try_into().unwrap(), 1, 0, Witness::new())],
+      p2tr: true,
+      op_return: Some(
+        Runestone {
+          edicts: vec![Edict {
+            id,
+            amount: 1000,
+            output: 1,
+          }],
+          pointer: Some(2),
+          ..default()
+        }
+        .encipher(),
+      ),
+      op_return_index: Some(0),
+      outputs: 2,
+      ..default()
+    });
 
-  #[test]
-  fn inscriptions_with_pointers_to_same_sat_one_becomes_cursed_reinscriptions() {
-    for context in Context::configurations() {
-      context.mine_blocks(2);
+    context.mine_blocks(1);
 
-      let inscription = Inscription {
-        content_type: Some("text/plain".into()),
-        body: Some("hello jupiter".into()),
-        ..default()
-      };
+    let second_address = context.core.address(OutPoint { txid, vout: 1 });
+    let third_address = context.core.address(OutPoint { txid, vout: 2 });
 
-      let cursed_reinscription = Inscription {
-        content_type: Some("text/plain".into()),
-        body: Some("hello mars".into()),
-        pointer: Some(0u64.to_le_bytes().to_vec()),
-        ..default()
-      };
+    assert_eq!(
+      context
+        .index
+        .get_rune_balances_for_address_paginated(&first_address, 100, 0)
+        .unwrap(),
+      (Vec::new(), false)
+    );
 
-      let txid = context.core.broadcast_tx(TransactionTemplate {
-        inputs: &[
-          (1, 0, 0, inscription.to_witness()),
-          (2, 0, 0, cursed_reinscription.to_witness()),
-        ],
-        outputs: 2,
-        ..default()
-      });
+    assert_eq!(
+      context
+        .index
+        .get_rune_balances_for_address_paginated(&second_address, 100, 0)
+        .unwrap(),
+      (vec![(spaced_rune, pile(1000))], false)
+    );
+
+    assert_eq!(
+      context
+        .index
+        .get_rune_balances_for_address_paginated(&third_address, 100, 0)
+        .unwrap(),
+      (vec![(spaced_rune, pile(u128::MAX - 1000))], false)
+    );
+  }
 
-      context.mine_blocks(1);
+  #[test]
+  fn fast_sync_reads_blocks_from_block_files() {
+    let tempdir = tempfile::TempDir::new().unwrap();
 
-      let inscription_id = InscriptionId { txid, index: 0 };
-      let cursed_reinscription_id = InscriptionId { txid, index: 1 };
+    let bitcoin_data_dir = tempdir.path().join("bitcoin");
 
-      context.index.assert_inscription_location(
-        inscription_id,
-        SatPoint {
-          outpoint: OutPoint { txid, vout: 0 },
-          offset: 0,
-        },
-        Some(50 * COIN_VALUE),
-      );
+    let context = Context::builder()
+      .arg("--fast-sync")
+      .arg("--index-sats")
+      .arg(format!("--bitcoin-data-dir={}", bitcoin_data_dir.display()))
+      .tempdir(tempdir)
+      .build();
 
-      context.index.assert_inscription_location(
-        cursed_reinscription_id,
-        SatPoint {
-          outpoint: OutPoint { txid, vout: 0 },
-          offset: 0,
-        },
-        Some(50 * COIN_VALUE),
-      );
+    context.mine_blocks_with_update(1, false);
 
-      assert_eq!(context.index.inscription_number(inscription_id), 0);
+    let txid = context.core.broadcast_tx(TransactionTemplate {
+      inputs: &[(1, 0, 0, inscription("text/plain", "hello").to_witness())],
+      ..default()
+    });
 
-      assert_eq!(
-        context.index.inscription_number(cursed_reinscription_id),
-        -1
-      );
-    }
-  }
+    context.mine_blocks_with_update(30, false);
 
-  #[test]
-  fn inscribe_into_fee() {
-    for context in Context::configurations() {
-      context.mine_blocks(1);
+    context.core.write_block_files(
+      &bitcoin_data_dir.join("regtest/blocks"),
+      4,
+      Some([1, 2, 3, 4, 5, 6, 7, 8]),
+    );
 
-      let inscription = Inscription::default();
+    context.index.update().unwrap();
 
-      let txid = context.core.broadcast_tx(TransactionTemplate {
-        inputs: &[(1, 0, 0, inscription.to_witness())],
-        fee: 50 * COIN_VALUE,
-        ..default()
-      });
+    assert_eq!(context.index.block_count().unwrap(), 32);
 
-      let blocks = context.mine_blocks(1);
+    assert_eq!(
+      context.index.block_hash(Some(31)).unwrap(),
+      Some(context.core.state().hashes[31]),
+    );
 
-      let inscription_id = InscriptionId { txid, index: 0 };
+    assert_eq!(
+      context
+        .index
+        .get_inscription_satpoint_by_id(InscriptionId { txid, index: 0 })
+        .unwrap(),
+      Some(SatPoint {
+        outpoint: OutPoint { txid, vout: 0 },
+        offset: 0,
+      })
+    );
 
-      context.index.assert_inscription_location(
-        inscription_id,
-        SatPoint {
-          outpoint: OutPoint {
-            txid: blocks[0].txdata[0].txid(),
-            vout: 0,
-          },
-          offset: 50 * COIN_VALUE,
-        },
-        Some(50 * COIN_VALUE),
-      );
-    }
+    assert_eq!(
+      context
+        .index
+        .rare_sat_satpoint(Sat(50 * COIN_VALUE))
+        .unwrap(),
+      Some(SatPoint {
+        outpoint: OutPoint { txid, vout: 0 },
+        offset: 0,
+      })
+    );
   }
 
   #[test]
-  fn inscribe_into_fee_with_reduced_subsidy() {
-    for context in Context::configurations() {
-      context.mine_blocks(1);
+  fn fast_sync_falls_back_to_rpc_without_block_files() {
+    let context = Context::builder()
+      .arg("--fast-sync")
+      .arg("--bitcoin-data-dir=/nonexistent")
+      .build();
 
-      let inscription = Inscription::default();
+    context.mine_blocks(30);
 
-      let txid = context.core.broadcast_tx(TransactionTemplate {
-        inputs: &[(1, 0, 0, inscription.to_witness())],
-        fee: 50 * COIN_VALUE,
-        ..default()
-      });
+    assert_eq!(context.index.block_count().unwrap(), 31);
+  }
 
-      let blocks = context.mine_blocks_with_subsidy(1, 25 * COIN_VALUE);
+  #[test]
+  fn recorded_fixture_is_replayed() {
+    let recorded = Context::builder().arg("--index-sats").build();
 
-      let inscription_id = InscriptionId { txid, index: 0 };
+    recorded.mine_blocks(1);
 
-      context.index.assert_inscription_location(
-        inscription_id,
-        SatPoint {
-          outpoint: OutPoint {
-            txid: blocks[0].txdata[0].txid(),
-            vout: 0,
-          },
-          offset: 50 * COIN_VALUE,
-        },
-        Some(50 * COIN_VALUE),
-      );
-    }
-  }
+    let txid = recorded.core.broadcast_tx(TransactionTemplate {
+      inputs: &[(1, 0, 0, inscription("text/plain", "hello").to_witness())],
+      ..default()
+    });
 
-  #[test]
-  fn pre_jubilee_first_reinscription_after_cursed_inscription_is_blessed() {
-    for context in Context::configurations() {
-      context.mine_blocks(1);
+    recorded.mine_blocks(1);
 
-      // Before the jubilee, an inscription on a sat using a pushnum opcode is
-      // cursed and not vindicated.
+    let fixture = recorded.tempdir.path().join("fixture.json");
 
-      let script = script::Builder::new()
-        .push_opcode(opcodes::OP_FALSE)
-        .push_opcode(opcodes::all::OP_IF)
-        .push_slice(b"ord")
-        .push_slice([])
-        .push_opcode(opcodes::all::OP_PUSHNUM_1)
-        .push_opcode(opcodes::all::OP_ENDIF)
-        .into_script();
+    block_source::Fixture::record(&*recorded.index.block_source, &fixture).unwrap();
 
-      let witness = Witness::from_slice(&[script.into_bytes(), Vec::new()]);
+    let replayed = Context::builder()
+      .arg("--index-sats")
+      .arg("--block-source=fixture")
+      .arg(format!("--block-source-fixture={}", fixture.display()))
+      .build();
 
-      let txid = context.core.broadcast_tx(TransactionTemplate {
-        inputs: &[(1, 0, 0, witness)],
-        ..default()
-      });
+    assert_eq!(replayed.index.block_count().unwrap(), 3);
 
-      let inscription_id = InscriptionId { txid, index: 0 };
+    assert_eq!(
+      replayed.index.block_hash(Some(2)).unwrap(),
+      recorded.index.block_hash(Some(2)).unwrap(),
+    );
 
-      context.mine_blocks(1);
+    assert_eq!(
+      replayed
+        .index
+        .get_inscription_satpoint_by_id(InscriptionId { txid, index: 0 })
+        .unwrap(),
+      Some(SatPoint {
+        outpoint: OutPoint { txid, vout: 0 },
+        offset: 0,
+      })
+    );
 
-      let entry = context
+    assert_eq!(
+      replayed
         .index
-        .get_inscription_entry(inscription_id)
-        .unwrap()
-        .unwrap();
+        .rare_sat_satpoint(Sat(50 * COIN_VALUE))
+        .unwrap(),
+      Some(SatPoint {
+        outpoint: OutPoint { txid, vout: 0 },
+        offset: 0,
+      })
+    );
+  }
 
-      assert!(Charm::charms(entry.charms)
-        .iter()
-        .any(|charm| *charm == Charm::Cursed));
+  #[test]
+  fn address_history_is_recorded() {
+    let context = Context::builder().arg("--index-address-history").build();
 
-      assert!(!Charm::charms(entry.charms)
-        .iter()
-        .any(|charm| *charm == Charm::Vindicated));
+    let coinbase = context.mine_blocks(1)[0].txdata[0].txid();
 
-      let sat = entry.sat;
+    let coinbase_address = context.core.address(OutPoint {
+      txid: coinbase,
+      vout: 0,
+    });
 
-      assert_eq!(entry.inscription_number, -1);
+    let first = context.core.broadcast_tx(TransactionTemplate {
+      inputs: &[(1, 0, 0, inscription("text/plain", "hello").to_witness())],
+      p2tr: true,
+      ..default()
+    });
 
-      // Before the jubilee, reinscription on the same sat is not cursed and
-      // not vindicated.
+    context.mine_blocks(1);
 
-      let inscription = Inscription::default();
+    let inscription_id = InscriptionId {
+      txid: first,
+      index: 0,
+    };
 
-      let txid = context.core.broadcast_tx(TransactionTemplate {
-        inputs: &[(2, 1, 0, inscription.to_witness())],
-        ..default()
-      });
+    let first_address = context.core.address(OutPoint {
+      txid: first,
+      vout: 0,
+    });
 
-      context.mine_blocks(1);
+    let second = context.core.broadcast_tx(TransactionTemplate {
+      inputs: &[(2, 1, 0, Default::default())],
+      p2tr: true,
+      ..default()
+    });
 
-      let inscription_id = InscriptionId { txid, index: 0 };
+    context.mine_blocks(1);
 
-      let entry = context
-        .index
-        .get_inscription_entry(inscription_id)
-        .unwrap()
-        .unwrap();
+    let subsidy = context.core.tx_by_id(coinbase).output[0].value;
+    let first_value = context.core.tx_by_id(first).output[0].value;
+    let second_value = context.core.tx_by_id(second).output[0].value;
 
-      assert_eq!(entry.inscription_number, 0);
+    let second_address = context.core.address(OutPoint {
+      txid: second,
+      vout: 0,
+    });
 
-      assert!(!Charm::charms(entry.charms)
-        .iter()
-        .any(|charm| *charm == Charm::Cursed));
+    assert_eq!(
+      context
+        .index
+        .get_address_transactions_paginated(&coinbase_address, 100, 0)
+        .unwrap(),
+      (
+        vec![
+          api::AddressTransaction {
+            direction: api::Direction::Outgoing,
+            height: 2,
+            inscriptions: Vec::new(),
+            received: 0,
+            runes: Vec::new(),
+            sent: subsidy,
+            txid: first,
+          },
+          api::AddressTransaction {
+            direction: api::Direction::Incoming,
+            height: 1,
+            inscriptions: Vec::new(),
+            received: subsidy,
+            runes: Vec::new(),
+            sent: 0,
+            txid: coinbase,
+          },
+        ],
+        false
+      )
+    );
 
-      assert!(!Charm::charms(entry.charms)
-        .iter()
-        .any(|charm| *charm == Charm::Vindicated));
+    assert_eq!(
+      context
+        .index
+        .get_address_transactions_paginated(&first_address, 1, 0)
+        .unwrap(),
+      (
+        vec![api::AddressTransaction {
+          direction: api::Direction::Outgoing,
+          height: 3,
+          inscriptions: vec![inscription_id],
+          received: 0,
+          runes: Vec::new(),
+          sent: first_value,
+          txid: second,
+        }],
+        true
+      )
+    );
 
-      assert_eq!(sat, entry.sat);
+    assert_eq!(
+      context
+        .index
+        .get_address_transactions_paginated(&first_address, 1, 1)
+        .unwrap(),
+      (
+        vec![api::AddressTransaction {
+          direction: api::Direction::Incoming,
+          height: 2,
+          inscriptions: vec![inscription_id],
+          received: first_value,
+          runes: Vec::new(),
+          sent: 0,
+          txid: first,
+        }],
+        false
+      )
+    );
 
-      // Before the jubilee, a third reinscription on the same sat is cursed
-      // and not vindicated.
+    assert_eq!(
+      context
+        .index
+        .get_address_transactions_paginated(&second_address, 100, 0)
+        .unwrap(),
+      (
+        vec![api::AddressTransaction {
+          direction: api::Direction::Incoming,
+          height: 3,
+          inscriptions: vec![inscription_id],
+          received: second_value,
+          runes: Vec::new(),
+          sent: 0,
+          txid: second,
+        }],
+        false
+      )
+    );
+  }
 
-      let inscription = Inscription::default();
+  #[test]
+  fn address_history_records_rune_deltas() {
+    const RUNE: u128 = 99246114928149462;
 
-      let txid = context.core.broadcast_tx(TransactionTemplate {
-        inputs: &[(3, 1, 0, inscription.to_witness())],
+    let context = Context::builder()
+      .arg("--index-address-history")
+      .arg("--index-runes")
+      .build();
+
+    let (txid, id) = context.etch(
+      Runestone {
+        edicts: vec![Edict {
+          id: RuneId::default(),
+          amount: u128::MAX,
+          output: 0,
+        }],
+        etching: Some(Etching {
+          rune: Some(Rune(RUNE)),
+          premine: Some(u128::MAX),
+          ..default()
+        }),
         ..default()
-      });
+      },
+      1,
+    );
+
+    let first_address = context.core.address(OutPoint { txid, vout: 0 });
+
+    let transfer = context.core.broadcast_tx(TransactionTemplate {
+      inputs: &[(id.block.try_into().unwrap(), 1, 0, Witness::new())],
+      p2tr: true,
+      op_return: Some(
+        Runestone {
+          edicts: vec![Edict {
+            id,
+            amount: 1000,
+            output: 1,
+          }],
+          pointer: Some(2),
+          ..default()
+        }
+        .encipher(),
+      ),
+      op_return_index: Some(0),
+      outputs: 2,
+      ..default()
+    });
 
-      context.mine_blocks(1);
+    context.mine_blocks(1);
 
-      let inscription_id = InscriptionId { txid, index: 0 };
+    let pile = |amount| Pile {
+      amount,
+      divisibility: 0,
+      symbol: None,
+    };
 
-      let entry = context
-        .index
-        .get_inscription_entry(inscription_id)
-        .unwrap()
-        .unwrap();
+    let rune = SpacedRune {
+      rune: Rune(RUNE),
+      spacers: 0,
+    };
 
-      assert!(Charm::charms(entry.charms)
-        .iter()
-        .any(|charm| *charm == Charm::Cursed));
+    let (transactions, _more) = context
+      .index
+      .get_address_transactions_paginated(&first_address, 1, 0)
+      .unwrap();
 
-      assert!(!Charm::charms(entry.charms)
-        .iter()
-        .any(|charm| *charm == Charm::Vindicated));
+    assert_eq!(transactions[0].txid, transfer);
+    assert_eq!(transactions[0].direction, api::Direction::Outgoing);
+    assert_eq!(
+      transactions[0].runes,
+      vec![api::AddressRuneDelta {
+        received: pile(0),
+        rune,
+        sent: pile(u128::MAX),
+      }]
+    );
 
-      assert_eq!(entry.inscription_number, -2);
+    let (transactions, more) = context
+      .index
+      .get_address_transactions_paginated(
+        &context.core.address(OutPoint {
+          txid: transfer,
+          vout: 1,
+        }),
+        100,
+        0,
+      )
+      .unwrap();
 
-      assert_eq!(sat, entry.sat);
-    }
+    assert!(!more);
+    assert_eq!(transactions.len(), 1);
+    assert_eq!(transactions[0].direction, api::Direction::Incoming);
+    assert_eq!(
+      transactions[0].runes,
+      vec![api::AddressRuneDelta {
+        received: pile(1000),
+        rune,
+        sent: pile(0),
+      }]
+    );
   }
 
   #[test]
-  fn post_jubilee_first_reinscription_after_vindicated_inscription_not_vindicated() {
-    for context in Context::configurations() {
-      context.mine_blocks(110);
-      // After the jubilee, an inscription on a sat using a pushnum opcode is
-      // vindicated and not cursed.
-
-      let script = script::Builder::new()
-        .push_opcode(opcodes::OP_FALSE)
-        .push_opcode(opcodes::all::OP_IF)
-        .push_slice(b"ord")
-        .push_slice([])
-        .push_opcode(opcodes::all::OP_PUSHNUM_1)
-        .push_opcode(opcodes::all::OP_ENDIF)
-        .into_script();
+  fn address_history_is_not_recorded_without_flag() {
+    let context = Context::builder().arg("--index-addresses").build();
 
-      let witness = Witness::from_slice(&[script.into_bytes(), Vec::new()]);
+    context.mine_blocks(1);
 
-      let txid = context.core.broadcast_tx(TransactionTemplate {
-        inputs: &[(1, 0, 0, witness)],
-        ..default()
-      });
+    let txid = context.core.broadcast_tx(TransactionTemplate {
+      inputs: &[(1, 0, 0, Default::default())],
+      p2tr: true,
+      ..default()
+    });
 
-      let inscription_id = InscriptionId { txid, index: 0 };
+    context.mine_blocks(1);
 
-      context.mine_blocks(1);
+    assert!(!context.index.has_address_history_index());
 
-      let entry = context
+    assert_eq!(
+      context
         .index
-        .get_inscription_entry(inscription_id)
-        .unwrap()
-        .unwrap();
+        .get_address_transactions_paginated(
+          &context.core.address(OutPoint { txid, vout: 0 }),
+          100,
+          0
+        )
+        .unwrap(),
+      (Vec::new(), false)
+    );
+  }
 
-      assert!(!Charm::charms(entry.charms)
-        .iter()
-        .any(|charm| *charm == Charm::Cursed));
+  #[test]
+  fn inscription_transfers_are_recorded() {
+    let context = Context::builder().arg("--index-transfers").build();
 
-      assert!(Charm::charms(entry.charms)
-        .iter()
-        .any(|charm| *charm == Charm::Vindicated));
+    context.mine_blocks(1);
 
-      let sat = entry.sat;
+    let create_txid = context.core.broadcast_tx(TransactionTemplate {
+      inputs: &[(1, 0, 0, inscription("text/plain", "hello").to_witness())],
+      ..default()
+    });
 
-      assert_eq!(entry.inscription_number, 0);
+    context.mine_blocks(1);
 
-      // After the jubilee, a reinscription on the same is not cursed and not
-      // vindicated.
+    let inscription_id = InscriptionId {
+      txid: create_txid,
+      index: 0,
+    };
 
-      let inscription = Inscription::default();
+    assert_eq!(
+      context
+        .index
+        .get_inscription_transfers_paginated(inscription_id, 100, 0)
+        .unwrap(),
+      Some((Vec::new(), false))
+    );
 
-      let txid = context.core.broadcast_tx(TransactionTemplate {
-        inputs: &[(111, 1, 0, inscription.to_witness())],
-        ..default()
-      });
+    let transfer_txid = context.core.broadcast_tx(TransactionTemplate {
+      inputs: &[(2, 1, 0, Default::default())],
+      fee: 1000,
+      p2tr: true,
+      ..default()
+    });
 
-      context.mine_blocks(1);
+    let blocks = context.mine_blocks(1);
 
-      let inscription_id = InscriptionId { txid, index: 0 };
+    let transfer = blocks[0]
+      .txdata
+      .iter()
+      .find(|tx| tx.txid() == transfer_txid)
+      .unwrap();
 
-      let entry = context
+    assert_eq!(
+      context
         .index
-        .get_inscription_entry(inscription_id)
-        .unwrap()
-        .unwrap();
+        .get_inscription_transfers_paginated(inscription_id, 100, 0)
+        .unwrap(),
+      Some((
+        vec![TransferEntry {
+          fee: 1000,
+          height: 3,
+          new_satpoint: SatPoint {
+            outpoint: OutPoint {
+              txid: transfer_txid,
+              vout: 0,
+            },
+            offset: 0,
+          },
+          old_satpoint: SatPoint {
+            outpoint: OutPoint {
+              txid: create_txid,
+              vout: 0,
+            },
+            offset: 0,
+          },
+          receiver: Some(transfer.output[0].script_pubkey.clone()),
+          sender: Some(
+            context.core.tx_by_id(create_txid).output[0]
+              .script_pubkey
+              .clone()
+          ),
+          txid: transfer_txid,
+        }],
+        false
+      ))
+    );
 
-      assert!(!Charm::charms(entry.charms)
-        .iter()
-        .any(|charm| *charm == Charm::Cursed));
+    let spend_txid = context.core.broadcast_tx(TransactionTemplate {
+      inputs: &[(3, 1, 0, Default::default())],
+      fee: 50 * COIN_VALUE - 1000,
+      outputs: 0,
+      ..default()
+    });
 
-      assert!(!Charm::charms(entry.charms)
-        .iter()
-        .any(|charm| *charm == Charm::Vindicated));
+    let blocks = context.mine_blocks(1);
 
-      assert_eq!(entry.inscription_number, 1);
+    let coinbase = &blocks[0].txdata[0];
 
-      assert_eq!(sat, entry.sat);
+    let (transfers, more) = context
+      .index
+      .get_inscription_transfers_paginated(inscription_id, 1, 1)
+      .unwrap()
+      .unwrap();
 
-      // After the jubilee, a third reinscription on the same is vindicated and
-      // not cursed.
+    assert!(!more);
 
-      let inscription = Inscription::default();
+    assert_eq!(transfers.len(), 1);
+    assert_eq!(transfers[0].txid, coinbase.txid());
+    assert_eq!(transfers[0].fee, 0);
+    assert_eq!(
+      transfers[0].receiver,
+      Some(coinbase.output[0].script_pubkey.clone())
+    );
+    assert_ne!(transfers[0].txid, spend_txid);
+  }
 
-      let txid = context.core.broadcast_tx(TransactionTemplate {
-        inputs: &[(112, 1, 0, inscription.to_witness())],
-        ..default()
-      });
+  #[test]
+  fn inscription_transfers_are_not_recorded_without_transfer_index() {
+    let context = Context::builder().build();
 
-      context.mine_blocks(1);
+    context.mine_blocks(1);
 
-      let inscription_id = InscriptionId { txid, index: 0 };
+    let txid = context.core.broadcast_tx(TransactionTemplate {
+      inputs: &[(1, 0, 0, inscription("text/plain", "hello").to_witness())],
+      ..default()
+    });
 
-      let entry = context
-        .index
-        .get_inscription_entry(inscription_id)
-        .unwrap()
-        .unwrap();
+    context.mine_blocks(1);
 
-      assert!(!Charm::charms(entry.charms)
-        .iter()
-        .any(|charm| *charm == Charm::Cursed));
+    context.core.broadcast_tx(TransactionTemplate {
+      inputs: &[(2, 1, 0, Default::default())],
+      ..default()
+    });
 
-      assert!(Charm::charms(entry.charms)
-        .iter()
-        .any(|charm| *charm == Charm::Vindicated));
+    context.mine_blocks(1);
 
-      assert_eq!(entry.inscription_number, 2);
+    assert!(!context.index.has_transfer_index());
 
-      assert_eq!(sat, entry.sat);
-    }
+    assert_eq!(
+      context
+        .index
+        .get_inscription_transfers_paginated(InscriptionId { txid, index: 0 }, 100, 0)
+        .unwrap(),
+      Some((Vec::new(), false))
+    );
   }
 
   #[test]
-  fn index_spent_sats_retains_spent_sat_range_entries() {
-    let ranges = {
-      let context = Context::builder().arg("--index-sats").build();
-
-      context.mine_blocks(1);
-
-      let outpoint = OutPoint {
-        txid: context.core.tx(1, 0).into(),
-        vout: 0,
-      };
-
-      let ranges = context.index.list(outpoint).unwrap().unwrap();
-
-      assert!(!ranges.is_empty());
+  fn fee_spent_inscriptions_are_numbered_last_in_block() {
+    for context in Context::configurations() {
+      context.mine_blocks(2);
 
-      context.core.broadcast_tx(TransactionTemplate {
-        inputs: &[(1, 0, 0, Default::default())],
+      let txid = context.core.broadcast_tx(TransactionTemplate {
+        inputs: &[(1, 0, 0, inscription("text/plain", "hello").to_witness())],
+        fee: 50 * COIN_VALUE,
         ..default()
       });
 
-      context.mine_blocks(1);
-
-      assert!(context.index.list(outpoint).unwrap().is_none());
-
-      ranges
-    };
-
-    {
-      let context = Context::builder()
-        .arg("--index-sats")
-        .arg("--index-spent-sats")
-        .build();
-
-      context.mine_blocks(1);
-
-      let outpoint = OutPoint {
-        txid: context.core.tx(1, 0).into(),
-        vout: 0,
-      };
-
-      let unspent_ranges = context.index.list(outpoint).unwrap().unwrap();
-
-      assert!(!unspent_ranges.is_empty());
-
-      assert_eq!(unspent_ranges, ranges);
+      let a = InscriptionId { txid, index: 0 };
 
-      context.core.broadcast_tx(TransactionTemplate {
-        inputs: &[(1, 0, 0, Default::default())],
+      let txid = context.core.broadcast_tx(TransactionTemplate {
+        inputs: &[(2, 0, 0, inscription("text/plain", "hello").to_witness())],
         ..default()
       });
 
+      let b = InscriptionId { txid, index: 0 };
+
       context.mine_blocks(1);
 
-      let spent_ranges = context.index.list(outpoint).unwrap().unwrap();
+      assert_eq!(context.index.inscription_number(a), 1);
+      assert_eq!(context.index.inscription_number(b), 0);
+    }
+  }
 
-      assert_eq!(spent_ranges, ranges);
+  fn next_non_block_event(event_receiver: &mut tokio::sync::mpsc::Receiver<Event>) -> Event {
+    loop {
+      let event = event_receiver.blocking_recv().unwrap();
+
+      if !matches!(
+        event,
+        Event::BlockStarted { .. } | Event::BlockCommitted { .. }
+      ) {
+        return event;
+      }
     }
   }
 
   #[test]
-  fn index_spent_sats_implies_index_sats() {
-    let context = Context::builder().arg("--index-spent-sats").build();
+  fn inscription_event_sender_channel() {
+    let (event_sender, mut event_receiver) = tokio::sync::mpsc::channel(1024);
+    let context = Context::builder().event_sender(event_sender).build();
 
     context.mine_blocks(1);
 
-    let outpoint = OutPoint {
-      txid: context.core.tx(1, 0).into(),
-      vout: 0,
+    let inscription = Inscription::default();
+    let create_txid = context.core.broadcast_tx(TransactionTemplate {
+      inputs: &[(1, 0, 0, inscription.to_witness())],
+      fee: 0,
+      outputs: 1,
+      ..default()
+    });
+
+    context.mine_blocks(1);
+
+    let inscription_id = InscriptionId {
+      txid: create_txid,
+      index: 0,
     };
+    let create_event = next_non_block_event(&mut event_receiver);
+    let expected_charms = if context.index.index_sats { 513 } else { 0 };
+    assert_eq!(
+      create_event,
+      Event::InscriptionCreated {
+        details: None,
+        inscription_id,
+        location: Some(SatPoint {
+          outpoint: OutPoint {
+            txid: create_txid,
+            vout: 0
+          },
+          offset: 0
+        }),
+        sequence_number: 0,
+        block_height: 2,
+        charms: expected_charms,
+        parent_inscription_ids: Vec::new(),
+      }
+    );
 
-    context.core.broadcast_tx(TransactionTemplate {
-      inputs: &[(1, 0, 0, Default::default())],
+    // Transfer inscription
+    let transfer_txid = context.core.broadcast_tx(TransactionTemplate {
+      inputs: &[(2, 1, 0, Default::default())],
+      fee: 0,
+      outputs: 1,
       ..default()
     });
 
     context.mine_blocks(1);
 
-    assert!(context.index.list(outpoint).unwrap().is_some());
+    let transfer_event = next_non_block_event(&mut event_receiver);
+    assert_eq!(
+      transfer_event,
+      Event::InscriptionTransferred {
+        block_height: 3,
+        inscription_id,
+        new_location: SatPoint {
+          outpoint: OutPoint {
+            txid: transfer_txid,
+            vout: 0
+          },
+          offset: 0
+        },
+        old_location: SatPoint {
+          outpoint: OutPoint {
+            txid: create_txid,
+            vout: 0
+          },
+          offset: 0
+        },
+        sequence_number: 0,
+      }
+    );
   }
 
   #[test]
-  fn spent_sats_are_retained_after_flush() {
-    let context = Context::builder().arg("--index-spent-sats").build();
+  fn events_are_recorded_in_outbox() {
+    let context = Context::builder().event_outbox().build();
 
     context.mine_blocks(1);
 
+    let events = context.index.pending_events(usize::MAX).unwrap();
+
+    assert_eq!(
+      events
+        .iter()
+        .map(|(event_id, event)| (*event_id, event.block_height()))
+        .collect::<Vec<(u64, u32)>>(),
+      [(0, 0), (1, 0), (2, 1), (3, 1)],
+    );
+
+    context.index.mark_events_delivered(3).unwrap();
+
+    assert!(context.index.pending_events(usize::MAX).unwrap().is_empty());
+
     let txid = context.core.broadcast_tx(TransactionTemplate {
-      inputs: &[(1, 0, 0, Default::default())],
+      inputs: &[(1, 0, 0, inscription("text/plain", "foo").to_witness())],
       ..default()
     });
 
-    context.mine_blocks_with_update(1, false);
+    context.mine_blocks(1);
 
-    let outpoint = OutPoint { txid, vout: 0 };
+    let inscription_id = InscriptionId { txid, index: 0 };
 
     context.core.broadcast_tx(TransactionTemplate {
       inputs: &[(2, 1, 0, Default::default())],
@@ -6282,260 +8946,294 @@ mod tests {
 
     context.mine_blocks(1);
 
-    assert!(context.index.list(outpoint).unwrap().is_some());
-  }
+    let events = context.index.pending_events(usize::MAX).unwrap();
 
-  #[test]
-  fn is_output_spent() {
-    let context = Context::builder().build();
+    assert_eq!(
+      events
+        .iter()
+        .map(|(event_id, event)| (*event_id, event.inscription_id()))
+        .collect::<Vec<(u64, Option<InscriptionId>)>>(),
+      [
+        (4, None),
+        (5, Some(inscription_id)),
+        (6, None),
+        (7, None),
+        (8, Some(inscription_id)),
+        (9, None),
+      ],
+    );
 
-    assert!(!context.index.is_output_spent(OutPoint::null()).unwrap());
-    assert!(!context
-      .index
-      .is_output_spent(Chain::Mainnet.genesis_coinbase_outpoint())
-      .unwrap());
+    assert_eq!(context.index.pending_events(1).unwrap(), events[..1]);
 
-    context.mine_blocks(1);
+    context.index.mark_events_delivered(4).unwrap();
 
-    assert!(!context
-      .index
-      .is_output_spent(OutPoint {
-        txid: context.core.tx(1, 0).txid(),
-        vout: 0,
-      })
-      .unwrap());
+    assert_eq!(
+      context.index.pending_events(usize::MAX).unwrap(),
+      events[1..]
+    );
+
+    context.index.mark_events_delivered(9).unwrap();
+
+    assert!(context.index.pending_events(usize::MAX).unwrap().is_empty());
 
     context.core.broadcast_tx(TransactionTemplate {
-      inputs: &[(1, 0, 0, Default::default())],
+      inputs: &[(3, 1, 0, Default::default())],
       ..default()
     });
 
     context.mine_blocks(1);
 
-    assert!(context
-      .index
-      .is_output_spent(OutPoint {
-        txid: context.core.tx(1, 0).txid(),
-        vout: 0,
-      })
-      .unwrap());
+    assert_eq!(
+      context
+        .index
+        .pending_events(usize::MAX)
+        .unwrap()
+        .into_iter()
+        .map(|(event_id, _)| event_id)
+        .collect::<Vec<u64>>(),
+      [10, 11, 12],
+    );
   }
 
   #[test]
-  fn is_output_in_active_chain() {
-    let context = Context::builder().build();
+  fn block_events() {
+    let context = Context::builder()
+      .arg("--index-runes")
+      .event_outbox()
+      .build();
 
-    assert!(context
-      .index
-      .is_output_in_active_chain(OutPoint::null())
-      .unwrap());
+    context.mine_blocks(1);
 
-    assert!(context
-      .index
-      .is_output_in_active_chain(Chain::Mainnet.genesis_coinbase_outpoint())
-      .unwrap());
+    context.index.mark_events_delivered(u64::MAX).unwrap();
 
-    context.mine_blocks(1);
+    let txid = context.core.broadcast_tx(TransactionTemplate {
+      inputs: &[(1, 0, 0, inscription("text/plain", "foo").to_witness())],
+      ..default()
+    });
 
-    assert!(context
-      .index
-      .is_output_in_active_chain(OutPoint {
-        txid: context.core.tx(1, 0).txid(),
-        vout: 0,
-      })
-      .unwrap());
+    let blocks = context.mine_blocks(1);
 
-    assert!(!context
-      .index
-      .is_output_in_active_chain(OutPoint {
-        txid: context.core.tx(1, 0).txid(),
-        vout: 1,
-      })
-      .unwrap());
+    let inscription_id = InscriptionId { txid, index: 0 };
 
-    assert!(!context
-      .index
-      .is_output_in_active_chain(OutPoint {
-        txid: Txid::all_zeros(),
-        vout: 0,
-      })
-      .unwrap());
+    let header = blocks[0].header;
+
+    assert_eq!(
+      context
+        .index
+        .pending_events(usize::MAX)
+        .unwrap()
+        .into_iter()
+        .map(|(_event_id, event)| event)
+        .collect::<Vec<Event>>(),
+      [
+        Event::BlockStarted {
+          block_hash: header.block_hash(),
+          block_height: 2,
+          prev_block_hash: header.prev_blockhash,
+          timestamp: header.time,
+        },
+        Event::InscriptionCreated {
+          block_height: 2,
+          charms: 0,
+          details: None,
+          inscription_id,
+          location: Some(SatPoint {
+            outpoint: OutPoint { txid, vout: 0 },
+            offset: 0,
+          }),
+          parent_inscription_ids: Vec::new(),
+          sequence_number: 0,
+        },
+        Event::BlockCommitted {
+          block_hash: header.block_hash(),
+          block_height: 2,
+          counts: BlockEventCounts {
+            inscriptions_created: 1,
+            ..default()
+          },
+        },
+      ],
+    );
+
+    assert_eq!(
+      context.index.block_hash(Some(1)).unwrap(),
+      Some(header.prev_blockhash)
+    );
   }
 
   #[test]
-  fn output_addresses_are_updated() {
+  fn rich_inscription_created_events() {
     let context = Context::builder()
-      .arg("--index-addresses")
-      .arg("--index-sats")
+      .args(["--rich-events", "--event-body-limit", "3"])
+      .event_outbox()
       .build();
 
     context.mine_blocks(2);
 
     let txid = context.core.broadcast_tx(TransactionTemplate {
-      inputs: &[(1, 0, 0, Witness::new()), (2, 0, 0, Witness::new())],
-      outputs: 2,
-      ..Default::default()
+      inputs: &[
+        (
+          1,
+          0,
+          0,
+          Inscription {
+            content_type: Some("text/plain".into()),
+            body: Some("foo".into()),
+            metadata: Some(vec![0x01]),
+            metaprotocol: Some("brc-20".into()),
+            ..default()
+          }
+          .to_witness(),
+        ),
+        (2, 0, 0, inscription("text/plain", "hello").to_witness()),
+      ],
+      fee: 1000,
+      ..default()
     });
 
     context.mine_blocks(1);
 
-    let transaction = context.index.get_transaction(txid).unwrap().unwrap();
-
-    let first_address = context
+    let details = context
       .index
-      .settings
-      .chain()
-      .address_from_script(&transaction.output[0].script_pubkey)
-      .unwrap();
+      .pending_events(usize::MAX)
+      .unwrap()
+      .into_iter()
+      .filter_map(|(_event_id, event)| match event {
+        Event::InscriptionCreated { details, .. } => details,
+        _ => None,
+      })
+      .collect::<Vec<Box<InscriptionDetails>>>();
 
-    let first_address_second_output = OutPoint {
-      txid: transaction.txid(),
-      vout: 1,
-    };
+    assert_eq!(details.len(), 2);
+
+    let script_pubkey = context.core.tx_by_id(txid).output[0].script_pubkey.clone();
 
     assert_eq!(
-      context.index.get_address_info(&first_address).unwrap(),
-      [
-        OutPoint {
-          txid: transaction.txid(),
-          vout: 0
-        },
-        first_address_second_output
-      ]
+      *details[0],
+      InscriptionDetails {
+        address: Some(
+          Chain::Regtest
+            .address_from_script(&script_pubkey)
+            .unwrap()
+            .to_string()
+        ),
+        body: Some("666f6f".into()),
+        content_length: Some(3),
+        content_type: Some("text/plain".into()),
+        delegate: None,
+        fee: 500,
+        inscription_number: 0,
+        metadata: Some("01".into()),
+        metaprotocol: Some("brc-20".into()),
+        sat: None,
+      }
     );
 
-    let txid = context.core.broadcast_tx(TransactionTemplate {
-      inputs: &[(3, 1, 0, Witness::new())],
-      p2tr: true,
-      ..Default::default()
+    assert_eq!(details[1].body, None);
+    assert_eq!(details[1].content_length, Some(5));
+    assert_eq!(details[1].inscription_number, -1);
+  }
+
+  #[test]
+  fn events_are_not_recorded_without_outbox() {
+    let context = Context::builder().build();
+
+    context.mine_blocks(1);
+
+    context.core.broadcast_tx(TransactionTemplate {
+      inputs: &[(1, 0, 0, inscription("text/plain", "foo").to_witness())],
+      ..default()
     });
 
     context.mine_blocks(1);
 
-    let transaction = context.index.get_transaction(txid).unwrap().unwrap();
+    assert!(context.index.pending_events(usize::MAX).unwrap().is_empty());
+  }
 
-    let second_address = context
-      .index
-      .settings
-      .chain()
-      .address_from_script(&transaction.output[0].script_pubkey)
-      .unwrap();
+  #[test]
+  fn event_outbox_has_one_kind_of_consumer() {
+    let Context {
+      index,
+      core: _core,
+      tempdir: _tempdir,
+    } = Context::builder().event_outbox().build();
 
-    assert_eq!(
-      context.index.get_address_info(&first_address).unwrap(),
-      [first_address_second_output]
-    );
+    let settings = index.settings.clone();
+
+    drop(index);
 
     assert_eq!(
-      context.index.get_address_info(&second_address).unwrap(),
-      [OutPoint {
-        txid: transaction.txid(),
-        vout: 0
-      }]
+      Index::open_with_event_outbox(&settings, EventConsumer::Feed)
+        .err()
+        .unwrap()
+        .to_string(),
+      "index event outbox is consumed by `ord index publish`, and cannot also be consumed by \
+      `ord server --events`",
     );
+
+    Index::open_with_event_outbox(&settings, EventConsumer::Sink).unwrap();
+
+    Index::open(&settings).unwrap();
   }
 
   #[test]
-  fn fee_spent_inscriptions_are_numbered_last_in_block() {
-    for context in Context::configurations() {
-      context.mine_blocks(2);
+  fn claimed_event_outbox_records_events_without_consumer() {
+    let Context {
+      index,
+      core,
+      tempdir: _tempdir,
+    } = Context::builder().event_outbox().build();
 
-      let txid = context.core.broadcast_tx(TransactionTemplate {
-        inputs: &[(1, 0, 0, inscription("text/plain", "hello").to_witness())],
-        fee: 50 * COIN_VALUE,
-        ..default()
-      });
+    let settings = index.settings.clone();
 
-      let a = InscriptionId { txid, index: 0 };
+    let next_event_id = index.next_event_id().unwrap();
 
-      let txid = context.core.broadcast_tx(TransactionTemplate {
-        inputs: &[(2, 0, 0, inscription("text/plain", "hello").to_witness())],
-        ..default()
-      });
+    drop(index);
 
-      let b = InscriptionId { txid, index: 0 };
+    let index = Index::open(&settings).unwrap();
+
+    core.mine_blocks(1);
+
+    index.update().unwrap();
 
-      context.mine_blocks(1);
+    assert!(index.next_event_id().unwrap() > next_event_id);
 
-      assert_eq!(context.index.inscription_number(a), 1);
-      assert_eq!(context.index.inscription_number(b), 0);
-    }
+    assert!(index
+      .pending_events(usize::MAX)
+      .unwrap()
+      .iter()
+      .any(|(_, event)| matches!(
+        event,
+        Event::BlockCommitted {
+          block_height: 1,
+          ..
+        }
+      )));
   }
 
   #[test]
-  fn inscription_event_sender_channel() {
+  fn event_sender_drains_outbox() {
     let (event_sender, mut event_receiver) = tokio::sync::mpsc::channel(1024);
     let context = Context::builder().event_sender(event_sender).build();
 
     context.mine_blocks(1);
 
-    let inscription = Inscription::default();
-    let create_txid = context.core.broadcast_tx(TransactionTemplate {
-      inputs: &[(1, 0, 0, inscription.to_witness())],
-      fee: 0,
-      outputs: 1,
+    context.core.broadcast_tx(TransactionTemplate {
+      inputs: &[(1, 0, 0, inscription("text/plain", "foo").to_witness())],
       ..default()
     });
 
     context.mine_blocks(1);
 
-    let inscription_id = InscriptionId {
-      txid: create_txid,
-      index: 0,
-    };
-    let create_event = event_receiver.blocking_recv().unwrap();
-    let expected_charms = if context.index.index_sats { 513 } else { 0 };
-    assert_eq!(
-      create_event,
-      Event::InscriptionCreated {
-        inscription_id,
-        location: Some(SatPoint {
-          outpoint: OutPoint {
-            txid: create_txid,
-            vout: 0
-          },
-          offset: 0
-        }),
-        sequence_number: 0,
-        block_height: 2,
-        charms: expected_charms,
-        parent_inscription_ids: Vec::new(),
-      }
-    );
-
-    // Transfer inscription
-    let transfer_txid = context.core.broadcast_tx(TransactionTemplate {
-      inputs: &[(2, 1, 0, Default::default())],
-      fee: 0,
-      outputs: 1,
-      ..default()
-    });
-
-    context.mine_blocks(1);
+    assert!(context.index.pending_events(usize::MAX).unwrap().is_empty());
 
-    let transfer_event = event_receiver.blocking_recv().unwrap();
-    assert_eq!(
-      transfer_event,
-      Event::InscriptionTransferred {
-        block_height: 3,
-        inscription_id,
-        new_location: SatPoint {
-          outpoint: OutPoint {
-            txid: transfer_txid,
-            vout: 0
-          },
-          offset: 0
-        },
-        old_location: SatPoint {
-          outpoint: OutPoint {
-            txid: create_txid,
-            vout: 0
-          },
-          offset: 0
-        },
-        sequence_number: 0,
+    assert!(matches!(
+      event_receiver.blocking_recv().unwrap(),
+      Event::BlockStarted {
+        block_height: 0,
+        ..
       }
-    );
+    ));
   }
 
   #[test]
@@ -6588,7 +9286,7 @@ mod tests {
     );
 
     assert_eq!(
-      event_receiver.blocking_recv().unwrap(),
+      next_non_block_event(&mut event_receiver),
       Event::RuneEtched {
         block_height: 8,
         txid: txid0,
@@ -6641,7 +9339,7 @@ mod tests {
     );
 
     assert_eq!(
-      event_receiver.blocking_recv().unwrap(),
+      next_non_block_event(&mut event_receiver),
       Event::RuneMinted {
         block_height: 9,
         txid: txid1,
@@ -6697,10 +9395,10 @@ mod tests {
       )],
     );
 
-    event_receiver.blocking_recv().unwrap();
+    next_non_block_event(&mut event_receiver);
 
     pretty_assert_eq!(
-      event_receiver.blocking_recv().unwrap(),
+      next_non_block_event(&mut event_receiver),
       Event::RuneTransferred {
         block_height: 10,
         txid: txid2,
@@ -6762,10 +9460,10 @@ mod tests {
       )],
     );
 
-    event_receiver.blocking_recv().unwrap();
+    next_non_block_event(&mut event_receiver);
 
     pretty_assert_eq!(
-      event_receiver.blocking_recv().unwrap(),
+      next_non_block_event(&mut event_receiver),
       Event::RuneBurned {
         block_height: 11,
         txid: txid3,
@@ -6774,4 +9472,294 @@ mod tests {
       }
     );
   }
+
+  fn mempool_events(context: &Context) -> Vec<Event> {
+    let events = context.index.pending_events(usize::MAX).unwrap();
+
+    if let Some((last, _)) = events.last() {
+      context.index.mark_events_delivered(*last).unwrap();
+    }
+
+    events
+      .into_iter()
+      .map(|(_, event)| event)
+      .filter(|event| {
+        !matches!(
+          event,
+          Event::BlockStarted { .. } | Event::BlockCommitted { .. }
+        )
+      })
+      .collect()
+  }
+
+  #[test]
+  fn mempool_events_require_flag() {
+    let context = Context::builder().event_outbox().build();
+
+    context.mine_blocks(1);
+
+    mempool_events(&context);
+
+    context.core.broadcast_tx(TransactionTemplate {
+      inputs: &[(1, 0, 0, inscription("text/plain", "foo").to_witness())],
+      ..default()
+    });
+
+    context.index.update().unwrap();
+
+    assert_eq!(mempool_events(&context), []);
+  }
+
+  #[test]
+  fn mempool_inscription_events() {
+    let context = Context::builder()
+      .event_outbox()
+      .arg("--mempool-events")
+      .build();
+
+    context.mine_blocks(1);
+
+    mempool_events(&context);
+
+    let txid = context.core.broadcast_tx(TransactionTemplate {
+      inputs: &[(1, 0, 0, inscription("text/plain", "foo").to_witness())],
+      ..default()
+    });
+
+    let inscription_id = InscriptionId { txid, index: 0 };
+
+    context.index.update().unwrap();
+
+    assert_eq!(
+      mempool_events(&context),
+      [Event::PendingInscriptionCreated {
+        block_height: 1,
+        content_type: Some("text/plain".into()),
+        inscription_id,
+        parent_inscription_ids: Vec::new(),
+      }]
+    );
+
+    context.index.update().unwrap();
+
+    assert_eq!(mempool_events(&context), []);
+
+    context.mine_blocks(1);
+
+    let events = mempool_events(&context);
+
+    assert!(matches!(events[0], Event::InscriptionCreated { .. }));
+
+    assert_eq!(
+      events[1..],
+      [Event::PendingTransactionConfirmed {
+        block_height: 2,
+        txid,
+      }]
+    );
+
+    let transfer = context.core.broadcast_tx(TransactionTemplate {
+      inputs: &[(2, 1, 0, Default::default())],
+      ..default()
+    });
+
+    context.index.update().unwrap();
+
+    assert_eq!(
+      mempool_events(&context),
+      [Event::PendingInscriptionTransferred {
+        block_height: 2,
+        inscription_id,
+        old_location: SatPoint {
+          outpoint: OutPoint { txid, vout: 0 },
+          offset: 0,
+        },
+        txid: transfer,
+      }]
+    );
+
+    context.core.evict(transfer);
+
+    context.index.update().unwrap();
+
+    assert_eq!(
+      mempool_events(&context),
+      [Event::PendingTransactionEvicted {
+        block_height: 2,
+        txid: transfer,
+      }]
+    );
+  }
+
+  #[test]
+  fn mempool_events_are_not_emitted_again_after_restart() {
+    let context = Context::builder()
+      .event_outbox()
+      .arg("--mempool-events")
+      .build();
+
+    context.mine_blocks(1);
+
+    mempool_events(&context);
+
+    let txid = context.core.broadcast_tx(TransactionTemplate {
+      inputs: &[(1, 0, 0, inscription("text/plain", "foo").to_witness())],
+      ..default()
+    });
+
+    context.index.update().unwrap();
+
+    assert_eq!(mempool_events(&context).len(), 1);
+
+    let Context {
+      index,
+      core,
+      tempdir: _tempdir,
+    } = context;
+
+    let settings = index.settings.clone();
+
+    drop(index);
+
+    let index = Index::open(&settings).unwrap();
+
+    index.update().unwrap();
+
+    assert!(index.pending_events(usize::MAX).unwrap().is_empty());
+
+    core.mine_blocks(1);
+
+    index.update().unwrap();
+
+    assert!(index
+      .pending_events(usize::MAX)
+      .unwrap()
+      .iter()
+      .any(|(_, event)| *event
+        == Event::PendingTransactionConfirmed {
+          block_height: 2,
+          txid,
+        }));
+  }
+
+  #[test]
+  fn mempool_transactions_without_inscriptions_or_runes_are_ignored() {
+    let context = Context::builder()
+      .event_outbox()
+      .args(["--mempool-events", "--index-runes"])
+      .build();
+
+    context.mine_blocks(1);
+
+    mempool_events(&context);
+
+    let txid = context.core.broadcast_tx(TransactionTemplate {
+      inputs: &[(1, 0, 0, Default::default())],
+      ..default()
+    });
+
+    context.index.update().unwrap();
+
+    assert_eq!(mempool_events(&context), []);
+
+    context.core.evict(txid);
+
+    context.index.update().unwrap();
+
+    assert_eq!(mempool_events(&context), []);
+  }
+
+  #[test]
+  fn mempool_rune_events() {
+    const RUNE: u128 = 99246114928149462;
+
+    let context = Context::builder()
+      .event_outbox()
+      .args(["--mempool-events", "--index-runes"])
+      .build();
+
+    let (txid0, id) = context.etch(
+      Runestone {
+        etching: Some(Etching {
+          rune: Some(Rune(RUNE)),
+          premine: Some(1000),
+          terms: Some(Terms {
+            amount: Some(10),
+            cap: Some(10),
+            ..default()
+          }),
+          ..default()
+        }),
+        ..default()
+      },
+      1,
+    );
+
+    mempool_events(&context);
+
+    let txid1 = context.core.broadcast_tx(TransactionTemplate {
+      inputs: &[(id.block.try_into().unwrap(), 1, 0, Witness::new())],
+      op_return: Some(
+        Runestone {
+          mint: Some(id),
+          ..default()
+        }
+        .encipher(),
+      ),
+      ..default()
+    });
+
+    context.index.update().unwrap();
+
+    assert_eq!(
+      mempool_events(&context),
+      [
+        Event::PendingRuneMinted {
+          block_height: 8,
+          rune_id: id,
+          txid: txid1,
+        },
+        Event::PendingRuneTransferred {
+          amount: 1000,
+          block_height: 8,
+          old_outpoint: OutPoint {
+            txid: txid0,
+            vout: 0,
+          },
+          rune_id: id,
+          txid: txid1,
+        },
+      ]
+    );
+
+    let txid2 = context.core.broadcast_tx(TransactionTemplate {
+      inputs: &[(1, 0, 0, Witness::new())],
+      op_return: Some(
+        Runestone {
+          etching: Some(Etching {
+            rune: Some(Rune(RUNE + 1)),
+            spacers: Some(1),
+            ..default()
+          }),
+          ..default()
+        }
+        .encipher(),
+      ),
+      ..default()
+    });
+
+    context.index.update().unwrap();
+
+    assert_eq!(
+      mempool_events(&context),
+      [Event::PendingRuneEtched {
+        block_height: 8,
+        rune: Some(SpacedRune {
+          rune: Rune(RUNE + 1),
+          spacers: 1,
+        }),
+        txid: txid2,
+      }]
+    );
+  }
 }
@@ -0,0 +1,234 @@
+use {super::*, async_trait::async_trait, clap::ValueEnum};
+
+mod files;
+mod fixture;
+mod rest;
+mod rpc;
+
+pub(crate) use files::{Chain, Files};
+
+#[cfg(test)]
+pub(crate) use fixture::Fixture;
+
+#[derive(Default, ValueEnum, Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum BlockSourceKind {
+  Files,
+  Fixture,
+  Rest,
+  #[default]
+  Rpc,
+}
+
+impl FromStr for BlockSourceKind {
+  type Err = Error;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s {
+      "files" => Ok(Self::Files),
+      "fixture" => Ok(Self::Fixture),
+      "rest" => Ok(Self::Rest),
+      "rpc" => Ok(Self::Rpc),
+      _ => bail!("invalid block source `{s}`"),
+    }
+  }
+}
+
+#[async_trait]
+pub(crate) trait BlockSource: Send + Sync {
+  fn block(&self, hash: BlockHash) -> Result<Block>;
+
+  fn block_count(&self) -> Result<u32>;
+
+  fn block_hash(&self, height: u32) -> Result<Option<BlockHash>>;
+
+  fn block_header(&self, hash: BlockHash) -> Result<Header>;
+
+  async fn transactions(&self, txids: Vec<Txid>) -> Result<Vec<Transaction>>;
+}
+
+pub(crate) fn open(settings: &Settings) -> Result<Arc<dyn BlockSource>> {
+  Ok(match settings.block_source() {
+    BlockSourceKind::Files => Arc::new(Files::new(settings)?),
+    BlockSourceKind::Fixture => Arc::new(fixture::Fixture::load(
+      settings
+        .block_source_fixture()
+        .ok_or_else(|| anyhow!("the fixture block source requires `--block-source-fixture`"))?,
+    )?),
+    BlockSourceKind::Rest => Arc::new(rest::Rest::new(settings)?),
+    BlockSourceKind::Rpc => Arc::new(rpc::Rpc::new(settings)?),
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use {super::*, tempfile::TempDir};
+
+  fn settings(core: &mockcore::Handle, tempdir: &TempDir, args: &[&str]) -> Settings {
+    let cookie_file = tempdir.path().join("cookie");
+
+    fs::write(&cookie_file, "username:password").unwrap();
+
+    Settings::from_options(
+      Options::try_parse_from(
+        [
+          "ord",
+          "--regtest",
+          "--bitcoin-rpc-url",
+          &core.url(),
+          "--cookie-file",
+          cookie_file.to_str().unwrap(),
+          "--bitcoin-data-dir",
+          tempdir.path().join("bitcoin").to_str().unwrap(),
+        ]
+        .into_iter()
+        .chain(args.iter().copied()),
+      )
+      .unwrap(),
+    )
+    .or_defaults()
+    .unwrap()
+  }
+
+  fn core() -> mockcore::Handle {
+    let core = mockcore::builder().network(Network::Regtest).build();
+
+    core.mine_blocks(1);
+
+    core.broadcast_tx(TransactionTemplate {
+      inputs: &[(1, 0, 0, Default::default())],
+      outputs: 2,
+      ..default()
+    });
+
+    core.mine_blocks(3);
+
+    core
+  }
+
+  fn assert_matches_core(source: &dyn BlockSource, core: &mockcore::Handle) {
+    let blocks = {
+      let state = core.state();
+
+      state
+        .hashes
+        .iter()
+        .map(|hash| state.blocks[hash].clone())
+        .collect::<Vec<Block>>()
+    };
+
+    assert_eq!(source.block_count().unwrap(), 4);
+
+    assert_eq!(source.block_hash(5).unwrap(), None);
+
+    for (height, block) in blocks.iter().enumerate() {
+      let hash = block.block_hash();
+
+      assert_eq!(
+        source.block_hash(height.try_into().unwrap()).unwrap(),
+        Some(hash)
+      );
+
+      assert_eq!(source.block(hash).unwrap(), *block);
+
+      assert_eq!(source.block_header(hash).unwrap(), block.header);
+    }
+
+    let transactions = blocks[2].txdata.clone();
+
+    assert_eq!(
+      tokio::runtime::Runtime::new()
+        .unwrap()
+        .block_on(source.transactions(transactions.iter().map(Transaction::txid).collect()))
+        .unwrap(),
+      transactions,
+    );
+  }
+
+  #[test]
+  fn from_str() {
+    assert_eq!(
+      "rest".parse::<BlockSourceKind>().unwrap(),
+      BlockSourceKind::Rest
+    );
+
+    assert_eq!(
+      "foo".parse::<BlockSourceKind>().unwrap_err().to_string(),
+      "invalid block source `foo`",
+    );
+  }
+
+  #[test]
+  fn rpc() {
+    let core = core();
+    let tempdir = TempDir::new().unwrap();
+    assert_matches_core(&*open(&settings(&core, &tempdir, &[])).unwrap(), &core);
+  }
+
+  #[test]
+  fn rest() {
+    let core = core();
+    let tempdir = TempDir::new().unwrap();
+    assert_matches_core(
+      &*open(&settings(&core, &tempdir, &["--block-source", "rest"])).unwrap(),
+      &core,
+    );
+  }
+
+  #[test]
+  fn files() {
+    let core = core();
+    let tempdir = TempDir::new().unwrap();
+    core.write_block_files(&tempdir.path().join("bitcoin/regtest/blocks"), 2, None);
+    assert_matches_core(
+      &*open(&settings(&core, &tempdir, &["--block-source", "files"])).unwrap(),
+      &core,
+    );
+  }
+
+  #[test]
+  fn files_falls_back_to_rpc() {
+    let core = core();
+    let tempdir = TempDir::new().unwrap();
+    assert_matches_core(
+      &*open(&settings(&core, &tempdir, &["--block-source", "files"])).unwrap(),
+      &core,
+    );
+  }
+
+  #[test]
+  fn fixture() {
+    let core = core();
+    let tempdir = TempDir::new().unwrap();
+    let path = tempdir.path().join("fixture.json");
+
+    Fixture::record(&*open(&settings(&core, &tempdir, &[])).unwrap(), &path).unwrap();
+
+    let fixture = open(&settings(
+      &mockcore::builder().network(Network::Regtest).build(),
+      &tempdir,
+      &[
+        "--block-source",
+        "fixture",
+        "--block-source-fixture",
+        path.to_str().unwrap(),
+      ],
+    ))
+    .unwrap();
+
+    assert_matches_core(&*fixture, &core);
+  }
+
+  #[test]
+  fn fixture_requires_path() {
+    let core = core();
+    let tempdir = TempDir::new().unwrap();
+    assert_eq!(
+      open(&settings(&core, &tempdir, &["--block-source", "fixture"]))
+        .err()
+        .unwrap()
+        .to_string(),
+      "the fixture block source requires `--block-source-fixture`",
+    );
+  }
+}
@@ -0,0 +1,457 @@
+use {
+  super::*,
+  std::{
+    fs::File,
+    io::{BufReader, Seek, SeekFrom},
+    sync::OnceLock,
+  },
+};
+
+pub(crate) struct Files {
+  dir: PathBuf,
+  files: OnceLock<Option<Mutex<BlockFiles>>>,
+  network: Network,
+  rpc: rpc::Rpc,
+}
+
+impl Files {
+  pub(crate) fn new(settings: &Settings) -> Result<Self> {
+    Ok(Self {
+      dir: settings.bitcoin_blocks_dir(),
+      files: OnceLock::new(),
+      network: settings.chain().network(),
+      rpc: rpc::Rpc::new(settings)?,
+    })
+  }
+
+  // read blocks `start` through `end`, where `end_hash` is the hash of the
+  // block at `end`, in height order
+  pub(crate) fn chain(self, start: u32, end: u32, end_hash: BlockHash) -> Result<Chain> {
+    BlockFiles::open(&self.dir, self.network)?.chain(start, end, end_hash)
+  }
+
+  fn files(&self) -> Option<&Mutex<BlockFiles>> {
+    self
+      .files
+      .get_or_init(|| match BlockFiles::open(&self.dir, self.network) {
+        Ok(files) => {
+          log::info!("Reading blocks from block files in {}", self.dir.display());
+          Some(Mutex::new(files))
+        }
+        Err(err) => {
+          log::warn!(
+            "not reading blocks from block files in {}: {err}",
+            self.dir.display()
+          );
+          None
+        }
+      })
+      .as_ref()
+  }
+}
+
+#[async_trait]
+impl BlockSource for Files {
+  fn block(&self, hash: BlockHash) -> Result<Block> {
+    if let Some(files) = self.files() {
+      if let Some(block) = files.lock().unwrap().block(hash)? {
+        return Ok(block);
+      }
+    }
+
+    self.rpc.block(hash)
+  }
+
+  fn block_count(&self) -> Result<u32> {
+    self.rpc.block_count()
+  }
+
+  fn block_hash(&self, height: u32) -> Result<Option<BlockHash>> {
+    self.rpc.block_hash(height)
+  }
+
+  fn block_header(&self, hash: BlockHash) -> Result<Header> {
+    if let Some(files) = self.files() {
+      if let Some(header) = files.lock().unwrap().block_header(hash)? {
+        return Ok(header);
+      }
+    }
+
+    self.rpc.block_header(hash)
+  }
+
+  async fn transactions(&self, txids: Vec<Txid>) -> Result<Vec<Transaction>> {
+    self.rpc.transactions(txids).await
+  }
+}
+
+#[derive(Clone, Copy)]
+struct Location {
+  file: u32,
+  len: usize,
+  offset: u64,
+}
+
+struct BlockFiles {
+  blocks: HashMap<BlockHash, (BlockHash, Location)>,
+  dir: PathBuf,
+  file: Option<(u32, File)>,
+  xor_key: [u8; 8],
+}
+
+impl BlockFiles {
+  fn open(dir: &Path, network: Network) -> Result<Self> {
+    let xor_key = match fs::read(dir.join("xor.dat")) {
+      Ok(key) => key
+        .try_into()
+        .map_err(|key: Vec<u8>| anyhow!("xor.dat is {} bytes, expected 8", key.len()))?,
+      Err(err) if err.kind() == io::ErrorKind::NotFound => [0; 8],
+      Err(err) => return Err(err.into()),
+    };
+
+    let mut files = Vec::new();
+
+    for entry in fs::read_dir(dir)? {
+      let name = entry?.file_name();
+
+      if let Some(file) = name
+        .to_str()
+        .and_then(|name| name.strip_prefix("blk"))
+        .and_then(|name| name.strip_suffix(".dat"))
+        .and_then(|n| n.parse::<u32>().ok())
+      {
+        files.push(file);
+      }
+    }
+
+    files.sort();
+
+    let magic = network.magic().to_bytes();
+
+    let mut blocks = HashMap::new();
+
+    for file in files {
+      let path = Self::path(dir, file);
+
+      let mut reader = BufReader::new(File::open(&path)?);
+
+      let mut offset = 0;
+
+      loop {
+        let mut prefix = [0; 88];
+
+        match reader.read_exact(&mut prefix) {
+          Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => break,
+          result => result?,
+        }
+
+        Self::xor(&xor_key, offset, &mut prefix);
+
+        // block files are preallocated and zero-filled past the last block
+        if prefix[..4] == [0; 4] {
+          break;
+        }
+
+        ensure!(
+          prefix[..4] == magic,
+          "unexpected network magic at offset {offset} of {}",
+          path.display(),
+        );
+
+        let len = u32::from_le_bytes(prefix[4..8].try_into().unwrap());
+
+        let header = consensus::encode::deserialize::<Header>(&prefix[8..])?;
+
+        blocks.insert(
+          header.block_hash(),
+          (
+            header.prev_blockhash,
+            Location {
+              file,
+              len: len.try_into().unwrap(),
+              offset: offset + 8,
+            },
+          ),
+        );
+
+        reader.seek_relative(i64::from(len) - 80)?;
+
+        offset += 8 + u64::from(len);
+      }
+    }
+
+    Ok(Self {
+      blocks,
+      dir: dir.into(),
+      file: None,
+      xor_key,
+    })
+  }
+
+  fn block(&mut self, hash: BlockHash) -> Result<Option<Block>> {
+    self
+      .blocks
+      .get(&hash)
+      .map(|(_prev_blockhash, location)| *location)
+      .map(|location| self.read(location))
+      .transpose()
+  }
+
+  fn block_header(&mut self, hash: BlockHash) -> Result<Option<Header>> {
+    self
+      .blocks
+      .get(&hash)
+      .map(|(_prev_blockhash, location)| Location {
+        len: 80,
+        ..*location
+      })
+      .map(|location| self.read(location))
+      .transpose()
+  }
+
+  fn chain(mut self, start: u32, end: u32, end_hash: BlockHash) -> Result<Chain> {
+    let mut locations = VecDeque::new();
+    let mut hash = end_hash;
+
+    for height in (start..=end).rev() {
+      let (prev_blockhash, location) = self
+        .blocks
+        .remove(&hash)
+        .ok_or_else(|| anyhow!("block {height} with hash {hash} not found in block files"))?;
+
+      locations.push_front(location);
+
+      hash = prev_blockhash;
+    }
+
+    Ok(Chain {
+      files: self,
+      locations,
+    })
+  }
+
+  fn path(dir: &Path, file: u32) -> PathBuf {
+    dir.join(format!("blk{file:05}.dat"))
+  }
+
+  fn read<T: consensus::Decodable>(&mut self, location: Location) -> Result<T> {
+    if self.file.as_ref().map(|(file, _)| *file) != Some(location.file) {
+      self.file = Some((
+        location.file,
+        File::open(Self::path(&self.dir, location.file))?,
+      ));
+    }
+
+    let (_, file) = self.file.as_mut().unwrap();
+
+    file.seek(SeekFrom::Start(location.offset))?;
+
+    let mut buffer = vec![0; location.len];
+
+    file.read_exact(&mut buffer)?;
+
+    Self::xor(&self.xor_key, location.offset, &mut buffer);
+
+    Ok(consensus::encode::deserialize(&buffer)?)
+  }
+
+  fn xor(key: &[u8; 8], offset: u64, buffer: &mut [u8]) {
+    if *key == [0; 8] {
+      return;
+    }
+
+    for (i, byte) in buffer.iter_mut().enumerate() {
+      *byte ^= key[usize::try_from((offset + i as u64) % 8).unwrap()];
+    }
+  }
+}
+
+pub(crate) struct Chain {
+  files: BlockFiles,
+  locations: VecDeque<Location>,
+}
+
+impl Chain {
+  pub(crate) fn len(&self) -> usize {
+    self.locations.len()
+  }
+}
+
+impl Iterator for Chain {
+  type Item = Result<Block>;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    let location = self.locations.pop_front()?;
+    Some(self.files.read(location))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use {super::*, tempfile::TempDir};
+
+  fn blocks(core: &mockcore::Handle) -> Vec<Block> {
+    let state = core.state();
+
+    state
+      .hashes
+      .iter()
+      .map(|hash| state.blocks[hash].clone())
+      .collect()
+  }
+
+  #[test]
+  fn blocks_are_read_in_height_order() {
+    let core = mockcore::builder().network(Network::Regtest).build();
+
+    core.mine_blocks(10);
+
+    let tempdir = TempDir::new().unwrap();
+
+    core.write_block_files(tempdir.path(), 3, None);
+
+    let blocks = blocks(&core);
+
+    let block_files = BlockFiles::open(tempdir.path(), Network::Regtest)
+      .unwrap()
+      .chain(2, 10, blocks[10].block_hash())
+      .unwrap();
+
+    assert_eq!(block_files.len(), 9);
+
+    assert_eq!(
+      block_files.map(Result::unwrap).collect::<Vec<Block>>(),
+      blocks[2..].to_vec(),
+    );
+  }
+
+  #[test]
+  fn blocks_are_read_by_hash() {
+    let core = mockcore::builder().network(Network::Regtest).build();
+
+    core.mine_blocks(4);
+
+    let tempdir = TempDir::new().unwrap();
+
+    core.write_block_files(tempdir.path(), 2, None);
+
+    let mut block_files = BlockFiles::open(tempdir.path(), Network::Regtest).unwrap();
+
+    for block in blocks(&core) {
+      assert_eq!(
+        block_files.block(block.block_hash()).unwrap(),
+        Some(block.clone())
+      );
+
+      assert_eq!(
+        block_files.block_header(block.block_hash()).unwrap(),
+        Some(block.header)
+      );
+    }
+
+    assert_eq!(block_files.block(BlockHash::all_zeros()).unwrap(), None);
+  }
+
+  #[test]
+  fn obfuscated_block_files_are_read() {
+    let core = mockcore::builder().network(Network::Regtest).build();
+
+    core.mine_blocks(5);
+
+    let tempdir = TempDir::new().unwrap();
+
+    core.write_block_files(
+      tempdir.path(),
+      2,
+      Some([0x01, 0x23, 0x45, 0x67, 0x89, 0xab, 0xcd, 0xef]),
+    );
+
+    let blocks = blocks(&core);
+
+    assert_eq!(
+      BlockFiles::open(tempdir.path(), Network::Regtest)
+        .unwrap()
+        .chain(0, 5, blocks[5].block_hash())
+        .unwrap()
+        .map(Result::unwrap)
+        .collect::<Vec<Block>>(),
+      blocks,
+    );
+  }
+
+  #[test]
+  fn stale_blocks_are_skipped() {
+    let core = mockcore::builder().network(Network::Regtest).build();
+
+    core.mine_blocks(3);
+
+    let tempdir = TempDir::new().unwrap();
+
+    core.write_block_files(tempdir.path(), 4, None);
+
+    fs::rename(
+      tempdir.path().join("blk00000.dat"),
+      tempdir.path().join("blk00099.dat"),
+    )
+    .unwrap();
+
+    let stale = core.invalidate_tip();
+
+    core.mine_blocks(2);
+
+    core.write_block_files(tempdir.path(), 4, None);
+
+    let blocks = blocks(&core);
+
+    assert_ne!(blocks[3].block_hash(), stale);
+
+    assert_eq!(
+      BlockFiles::open(tempdir.path(), Network::Regtest)
+        .unwrap()
+        .chain(1, 4, blocks[4].block_hash())
+        .unwrap()
+        .map(Result::unwrap)
+        .collect::<Vec<Block>>(),
+      blocks[1..].to_vec(),
+    );
+  }
+
+  #[test]
+  fn missing_blocks_are_an_error() {
+    let core = mockcore::builder().network(Network::Regtest).build();
+
+    core.mine_blocks(3);
+
+    let tempdir = TempDir::new().unwrap();
+
+    core.write_block_files(tempdir.path(), 1, None);
+
+    let blocks = blocks(&core);
+
+    fs::remove_file(tempdir.path().join("blk00001.dat")).unwrap();
+
+    assert!(BlockFiles::open(tempdir.path(), Network::Regtest)
+      .unwrap()
+      .chain(0, 3, blocks[3].block_hash())
+      .is_err());
+  }
+
+  #[test]
+  fn wrong_network_is_an_error() {
+    let core = mockcore::builder().network(Network::Regtest).build();
+
+    core.mine_blocks(1);
+
+    let tempdir = TempDir::new().unwrap();
+
+    core.write_block_files(tempdir.path(), 1, None);
+
+    assert_regex_match!(
+      BlockFiles::open(tempdir.path(), Network::Signet)
+        .err()
+        .unwrap()
+        .to_string(),
+      "unexpected network magic at offset 0 of .*blk0000[01].dat",
+    );
+  }
+}
@@ -0,0 +1,113 @@
+use super::*;
+
+#[derive(Default, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+struct Recording {
+  blocks: Vec<String>,
+}
+
+pub(crate) struct Fixture {
+  blocks: Vec<Block>,
+  heights: HashMap<BlockHash, usize>,
+  transactions: HashMap<Txid, Transaction>,
+}
+
+impl Fixture {
+  pub(crate) fn load(path: &Path) -> Result<Self> {
+    let recording = serde_json::from_reader::<_, Recording>(
+      fs::File::open(path)
+        .with_context(|| format!("failed to open block fixture `{}`", path.display()))?,
+    )
+    .with_context(|| format!("failed to deserialize block fixture `{}`", path.display()))?;
+
+    let blocks = recording
+      .blocks
+      .iter()
+      .map(|block| Ok(consensus::encode::deserialize(&hex::decode(block)?)?))
+      .collect::<Result<Vec<Block>>>()?;
+
+    ensure!(!blocks.is_empty(), "block fixture contains no blocks");
+
+    let heights = blocks
+      .iter()
+      .enumerate()
+      .map(|(height, block)| (block.block_hash(), height))
+      .collect();
+
+    let transactions = blocks
+      .iter()
+      .flat_map(|block| &block.txdata)
+      .map(|transaction| (transaction.txid(), transaction.clone()))
+      .collect();
+
+    Ok(Self {
+      blocks,
+      heights,
+      transactions,
+    })
+  }
+
+  #[cfg(test)]
+  pub(crate) fn record(source: &dyn BlockSource, path: &Path) -> Result {
+    let mut recording = Recording::default();
+
+    for height in 0..=source.block_count()? {
+      let hash = source
+        .block_hash(height)?
+        .ok_or_else(|| anyhow!("block {height} not found"))?;
+
+      recording
+        .blocks
+        .push(consensus::encode::serialize_hex(&source.block(hash)?));
+    }
+
+    fs::write(path, serde_json::to_vec_pretty(&recording)?)?;
+
+    Ok(())
+  }
+
+  fn get(&self, hash: BlockHash) -> Result<&Block> {
+    self
+      .heights
+      .get(&hash)
+      .map(|height| &self.blocks[*height])
+      .ok_or_else(|| anyhow!("block {hash} not in fixture"))
+  }
+}
+
+#[async_trait]
+impl BlockSource for Fixture {
+  fn block(&self, hash: BlockHash) -> Result<Block> {
+    self.get(hash).cloned()
+  }
+
+  fn block_count(&self) -> Result<u32> {
+    Ok((self.blocks.len() - 1).try_into()?)
+  }
+
+  fn block_hash(&self, height: u32) -> Result<Option<BlockHash>> {
+    Ok(
+      self
+        .blocks
+        .get(usize::try_from(height)?)
+        .map(Block::block_hash),
+    )
+  }
+
+  fn block_header(&self, hash: BlockHash) -> Result<Header> {
+    Ok(self.get(hash)?.header)
+  }
+
+  async fn transactions(&self, txids: Vec<Txid>) -> Result<Vec<Transaction>> {
+    txids
+      .into_iter()
+      .map(|txid| {
+        self
+          .transactions
+          .get(&txid)
+          .cloned()
+          .ok_or_else(|| anyhow!("transaction {txid} not in fixture"))
+      })
+      .collect()
+  }
+}
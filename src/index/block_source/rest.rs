@@ -0,0 +1,124 @@
+use {super::*, futures::future::try_join_all, reqwest::StatusCode, tokio::sync::Semaphore};
+
+#[derive(Deserialize)]
+struct ChainInfo {
+  blocks: u32,
+}
+
+pub(crate) struct Rest {
+  async_client: reqwest::Client,
+  client: reqwest::blocking::Client,
+  requests: Semaphore,
+  url: String,
+}
+
+impl Rest {
+  pub(crate) fn new(settings: &Settings) -> Result<Self> {
+    Ok(Self {
+      async_client: reqwest::Client::new(),
+      client: reqwest::blocking::Client::new(),
+      requests: Semaphore::new(settings.bitcoin_rpc_limit().into_usize()),
+      url: Self::url(&settings.bitcoin_rpc_url(None))?,
+    })
+  }
+
+  fn url(rpc_url: &str) -> Result<String> {
+    // URLs without a scheme, like `localhost:8332`, either fail to parse or
+    // parse with the host as the scheme
+    let url = match Url::parse(rpc_url) {
+      Ok(url) if url.has_host() => url,
+      _ => Url::parse(&format!("http://{rpc_url}"))
+        .with_context(|| format!("invalid bitcoin RPC URL `{rpc_url}`"))?,
+    };
+
+    Ok(format!("{}/rest", url.as_str().trim_end_matches('/')))
+  }
+
+  fn get(&self, path: &str) -> Result<Option<Vec<u8>>> {
+    let response = self.client.get(format!("{}/{path}", self.url)).send()?;
+
+    if response.status() == StatusCode::NOT_FOUND {
+      return Ok(None);
+    }
+
+    Ok(Some(response.error_for_status()?.bytes()?.into()))
+  }
+
+  async fn transaction(&self, txid: Txid) -> Result<Transaction> {
+    let _permit = self.requests.acquire().await?;
+
+    let transaction = self
+      .async_client
+      .get(format!("{}/tx/{txid}.bin", self.url))
+      .send()
+      .await?
+      .error_for_status()?
+      .bytes()
+      .await?;
+
+    Ok(consensus::encode::deserialize(&transaction)?)
+  }
+
+  fn get_block_data<T: consensus::Decodable>(&self, path: &str, hash: BlockHash) -> Result<T> {
+    Ok(consensus::encode::deserialize(
+      &self
+        .get(path)?
+        .ok_or_else(|| anyhow!("block {hash} not found"))?,
+    )?)
+  }
+}
+
+#[async_trait]
+impl BlockSource for Rest {
+  fn block(&self, hash: BlockHash) -> Result<Block> {
+    self.get_block_data(&format!("block/{hash}.bin"), hash)
+  }
+
+  fn block_count(&self) -> Result<u32> {
+    let chain_info = self
+      .get("chaininfo.json")?
+      .ok_or_else(|| anyhow!("bitcoind REST interface not found, is it enabled with `-rest`?"))?;
+
+    Ok(serde_json::from_slice::<ChainInfo>(&chain_info)?.blocks)
+  }
+
+  fn block_hash(&self, height: u32) -> Result<Option<BlockHash>> {
+    self
+      .get(&format!("blockhashbyheight/{height}.bin"))?
+      .map(|hash| Ok(consensus::encode::deserialize(&hash)?))
+      .transpose()
+  }
+
+  fn block_header(&self, hash: BlockHash) -> Result<Header> {
+    self.get_block_data(&format!("headers/{hash}.bin?count=1"), hash)
+  }
+
+  // the REST interface has no batch requests, so transactions are requested
+  // concurrently, with at most `--bitcoin-rpc-limit` requests in flight
+  // across all callers
+  async fn transactions(&self, txids: Vec<Txid>) -> Result<Vec<Transaction>> {
+    try_join_all(txids.into_iter().map(|txid| self.transaction(txid))).await
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn url() {
+    #[track_caller]
+    fn case(rpc_url: &str, expected: &str) {
+      assert_eq!(Rest::url(rpc_url).unwrap(), expected);
+    }
+
+    case("127.0.0.1:8332/", "http://127.0.0.1:8332/rest");
+    case("localhost:8332/", "http://localhost:8332/rest");
+    case("http://localhost:8332/", "http://localhost:8332/rest");
+    case("https://node.example.com/", "https://node.example.com/rest");
+    case(
+      "https://node.example.com:8332/bitcoin/",
+      "https://node.example.com:8332/bitcoin/rest",
+    );
+  }
+}
@@ -0,0 +1,38 @@
+use {super::*, crate::index::fetcher::Fetcher};
+
+pub(crate) struct Rpc {
+  client: Client,
+  fetcher: Fetcher,
+}
+
+impl Rpc {
+  pub(crate) fn new(settings: &Settings) -> Result<Self> {
+    Ok(Self {
+      client: settings.bitcoin_rpc_client(None)?,
+      fetcher: Fetcher::new(settings)?,
+    })
+  }
+}
+
+#[async_trait]
+impl BlockSource for Rpc {
+  fn block(&self, hash: BlockHash) -> Result<Block> {
+    Ok(self.client.get_block(&hash)?)
+  }
+
+  fn block_count(&self) -> Result<u32> {
+    Ok(self.client.get_block_count()?.try_into()?)
+  }
+
+  fn block_hash(&self, height: u32) -> Result<Option<BlockHash>> {
+    self.client.get_block_hash(height.into()).into_option()
+  }
+
+  fn block_header(&self, hash: BlockHash) -> Result<Header> {
+    Ok(self.client.get_block_header(&hash)?)
+  }
+
+  async fn transactions(&self, txids: Vec<Txid>) -> Result<Vec<Transaction>> {
+    self.fetcher.get_transactions(txids).await
+  }
+}
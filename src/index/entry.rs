@@ -28,6 +28,64 @@ impl Entry for Header {
   }
 }
 
+#[derive(Debug, PartialEq, Clone)]
+pub struct AddressTransactionEntry {
+  pub height: u32,
+  pub inscriptions: Vec<InscriptionId>,
+  pub received: u64,
+  pub runes: Vec<(RuneId, u128, u128)>,
+  pub sent: u64,
+  pub txid: Txid,
+}
+
+pub(super) type AddressTransactionEntryValue = (
+  u32,                            // height
+  Vec<InscriptionIdValue>,        // inscriptions
+  u64,                            // received
+  Vec<(RuneIdValue, u128, u128)>, // runes
+  u64,                            // sent
+  TxidValue,                      // txid
+);
+
+impl Entry for AddressTransactionEntry {
+  type Value = AddressTransactionEntryValue;
+
+  fn load(
+    (height, inscriptions, received, runes, sent, txid): AddressTransactionEntryValue,
+  ) -> Self {
+    Self {
+      height,
+      inscriptions: inscriptions.into_iter().map(InscriptionId::load).collect(),
+      received,
+      runes: runes
+        .into_iter()
+        .map(|(id, received, sent)| (RuneId::load(id), received, sent))
+        .collect(),
+      sent,
+      txid: Txid::load(txid),
+    }
+  }
+
+  fn store(self) -> Self::Value {
+    (
+      self.height,
+      self
+        .inscriptions
+        .into_iter()
+        .map(InscriptionId::store)
+        .collect(),
+      self.received,
+      self
+        .runes
+        .into_iter()
+        .map(|(id, received, sent)| (id.store(), received, sent))
+        .collect(),
+      self.sent,
+      self.txid.store(),
+    )
+  }
+}
+
 impl Entry for Rune {
   type Value = u128;
 
@@ -358,6 +416,61 @@ impl Entry for InscriptionEntry {
   }
 }
 
+#[derive(Debug, Default, Eq, PartialEq, Clone)]
+pub struct InscriptionProperties {
+  pub content_length: u64,
+  pub content_type: Option<Vec<u8>>,
+  pub delegate: bool,
+  pub metadata: bool,
+  pub metaprotocol: Option<Vec<u8>>,
+}
+
+pub(crate) type InscriptionPropertiesValue = (
+  u64,             // content length
+  Option<Vec<u8>>, // content type
+  bool,            // delegate
+  bool,            // metadata
+  Option<Vec<u8>>, // metaprotocol
+);
+
+impl Entry for InscriptionProperties {
+  type Value = InscriptionPropertiesValue;
+
+  fn load(
+    (content_length, content_type, delegate, metadata, metaprotocol): InscriptionPropertiesValue,
+  ) -> Self {
+    Self {
+      content_length,
+      content_type,
+      delegate,
+      metadata,
+      metaprotocol,
+    }
+  }
+
+  fn store(self) -> Self::Value {
+    (
+      self.content_length,
+      self.content_type,
+      self.delegate,
+      self.metadata,
+      self.metaprotocol,
+    )
+  }
+}
+
+impl From<&Inscription> for InscriptionProperties {
+  fn from(inscription: &Inscription) -> Self {
+    Self {
+      content_length: inscription.content_length().unwrap_or_default() as u64,
+      content_type: inscription.content_type.clone(),
+      delegate: inscription.delegate().is_some(),
+      metadata: inscription.metadata.is_some(),
+      metaprotocol: inscription.metaprotocol.clone(),
+    }
+  }
+}
+
 pub(crate) type InscriptionIdValue = (u128, u128, u32);
 
 impl Entry for InscriptionId {
@@ -432,6 +545,57 @@ impl Entry for OutPoint {
   }
 }
 
+#[derive(Debug, PartialEq, Clone)]
+pub struct TransferEntry {
+  pub fee: u64,
+  pub height: u32,
+  pub new_satpoint: SatPoint,
+  pub old_satpoint: SatPoint,
+  pub receiver: Option<ScriptBuf>,
+  pub sender: Option<ScriptBuf>,
+  pub txid: Txid,
+}
+
+pub(super) type TransferEntryValue = (
+  u64,             // fee
+  u32,             // height
+  SatPointValue,   // new satpoint
+  SatPointValue,   // old satpoint
+  Option<Vec<u8>>, // receiver
+  Option<Vec<u8>>, // sender
+  TxidValue,       // txid
+);
+
+impl Entry for TransferEntry {
+  type Value = TransferEntryValue;
+
+  fn load(
+    (fee, height, new_satpoint, old_satpoint, receiver, sender, txid): TransferEntryValue,
+  ) -> Self {
+    Self {
+      fee,
+      height,
+      new_satpoint: SatPoint::load(new_satpoint),
+      old_satpoint: SatPoint::load(old_satpoint),
+      receiver: receiver.map(ScriptBuf::from_bytes),
+      sender: sender.map(ScriptBuf::from_bytes),
+      txid: Txid::load(txid),
+    }
+  }
+
+  fn store(self) -> Self::Value {
+    (
+      self.fee,
+      self.height,
+      self.new_satpoint.store(),
+      self.old_satpoint.store(),
+      self.receiver.map(ScriptBuf::into_bytes),
+      self.sender.map(ScriptBuf::into_bytes),
+      self.txid.store(),
+    )
+  }
+}
+
 pub(super) type TxOutValue = (
   u64,     // value
   Vec<u8>, // script_pubkey
@@ -526,6 +690,76 @@ mod tests {
     assert_eq!(TxOut::load(value), txout);
   }
 
+  #[test]
+  fn address_transaction_entry() {
+    let id = "0123456789abcdef0123456789abcdef0123456789abcdef0123456789abcdefi0"
+      .parse::<InscriptionId>()
+      .unwrap();
+
+    let entry = AddressTransactionEntry {
+      height: 1,
+      inscriptions: vec![id],
+      received: 2,
+      runes: vec![(RuneId { block: 3, tx: 4 }, 5, 6)],
+      sent: 7,
+      txid: txid(8),
+    };
+
+    let value = (
+      1,
+      vec![id.store()],
+      2,
+      vec![((3, 4), 5, 6)],
+      7,
+      txid(8).store(),
+    );
+
+    assert_eq!(entry.clone().store(), value);
+    assert_eq!(AddressTransactionEntry::load(value), entry);
+  }
+
+  #[test]
+  fn transfer_entry() {
+    let entry = TransferEntry {
+      fee: 1,
+      height: 2,
+      new_satpoint: satpoint(3, 4),
+      old_satpoint: satpoint(5, 6),
+      receiver: Some(change(0).script_pubkey()),
+      sender: None,
+      txid: txid(7),
+    };
+
+    let value = (
+      1,
+      2,
+      satpoint(3, 4).store(),
+      satpoint(5, 6).store(),
+      Some(change(0).script_pubkey().to_bytes()),
+      None,
+      txid(7).store(),
+    );
+
+    assert_eq!(entry.clone().store(), value);
+    assert_eq!(TransferEntry::load(value), entry);
+  }
+
+  #[test]
+  fn inscription_properties() {
+    let properties = InscriptionProperties {
+      content_length: 1,
+      content_type: Some(b"text/plain".to_vec()),
+      delegate: true,
+      metadata: false,
+      metaprotocol: None,
+    };
+
+    let value = (1, Some(b"text/plain".to_vec()), true, false, None);
+
+    assert_eq!(properties.clone().store(), value);
+    assert_eq!(InscriptionProperties::load(value), properties);
+  }
+
   #[test]
   fn inscription_entry() {
     let id = "0123456789abcdef0123456789abcdef0123456789abcdef0123456789abcdefi0"
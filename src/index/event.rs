@@ -1,44 +1,647 @@
-use super::*;
+use {super::*, schemars::JsonSchema};
 
-#[derive(Debug, Clone, PartialEq)]
+mod protobuf;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct EventMessage {
+  pub event_id: u64,
+  pub schema_version: u32,
+  pub event: Event,
+}
+
+impl EventMessage {
+  pub const PROTOBUF_SCHEMA: &'static str = include_str!("../../schema/event.proto");
+
+  pub const SCHEMA_VERSION: u32 = 1;
+
+  pub fn new(event_id: u64, event: Event) -> Self {
+    Self {
+      event_id,
+      schema_version: Self::SCHEMA_VERSION,
+      event,
+    }
+  }
+
+  pub fn json_schema() -> String {
+    let mut schema = schemars::schema_for!(EventMessage);
+
+    schema.schema.metadata().title = Some(format!("EventMessage v{}", Self::SCHEMA_VERSION));
+
+    serde_json::to_string_pretty(&schema).unwrap() + "\n"
+  }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct BlockEventCounts {
+  pub inscriptions_created: u64,
+  pub inscriptions_transferred: u64,
+  pub runes_burned: u64,
+  pub runes_etched: u64,
+  pub runes_minted: u64,
+  pub runes_transferred: u64,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct InscriptionDetails {
+  pub address: Option<String>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub body: Option<String>,
+  pub content_length: Option<usize>,
+  pub content_type: Option<String>,
+  #[schemars(with = "Option<String>")]
+  pub delegate: Option<InscriptionId>,
+  pub fee: u64,
+  pub inscription_number: i32,
+  pub metadata: Option<String>,
+  pub metaprotocol: Option<String>,
+  #[schemars(with = "Option<u64>")]
+  pub sat: Option<Sat>,
+}
+
+impl BlockEventCounts {
+  pub(crate) fn record(&mut self, event: &Event) {
+    match event {
+      Event::InscriptionCreated { .. } => self.inscriptions_created += 1,
+      Event::InscriptionTransferred { .. } => self.inscriptions_transferred += 1,
+      Event::RuneBurned { .. } => self.runes_burned += 1,
+      Event::RuneEtched { .. } => self.runes_etched += 1,
+      Event::RuneMinted { .. } => self.runes_minted += 1,
+      Event::RuneTransferred { .. } => self.runes_transferred += 1,
+      Event::BlockCommitted { .. }
+      | Event::BlockRetracted { .. }
+      | Event::BlockStarted { .. }
+      | Event::BlocksRolledBack { .. }
+      | Event::MetaprotocolEvent { .. }
+      | Event::PendingInscriptionCreated { .. }
+      | Event::PendingInscriptionTransferred { .. }
+      | Event::PendingRuneEtched { .. }
+      | Event::PendingRuneMinted { .. }
+      | Event::PendingRuneTransferred { .. }
+      | Event::PendingTransactionConfirmed { .. }
+      | Event::PendingTransactionEvicted { .. } => {}
+    }
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub enum Event {
+  BlockCommitted {
+    #[schemars(with = "String")]
+    block_hash: BlockHash,
+    block_height: u32,
+    counts: BlockEventCounts,
+  },
+  BlockRetracted {
+    #[schemars(with = "String")]
+    block_hash: BlockHash,
+    block_height: u32,
+  },
+  BlockStarted {
+    #[schemars(with = "String")]
+    block_hash: BlockHash,
+    block_height: u32,
+    #[schemars(with = "String")]
+    prev_block_hash: BlockHash,
+    timestamp: u32,
+  },
+  BlocksRolledBack {
+    block_height: u32,
+    depth: u32,
+    from_height: u32,
+    to_height: u32,
+  },
   InscriptionCreated {
     block_height: u32,
     charms: u16,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    details: Option<Box<InscriptionDetails>>,
+    #[schemars(with = "String")]
     inscription_id: InscriptionId,
+    #[schemars(with = "Option<String>")]
     location: Option<SatPoint>,
+    #[schemars(with = "Vec<String>")]
     parent_inscription_ids: Vec<InscriptionId>,
     sequence_number: u32,
   },
   InscriptionTransferred {
     block_height: u32,
+    #[schemars(with = "String")]
     inscription_id: InscriptionId,
+    #[schemars(with = "String")]
     new_location: SatPoint,
+    #[schemars(with = "String")]
     old_location: SatPoint,
     sequence_number: u32,
   },
+  MetaprotocolEvent {
+    block_height: u32,
+    data: serde_json::Value,
+    #[schemars(with = "String")]
+    inscription_id: InscriptionId,
+    kind: String,
+    protocol: String,
+  },
+  PendingInscriptionCreated {
+    block_height: u32,
+    content_type: Option<String>,
+    #[schemars(with = "String")]
+    inscription_id: InscriptionId,
+    #[schemars(with = "Vec<String>")]
+    parent_inscription_ids: Vec<InscriptionId>,
+  },
+  PendingInscriptionTransferred {
+    block_height: u32,
+    #[schemars(with = "String")]
+    inscription_id: InscriptionId,
+    #[schemars(with = "String")]
+    old_location: SatPoint,
+    #[schemars(with = "String")]
+    txid: Txid,
+  },
+  PendingRuneEtched {
+    block_height: u32,
+    #[schemars(with = "Option<String>")]
+    rune: Option<SpacedRune>,
+    #[schemars(with = "String")]
+    txid: Txid,
+  },
+  PendingRuneMinted {
+    block_height: u32,
+    #[schemars(with = "String")]
+    rune_id: RuneId,
+    #[schemars(with = "String")]
+    txid: Txid,
+  },
+  PendingRuneTransferred {
+    amount: u128,
+    block_height: u32,
+    #[schemars(with = "String")]
+    old_outpoint: OutPoint,
+    #[schemars(with = "String")]
+    rune_id: RuneId,
+    #[schemars(with = "String")]
+    txid: Txid,
+  },
+  PendingTransactionConfirmed {
+    block_height: u32,
+    #[schemars(with = "String")]
+    txid: Txid,
+  },
+  PendingTransactionEvicted {
+    block_height: u32,
+    #[schemars(with = "String")]
+    txid: Txid,
+  },
   RuneBurned {
     amount: u128,
     block_height: u32,
+    #[schemars(with = "String")]
     rune_id: RuneId,
+    #[schemars(with = "String")]
     txid: Txid,
   },
   RuneEtched {
     block_height: u32,
+    #[schemars(with = "String")]
     rune_id: RuneId,
+    #[schemars(with = "String")]
     txid: Txid,
   },
   RuneMinted {
     amount: u128,
     block_height: u32,
+    #[schemars(with = "String")]
     rune_id: RuneId,
+    #[schemars(with = "String")]
     txid: Txid,
   },
   RuneTransferred {
     amount: u128,
     block_height: u32,
+    #[schemars(with = "String")]
     outpoint: OutPoint,
+    #[schemars(with = "String")]
     rune_id: RuneId,
+    #[schemars(with = "String")]
     txid: Txid,
   },
 }
+
+impl Event {
+  pub const NAMES: [&'static str; 18] = [
+    "BlockCommitted",
+    "BlockRetracted",
+    "BlockStarted",
+    "BlocksRolledBack",
+    "InscriptionCreated",
+    "InscriptionTransferred",
+    "MetaprotocolEvent",
+    "PendingInscriptionCreated",
+    "PendingInscriptionTransferred",
+    "PendingRuneEtched",
+    "PendingRuneMinted",
+    "PendingRuneTransferred",
+    "PendingTransactionConfirmed",
+    "PendingTransactionEvicted",
+    "RuneBurned",
+    "RuneEtched",
+    "RuneMinted",
+    "RuneTransferred",
+  ];
+
+  pub fn name(&self) -> &'static str {
+    match self {
+      Self::BlockCommitted { .. } => "BlockCommitted",
+      Self::BlockRetracted { .. } => "BlockRetracted",
+      Self::BlockStarted { .. } => "BlockStarted",
+      Self::BlocksRolledBack { .. } => "BlocksRolledBack",
+      Self::InscriptionCreated { .. } => "InscriptionCreated",
+      Self::InscriptionTransferred { .. } => "InscriptionTransferred",
+      Self::MetaprotocolEvent { .. } => "MetaprotocolEvent",
+      Self::PendingInscriptionCreated { .. } => "PendingInscriptionCreated",
+      Self::PendingInscriptionTransferred { .. } => "PendingInscriptionTransferred",
+      Self::PendingRuneEtched { .. } => "PendingRuneEtched",
+      Self::PendingRuneMinted { .. } => "PendingRuneMinted",
+      Self::PendingRuneTransferred { .. } => "PendingRuneTransferred",
+      Self::PendingTransactionConfirmed { .. } => "PendingTransactionConfirmed",
+      Self::PendingTransactionEvicted { .. } => "PendingTransactionEvicted",
+      Self::RuneBurned { .. } => "RuneBurned",
+      Self::RuneEtched { .. } => "RuneEtched",
+      Self::RuneMinted { .. } => "RuneMinted",
+      Self::RuneTransferred { .. } => "RuneTransferred",
+    }
+  }
+
+  pub fn block_height(&self) -> u32 {
+    match self {
+      Self::BlockCommitted { block_height, .. }
+      | Self::BlockRetracted { block_height, .. }
+      | Self::BlockStarted { block_height, .. }
+      | Self::BlocksRolledBack { block_height, .. }
+      | Self::InscriptionCreated { block_height, .. }
+      | Self::InscriptionTransferred { block_height, .. }
+      | Self::MetaprotocolEvent { block_height, .. }
+      | Self::PendingInscriptionCreated { block_height, .. }
+      | Self::PendingInscriptionTransferred { block_height, .. }
+      | Self::PendingRuneEtched { block_height, .. }
+      | Self::PendingRuneMinted { block_height, .. }
+      | Self::PendingRuneTransferred { block_height, .. }
+      | Self::PendingTransactionConfirmed { block_height, .. }
+      | Self::PendingTransactionEvicted { block_height, .. }
+      | Self::RuneBurned { block_height, .. }
+      | Self::RuneEtched { block_height, .. }
+      | Self::RuneMinted { block_height, .. }
+      | Self::RuneTransferred { block_height, .. } => *block_height,
+    }
+  }
+
+  pub fn inscription_id(&self) -> Option<InscriptionId> {
+    match self {
+      Self::InscriptionCreated { inscription_id, .. }
+      | Self::InscriptionTransferred { inscription_id, .. }
+      | Self::MetaprotocolEvent { inscription_id, .. }
+      | Self::PendingInscriptionCreated { inscription_id, .. }
+      | Self::PendingInscriptionTransferred { inscription_id, .. } => Some(*inscription_id),
+      _ => None,
+    }
+  }
+
+  pub fn rune_id(&self) -> Option<RuneId> {
+    match self {
+      Self::RuneBurned { rune_id, .. }
+      | Self::RuneEtched { rune_id, .. }
+      | Self::RuneMinted { rune_id, .. }
+      | Self::RuneTransferred { rune_id, .. }
+      | Self::PendingRuneMinted { rune_id, .. }
+      | Self::PendingRuneTransferred { rune_id, .. } => Some(*rune_id),
+      _ => None,
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use {
+    super::*,
+    jsonschema::JSONSchema,
+    prost::Message,
+    protobuf::{ProtoEvent, ProtoEventMessage},
+  };
+
+  const V1_JSON: &str = include_str!("../../schema/fixtures/v1.ndjson");
+
+  const V1_PROTOBUF: &str = include_str!("../../schema/fixtures/v1.protobuf.hex");
+
+  fn schema() -> JSONSchema {
+    JSONSchema::compile(&serde_json::from_str(&EventMessage::json_schema()).unwrap()).unwrap()
+  }
+
+  struct ProtoField {
+    kind: String,
+    name: String,
+    oneof: bool,
+  }
+
+  impl ProtoField {
+    fn wire_type(&self) -> u64 {
+      match self.kind.as_str() {
+        "bool" | "int32" | "int64" | "sint32" | "sint64" | "uint32" | "uint64" => 0,
+        _ => 2,
+      }
+    }
+  }
+
+  // parses the messages in schema/event.proto, returning the fields of each
+  // message by tag
+  fn proto_messages() -> BTreeMap<String, BTreeMap<u64, ProtoField>> {
+    let message = Regex::new(r"^message (\w+) \{$").unwrap();
+    let field = Regex::new(r"^(?:optional |repeated )?(\w+) (\w+) = (\d+);$").unwrap();
+
+    let mut messages = BTreeMap::<String, BTreeMap<u64, ProtoField>>::new();
+    let mut current = None;
+    let mut oneof = false;
+
+    for line in EventMessage::PROTOBUF_SCHEMA.lines().map(str::trim) {
+      if let Some(captures) = message.captures(line) {
+        current = Some(captures[1].to_string());
+      } else if line.starts_with("oneof ") {
+        oneof = true;
+      } else if line == "}" {
+        if oneof {
+          oneof = false;
+        } else {
+          current = None;
+        }
+      } else if let Some(captures) = field.captures(line) {
+        messages
+          .entry(current.clone().unwrap())
+          .or_default()
+          .insert(
+            captures[3].parse().unwrap(),
+            ProtoField {
+              kind: captures[1].into(),
+              name: captures[2].into(),
+              oneof,
+            },
+          );
+      }
+    }
+
+    messages
+  }
+
+  // checks that every field of `bytes` is declared in schema/event.proto
+  // with the same tag and wire type, and returns its fields in tag order, with
+  // nested messages canonicalized in turn
+  fn canonicalize(
+    messages: &BTreeMap<String, BTreeMap<u64, ProtoField>>,
+    name: &str,
+    mut bytes: &[u8],
+  ) -> Vec<(u64, Vec<u8>)> {
+    let mut fields = Vec::new();
+
+    while !bytes.is_empty() {
+      let key = prost::encoding::decode_varint(&mut bytes).unwrap();
+
+      let tag = key >> 3;
+
+      let field = messages[name]
+        .get(&tag)
+        .unwrap_or_else(|| panic!("{name} field {tag} is not in schema/event.proto"));
+
+      assert_eq!(
+        key & 0b111,
+        field.wire_type(),
+        "{name}.{} has a different wire type in schema/event.proto",
+        field.name,
+      );
+
+      let value = if field.wire_type() == 0 {
+        let value = prost::encoding::decode_varint(&mut bytes).unwrap();
+
+        // negative int32 values are sign-extended to 64 bits, so this also
+        // catches int32 fields that are declared as sint32
+        if field.kind.ends_with("32") {
+          assert!(
+            value <= u32::MAX.into(),
+            "{name}.{} is not a {}",
+            field.name,
+            field.kind,
+          );
+        }
+
+        let mut buffer = Vec::new();
+        prost::encoding::encode_varint(value, &mut buffer);
+        buffer
+      } else {
+        let len = usize::try_from(prost::encoding::decode_varint(&mut bytes).unwrap()).unwrap();
+
+        let (value, rest) = bytes.split_at(len);
+
+        bytes = rest;
+
+        if messages.contains_key(&field.kind) {
+          canonicalize(messages, &field.kind, value)
+            .into_iter()
+            .flat_map(|(tag, value)| {
+              let mut buffer = Vec::new();
+              prost::encoding::encode_varint(tag, &mut buffer);
+              buffer.extend(value);
+              buffer
+            })
+            .collect()
+        } else {
+          value.to_vec()
+        }
+      };
+
+      fields.push((tag, value));
+    }
+
+    fields.sort_by_key(|(tag, _value)| *tag);
+
+    fields
+  }
+
+  // encodes a message with every field in schema/event.proto set to a
+  // non-default value, and `variant` set if the message has a oneof
+  fn proto_example(
+    messages: &BTreeMap<String, BTreeMap<u64, ProtoField>>,
+    name: &str,
+    variant: Option<u64>,
+  ) -> Vec<u8> {
+    let mut buffer = Vec::new();
+
+    for (tag, field) in &messages[name] {
+      if field.oneof && Some(*tag) != variant {
+        continue;
+      }
+
+      prost::encoding::encode_varint(tag << 3 | field.wire_type(), &mut buffer);
+
+      if field.wire_type() == 0 {
+        prost::encoding::encode_varint(1, &mut buffer);
+      } else {
+        let value = if messages.contains_key(&field.kind) {
+          proto_example(messages, &field.kind, None)
+        } else {
+          b"1".to_vec()
+        };
+
+        prost::encoding::encode_varint(value.len().try_into().unwrap(), &mut buffer);
+        buffer.extend(value);
+      }
+    }
+
+    buffer
+  }
+
+  #[test]
+  fn protobuf_messages_match_schema() {
+    let messages = proto_messages();
+
+    for line in V1_JSON.lines() {
+      canonicalize(
+        &messages,
+        "EventMessage",
+        &ProtoEventMessage::try_from(&serde_json::from_str::<EventMessage>(line).unwrap())
+          .unwrap()
+          .encode_to_vec(),
+      );
+    }
+  }
+
+  #[test]
+  fn protobuf_schema_fields_are_encoded() {
+    let messages = proto_messages();
+
+    let variants = messages["EventMessage"]
+      .iter()
+      .filter(|(_tag, field)| field.oneof)
+      .map(|(tag, _field)| *tag)
+      .collect::<Vec<u64>>();
+
+    assert!(!variants.is_empty());
+
+    for variant in variants {
+      let example = proto_example(&messages, "EventMessage", Some(variant));
+
+      pretty_assert_eq!(
+        canonicalize(
+          &messages,
+          "EventMessage",
+          &ProtoEventMessage::decode(example.as_slice())
+            .unwrap()
+            .encode_to_vec(),
+        ),
+        canonicalize(&messages, "EventMessage", &example),
+        "fields of event {variant} in schema/event.proto are not encoded by src/index/event/protobuf.rs",
+      );
+    }
+  }
+
+  #[test]
+  fn json_schema_is_up_to_date() {
+    pretty_assert_eq!(
+      EventMessage::json_schema(),
+      include_str!("../../schema/event.schema.json"),
+      "event schema changed, update schema/event.schema.json with `ord index schema`, and bump \
+      `EventMessage::SCHEMA_VERSION` if the change is not backward compatible",
+    );
+  }
+
+  #[test]
+  fn fixtures_cover_all_events() {
+    let names = V1_JSON
+      .lines()
+      .map(|line| {
+        serde_json::from_str::<EventMessage>(line)
+          .unwrap()
+          .event
+          .name()
+      })
+      .collect::<BTreeSet<&str>>();
+
+    assert_eq!(names, Event::NAMES.into_iter().collect());
+
+    let schema = serde_json::from_str::<serde_json::Value>(&EventMessage::json_schema()).unwrap();
+
+    for variant in schema["definitions"]["Event"]["oneOf"].as_array().unwrap() {
+      let name = variant["required"][0].as_str().unwrap();
+      assert!(Event::NAMES.contains(&name), "{name} missing from fixtures");
+    }
+  }
+
+  #[test]
+  fn v1_json_messages_match_schema() {
+    let schema = schema();
+
+    for line in V1_JSON.lines() {
+      let instance = serde_json::from_str(line).unwrap();
+
+      let errors = match schema.validate(&instance) {
+        Ok(()) => continue,
+        Err(errors) => errors
+          .map(|error| error.to_string())
+          .collect::<Vec<String>>(),
+      };
+
+      panic!("{line} does not match schema: {}", errors.join(", "));
+    }
+  }
+
+  #[test]
+  fn v1_json_messages_can_be_decoded() {
+    for line in V1_JSON.lines() {
+      let message = serde_json::from_str::<EventMessage>(line).unwrap();
+
+      assert_eq!(message.schema_version, 1);
+
+      assert_eq!(
+        serde_json::from_str::<EventMessage>(&serde_json::to_string(&message).unwrap()).unwrap(),
+        message,
+      );
+    }
+  }
+
+  #[test]
+  fn v1_protobuf_messages_can_be_decoded() {
+    assert_eq!(V1_PROTOBUF.lines().count(), V1_JSON.lines().count());
+
+    for (json, protobuf) in V1_JSON.lines().zip(V1_PROTOBUF.lines()) {
+      let bytes = hex::decode(protobuf).unwrap();
+
+      let message = EventMessage::from_protobuf(&bytes).unwrap();
+
+      assert_eq!(message, serde_json::from_str::<EventMessage>(json).unwrap());
+
+      assert_eq!(message.to_protobuf().unwrap(), bytes);
+    }
+  }
+
+  #[test]
+  fn invalid_protobuf_messages_are_rejected() {
+    assert_eq!(
+      EventMessage::from_protobuf(&[0xff])
+        .unwrap_err()
+        .to_string(),
+      "failed to decode protobuf event message",
+    );
+
+    let mut message = ProtoEventMessage::try_from(
+      &serde_json::from_str::<EventMessage>(V1_JSON.lines().next().unwrap()).unwrap(),
+    )
+    .unwrap();
+
+    let Some(ProtoEvent::BlockStarted(event)) = &mut message.event else {
+      panic!();
+    };
+
+    event.block_hash = "foo".into();
+
+    assert_eq!(
+      EventMessage::try_from(message).unwrap_err().to_string(),
+      "invalid `block_hash` in event message: `foo`",
+    );
+  }
+}
@@ -0,0 +1,754 @@
+use {
+  super::{BlockEventCounts, Event, EventMessage, InscriptionDetails},
+  crate::{InscriptionId, SatPoint},
+  anyhow::{Context, Error, Result},
+  bitcoin::{BlockHash, OutPoint, Txid},
+  ordinals::{RuneId, Sat, SpacedRune},
+  prost::{Message, Oneof},
+  std::str::FromStr,
+};
+
+// messages must be kept in sync with schema/event.proto, which is checked by
+// `protobuf_messages_match_schema` and `protobuf_schema_fields_are_encoded`
+
+#[derive(Clone, PartialEq, Message)]
+pub struct ProtoEventMessage {
+  #[prost(uint64, tag = "1")]
+  pub event_id: u64,
+  #[prost(uint32, tag = "2")]
+  pub schema_version: u32,
+  #[prost(
+    oneof = "ProtoEvent",
+    tags = "3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20"
+  )]
+  pub event: Option<ProtoEvent>,
+}
+
+#[derive(Clone, PartialEq, Oneof)]
+pub enum ProtoEvent {
+  #[prost(message, tag = "3")]
+  BlockCommitted(ProtoBlockCommitted),
+  #[prost(message, tag = "4")]
+  BlockRetracted(ProtoBlockRetracted),
+  #[prost(message, tag = "5")]
+  BlockStarted(ProtoBlockStarted),
+  #[prost(message, tag = "6")]
+  BlocksRolledBack(ProtoBlocksRolledBack),
+  #[prost(message, tag = "7")]
+  InscriptionCreated(ProtoInscriptionCreated),
+  #[prost(message, tag = "8")]
+  InscriptionTransferred(ProtoInscriptionTransferred),
+  #[prost(message, tag = "9")]
+  RuneBurned(ProtoRuneBurned),
+  #[prost(message, tag = "10")]
+  RuneEtched(ProtoRuneEtched),
+  #[prost(message, tag = "11")]
+  RuneMinted(ProtoRuneMinted),
+  #[prost(message, tag = "12")]
+  RuneTransferred(ProtoRuneTransferred),
+  #[prost(message, tag = "13")]
+  PendingInscriptionCreated(ProtoPendingInscriptionCreated),
+  #[prost(message, tag = "14")]
+  PendingInscriptionTransferred(ProtoPendingInscriptionTransferred),
+  #[prost(message, tag = "15")]
+  PendingRuneEtched(ProtoPendingRuneEtched),
+  #[prost(message, tag = "16")]
+  PendingRuneMinted(ProtoPendingRuneMinted),
+  #[prost(message, tag = "17")]
+  PendingRuneTransferred(ProtoPendingRuneTransferred),
+  #[prost(message, tag = "18")]
+  PendingTransactionConfirmed(ProtoPendingTransaction),
+  #[prost(message, tag = "19")]
+  PendingTransactionEvicted(ProtoPendingTransaction),
+  #[prost(message, tag = "20")]
+  MetaprotocolEvent(ProtoMetaprotocolEvent),
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct ProtoBlockEventCounts {
+  #[prost(uint64, tag = "1")]
+  pub inscriptions_created: u64,
+  #[prost(uint64, tag = "2")]
+  pub inscriptions_transferred: u64,
+  #[prost(uint64, tag = "3")]
+  pub runes_burned: u64,
+  #[prost(uint64, tag = "4")]
+  pub runes_etched: u64,
+  #[prost(uint64, tag = "5")]
+  pub runes_minted: u64,
+  #[prost(uint64, tag = "6")]
+  pub runes_transferred: u64,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct ProtoBlockCommitted {
+  #[prost(string, tag = "1")]
+  pub block_hash: String,
+  #[prost(uint32, tag = "2")]
+  pub block_height: u32,
+  #[prost(message, optional, tag = "3")]
+  pub counts: Option<ProtoBlockEventCounts>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct ProtoBlockRetracted {
+  #[prost(string, tag = "1")]
+  pub block_hash: String,
+  #[prost(uint32, tag = "2")]
+  pub block_height: u32,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct ProtoBlockStarted {
+  #[prost(string, tag = "1")]
+  pub block_hash: String,
+  #[prost(uint32, tag = "2")]
+  pub block_height: u32,
+  #[prost(string, tag = "3")]
+  pub prev_block_hash: String,
+  #[prost(uint32, tag = "4")]
+  pub timestamp: u32,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct ProtoBlocksRolledBack {
+  #[prost(uint32, tag = "1")]
+  pub block_height: u32,
+  #[prost(uint32, tag = "2")]
+  pub depth: u32,
+  #[prost(uint32, tag = "3")]
+  pub from_height: u32,
+  #[prost(uint32, tag = "4")]
+  pub to_height: u32,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct ProtoInscriptionDetails {
+  #[prost(string, optional, tag = "1")]
+  pub address: Option<String>,
+  #[prost(bytes = "vec", optional, tag = "2")]
+  pub body: Option<Vec<u8>>,
+  #[prost(uint64, optional, tag = "3")]
+  pub content_length: Option<u64>,
+  #[prost(string, optional, tag = "4")]
+  pub content_type: Option<String>,
+  #[prost(string, optional, tag = "5")]
+  pub delegate: Option<String>,
+  #[prost(uint64, tag = "6")]
+  pub fee: u64,
+  #[prost(sint32, tag = "7")]
+  pub inscription_number: i32,
+  #[prost(bytes = "vec", optional, tag = "8")]
+  pub metadata: Option<Vec<u8>>,
+  #[prost(string, optional, tag = "9")]
+  pub metaprotocol: Option<String>,
+  #[prost(uint64, optional, tag = "10")]
+  pub sat: Option<u64>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct ProtoInscriptionCreated {
+  #[prost(uint32, tag = "1")]
+  pub block_height: u32,
+  #[prost(uint32, tag = "2")]
+  pub charms: u32,
+  #[prost(message, optional, tag = "3")]
+  pub details: Option<ProtoInscriptionDetails>,
+  #[prost(string, tag = "4")]
+  pub inscription_id: String,
+  #[prost(string, optional, tag = "5")]
+  pub location: Option<String>,
+  #[prost(string, repeated, tag = "6")]
+  pub parent_inscription_ids: Vec<String>,
+  #[prost(uint32, tag = "7")]
+  pub sequence_number: u32,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct ProtoInscriptionTransferred {
+  #[prost(uint32, tag = "1")]
+  pub block_height: u32,
+  #[prost(string, tag = "2")]
+  pub inscription_id: String,
+  #[prost(string, tag = "3")]
+  pub new_location: String,
+  #[prost(string, tag = "4")]
+  pub old_location: String,
+  #[prost(uint32, tag = "5")]
+  pub sequence_number: u32,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct ProtoMetaprotocolEvent {
+  #[prost(uint32, tag = "1")]
+  pub block_height: u32,
+  #[prost(string, tag = "2")]
+  pub data: String,
+  #[prost(string, tag = "3")]
+  pub inscription_id: String,
+  #[prost(string, tag = "4")]
+  pub kind: String,
+  #[prost(string, tag = "5")]
+  pub protocol: String,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct ProtoPendingInscriptionCreated {
+  #[prost(uint32, tag = "1")]
+  pub block_height: u32,
+  #[prost(string, optional, tag = "2")]
+  pub content_type: Option<String>,
+  #[prost(string, tag = "3")]
+  pub inscription_id: String,
+  #[prost(string, repeated, tag = "4")]
+  pub parent_inscription_ids: Vec<String>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct ProtoPendingInscriptionTransferred {
+  #[prost(uint32, tag = "1")]
+  pub block_height: u32,
+  #[prost(string, tag = "2")]
+  pub inscription_id: String,
+  #[prost(string, tag = "3")]
+  pub old_location: String,
+  #[prost(string, tag = "4")]
+  pub txid: String,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct ProtoPendingRuneEtched {
+  #[prost(uint32, tag = "1")]
+  pub block_height: u32,
+  #[prost(string, optional, tag = "2")]
+  pub rune: Option<String>,
+  #[prost(string, tag = "3")]
+  pub txid: String,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct ProtoPendingRuneMinted {
+  #[prost(uint32, tag = "1")]
+  pub block_height: u32,
+  #[prost(string, tag = "2")]
+  pub rune_id: String,
+  #[prost(string, tag = "3")]
+  pub txid: String,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct ProtoPendingRuneTransferred {
+  #[prost(string, tag = "1")]
+  pub amount: String,
+  #[prost(uint32, tag = "2")]
+  pub block_height: u32,
+  #[prost(string, tag = "3")]
+  pub old_outpoint: String,
+  #[prost(string, tag = "4")]
+  pub rune_id: String,
+  #[prost(string, tag = "5")]
+  pub txid: String,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct ProtoPendingTransaction {
+  #[prost(uint32, tag = "1")]
+  pub block_height: u32,
+  #[prost(string, tag = "2")]
+  pub txid: String,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct ProtoRuneBurned {
+  #[prost(string, tag = "1")]
+  pub amount: String,
+  #[prost(uint32, tag = "2")]
+  pub block_height: u32,
+  #[prost(string, tag = "3")]
+  pub rune_id: String,
+  #[prost(string, tag = "4")]
+  pub txid: String,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct ProtoRuneEtched {
+  #[prost(uint32, tag = "1")]
+  pub block_height: u32,
+  #[prost(string, tag = "2")]
+  pub rune_id: String,
+  #[prost(string, tag = "3")]
+  pub txid: String,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct ProtoRuneMinted {
+  #[prost(string, tag = "1")]
+  pub amount: String,
+  #[prost(uint32, tag = "2")]
+  pub block_height: u32,
+  #[prost(string, tag = "3")]
+  pub rune_id: String,
+  #[prost(string, tag = "4")]
+  pub txid: String,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct ProtoRuneTransferred {
+  #[prost(string, tag = "1")]
+  pub amount: String,
+  #[prost(uint32, tag = "2")]
+  pub block_height: u32,
+  #[prost(string, tag = "3")]
+  pub outpoint: String,
+  #[prost(string, tag = "4")]
+  pub rune_id: String,
+  #[prost(string, tag = "5")]
+  pub txid: String,
+}
+
+fn parse<T>(field: &str, s: &str) -> Result<T>
+where
+  T: FromStr,
+  T::Err: Into<Error>,
+{
+  s.parse::<T>()
+    .map_err(Into::into)
+    .with_context(|| format!("invalid `{field}` in event message: `{s}`"))
+}
+
+fn decode_hex(field: &str, s: &Option<String>) -> Result<Option<Vec<u8>>> {
+  s.as_ref()
+    .map(|s| hex::decode(s).with_context(|| format!("invalid `{field}` in event message")))
+    .transpose()
+}
+
+impl From<&BlockEventCounts> for ProtoBlockEventCounts {
+  fn from(counts: &BlockEventCounts) -> Self {
+    Self {
+      inscriptions_created: counts.inscriptions_created,
+      inscriptions_transferred: counts.inscriptions_transferred,
+      runes_burned: counts.runes_burned,
+      runes_etched: counts.runes_etched,
+      runes_minted: counts.runes_minted,
+      runes_transferred: counts.runes_transferred,
+    }
+  }
+}
+
+impl From<ProtoBlockEventCounts> for BlockEventCounts {
+  fn from(counts: ProtoBlockEventCounts) -> Self {
+    Self {
+      inscriptions_created: counts.inscriptions_created,
+      inscriptions_transferred: counts.inscriptions_transferred,
+      runes_burned: counts.runes_burned,
+      runes_etched: counts.runes_etched,
+      runes_minted: counts.runes_minted,
+      runes_transferred: counts.runes_transferred,
+    }
+  }
+}
+
+impl TryFrom<&InscriptionDetails> for ProtoInscriptionDetails {
+  type Error = Error;
+
+  fn try_from(details: &InscriptionDetails) -> Result<Self> {
+    Ok(Self {
+      address: details.address.clone(),
+      body: decode_hex("body", &details.body)?,
+      content_length: details.content_length.map(u64::try_from).transpose()?,
+      content_type: details.content_type.clone(),
+      delegate: details.delegate.map(|delegate| delegate.to_string()),
+      fee: details.fee,
+      inscription_number: details.inscription_number,
+      metadata: decode_hex("metadata", &details.metadata)?,
+      metaprotocol: details.metaprotocol.clone(),
+      sat: details.sat.map(|sat| sat.n()),
+    })
+  }
+}
+
+impl TryFrom<ProtoInscriptionDetails> for InscriptionDetails {
+  type Error = Error;
+
+  fn try_from(details: ProtoInscriptionDetails) -> Result<Self> {
+    Ok(Self {
+      address: details.address,
+      body: details.body.map(hex::encode),
+      content_length: details.content_length.map(usize::try_from).transpose()?,
+      content_type: details.content_type,
+      delegate: details
+        .delegate
+        .map(|delegate| parse::<InscriptionId>("delegate", &delegate))
+        .transpose()?,
+      fee: details.fee,
+      inscription_number: details.inscription_number,
+      metadata: details.metadata.map(hex::encode),
+      metaprotocol: details.metaprotocol,
+      sat: details.sat.map(Sat),
+    })
+  }
+}
+
+impl TryFrom<&EventMessage> for ProtoEventMessage {
+  type Error = Error;
+
+  fn try_from(message: &EventMessage) -> Result<Self> {
+    let event = match &message.event {
+      Event::BlockCommitted {
+        block_hash,
+        block_height,
+        counts,
+      } => ProtoEvent::BlockCommitted(ProtoBlockCommitted {
+        block_hash: block_hash.to_string(),
+        block_height: *block_height,
+        counts: Some(counts.into()),
+      }),
+      Event::BlockRetracted {
+        block_hash,
+        block_height,
+      } => ProtoEvent::BlockRetracted(ProtoBlockRetracted {
+        block_hash: block_hash.to_string(),
+        block_height: *block_height,
+      }),
+      Event::BlockStarted {
+        block_hash,
+        block_height,
+        prev_block_hash,
+        timestamp,
+      } => ProtoEvent::BlockStarted(ProtoBlockStarted {
+        block_hash: block_hash.to_string(),
+        block_height: *block_height,
+        prev_block_hash: prev_block_hash.to_string(),
+        timestamp: *timestamp,
+      }),
+      Event::BlocksRolledBack {
+        block_height,
+        depth,
+        from_height,
+        to_height,
+      } => ProtoEvent::BlocksRolledBack(ProtoBlocksRolledBack {
+        block_height: *block_height,
+        depth: *depth,
+        from_height: *from_height,
+        to_height: *to_height,
+      }),
+      Event::InscriptionCreated {
+        block_height,
+        charms,
+        details,
+        inscription_id,
+        location,
+        parent_inscription_ids,
+        sequence_number,
+      } => ProtoEvent::InscriptionCreated(ProtoInscriptionCreated {
+        block_height: *block_height,
+        charms: (*charms).into(),
+        details: details
+          .as_deref()
+          .map(ProtoInscriptionDetails::try_from)
+          .transpose()?,
+        inscription_id: inscription_id.to_string(),
+        location: location.map(|location| location.to_string()),
+        parent_inscription_ids: parent_inscription_ids
+          .iter()
+          .map(|parent| parent.to_string())
+          .collect(),
+        sequence_number: *sequence_number,
+      }),
+      Event::InscriptionTransferred {
+        block_height,
+        inscription_id,
+        new_location,
+        old_location,
+        sequence_number,
+      } => ProtoEvent::InscriptionTransferred(ProtoInscriptionTransferred {
+        block_height: *block_height,
+        inscription_id: inscription_id.to_string(),
+        new_location: new_location.to_string(),
+        old_location: old_location.to_string(),
+        sequence_number: *sequence_number,
+      }),
+      Event::MetaprotocolEvent {
+        block_height,
+        data,
+        inscription_id,
+        kind,
+        protocol,
+      } => ProtoEvent::MetaprotocolEvent(ProtoMetaprotocolEvent {
+        block_height: *block_height,
+        data: data.to_string(),
+        inscription_id: inscription_id.to_string(),
+        kind: kind.clone(),
+        protocol: protocol.clone(),
+      }),
+      Event::PendingInscriptionCreated {
+        block_height,
+        content_type,
+        inscription_id,
+        parent_inscription_ids,
+      } => ProtoEvent::PendingInscriptionCreated(ProtoPendingInscriptionCreated {
+        block_height: *block_height,
+        content_type: content_type.clone(),
+        inscription_id: inscription_id.to_string(),
+        parent_inscription_ids: parent_inscription_ids
+          .iter()
+          .map(|parent| parent.to_string())
+          .collect(),
+      }),
+      Event::PendingInscriptionTransferred {
+        block_height,
+        inscription_id,
+        old_location,
+        txid,
+      } => ProtoEvent::PendingInscriptionTransferred(ProtoPendingInscriptionTransferred {
+        block_height: *block_height,
+        inscription_id: inscription_id.to_string(),
+        old_location: old_location.to_string(),
+        txid: txid.to_string(),
+      }),
+      Event::PendingRuneEtched {
+        block_height,
+        rune,
+        txid,
+      } => ProtoEvent::PendingRuneEtched(ProtoPendingRuneEtched {
+        block_height: *block_height,
+        rune: rune.map(|rune| rune.to_string()),
+        txid: txid.to_string(),
+      }),
+      Event::PendingRuneMinted {
+        block_height,
+        rune_id,
+        txid,
+      } => ProtoEvent::PendingRuneMinted(ProtoPendingRuneMinted {
+        block_height: *block_height,
+        rune_id: rune_id.to_string(),
+        txid: txid.to_string(),
+      }),
+      Event::PendingRuneTransferred {
+        amount,
+        block_height,
+        old_outpoint,
+        rune_id,
+        txid,
+      } => ProtoEvent::PendingRuneTransferred(ProtoPendingRuneTransferred {
+        amount: amount.to_string(),
+        block_height: *block_height,
+        old_outpoint: old_outpoint.to_string(),
+        rune_id: rune_id.to_string(),
+        txid: txid.to_string(),
+      }),
+      Event::PendingTransactionConfirmed { block_height, txid } => {
+        ProtoEvent::PendingTransactionConfirmed(ProtoPendingTransaction {
+          block_height: *block_height,
+          txid: txid.to_string(),
+        })
+      }
+      Event::PendingTransactionEvicted { block_height, txid } => {
+        ProtoEvent::PendingTransactionEvicted(ProtoPendingTransaction {
+          block_height: *block_height,
+          txid: txid.to_string(),
+        })
+      }
+      Event::RuneBurned {
+        amount,
+        block_height,
+        rune_id,
+        txid,
+      } => ProtoEvent::RuneBurned(ProtoRuneBurned {
+        amount: amount.to_string(),
+        block_height: *block_height,
+        rune_id: rune_id.to_string(),
+        txid: txid.to_string(),
+      }),
+      Event::RuneEtched {
+        block_height,
+        rune_id,
+        txid,
+      } => ProtoEvent::RuneEtched(ProtoRuneEtched {
+        block_height: *block_height,
+        rune_id: rune_id.to_string(),
+        txid: txid.to_string(),
+      }),
+      Event::RuneMinted {
+        amount,
+        block_height,
+        rune_id,
+        txid,
+      } => ProtoEvent::RuneMinted(ProtoRuneMinted {
+        amount: amount.to_string(),
+        block_height: *block_height,
+        rune_id: rune_id.to_string(),
+        txid: txid.to_string(),
+      }),
+      Event::RuneTransferred {
+        amount,
+        block_height,
+        outpoint,
+        rune_id,
+        txid,
+      } => ProtoEvent::RuneTransferred(ProtoRuneTransferred {
+        amount: amount.to_string(),
+        block_height: *block_height,
+        outpoint: outpoint.to_string(),
+        rune_id: rune_id.to_string(),
+        txid: txid.to_string(),
+      }),
+    };
+
+    Ok(Self {
+      event_id: message.event_id,
+      schema_version: message.schema_version,
+      event: Some(event),
+    })
+  }
+}
+
+impl TryFrom<ProtoEventMessage> for EventMessage {
+  type Error = Error;
+
+  fn try_from(message: ProtoEventMessage) -> Result<Self> {
+    let event = match message.event.context("event message has no event")? {
+      ProtoEvent::BlockCommitted(event) => Event::BlockCommitted {
+        block_hash: parse::<BlockHash>("block_hash", &event.block_hash)?,
+        block_height: event.block_height,
+        counts: event.counts.unwrap_or_default().into(),
+      },
+      ProtoEvent::BlockRetracted(event) => Event::BlockRetracted {
+        block_hash: parse::<BlockHash>("block_hash", &event.block_hash)?,
+        block_height: event.block_height,
+      },
+      ProtoEvent::BlockStarted(event) => Event::BlockStarted {
+        block_hash: parse::<BlockHash>("block_hash", &event.block_hash)?,
+        block_height: event.block_height,
+        prev_block_hash: parse::<BlockHash>("prev_block_hash", &event.prev_block_hash)?,
+        timestamp: event.timestamp,
+      },
+      ProtoEvent::BlocksRolledBack(event) => Event::BlocksRolledBack {
+        block_height: event.block_height,
+        depth: event.depth,
+        from_height: event.from_height,
+        to_height: event.to_height,
+      },
+      ProtoEvent::InscriptionCreated(event) => Event::InscriptionCreated {
+        block_height: event.block_height,
+        charms: event.charms.try_into()?,
+        details: event
+          .details
+          .map(InscriptionDetails::try_from)
+          .transpose()?
+          .map(Box::new),
+        inscription_id: parse::<InscriptionId>("inscription_id", &event.inscription_id)?,
+        location: event
+          .location
+          .map(|location| parse::<SatPoint>("location", &location))
+          .transpose()?,
+        parent_inscription_ids: event
+          .parent_inscription_ids
+          .iter()
+          .map(|parent| parse::<InscriptionId>("parent_inscription_ids", parent))
+          .collect::<Result<Vec<InscriptionId>>>()?,
+        sequence_number: event.sequence_number,
+      },
+      ProtoEvent::InscriptionTransferred(event) => Event::InscriptionTransferred {
+        block_height: event.block_height,
+        inscription_id: parse::<InscriptionId>("inscription_id", &event.inscription_id)?,
+        new_location: parse::<SatPoint>("new_location", &event.new_location)?,
+        old_location: parse::<SatPoint>("old_location", &event.old_location)?,
+        sequence_number: event.sequence_number,
+      },
+      ProtoEvent::MetaprotocolEvent(event) => Event::MetaprotocolEvent {
+        block_height: event.block_height,
+        data: parse::<serde_json::Value>("data", &event.data)?,
+        inscription_id: parse::<InscriptionId>("inscription_id", &event.inscription_id)?,
+        kind: event.kind,
+        protocol: event.protocol,
+      },
+      ProtoEvent::PendingInscriptionCreated(event) => Event::PendingInscriptionCreated {
+        block_height: event.block_height,
+        content_type: event.content_type,
+        inscription_id: parse::<InscriptionId>("inscription_id", &event.inscription_id)?,
+        parent_inscription_ids: event
+          .parent_inscription_ids
+          .iter()
+          .map(|parent| parse::<InscriptionId>("parent_inscription_ids", parent))
+          .collect::<Result<Vec<InscriptionId>>>()?,
+      },
+      ProtoEvent::PendingInscriptionTransferred(event) => Event::PendingInscriptionTransferred {
+        block_height: event.block_height,
+        inscription_id: parse::<InscriptionId>("inscription_id", &event.inscription_id)?,
+        old_location: parse::<SatPoint>("old_location", &event.old_location)?,
+        txid: parse::<Txid>("txid", &event.txid)?,
+      },
+      ProtoEvent::PendingRuneEtched(event) => Event::PendingRuneEtched {
+        block_height: event.block_height,
+        rune: event
+          .rune
+          .map(|rune| parse::<SpacedRune>("rune", &rune))
+          .transpose()?,
+        txid: parse::<Txid>("txid", &event.txid)?,
+      },
+      ProtoEvent::PendingRuneMinted(event) => Event::PendingRuneMinted {
+        block_height: event.block_height,
+        rune_id: parse::<RuneId>("rune_id", &event.rune_id)?,
+        txid: parse::<Txid>("txid", &event.txid)?,
+      },
+      ProtoEvent::PendingRuneTransferred(event) => Event::PendingRuneTransferred {
+        amount: parse::<u128>("amount", &event.amount)?,
+        block_height: event.block_height,
+        old_outpoint: parse::<OutPoint>("old_outpoint", &event.old_outpoint)?,
+        rune_id: parse::<RuneId>("rune_id", &event.rune_id)?,
+        txid: parse::<Txid>("txid", &event.txid)?,
+      },
+      ProtoEvent::PendingTransactionConfirmed(event) => Event::PendingTransactionConfirmed {
+        block_height: event.block_height,
+        txid: parse::<Txid>("txid", &event.txid)?,
+      },
+      ProtoEvent::PendingTransactionEvicted(event) => Event::PendingTransactionEvicted {
+        block_height: event.block_height,
+        txid: parse::<Txid>("txid", &event.txid)?,
+      },
+      ProtoEvent::RuneBurned(event) => Event::RuneBurned {
+        amount: parse::<u128>("amount", &event.amount)?,
+        block_height: event.block_height,
+        rune_id: parse::<RuneId>("rune_id", &event.rune_id)?,
+        txid: parse::<Txid>("txid", &event.txid)?,
+      },
+      ProtoEvent::RuneEtched(event) => Event::RuneEtched {
+        block_height: event.block_height,
+        rune_id: parse::<RuneId>("rune_id", &event.rune_id)?,
+        txid: parse::<Txid>("txid", &event.txid)?,
+      },
+      ProtoEvent::RuneMinted(event) => Event::RuneMinted {
+        amount: parse::<u128>("amount", &event.amount)?,
+        block_height: event.block_height,
+        rune_id: parse::<RuneId>("rune_id", &event.rune_id)?,
+        txid: parse::<Txid>("txid", &event.txid)?,
+      },
+      ProtoEvent::RuneTransferred(event) => Event::RuneTransferred {
+        amount: parse::<u128>("amount", &event.amount)?,
+        block_height: event.block_height,
+        outpoint: parse::<OutPoint>("outpoint", &event.outpoint)?,
+        rune_id: parse::<RuneId>("rune_id", &event.rune_id)?,
+        txid: parse::<Txid>("txid", &event.txid)?,
+      },
+    };
+
+    Ok(Self {
+      event_id: message.event_id,
+      schema_version: message.schema_version,
+      event,
+    })
+  }
+}
+
+impl EventMessage {
+  pub fn to_protobuf(&self) -> Result<Vec<u8>> {
+    Ok(ProtoEventMessage::try_from(self)?.encode_to_vec())
+  }
+
+  pub fn from_protobuf(bytes: &[u8]) -> Result<Self> {
+    ProtoEventMessage::decode(bytes)
+      .context("failed to decode protobuf event message")?
+      .try_into()
+  }
+}
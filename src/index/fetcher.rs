@@ -46,6 +46,46 @@ impl Fetcher {
   }
 
   pub(crate) async fn get_transactions(&self, txids: Vec<Txid>) -> Result<Vec<Transaction>> {
+    let results = self.fetch(&txids).await?;
+
+    // Return early on any error, because we need all results to proceed
+    if let Some(err) = results.iter().find_map(|res| res.error.as_ref()) {
+      return Err(anyhow!(
+        "failed to fetch raw transaction: code {} message {}",
+        err.code,
+        err.message
+      ));
+    }
+
+    results
+      .into_iter()
+      .map(|res| Self::decode(res.result))
+      .collect()
+  }
+
+  // Transactions that bitcoind doesn't know about, for example because they
+  // left the mempool after it was listed, are returned as `None`
+  pub(crate) async fn get_transactions_if_present(
+    &self,
+    txids: Vec<Txid>,
+  ) -> Result<Vec<Option<Transaction>>> {
+    self
+      .fetch(&txids)
+      .await?
+      .into_iter()
+      .map(|res| match res.error {
+        Some(JsonError { code: -5 | -8, .. }) => Ok(None),
+        Some(err) => Err(anyhow!(
+          "failed to fetch raw transaction: code {} message {}",
+          err.code,
+          err.message
+        )),
+        None => Self::decode(res.result).map(Some),
+      })
+      .collect()
+  }
+
+  async fn fetch(&self, txids: &[Txid]) -> Result<Vec<JsonResponse<String>>> {
     if txids.is_empty() {
       return Ok(Vec::new());
     }
@@ -79,6 +119,8 @@ impl Fetcher {
 
           log::info!("failed to fetch raw transactions, retrying: {}", error);
 
+          metrics::rpc_retry("transactions");
+
           tokio::time::sleep(Duration::from_millis(100 * u64::pow(2, retries))).await;
           retries += 1;
           continue;
@@ -87,36 +129,23 @@ impl Fetcher {
       break;
     }
 
-    // Return early on any error, because we need all results to proceed
-    if let Some(err) = results.iter().find_map(|res| res.error.as_ref()) {
-      return Err(anyhow!(
-        "failed to fetch raw transaction: code {} message {}",
-        err.code,
-        err.message
-      ));
-    }
-
     // Results from batched JSON-RPC requests can come back in any order, so we must sort them by id
-    results.sort_by(|a, b| a.id.cmp(&b.id));
+    results.sort_by_key(|result| result.id);
 
-    let txs = results
-      .into_iter()
-      .map(|res| {
-        res
-          .result
-          .ok_or_else(|| anyhow!("Missing result for batched JSON-RPC response"))
-          .and_then(|str| {
-            hex::decode(str)
-              .map_err(|e| anyhow!("Result for batched JSON-RPC response not valid hex: {e}"))
-          })
-          .and_then(|hex| {
-            consensus::deserialize(&hex).map_err(|e| {
-              anyhow!("Result for batched JSON-RPC response not valid bitcoin tx: {e}")
-            })
-          })
+    Ok(results)
+  }
+
+  fn decode(result: Option<String>) -> Result<Transaction> {
+    result
+      .ok_or_else(|| anyhow!("Missing result for batched JSON-RPC response"))
+      .and_then(|str| {
+        hex::decode(str)
+          .map_err(|e| anyhow!("Result for batched JSON-RPC response not valid hex: {e}"))
+      })
+      .and_then(|hex| {
+        consensus::deserialize(&hex)
+          .map_err(|e| anyhow!("Result for batched JSON-RPC response not valid bitcoin tx: {e}"))
       })
-      .collect::<Result<Vec<Transaction>>>()?;
-    Ok(txs)
   }
 
   async fn try_get_transactions(&self, body: String) -> Result<Vec<JsonResponse<String>>> {
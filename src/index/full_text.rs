@@ -0,0 +1,282 @@
+use super::*;
+
+const BM25_B: f64 = 0.75;
+const BM25_K1: f64 = 1.2;
+const MAX_DOCUMENT_LENGTH: usize = 64 * 1024;
+// postings examined per query term, newest first
+const MAX_POSTINGS: usize = 10_000;
+const MAX_QUERY_TERMS: usize = 8;
+const MAX_TERM_LENGTH: usize = 64;
+const SNIPPET_CONTEXT: usize = 40;
+const SNIPPET_LENGTH: usize = 160;
+
+pub(crate) fn document(inscription: &Inscription) -> Option<String> {
+  let content_type = inscription
+    .content_type()?
+    .split(';')
+    .next()?
+    .trim()
+    .to_ascii_lowercase();
+
+  if !content_type.starts_with("text/") && content_type != "application/json" {
+    return None;
+  }
+
+  let body = inscription.body()?;
+
+  let mut bytes = match inscription.content_encoding.as_deref() {
+    None => body.to_vec(),
+    Some(b"br") => {
+      let mut decompressed = Vec::new();
+      brotli::Decompressor::new(body, body.len())
+        .take(MAX_DOCUMENT_LENGTH.try_into().unwrap())
+        .read_to_end(&mut decompressed)
+        .ok()?;
+      decompressed
+    }
+    Some(_) => return None,
+  };
+
+  bytes.truncate(MAX_DOCUMENT_LENGTH);
+
+  match String::from_utf8(bytes) {
+    Ok(text) => Some(text),
+    Err(err) if err.utf8_error().error_len().is_none() => {
+      let valid = err.utf8_error().valid_up_to();
+      let mut bytes = err.into_bytes();
+      bytes.truncate(valid);
+      String::from_utf8(bytes).ok()
+    }
+    Err(_) => None,
+  }
+}
+
+fn words(text: &str) -> impl Iterator<Item = (usize, &str)> {
+  let mut start = None;
+
+  text
+    .char_indices()
+    .chain(std::iter::once((text.len(), ' ')))
+    .filter_map(move |(i, c)| {
+      if c.is_alphanumeric() {
+        start.get_or_insert(i);
+        None
+      } else {
+        start.take().map(|start| (start, &text[start..i]))
+      }
+    })
+    .filter(|(_, word)| word.len() <= MAX_TERM_LENGTH)
+}
+
+pub(crate) fn terms(text: &str) -> impl Iterator<Item = String> + '_ {
+  words(text).map(|(_, word)| word.to_lowercase())
+}
+
+fn snippet(text: &str, terms: &BTreeSet<String>) -> String {
+  let position = words(text)
+    .find(|(_, word)| terms.contains(&word.to_lowercase()))
+    .map(|(position, _)| position)
+    .unwrap_or_default();
+
+  let begin = text[..position]
+    .char_indices()
+    .rev()
+    .take(SNIPPET_CONTEXT)
+    .last()
+    .map_or(position, |(i, _)| i);
+
+  let end = text[begin..]
+    .char_indices()
+    .nth(SNIPPET_LENGTH)
+    .map_or(text.len(), |(i, _)| begin + i);
+
+  let mut snippet = text[begin..end]
+    .split_whitespace()
+    .collect::<Vec<&str>>()
+    .join(" ");
+
+  if begin > 0 {
+    snippet.insert(0, '…');
+  }
+
+  if end < text.len() {
+    snippet.push('…');
+  }
+
+  snippet
+}
+
+pub(crate) fn search(
+  index: &Index,
+  query: &str,
+  page_size: usize,
+  page_index: usize,
+) -> Result<(Vec<api::TextSearchResult>, bool)> {
+  let terms = terms(query)
+    .take(MAX_QUERY_TERMS)
+    .collect::<BTreeSet<String>>();
+
+  let rtx = index.database.begin_read()?;
+
+  let sequence_number_to_entry = rtx.open_table(SEQUENCE_NUMBER_TO_INSCRIPTION_ENTRY)?;
+  let sequence_number_to_text = rtx.open_table(SEQUENCE_NUMBER_TO_TEXT)?;
+  let term_to_sequence_number = rtx.open_multimap_table(TERM_TO_SEQUENCE_NUMBER)?;
+
+  let documents = sequence_number_to_text.len()? as f64;
+
+  let total_terms = rtx
+    .open_table(STATISTIC_TO_COUNT)?
+    .get(&Statistic::FullTextTerms.key())?
+    .map(|count| count.value())
+    .unwrap_or_default();
+
+  let average_length = (total_terms as f64 / documents).max(1.0);
+
+  let mut scores = BTreeMap::<u32, f64>::new();
+
+  for term in &terms {
+    let postings = term_to_sequence_number.get(term.as_str())?;
+
+    let frequency = postings.len() as f64;
+
+    let idf = (1.0 + (documents - frequency + 0.5) / (frequency + 0.5)).ln();
+
+    for posting in postings.rev().take(MAX_POSTINGS) {
+      let (sequence_number, count, length) = posting?.value();
+
+      let count = f64::from(count);
+
+      let score = idf * count * (BM25_K1 + 1.0)
+        / (count + BM25_K1 * (1.0 - BM25_B + BM25_B * f64::from(length) / average_length));
+
+      *scores.entry(sequence_number).or_default() += score;
+    }
+  }
+
+  let mut ranked = scores.into_iter().collect::<Vec<(u32, f64)>>();
+
+  ranked.sort_by(
+    |(a_sequence_number, a_score), (b_sequence_number, b_score)| {
+      b_score
+        .total_cmp(a_score)
+        .then(b_sequence_number.cmp(a_sequence_number))
+    },
+  );
+
+  let mut results = ranked
+    .into_iter()
+    .skip(page_index.saturating_mul(page_size))
+    .take(page_size.saturating_add(1))
+    .map(|(sequence_number, score)| {
+      let entry = InscriptionEntry::load(
+        sequence_number_to_entry
+          .get(sequence_number)?
+          .ok_or_else(|| anyhow!("missing entry for sequence number {sequence_number}"))?
+          .value(),
+      );
+
+      let snippet = sequence_number_to_text
+        .get(sequence_number)?
+        .map(|text| snippet(text.value(), &terms))
+        .unwrap_or_default();
+
+      Ok(api::TextSearchResult {
+        id: entry.id,
+        number: entry.inscription_number,
+        score,
+        snippet,
+      })
+    })
+    .collect::<Result<Vec<api::TextSearchResult>>>()?;
+
+  let more = results.len() > page_size;
+
+  if more {
+    results.pop();
+  }
+
+  Ok((results, more))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn documents() {
+    assert_eq!(
+      document(&inscription("text/plain;charset=utf-8", "foo")),
+      Some("foo".into())
+    );
+    assert_eq!(
+      document(&inscription("application/json", r#"{"p":"brc-20"}"#)),
+      Some(r#"{"p":"brc-20"}"#.into())
+    );
+    assert_eq!(
+      document(&inscription("text/markdown", "# foo")),
+      Some("# foo".into())
+    );
+    assert_eq!(document(&inscription("image/png", "foo")), None);
+    assert_eq!(document(&inscription("text/plain", [0xff, 0xfe])), None);
+    assert_eq!(
+      document(&Inscription {
+        content_encoding: Some("gzip".into()),
+        ..inscription("text/plain", "foo")
+      }),
+      None
+    );
+    assert_eq!(
+      document(&inscription("text/plain", "é".repeat(MAX_DOCUMENT_LENGTH))),
+      Some("é".repeat(MAX_DOCUMENT_LENGTH / 2))
+    );
+  }
+
+  #[test]
+  fn brotli_documents_are_decompressed() {
+    let mut body = Vec::new();
+
+    brotli::BrotliCompress(
+      &mut "hello world".as_bytes(),
+      &mut body,
+      &Default::default(),
+    )
+    .unwrap();
+
+    assert_eq!(
+      document(&Inscription {
+        content_encoding: Some("br".into()),
+        ..inscription("text/plain", body)
+      }),
+      Some("hello world".into())
+    );
+  }
+
+  #[test]
+  fn tokenization() {
+    assert_eq!(
+      terms(r#"Hello, WORLD! {"p":"brc-20"} Ünïcödé"#).collect::<Vec<String>>(),
+      ["hello", "world", "p", "brc", "20", "ünïcödé"],
+    );
+
+    assert_eq!(terms(&"a".repeat(MAX_TERM_LENGTH + 1)).count(), 0);
+  }
+
+  #[test]
+  fn snippets() {
+    let terms = ["needle".to_string()].into_iter().collect();
+
+    assert_eq!(
+      snippet("a needle\nin  a haystack", &terms),
+      "a needle in a haystack"
+    );
+
+    let text = format!("{}needle{}", "x ".repeat(100), " y".repeat(100));
+
+    let snippet = snippet(&text, &terms);
+
+    assert!(snippet.starts_with('…'));
+    assert!(snippet.ends_with('…'));
+    assert!(snippet.contains("needle"));
+    assert_eq!(snippet.chars().count(), SNIPPET_LENGTH + 2);
+  }
+}
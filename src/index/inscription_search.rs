@@ -0,0 +1,354 @@
+use {super::*, std::ops::Bound};
+
+// maximum number of inscriptions examined per request, so that selective
+// filters return a cursor instead of scanning the whole index
+const SCAN_LIMIT: usize = 10_000;
+
+const PAGE_SIZE: usize = 100;
+
+#[derive(Default, Copy, Clone, Debug, PartialEq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Sort {
+  #[default]
+  Number,
+  Fee,
+  Size,
+}
+
+#[derive(Default, Copy, Clone, Debug, PartialEq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Order {
+  Asc,
+  #[default]
+  Desc,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct InscriptionSearch {
+  pub charm: Option<DeserializeFromStr<Charm>>,
+  pub content_type: Option<String>,
+  pub cursor: Option<String>,
+  pub delegate: Option<bool>,
+  pub limit: Option<usize>,
+  pub max_fee: Option<u64>,
+  pub max_height: Option<u32>,
+  pub metadata: Option<bool>,
+  pub metaprotocol: Option<String>,
+  pub min_fee: Option<u64>,
+  pub min_height: Option<u32>,
+  pub order: Option<Order>,
+  pub parent: Option<DeserializeFromStr<InscriptionId>>,
+  pub rarity: Option<DeserializeFromStr<Rarity>>,
+  pub sort: Option<Sort>,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum InvalidSearch {
+  BadRequest(String),
+  MissingIndex(&'static str),
+}
+
+impl Display for InvalidSearch {
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    match self {
+      Self::BadRequest(message) => write!(f, "{message}"),
+      Self::MissingIndex(index) => write!(f, "this server has no {index} index"),
+    }
+  }
+}
+
+impl std::error::Error for InvalidSearch {}
+
+#[derive(Debug, PartialEq)]
+pub struct InscriptionSearchResults {
+  pub ids: Vec<InscriptionId>,
+  pub next: Option<String>,
+}
+
+#[derive(Copy, Clone)]
+enum Cursor {
+  Number(i32),
+  Value(u64, u32),
+}
+
+impl Cursor {
+  fn parse(s: &str, sort: Sort) -> Result<Self> {
+    let invalid = || anyhow!("invalid cursor `{s}`");
+
+    Ok(match sort {
+      Sort::Number => Self::Number(s.parse().map_err(|_| invalid())?),
+      Sort::Fee | Sort::Size => {
+        let (value, sequence_number) = s.split_once(':').ok_or_else(invalid)?;
+        Self::Value(
+          value.parse().map_err(|_| invalid())?,
+          sequence_number.parse().map_err(|_| invalid())?,
+        )
+      }
+    })
+  }
+}
+
+impl Display for Cursor {
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    match self {
+      Self::Number(number) => write!(f, "{number}"),
+      Self::Value(value, sequence_number) => write!(f, "{value}:{sequence_number}"),
+    }
+  }
+}
+
+impl InscriptionSearch {
+  fn cursor(&self) -> Result<Option<Cursor>> {
+    self
+      .cursor
+      .as_deref()
+      .map(|cursor| Cursor::parse(cursor, self.sort.unwrap_or_default()))
+      .transpose()
+  }
+
+  pub(crate) fn validate(&self, index: &Index) -> Result<(), InvalidSearch> {
+    if !index.has_search_index() {
+      return Err(InvalidSearch::MissingIndex("search"));
+    }
+
+    if self.rarity.is_some() && !index.has_sat_index() {
+      return Err(InvalidSearch::MissingIndex("sat"));
+    }
+
+    self
+      .cursor()
+      .map_err(|err| InvalidSearch::BadRequest(err.to_string()))?;
+
+    if let (Some(min), Some(max)) = (self.min_fee, self.max_fee) {
+      if min > max {
+        return Err(InvalidSearch::BadRequest(
+          "min-fee must not exceed max-fee".into(),
+        ));
+      }
+    }
+
+    if let (Some(min), Some(max)) = (self.min_height, self.max_height) {
+      if min > max {
+        return Err(InvalidSearch::BadRequest(
+          "min-height must not exceed max-height".into(),
+        ));
+      }
+    }
+
+    Ok(())
+  }
+
+  pub(crate) fn run(&self, index: &Index) -> Result<InscriptionSearchResults> {
+    let sort = self.sort.unwrap_or_default();
+    let order = self.order.unwrap_or_default();
+    let limit = self.limit.unwrap_or(PAGE_SIZE).clamp(1, PAGE_SIZE);
+
+    self.validate(index)?;
+
+    let cursor = self.cursor()?;
+
+    let rtx = index.database.begin_read()?;
+
+    let sequence_number_to_entry = rtx.open_table(SEQUENCE_NUMBER_TO_INSCRIPTION_ENTRY)?;
+    let sequence_number_to_properties =
+      rtx.open_table(SEQUENCE_NUMBER_TO_INSCRIPTION_PROPERTIES)?;
+
+    let parent = match &self.parent {
+      Some(DeserializeFromStr(parent)) => match rtx
+        .open_table(INSCRIPTION_ID_TO_SEQUENCE_NUMBER)?
+        .get(&parent.store())?
+      {
+        Some(sequence_number) => Some(sequence_number.value()),
+        None => {
+          return Ok(InscriptionSearchResults {
+            ids: Vec::new(),
+            next: None,
+          })
+        }
+      },
+      None => None,
+    };
+
+    let inscription_number_to_sequence_number;
+    let value_to_sequence_number;
+
+    let candidates: Box<dyn Iterator<Item = Result<(Cursor, u32)>>> = match sort {
+      Sort::Number => {
+        inscription_number_to_sequence_number =
+          rtx.open_table(INSCRIPTION_NUMBER_TO_SEQUENCE_NUMBER)?;
+
+        let range = match (order, cursor) {
+          (_, None) => (Bound::Unbounded, Bound::Unbounded),
+          (Order::Asc, Some(Cursor::Number(number))) => (Bound::Excluded(number), Bound::Unbounded),
+          (Order::Desc, Some(Cursor::Number(number))) => {
+            (Bound::Unbounded, Bound::Excluded(number))
+          }
+          (_, Some(Cursor::Value(..))) => unreachable!(),
+        };
+
+        let iter = inscription_number_to_sequence_number
+          .range::<i32>(range)?
+          .map(|result| {
+            result
+              .map(|(number, sequence_number)| {
+                (Cursor::Number(number.value()), sequence_number.value())
+              })
+              .map_err(Error::from)
+          });
+
+        match order {
+          Order::Asc => Box::new(iter),
+          Order::Desc => Box::new(iter.rev()),
+        }
+      }
+      Sort::Fee | Sort::Size => {
+        value_to_sequence_number = rtx.open_table(if sort == Sort::Fee {
+          FEE_TO_SEQUENCE_NUMBER
+        } else {
+          SIZE_TO_SEQUENCE_NUMBER
+        })?;
+
+        let (min, max) = if sort == Sort::Fee {
+          (
+            self.min_fee.unwrap_or_default(),
+            self.max_fee.unwrap_or(u64::MAX),
+          )
+        } else {
+          (0, u64::MAX)
+        };
+
+        let mut start = Bound::Included((min, 0));
+        let mut end = Bound::Included((max, u32::MAX));
+
+        if let Some(Cursor::Value(value, sequence_number)) = cursor {
+          let key = (value, sequence_number);
+
+          if !(min..=max).contains(&value) {
+            return Ok(InscriptionSearchResults {
+              ids: Vec::new(),
+              next: None,
+            });
+          }
+
+          match order {
+            Order::Asc => start = Bound::Excluded(key),
+            Order::Desc => end = Bound::Excluded(key),
+          }
+        }
+
+        let iter = value_to_sequence_number
+          .range::<(u64, u32)>((start, end))?
+          .map(|result| {
+            result
+              .map(|(key, _)| {
+                let (value, sequence_number) = key.value();
+                (Cursor::Value(value, sequence_number), sequence_number)
+              })
+              .map_err(Error::from)
+          });
+
+        match order {
+          Order::Asc => Box::new(iter),
+          Order::Desc => Box::new(iter.rev()),
+        }
+      }
+    };
+
+    let mut ids = Vec::new();
+    let mut last = None;
+    let mut exhausted = true;
+
+    for (scanned, result) in candidates.enumerate() {
+      if ids.len() == limit || scanned == SCAN_LIMIT {
+        exhausted = false;
+        break;
+      }
+
+      let (cursor, sequence_number) = result?;
+
+      last = Some(cursor);
+
+      let entry = InscriptionEntry::load(
+        sequence_number_to_entry
+          .get(sequence_number)?
+          .ok_or_else(|| anyhow!("missing entry for sequence number {sequence_number}"))?
+          .value(),
+      );
+
+      let properties = InscriptionProperties::load(
+        sequence_number_to_properties
+          .get(sequence_number)?
+          .ok_or_else(|| anyhow!("missing properties for sequence number {sequence_number}"))?
+          .value(),
+      );
+
+      if self.matches(&entry, &properties, parent) {
+        ids.push(entry.id);
+      }
+    }
+
+    Ok(InscriptionSearchResults {
+      ids,
+      next: if exhausted {
+        None
+      } else {
+        last.map(|cursor| cursor.to_string())
+      },
+    })
+  }
+
+  fn matches(
+    &self,
+    entry: &InscriptionEntry,
+    properties: &InscriptionProperties,
+    parent: Option<u32>,
+  ) -> bool {
+    if let Some(DeserializeFromStr(charm)) = self.charm {
+      if !charm.is_set(entry.charms) {
+        return false;
+      }
+    }
+
+    if let Some(content_type) = &self.content_type {
+      if properties.content_type.as_deref() != Some(content_type.as_bytes()) {
+        return false;
+      }
+    }
+
+    if let Some(metaprotocol) = &self.metaprotocol {
+      if properties.metaprotocol.as_deref() != Some(metaprotocol.as_bytes()) {
+        return false;
+      }
+    }
+
+    if let Some(delegate) = self.delegate {
+      if properties.delegate != delegate {
+        return false;
+      }
+    }
+
+    if let Some(metadata) = self.metadata {
+      if properties.metadata != metadata {
+        return false;
+      }
+    }
+
+    if let Some(parent) = parent {
+      if !entry.parents.contains(&parent) {
+        return false;
+      }
+    }
+
+    if let Some(DeserializeFromStr(rarity)) = self.rarity {
+      if entry.sat.map(Sat::rarity) != Some(rarity) {
+        return false;
+      }
+    }
+
+    self.min_height.map_or(true, |min| entry.height >= min)
+      && self.max_height.map_or(true, |max| entry.height <= max)
+      && self.min_fee.map_or(true, |min| entry.fee >= min)
+      && self.max_fee.map_or(true, |max| entry.fee <= max)
+  }
+}
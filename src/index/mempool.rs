@@ -0,0 +1,303 @@
+use {
+  super::*,
+  crate::index::fetcher::Fetcher,
+  futures::{stream, StreamExt, TryStreamExt},
+};
+
+pub(super) struct Mempool {
+  fetcher: Fetcher,
+  height: Option<u32>,
+  transactions: HashMap<Txid, bool>,
+}
+
+impl Mempool {
+  const BATCH_SIZE: usize = 2048;
+
+  pub(super) fn new(settings: &Settings) -> Result<Self> {
+    Ok(Self {
+      fetcher: Fetcher::new(settings)?,
+      height: None,
+      transactions: HashMap::new(),
+    })
+  }
+
+  pub(super) fn update(&mut self, index: &Index) -> Result {
+    let Some(height) = index.block_height()?.map(|height| height.n()) else {
+      return Ok(());
+    };
+
+    if self.height.is_none() {
+      self.load(index)?;
+    }
+
+    let mempool = index
+      .client
+      .get_raw_mempool()?
+      .into_iter()
+      .collect::<HashSet<Txid>>();
+
+    let mut events = Vec::new();
+
+    let removed = self
+      .transactions
+      .iter()
+      .filter(|(txid, relevant)| **relevant && !mempool.contains(*txid))
+      .map(|(txid, _relevant)| *txid)
+      .collect::<Vec<Txid>>();
+
+    if !removed.is_empty() {
+      let confirmed = self.confirmed(index, height)?;
+
+      for txid in &removed {
+        events.push(match confirmed.get(txid) {
+          Some(block_height) => Event::PendingTransactionConfirmed {
+            block_height: *block_height,
+            txid: *txid,
+          },
+          None => Event::PendingTransactionEvicted {
+            block_height: height,
+            txid: *txid,
+          },
+        });
+      }
+    }
+
+    let mut added = Vec::new();
+
+    for (txid, tx) in self.fetch(
+      index,
+      mempool
+        .iter()
+        .filter(|txid| !self.transactions.contains_key(*txid))
+        .copied()
+        .collect(),
+    )? {
+      let pending = Self::pending_events(index, height, txid, &tx)?;
+
+      added.push((txid, !pending.is_empty()));
+
+      events.extend(pending);
+    }
+
+    if !events.is_empty() {
+      let wtx = index.begin_write()?;
+
+      {
+        let mut statistic_to_count = wtx.open_table(STATISTIC_TO_COUNT)?;
+
+        let mut event_outbox = EventOutbox::new(
+          wtx.open_table(EVENT_ID_TO_EVENT)?,
+          statistic_to_count
+            .get(&Statistic::Events.key())?
+            .map(|count| count.value())
+            .unwrap_or(0),
+        );
+
+        for event in events {
+          event_outbox.push(event)?;
+        }
+
+        statistic_to_count.insert(&Statistic::Events.key(), &event_outbox.next_event_id())?;
+
+        // record the transactions that events were emitted for in the same
+        // transaction as the events, so they aren't emitted again after a
+        // restart
+        let mut pending_transaction_id_to_height =
+          wtx.open_table(PENDING_TRANSACTION_ID_TO_HEIGHT)?;
+
+        for txid in removed {
+          pending_transaction_id_to_height.remove(&txid.store())?;
+        }
+
+        for (txid, relevant) in &added {
+          if *relevant {
+            pending_transaction_id_to_height.insert(&txid.store(), height)?;
+          }
+        }
+      }
+
+      wtx.commit()?;
+    }
+
+    self
+      .transactions
+      .retain(|txid, _relevant| mempool.contains(txid));
+    self.transactions.extend(added);
+    self.height = Some(height);
+
+    Ok(())
+  }
+
+  // restore the transactions that events were emitted for before a restart,
+  // and scan for their confirmations from the earliest height they were seen
+  fn load(&mut self, index: &Index) -> Result {
+    for result in index
+      .database
+      .begin_read()?
+      .open_table(PENDING_TRANSACTION_ID_TO_HEIGHT)?
+      .iter()?
+    {
+      let (txid, height) = result?;
+
+      self.transactions.insert(Txid::load(*txid.value()), true);
+
+      self.height = Some(
+        self
+          .height
+          .map_or(height.value(), |min| min.min(height.value())),
+      );
+    }
+
+    Ok(())
+  }
+
+  // fetch new transactions in batches, skipping those that left the mempool
+  // after it was listed
+  fn fetch(&self, index: &Index, txids: Vec<Txid>) -> Result<Vec<(Txid, Transaction)>> {
+    if txids.is_empty() {
+      return Ok(Vec::new());
+    }
+
+    let parallel_requests = index.settings.bitcoin_rpc_limit().into_usize();
+
+    // `Index::update` may be called from within an async runtime, so requests
+    // are made from a separate thread
+    thread::scope(|scope| {
+      scope
+        .spawn(|| {
+          tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()?
+            .block_on(
+              stream::iter(txids.chunks(Self::BATCH_SIZE))
+                .map(|chunk| async move {
+                  Ok::<_, Error>(
+                    chunk
+                      .iter()
+                      .copied()
+                      .zip(
+                        self
+                          .fetcher
+                          .get_transactions_if_present(chunk.to_vec())
+                          .await?,
+                      )
+                      .filter_map(|(txid, tx)| Some((txid, tx?)))
+                      .collect::<Vec<(Txid, Transaction)>>(),
+                  )
+                })
+                .buffered(parallel_requests)
+                .try_concat(),
+            )
+        })
+        .join()
+        .unwrap()
+    })
+  }
+
+  fn confirmed(&self, index: &Index, height: u32) -> Result<HashMap<Txid, u32>> {
+    let mut confirmed = HashMap::new();
+
+    let Some(last) = self.height else {
+      return Ok(confirmed);
+    };
+
+    for block_height in last + 1..=height {
+      let Some(hash) = index.block_hash(Some(block_height))? else {
+        continue;
+      };
+
+      for tx in index.client.get_block(&hash)?.txdata {
+        confirmed.insert(tx.txid(), block_height);
+      }
+    }
+
+    Ok(confirmed)
+  }
+
+  fn pending_events(
+    index: &Index,
+    height: u32,
+    txid: Txid,
+    tx: &Transaction,
+  ) -> Result<Vec<Event>> {
+    let mut events = Vec::new();
+
+    if index.settings.index_inscriptions() {
+      for (i, envelope) in ParsedEnvelope::from_transaction(tx).into_iter().enumerate() {
+        events.push(Event::PendingInscriptionCreated {
+          block_height: height,
+          content_type: envelope.payload.content_type().map(str::to_string),
+          inscription_id: InscriptionId {
+            txid,
+            index: u32::try_from(i).unwrap(),
+          },
+          parent_inscription_ids: envelope.payload.parents(),
+        });
+      }
+
+      for input in &tx.input {
+        for (old_location, inscription_id) in
+          index.get_inscriptions_on_output_with_satpoints(input.previous_output)?
+        {
+          events.push(Event::PendingInscriptionTransferred {
+            block_height: height,
+            inscription_id,
+            old_location,
+            txid,
+          });
+        }
+      }
+    }
+
+    if index.index_runes {
+      if let Some(Artifact::Runestone(runestone)) = Runestone::decipher(tx) {
+        if let Some(etching) = runestone.etching {
+          events.push(Event::PendingRuneEtched {
+            block_height: height,
+            rune: etching.rune.map(|rune| SpacedRune {
+              rune,
+              spacers: etching.spacers.unwrap_or_default(),
+            }),
+            txid,
+          });
+        }
+
+        if let Some(rune_id) = runestone.mint {
+          events.push(Event::PendingRuneMinted {
+            block_height: height,
+            rune_id,
+            txid,
+          });
+        }
+      }
+
+      let rtx = index.database.begin_read()?;
+
+      let outpoint_to_balances = rtx.open_table(OUTPOINT_TO_RUNE_BALANCES)?;
+
+      for input in &tx.input {
+        let Some(balances) = outpoint_to_balances.get(&input.previous_output.store())? else {
+          continue;
+        };
+
+        let buffer = balances.value();
+
+        let mut i = 0;
+        while i < buffer.len() {
+          let ((rune_id, amount), length) = Index::decode_rune_balance(&buffer[i..])?;
+          i += length;
+
+          events.push(Event::PendingRuneTransferred {
+            amount,
+            block_height: height,
+            old_outpoint: input.previous_output,
+            rune_id,
+            txid,
+          });
+        }
+      }
+    }
+
+    Ok(events)
+  }
+}
@@ -0,0 +1,188 @@
+use super::*;
+
+pub(crate) mod token;
+
+pub(crate) const INDEXERS: &[&dyn MetaprotocolIndexer] = &[&token::Token];
+
+pub(crate) const TABLE_PREFIX: &str = "METAPROTOCOL_";
+
+pub(crate) fn indexer(name: &str) -> Option<&'static dyn MetaprotocolIndexer> {
+  INDEXERS
+    .iter()
+    .copied()
+    .find(|indexer| indexer.name() == name)
+}
+
+/// An in-process indexer for a protocol built on top of inscriptions.
+///
+/// Indexers are called by the inscription updater, in the order in which
+/// inscriptions are created and transferred, and write to their own tables in
+/// the same write transaction, so their state is committed and rolled back
+/// along with the rest of the index.
+pub(crate) trait MetaprotocolIndexer: Send + Sync {
+  /// Identifies the protocol in `--index-metaprotocol`, routes, and events.
+  fn name(&self) -> &'static str;
+
+  /// Byte-keyed tables owned by the indexer. Names must start with
+  /// `METAPROTOCOL_`.
+  fn tables(&self) -> &'static [&'static str];
+
+  fn inscription_created(&self, store: &mut MetaprotocolStore, creation: &Creation) -> Result;
+
+  fn inscription_transferred(&self, store: &mut MetaprotocolStore, transfer: &Transfer) -> Result;
+
+  /// Handles `/r/metaprotocol/<NAME>/<PATH>`, returning `None` if nothing
+  /// was found.
+  fn route(&self, reader: &MetaprotocolReader, path: &[&str]) -> Result<Option<serde_json::Value>>;
+}
+
+pub(crate) struct Creation<'a> {
+  pub(crate) height: u32,
+  pub(crate) inscription: &'a Inscription,
+  pub(crate) inscription_id: InscriptionId,
+  pub(crate) inscription_number: i32,
+  pub(crate) owner: Option<&'a Script>,
+  pub(crate) sequence_number: u32,
+}
+
+pub(crate) struct Transfer<'a> {
+  pub(crate) height: u32,
+  pub(crate) inscription_id: InscriptionId,
+  pub(crate) receiver: Option<&'a Script>,
+  pub(crate) sequence_number: u32,
+  // spent as a fee, so the receiver is the miner's coinbase output
+  pub(crate) spent_as_fee: bool,
+}
+
+pub(crate) type MetaprotocolTables<'tx> =
+  BTreeMap<&'static str, LoggedTable<'tx, &'static [u8], &'static [u8]>>;
+
+pub(crate) struct MetaprotocolStore<'a, 'tx> {
+  pub(crate) events: Vec<(String, serde_json::Value)>,
+  pub(crate) tables: &'a mut MetaprotocolTables<'tx>,
+}
+
+impl<'a, 'tx> MetaprotocolStore<'a, 'tx> {
+  pub(crate) fn new(tables: &'a mut MetaprotocolTables<'tx>) -> Self {
+    Self {
+      events: Vec::new(),
+      tables,
+    }
+  }
+
+  fn table(&mut self, table: &str) -> Result<&mut LoggedTable<'tx, &'static [u8], &'static [u8]>> {
+    self
+      .tables
+      .get_mut(table)
+      .ok_or_else(|| anyhow!("undeclared metaprotocol table `{table}`"))
+  }
+
+  pub(crate) fn get(&mut self, table: &str, key: &[u8]) -> Result<Option<Vec<u8>>> {
+    Ok(
+      self
+        .table(table)?
+        .get(key)?
+        .map(|value| value.value().to_vec()),
+    )
+  }
+
+  pub(crate) fn insert(&mut self, table: &str, key: &[u8], value: &[u8]) -> Result {
+    self.table(table)?.insert(key, value)?;
+    Ok(())
+  }
+
+  pub(crate) fn remove(&mut self, table: &str, key: &[u8]) -> Result {
+    self.table(table)?.remove(key)?;
+    Ok(())
+  }
+
+  pub(crate) fn event(&mut self, kind: &str, data: serde_json::Value) {
+    self.events.push((kind.into(), data));
+  }
+}
+
+pub(crate) struct MetaprotocolReader {
+  chain: Chain,
+  rtx: redb::ReadTransaction,
+  tables: &'static [&'static str],
+}
+
+impl MetaprotocolReader {
+  pub(crate) fn new(
+    chain: Chain,
+    rtx: redb::ReadTransaction,
+    indexer: &dyn MetaprotocolIndexer,
+  ) -> Self {
+    Self {
+      chain,
+      rtx,
+      tables: indexer.tables(),
+    }
+  }
+
+  pub(crate) fn chain(&self) -> Chain {
+    self.chain
+  }
+
+  fn table(&self, table: &str) -> Result<redb::ReadOnlyTable<&'static [u8], &'static [u8]>> {
+    ensure!(
+      self.tables.contains(&table),
+      "undeclared metaprotocol table `{table}`"
+    );
+
+    Ok(self.rtx.open_table(TableDefinition::new(table))?)
+  }
+
+  pub(crate) fn get(&self, table: &str, key: &[u8]) -> Result<Option<Vec<u8>>> {
+    Ok(
+      self
+        .table(table)?
+        .get(key)?
+        .map(|value| value.value().to_vec()),
+    )
+  }
+
+  /// Returns the entries of `table` whose keys start with `prefix`, in key
+  /// order.
+  pub(crate) fn prefix(&self, table: &str, prefix: &[u8]) -> Result<Vec<(Vec<u8>, Vec<u8>)>> {
+    let mut entries = Vec::new();
+
+    for entry in self.table(table)?.range(prefix..)? {
+      let (key, value) = entry?;
+
+      if !key.value().starts_with(prefix) {
+        break;
+      }
+
+      entries.push((key.value().to_vec(), value.value().to_vec()));
+    }
+
+    Ok(entries)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn indexer_names_and_tables_are_unique_and_prefixed() {
+    let mut names = HashSet::new();
+    let mut tables = HashSet::new();
+
+    for indexer in INDEXERS {
+      assert!(names.insert(indexer.name()));
+
+      for table in indexer.tables() {
+        assert!(table.starts_with(TABLE_PREFIX), "{table}");
+        assert!(tables.insert(table));
+      }
+    }
+  }
+
+  #[test]
+  fn indexers_are_found_by_name() {
+    assert_eq!(indexer("token").unwrap().name(), "token");
+    assert!(indexer("foo").is_none());
+  }
+}
@@ -0,0 +1,693 @@
+use super::*;
+
+const BALANCES: &str = "METAPROTOCOL_TOKEN_BALANCES";
+const MAX_TICK_LENGTH: usize = 32;
+const TICKERS: &str = "METAPROTOCOL_TOKEN_TICKERS";
+const TRANSFERABLE: &str = "METAPROTOCOL_TOKEN_TRANSFERABLE";
+
+/// A fungible token protocol in which inscriptions deploy tickers, mint
+/// tokens to their owner, and move tokens by inscribing and then sending a
+/// transfer inscription.
+pub(crate) struct Token;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "lowercase", tag = "op")]
+enum Operation {
+  Deploy {
+    tick: String,
+    max: String,
+    lim: Option<String>,
+  },
+  Mint {
+    tick: String,
+    amt: String,
+  },
+  Transfer {
+    tick: String,
+    amt: String,
+  },
+}
+
+#[derive(Debug, Deserialize)]
+struct Payload {
+  p: String,
+  #[serde(flatten)]
+  operation: Operation,
+}
+
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+struct Ticker {
+  deployer: String,
+  height: u32,
+  inscription_id: InscriptionId,
+  limit: u128,
+  max: u128,
+  minted: u128,
+  tick: String,
+}
+
+#[derive(Debug, Default, PartialEq)]
+struct Balance {
+  available: u128,
+  transferable: u128,
+}
+
+impl Balance {
+  fn load(bytes: &[u8]) -> Result<Self> {
+    ensure!(bytes.len() == 32, "invalid token balance");
+
+    Ok(Self {
+      available: u128::from_be_bytes(bytes[..16].try_into().unwrap()),
+      transferable: u128::from_be_bytes(bytes[16..].try_into().unwrap()),
+    })
+  }
+
+  fn store(&self) -> [u8; 32] {
+    let mut bytes = [0; 32];
+    bytes[..16].copy_from_slice(&self.available.to_be_bytes());
+    bytes[16..].copy_from_slice(&self.transferable.to_be_bytes());
+    bytes
+  }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+struct Transferable {
+  amount: u128,
+  owner: String,
+  tick: String,
+}
+
+fn amount(s: &str) -> Option<u128> {
+  if s.is_empty() || !s.bytes().all(|b| b.is_ascii_digit()) {
+    return None;
+  }
+
+  s.parse().ok().filter(|amount| *amount > 0)
+}
+
+fn tick(s: &str) -> Option<String> {
+  (!s.is_empty() && s.len() <= MAX_TICK_LENGTH).then(|| s.to_lowercase())
+}
+
+fn balance_key(owner: &Script, tick: &str) -> Vec<u8> {
+  let mut key = owner_prefix(owner);
+  key.extend_from_slice(tick.as_bytes());
+  key
+}
+
+fn owner_prefix(owner: &Script) -> Vec<u8> {
+  let mut prefix = u16::try_from(owner.len())
+    .unwrap_or(u16::MAX)
+    .to_be_bytes()
+    .to_vec();
+  prefix.extend_from_slice(owner.as_bytes());
+  prefix
+}
+
+impl Token {
+  fn payload(inscription: &Inscription) -> Option<Payload> {
+    if inscription
+      .metaprotocol()
+      .is_some_and(|metaprotocol| metaprotocol != "token")
+    {
+      return None;
+    }
+
+    let content_type = inscription.content_type()?.split(';').next()?.trim();
+
+    if content_type != "text/plain" && content_type != "application/json" {
+      return None;
+    }
+
+    if inscription.content_encoding.is_some() {
+      return None;
+    }
+
+    serde_json::from_slice::<Payload>(inscription.body()?)
+      .ok()
+      .filter(|payload| payload.p == "token")
+  }
+
+  fn ticker(store: &mut MetaprotocolStore, tick: &str) -> Result<Option<Ticker>> {
+    store
+      .get(TICKERS, tick.as_bytes())?
+      .map(|bytes| Ok(serde_json::from_slice(&bytes)?))
+      .transpose()
+  }
+
+  fn balance(store: &mut MetaprotocolStore, owner: &Script, tick: &str) -> Result<Balance> {
+    store
+      .get(BALANCES, &balance_key(owner, tick))?
+      .map(|bytes| Balance::load(&bytes))
+      .transpose()
+      .map(Option::unwrap_or_default)
+  }
+
+  fn set_balance(
+    store: &mut MetaprotocolStore,
+    owner: &Script,
+    tick: &str,
+    balance: &Balance,
+  ) -> Result {
+    store.insert(BALANCES, &balance_key(owner, tick), &balance.store())
+  }
+
+  fn deploy(
+    store: &mut MetaprotocolStore,
+    creation: &Creation,
+    owner: &Script,
+    tick: &str,
+    max: u128,
+    limit: u128,
+  ) -> Result {
+    if Self::ticker(store, tick)?.is_some() {
+      return Ok(());
+    }
+
+    let ticker = Ticker {
+      deployer: owner.to_hex_string(),
+      height: creation.height,
+      inscription_id: creation.inscription_id,
+      limit,
+      max,
+      minted: 0,
+      tick: tick.into(),
+    };
+
+    store.insert(TICKERS, tick.as_bytes(), &serde_json::to_vec(&ticker)?)?;
+
+    store.event(
+      "deploy",
+      serde_json::json!({
+        "deployer": ticker.deployer,
+        "limit": limit.to_string(),
+        "max": max.to_string(),
+        "tick": tick,
+      }),
+    );
+
+    Ok(())
+  }
+
+  fn mint(store: &mut MetaprotocolStore, owner: &Script, tick: &str, amount: u128) -> Result {
+    let Some(mut ticker) = Self::ticker(store, tick)? else {
+      return Ok(());
+    };
+
+    if amount > ticker.limit {
+      return Ok(());
+    }
+
+    let amount = amount.min(ticker.max - ticker.minted);
+
+    if amount == 0 {
+      return Ok(());
+    }
+
+    ticker.minted += amount;
+    store.insert(TICKERS, tick.as_bytes(), &serde_json::to_vec(&ticker)?)?;
+
+    let mut balance = Self::balance(store, owner, tick)?;
+    balance.available += amount;
+    Self::set_balance(store, owner, tick, &balance)?;
+
+    store.event(
+      "mint",
+      serde_json::json!({
+        "amount": amount.to_string(),
+        "owner": owner.to_hex_string(),
+        "tick": tick,
+      }),
+    );
+
+    Ok(())
+  }
+
+  fn inscribe_transfer(
+    store: &mut MetaprotocolStore,
+    creation: &Creation,
+    owner: &Script,
+    tick: &str,
+    amount: u128,
+  ) -> Result {
+    if Self::ticker(store, tick)?.is_none() {
+      return Ok(());
+    }
+
+    let mut balance = Self::balance(store, owner, tick)?;
+
+    if balance.available < amount {
+      return Ok(());
+    }
+
+    balance.available -= amount;
+    balance.transferable += amount;
+    Self::set_balance(store, owner, tick, &balance)?;
+
+    let transferable = Transferable {
+      amount,
+      owner: owner.to_hex_string(),
+      tick: tick.into(),
+    };
+
+    store.insert(
+      TRANSFERABLE,
+      &creation.sequence_number.to_be_bytes(),
+      &serde_json::to_vec(&transferable)?,
+    )?;
+
+    store.event(
+      "inscribe-transfer",
+      serde_json::json!({
+        "amount": amount.to_string(),
+        "owner": transferable.owner,
+        "tick": tick,
+      }),
+    );
+
+    Ok(())
+  }
+
+  fn ticker_json(ticker: &Ticker) -> serde_json::Value {
+    serde_json::json!({
+      "deployer": ticker.deployer,
+      "height": ticker.height,
+      "inscription_id": ticker.inscription_id,
+      "limit": ticker.limit.to_string(),
+      "max": ticker.max.to_string(),
+      "minted": ticker.minted.to_string(),
+      "tick": ticker.tick,
+    })
+  }
+}
+
+impl MetaprotocolIndexer for Token {
+  fn name(&self) -> &'static str {
+    "token"
+  }
+
+  fn tables(&self) -> &'static [&'static str] {
+    &[BALANCES, TICKERS, TRANSFERABLE]
+  }
+
+  fn inscription_created(&self, store: &mut MetaprotocolStore, creation: &Creation) -> Result {
+    if creation.inscription_number < 0 {
+      return Ok(());
+    }
+
+    let Some(owner) = creation.owner else {
+      return Ok(());
+    };
+
+    let Some(payload) = Self::payload(creation.inscription) else {
+      return Ok(());
+    };
+
+    match payload.operation {
+      Operation::Deploy { tick, max, lim } => {
+        let (Some(tick), Some(max)) = (self::tick(&tick), amount(&max)) else {
+          return Ok(());
+        };
+
+        let limit = match lim {
+          Some(lim) => match amount(&lim) {
+            Some(limit) => limit,
+            None => return Ok(()),
+          },
+          None => max,
+        };
+
+        Self::deploy(store, creation, owner, &tick, max, limit)
+      }
+      Operation::Mint { tick, amt } => match (self::tick(&tick), amount(&amt)) {
+        (Some(tick), Some(amount)) => Self::mint(store, owner, &tick, amount),
+        _ => Ok(()),
+      },
+      Operation::Transfer { tick, amt } => match (self::tick(&tick), amount(&amt)) {
+        (Some(tick), Some(amount)) => {
+          Self::inscribe_transfer(store, creation, owner, &tick, amount)
+        }
+        _ => Ok(()),
+      },
+    }
+  }
+
+  fn inscription_transferred(&self, store: &mut MetaprotocolStore, transfer: &Transfer) -> Result {
+    let key = transfer.sequence_number.to_be_bytes();
+
+    let Some(bytes) = store.get(TRANSFERABLE, &key)? else {
+      return Ok(());
+    };
+
+    store.remove(TRANSFERABLE, &key)?;
+
+    let transferable = serde_json::from_slice::<Transferable>(&bytes)?;
+
+    let sender = ScriptBuf::from_hex(&transferable.owner)?;
+
+    let mut balance = Self::balance(store, &sender, &transferable.tick)?;
+    balance.transferable -= transferable.amount;
+    Self::set_balance(store, &sender, &transferable.tick, &balance)?;
+
+    // inscriptions spent as fees return their tokens to the sender, instead of
+    // crediting the miner
+    let receiver = match transfer.receiver {
+      Some(receiver) if !transfer.spent_as_fee => receiver,
+      _ => &sender,
+    };
+
+    let mut balance = Self::balance(store, receiver, &transferable.tick)?;
+    balance.available += transferable.amount;
+    Self::set_balance(store, receiver, &transferable.tick, &balance)?;
+
+    store.event(
+      "transfer",
+      serde_json::json!({
+        "amount": transferable.amount.to_string(),
+        "receiver": receiver.to_hex_string(),
+        "sender": transferable.owner,
+        "tick": transferable.tick,
+      }),
+    );
+
+    Ok(())
+  }
+
+  fn route(&self, reader: &MetaprotocolReader, path: &[&str]) -> Result<Option<serde_json::Value>> {
+    match path {
+      ["tokens"] => Ok(Some(
+        reader
+          .prefix(TICKERS, &[])?
+          .into_iter()
+          .map(|(_, value)| Ok(Self::ticker_json(&serde_json::from_slice(&value)?)))
+          .collect::<Result<Vec<serde_json::Value>>>()?
+          .into(),
+      )),
+      ["token", tick] => reader
+        .get(TICKERS, tick.to_lowercase().as_bytes())?
+        .map(|value| Ok(Self::ticker_json(&serde_json::from_slice(&value)?)))
+        .transpose(),
+      ["balances", address] => {
+        let Ok(address) = address
+          .parse::<Address<NetworkUnchecked>>()
+          .map_err(|err| anyhow!(err))
+          .and_then(|address| Ok(address.require_network(reader.chain().network())?))
+        else {
+          return Ok(None);
+        };
+
+        let prefix = owner_prefix(&address.script_pubkey());
+
+        let mut balances = serde_json::Map::new();
+
+        for (key, value) in reader.prefix(BALANCES, &prefix)? {
+          let balance = Balance::load(&value)?;
+
+          balances.insert(
+            String::from_utf8(key[prefix.len()..].to_vec())?,
+            serde_json::json!({
+              "available": balance.available.to_string(),
+              "transferable": balance.transferable.to_string(),
+            }),
+          );
+        }
+
+        Ok(Some(balances.into()))
+      }
+      _ => Ok(None),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use {super::*, redb::backends::InMemoryBackend};
+
+  #[derive(Deserialize)]
+  struct Fixture {
+    addresses: BTreeMap<String, String>,
+    balances: BTreeMap<String, serde_json::Value>,
+    steps: Vec<Step>,
+    tokens: serde_json::Value,
+  }
+
+  #[derive(Deserialize)]
+  #[serde(rename_all = "lowercase")]
+  enum Action {
+    Create {
+      body: serde_json::Value,
+      content_type: Option<String>,
+      cursed: Option<bool>,
+      owner: Option<String>,
+    },
+    Transfer {
+      fee: Option<bool>,
+      inscription: u32,
+      receiver: Option<String>,
+    },
+  }
+
+  #[derive(Deserialize)]
+  struct Step {
+    #[serde(flatten)]
+    action: Action,
+    events: Vec<(String, serde_json::Value)>,
+  }
+
+  fn names(value: serde_json::Value, scripts: &BTreeMap<String, String>) -> serde_json::Value {
+    match value {
+      serde_json::Value::String(s) => scripts.get(&s).cloned().unwrap_or(s).into(),
+      serde_json::Value::Array(array) => array
+        .into_iter()
+        .map(|value| names(value, scripts))
+        .collect(),
+      serde_json::Value::Object(object) => object
+        .into_iter()
+        .map(|(key, value)| (key, names(value, scripts)))
+        .collect::<serde_json::Map<String, serde_json::Value>>()
+        .into(),
+      value => value,
+    }
+  }
+
+  #[test]
+  fn amounts() {
+    assert_eq!(amount("1"), Some(1));
+    assert_eq!(
+      amount("340282366920938463463374607431768211455"),
+      Some(u128::MAX)
+    );
+    assert_eq!(amount("340282366920938463463374607431768211456"), None);
+    assert_eq!(amount("0"), None);
+    assert_eq!(amount(""), None);
+    assert_eq!(amount("+1"), None);
+    assert_eq!(amount("1.5"), None);
+  }
+
+  #[test]
+  fn ticks() {
+    assert_eq!(tick("ORDI"), Some("ordi".into()));
+    assert_eq!(tick(""), None);
+    assert_eq!(tick(&"a".repeat(MAX_TICK_LENGTH + 1)), None);
+  }
+
+  #[test]
+  fn balances_round_trip() {
+    let balance = Balance {
+      available: 1,
+      transferable: u128::MAX,
+    };
+
+    assert_eq!(Balance::load(&balance.store()).unwrap(), balance);
+  }
+
+  #[test]
+  fn payloads() {
+    assert!(Token::payload(&inscription(
+      "text/plain;charset=utf-8",
+      r#"{"p":"token","op":"mint","tick":"a","amt":"1"}"#
+    ))
+    .is_some());
+
+    assert!(Token::payload(&inscription(
+      "text/plain",
+      r#"{"p":"brc-20","op":"mint","tick":"a","amt":"1"}"#
+    ))
+    .is_none());
+
+    assert!(Token::payload(&inscription(
+      "image/png",
+      r#"{"p":"token","op":"mint","tick":"a","amt":"1"}"#
+    ))
+    .is_none());
+
+    assert!(Token::payload(&Inscription {
+      metaprotocol: Some("foo".into()),
+      ..inscription(
+        "text/plain",
+        r#"{"p":"token","op":"mint","tick":"a","amt":"1"}"#
+      )
+    })
+    .is_none());
+  }
+
+  #[test]
+  fn fixtures() {
+    let fixture = serde_json::from_str::<Fixture>(include_str!("fixtures/token.json")).unwrap();
+
+    let chain = Chain::Mainnet;
+
+    let scripts = fixture
+      .addresses
+      .iter()
+      .map(|(name, address)| {
+        (
+          name.clone(),
+          address
+            .parse::<Address<NetworkUnchecked>>()
+            .unwrap()
+            .require_network(chain.network())
+            .unwrap()
+            .script_pubkey(),
+        )
+      })
+      .collect::<BTreeMap<String, ScriptBuf>>();
+
+    let script_names = scripts
+      .iter()
+      .map(|(name, script)| (script.to_hex_string(), name.clone()))
+      .collect::<BTreeMap<String, String>>();
+
+    let database = Database::builder()
+      .create_with_backend(InMemoryBackend::new())
+      .unwrap();
+
+    let wtx = database.begin_write().unwrap();
+
+    {
+      let mut tables = Token
+        .tables()
+        .iter()
+        .map(|table| {
+          (
+            *table,
+            UndoLog::new(false)
+              .table(&wtx, TableDefinition::new(table))
+              .unwrap(),
+          )
+        })
+        .collect::<MetaprotocolTables>();
+
+      let mut owners = Vec::new();
+
+      for (i, step) in fixture.steps.into_iter().enumerate() {
+        let mut store = MetaprotocolStore::new(&mut tables);
+
+        match step.action {
+          Action::Create {
+            body,
+            content_type,
+            cursed,
+            owner,
+          } => {
+            let sequence_number = u32::try_from(owners.len()).unwrap();
+
+            let owner = owner.map(|owner| scripts[&owner].clone());
+
+            let inscription = inscription(
+              content_type.as_deref().unwrap_or("text/plain"),
+              body.to_string(),
+            );
+
+            Token
+              .inscription_created(
+                &mut store,
+                &Creation {
+                  height: u32::try_from(i).unwrap(),
+                  inscription: &inscription,
+                  inscription_id: inscription_id(sequence_number),
+                  inscription_number: if cursed.unwrap_or_default() {
+                    -1
+                  } else {
+                    i32::try_from(sequence_number).unwrap()
+                  },
+                  owner: owner.as_deref(),
+                  sequence_number,
+                },
+              )
+              .unwrap();
+
+            owners.push(owner);
+          }
+          Action::Transfer {
+            fee,
+            inscription,
+            receiver,
+          } => {
+            let receiver = receiver.map(|receiver| scripts[&receiver].clone());
+
+            Token
+              .inscription_transferred(
+                &mut store,
+                &Transfer {
+                  height: u32::try_from(i).unwrap(),
+                  inscription_id: inscription_id(inscription),
+                  receiver: receiver.as_deref(),
+                  sequence_number: inscription,
+                  spent_as_fee: fee.unwrap_or_default(),
+                },
+              )
+              .unwrap();
+
+            owners[usize::try_from(inscription).unwrap()] = receiver;
+          }
+        }
+
+        let events = store
+          .events
+          .into_iter()
+          .map(|(kind, data)| (kind, names(data, &script_names)))
+          .collect::<Vec<(String, serde_json::Value)>>();
+
+        assert_eq!(events, step.events, "step {i}");
+      }
+    }
+
+    wtx.commit().unwrap();
+
+    let reader = MetaprotocolReader::new(chain, database.begin_read().unwrap(), &Token);
+
+    assert_eq!(
+      names(
+        Token.route(&reader, &["tokens"]).unwrap().unwrap(),
+        &script_names
+      ),
+      fixture.tokens,
+    );
+
+    for (name, expected) in fixture.balances {
+      assert_eq!(
+        Token
+          .route(&reader, &["balances", &fixture.addresses[&name]])
+          .unwrap()
+          .unwrap(),
+        expected,
+        "{name}",
+      );
+    }
+
+    assert_eq!(
+      Token.route(&reader, &["token", "ORDI"]).unwrap(),
+      Token
+        .route(&reader, &["tokens"])
+        .unwrap()
+        .unwrap()
+        .get(0)
+        .cloned(),
+    );
+
+    assert_eq!(Token.route(&reader, &["token", "foo"]).unwrap(), None);
+    assert_eq!(Token.route(&reader, &["balances", "foo"]).unwrap(), None);
+    assert_eq!(Token.route(&reader, &["foo"]).unwrap(), None);
+  }
+}
@@ -1,4 +1,8 @@
-use {super::*, updater::BlockData};
+use {
+  super::*,
+  clap::ValueEnum,
+  updater::{BlockData, EventOutbox},
+};
 
 #[derive(Debug, PartialEq)]
 pub(crate) enum Error {
@@ -19,9 +23,37 @@ impl Display for Error {
 
 impl std::error::Error for Error {}
 
-const MAX_SAVEPOINTS: u32 = 2;
-const SAVEPOINT_INTERVAL: u32 = 10;
-const CHAIN_TIP_DISTANCE: u32 = 21;
+#[derive(Default, ValueEnum, Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Rollback {
+  #[default]
+  Savepoints = 0,
+  UndoLog = 1,
+}
+
+impl Display for Rollback {
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    write!(f, "{}", self.to_possible_value().unwrap().get_name())
+  }
+}
+
+impl Rollback {
+  pub(crate) fn from_statistic(statistic: u64) -> Result<Self> {
+    <Self as ValueEnum>::value_variants()
+      .iter()
+      .find(|variant| **variant as u64 == statistic)
+      .copied()
+      .ok_or_else(|| anyhow!("index has unknown rollback mode {statistic}"))
+  }
+}
+
+impl FromStr for Rollback {
+  type Err = anyhow::Error;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    <Self as ValueEnum>::from_str(s, false).map_err(|_| anyhow!("invalid rollback `{s}`"))
+  }
+}
 
 pub(crate) struct Reorg {}
 
@@ -32,15 +64,20 @@ impl Reorg {
     match index.block_hash(height.checked_sub(1))? {
       Some(index_prev_blockhash) if index_prev_blockhash == bitcoind_prev_blockhash => Ok(()),
       Some(index_prev_blockhash) if index_prev_blockhash != bitcoind_prev_blockhash => {
-        let max_recoverable_reorg_depth =
-          (MAX_SAVEPOINTS - 1) * SAVEPOINT_INTERVAL + height % SAVEPOINT_INTERVAL;
+        let max_recoverable_reorg_depth = match index.reorg_rollback {
+          Rollback::Savepoints => {
+            let savepoint_interval = index.settings.savepoint_interval();
+            (index.settings.max_savepoints() - 1) * savepoint_interval + height % savepoint_interval
+          }
+          // a reorg of depth `depth` rolls back `depth - 1` blocks
+          Rollback::UndoLog => UndoLog::blocks(&index.begin_read()?, height)? + 2,
+        };
 
         for depth in 1..max_recoverable_reorg_depth {
           let index_block_hash = index.block_hash(height.checked_sub(depth))?;
           let bitcoind_block_hash = index
-            .client
-            .get_block_hash(u64::from(height.saturating_sub(depth)))
-            .into_option()?;
+            .block_source
+            .block_hash(height.saturating_sub(depth))?;
 
           if index_block_hash == bitcoind_block_hash {
             return Err(anyhow!(reorg::Error::Recoverable { height, depth }));
@@ -56,16 +93,89 @@ impl Reorg {
   pub(crate) fn handle_reorg(index: &Index, height: u32, depth: u32) -> Result {
     log::info!("rolling back database after reorg of depth {depth} at height {height}");
 
-    if let redb::Durability::None = index.durability {
-      panic!("set index durability to `Durability::Immediate` to test reorg handling");
-    }
+    let rtx = index.begin_read()?;
 
     let mut wtx = index.begin_write()?;
 
-    let oldest_savepoint =
-      wtx.get_persistent_savepoint(wtx.list_persistent_savepoints()?.min().unwrap())?;
+    let (next_event_id, next_undelivered_event_id) = {
+      let next_event_id = wtx
+        .open_table(STATISTIC_TO_COUNT)?
+        .get(&Statistic::Events.key())?
+        .map(|count| count.value())
+        .unwrap_or(0);
+
+      let next_undelivered_event_id = wtx
+        .open_table(EVENT_ID_TO_EVENT)?
+        .first()?
+        .map(|(id, _event)| id.value())
+        .unwrap_or(next_event_id);
+
+      (next_event_id, next_undelivered_event_id)
+    };
+
+    match index.reorg_rollback {
+      Rollback::Savepoints => {
+        if let redb::Durability::None = index.durability {
+          panic!("set index durability to `Durability::Immediate` to test reorg handling");
+        }
+
+        let oldest_savepoint =
+          wtx.get_persistent_savepoint(wtx.list_persistent_savepoints()?.min().unwrap())?;
+
+        wtx.restore_savepoint(&oldest_savepoint)?;
+      }
+      Rollback::UndoLog => UndoLog::rollback(&wtx, height - depth + 1)?,
+    }
+
+    // events delivered after the savepoint was taken must not be delivered
+    // again, and event IDs must not be reused
+    wtx
+      .open_table(EVENT_ID_TO_EVENT)?
+      .retain_in(..next_undelivered_event_id, |_, _| false)?;
+
+    let next_event_id = if index.event_outbox {
+      let from_height = wtx
+        .open_table(HEIGHT_TO_BLOCK_HEADER)?
+        .last()?
+        .map(|(height, _header)| height.value() + 1)
+        .unwrap_or(0);
+
+      let retracted = rtx
+        .0
+        .open_table(HEIGHT_TO_BLOCK_HEADER)?
+        .range(from_height..)?
+        .map(|result| {
+          result
+            .map(|(height, header)| (height.value(), Header::load(*header.value()).block_hash()))
+        })
+        .collect::<Result<Vec<(u32, BlockHash)>, StorageError>>()?;
+
+      let mut event_outbox = EventOutbox::new(wtx.open_table(EVENT_ID_TO_EVENT)?, next_event_id);
+
+      if let Some((to_height, _block_hash)) = retracted.last() {
+        event_outbox.push(Event::BlocksRolledBack {
+          block_height: height,
+          depth,
+          from_height,
+          to_height: *to_height,
+        })?;
+      }
 
-    wtx.restore_savepoint(&oldest_savepoint)?;
+      for (block_height, block_hash) in retracted.into_iter().rev() {
+        event_outbox.push(Event::BlockRetracted {
+          block_hash,
+          block_height,
+        })?;
+      }
+
+      event_outbox.next_event_id()
+    } else {
+      next_event_id
+    };
+
+    wtx
+      .open_table(STATISTIC_TO_COUNT)?
+      .insert(&Statistic::Events.key(), &next_event_id)?;
 
     Index::increment_statistic(&wtx, Statistic::Commits, 1)?;
     wtx.commit()?;
@@ -75,6 +185,8 @@ impl Reorg {
       index.begin_read()?.block_count()?
     );
 
+    index.dispatch_events()?;
+
     Ok(())
   }
 
@@ -83,7 +195,13 @@ impl Reorg {
       return Ok(());
     }
 
-    if (height < SAVEPOINT_INTERVAL || height % SAVEPOINT_INTERVAL == 0)
+    if index.reorg_rollback == Rollback::UndoLog {
+      return Ok(());
+    }
+
+    let savepoint_interval = index.settings.savepoint_interval();
+
+    if (height < savepoint_interval || height % savepoint_interval == 0)
       && u32::try_from(
         index
           .settings
@@ -93,13 +211,13 @@ impl Reorg {
       )
       .unwrap()
       .saturating_sub(height)
-        <= CHAIN_TIP_DISTANCE
+        <= index.settings.chain_tip_distance()
     {
       let wtx = index.begin_write()?;
 
       let savepoints = wtx.list_persistent_savepoints()?.collect::<Vec<u64>>();
 
-      if savepoints.len() >= usize::try_from(MAX_SAVEPOINTS).unwrap() {
+      if savepoints.len() >= usize::try_from(index.settings.max_savepoints()).unwrap() {
         wtx.delete_persistent_savepoint(savepoints.into_iter().min().unwrap())?;
       }
 
@@ -0,0 +1,705 @@
+use {
+  super::*,
+  crate::index::event::{BlockEventCounts, InscriptionDetails},
+};
+
+// Events are ordered within a block by the position at which the updater
+// processed them: inscription events by transaction, with the coinbase last,
+// and then by output and offset, followed by rune events.
+type Position = (u32, u32, u64, u32);
+
+/// Rebuilds the events for blocks `from_height..=to_height` from an existing
+/// index, without modifying it, passing the events for each block to `send`
+/// in turn. Inscription transfers, and the balances of spent rune outputs and
+/// successful mints, are read from the tables written with
+/// `--index-transfers`. Rune events are re-derived from the runestones in
+/// each block. Metaprotocol events are not recorded, so they are not
+/// replayed.
+pub(crate) fn replay(
+  index: &Index,
+  from_height: u32,
+  to_height: u32,
+  mut send: impl FnMut(Vec<Event>) -> Result,
+) -> Result {
+  let rtx = index.begin_read()?;
+
+  let height = rtx.block_height()?;
+
+  if height.map(|height| height.n() < to_height).unwrap_or(true) {
+    bail!(
+      "cannot replay events up to height {to_height}, index is at height {}",
+      height
+        .map(|height| height.to_string())
+        .unwrap_or("none".into()),
+    );
+  }
+
+  let rtx = rtx.0;
+
+  if index.has_rune_index() && !index.has_transfer_index() {
+    bail!("replaying rune events requires an index built with `--index-transfers`");
+  }
+
+  let inscriptions = last_sequence_number(&rtx, from_height.checked_sub(1))?
+    ..last_sequence_number(&rtx, Some(to_height))?;
+
+  if !inscriptions.is_empty() && !index.has_transfer_index() {
+    bail!("replaying inscription events requires an index built with `--index-transfers`");
+  }
+
+  let height_to_block_header = rtx.open_table(HEIGHT_TO_BLOCK_HEADER)?;
+
+  for height in from_height..=to_height {
+    let header = Header::load(
+      *height_to_block_header
+        .get(height)?
+        .ok_or_else(|| anyhow!("missing header for block {height}"))?
+        .value(),
+    );
+
+    let block_hash = header.block_hash();
+
+    let mut events = vec![Event::BlockStarted {
+      block_hash,
+      block_height: height,
+      prev_block_hash: header.prev_blockhash,
+      timestamp: header.time,
+    }];
+
+    let inscription_events = inscription_events(index, &rtx, height)?;
+
+    let runes = index.has_rune_index() && height >= index.settings.first_rune_height();
+
+    let mut block_events = Vec::new();
+
+    if !inscription_events.is_empty() || runes {
+      let block = index
+        .get_block_by_hash(block_hash)?
+        .ok_or_else(|| anyhow!("block {block_hash} at height {height} not found"))?;
+
+      if !inscription_events.is_empty() {
+        block_events.extend(inscription_events_in_block(
+          index,
+          &block,
+          inscription_events,
+        )?);
+      }
+
+      if runes {
+        block_events.extend(rune_events(&rtx, height, &block)?);
+      }
+    }
+
+    let mut counts = BlockEventCounts::default();
+
+    for event in &block_events {
+      counts.record(event);
+    }
+
+    events.extend(block_events);
+
+    events.push(Event::BlockCommitted {
+      block_hash,
+      block_height: height,
+      counts,
+    });
+
+    send(events)?;
+  }
+
+  Ok(())
+}
+
+/// Returns the sequence number after the last inscription created at or
+/// below `height`.
+fn last_sequence_number(rtx: &redb::ReadTransaction, height: Option<u32>) -> Result<u32> {
+  let Some(height) = height else {
+    return Ok(0);
+  };
+
+  Ok(
+    rtx
+      .open_table(HEIGHT_TO_LAST_SEQUENCE_NUMBER)?
+      .range(..=height)?
+      .next_back()
+      .transpose()?
+      .map(|(_height, sequence_number)| sequence_number.value())
+      .unwrap_or_default(),
+  )
+}
+
+/// Returns the inscriptions created and transferred in the block at
+/// `height`, along with the transaction and location used to order them.
+fn inscription_events(
+  index: &Index,
+  rtx: &redb::ReadTransaction,
+  height: u32,
+) -> Result<Vec<(Txid, SatPoint, u32, Event)>> {
+  let sequence_number_to_entry = rtx.open_table(SEQUENCE_NUMBER_TO_INSCRIPTION_ENTRY)?;
+  let sequence_number_to_satpoint = rtx.open_table(SEQUENCE_NUMBER_TO_SATPOINT)?;
+  let sequence_number_to_transfer = rtx.open_table(SEQUENCE_NUMBER_TO_TRANSFER)?;
+
+  let load_entry = |sequence_number: u32| -> Result<InscriptionEntry> {
+    Ok(InscriptionEntry::load(
+      sequence_number_to_entry
+        .get(sequence_number)?
+        .ok_or_else(|| anyhow!("missing entry for sequence number {sequence_number}"))?
+        .value(),
+    ))
+  };
+
+  let mut events = Vec::new();
+
+  for sequence_number in
+    last_sequence_number(rtx, height.checked_sub(1))?..last_sequence_number(rtx, Some(height))?
+  {
+    let entry = load_entry(sequence_number)?;
+
+    let first_transfer = sequence_number_to_transfer
+      .range((sequence_number, 0)..=(sequence_number, u32::MAX))?
+      .next()
+      .transpose()?
+      .map(|(_key, transfer)| TransferEntry::load(transfer.value()));
+
+    let location = match &first_transfer {
+      Some(transfer) => transfer.old_satpoint,
+      None => SatPoint::load(
+        *sequence_number_to_satpoint
+          .get(sequence_number)?
+          .ok_or_else(|| anyhow!("missing satpoint for sequence number {sequence_number}"))?
+          .value(),
+      ),
+    };
+
+    // inscriptions can only be burned on creation if they are never
+    // transferred, so a burned inscription with transfers was burned later
+    let charms = if first_transfer.is_none() {
+      entry.charms
+    } else {
+      Charm::Burned.unset(entry.charms)
+    };
+
+    let parent_inscription_ids = entry
+      .parents
+      .iter()
+      .map(|parent| Ok(load_entry(*parent)?.id))
+      .collect::<Result<Vec<InscriptionId>>>()?;
+
+    events.push((
+      entry.id.txid,
+      location,
+      sequence_number,
+      Event::InscriptionCreated {
+        block_height: entry.height,
+        charms,
+        details: index.settings.rich_events().then(|| {
+          Box::new(InscriptionDetails {
+            fee: entry.fee,
+            inscription_number: entry.inscription_number,
+            sat: entry.sat,
+            ..default()
+          })
+        }),
+        inscription_id: entry.id,
+        location: (location.outpoint != unbound_outpoint()).then_some(location),
+        parent_inscription_ids,
+        sequence_number,
+      },
+    ));
+  }
+
+  for result in rtx.open_multimap_table(HEIGHT_TO_TRANSFER)?.get(height)? {
+    let key = result?.value();
+
+    let (sequence_number, _index) = key;
+
+    let transfer = TransferEntry::load(
+      sequence_number_to_transfer
+        .get(key)?
+        .ok_or_else(|| anyhow!("missing transfer for sequence number {sequence_number}"))?
+        .value(),
+    );
+
+    events.push((
+      transfer.txid,
+      transfer.new_satpoint,
+      sequence_number,
+      Event::InscriptionTransferred {
+        block_height: transfer.height,
+        inscription_id: load_entry(sequence_number)?.id,
+        new_location: transfer.new_satpoint,
+        old_location: transfer.old_satpoint,
+        sequence_number,
+      },
+    ));
+  }
+
+  Ok(events)
+}
+
+/// Re-derives the rune events for the block at `height` from its runestones,
+/// in the order in which the updater records them.
+fn rune_events(rtx: &redb::ReadTransaction, height: u32, block: &Block) -> Result<Vec<Event>> {
+  let outpoint_to_spent_balances = rtx.open_table(OUTPOINT_TO_SPENT_RUNE_BALANCES)?;
+  let transaction_id_to_mint = rtx.open_table(TRANSACTION_ID_TO_RUNE_MINT)?;
+  let transaction_id_to_rune = rtx.open_table(TRANSACTION_ID_TO_RUNE)?;
+
+  let mut events = Vec::new();
+
+  for (tx_index, tx) in block.txdata.iter().enumerate() {
+    let txid = tx.txid();
+
+    let artifact = Runestone::decipher(tx);
+
+    let mut unallocated = HashMap::<RuneId, Lot>::new();
+
+    for input in &tx.input {
+      if let Some(balances) = outpoint_to_spent_balances.get(&input.previous_output.store())? {
+        let buffer = balances.value();
+        let mut i = 0;
+        while i < buffer.len() {
+          let ((id, balance), len) = Index::decode_rune_balance(&buffer[i..])?;
+          i += len;
+          *unallocated.entry(id).or_default() += balance;
+        }
+      }
+    }
+
+    let mut etched = None;
+
+    if let Some(artifact) = &artifact {
+      if let Some(id) = artifact.mint() {
+        if let Some(amount) = transaction_id_to_mint.get(&txid.store())? {
+          let amount = amount.value();
+
+          *unallocated.entry(id).or_default() += amount;
+
+          events.push(Event::RuneMinted {
+            block_height: height,
+            txid,
+            rune_id: id,
+            amount,
+          });
+        }
+      }
+
+      if transaction_id_to_rune.get(&txid.store())?.is_some() {
+        let id = RuneId {
+          block: height.into(),
+          tx: u32::try_from(tx_index)?,
+        };
+
+        if let Artifact::Runestone(runestone) = artifact {
+          *unallocated.entry(id).or_default() +=
+            runestone.etching.unwrap().premine.unwrap_or_default();
+        }
+
+        etched = Some(id);
+      }
+    }
+
+    let (allocated, burned) = updater::allocate(tx, artifact.as_ref(), etched, unallocated);
+
+    if let Some(id) = etched {
+      events.push(Event::RuneEtched {
+        block_height: height,
+        txid,
+        rune_id: id,
+      });
+    }
+
+    for (vout, balances) in allocated.into_iter().enumerate() {
+      let mut balances = balances.into_iter().collect::<Vec<(RuneId, Lot)>>();
+
+      balances.sort();
+
+      for (id, balance) in balances {
+        events.push(Event::RuneTransferred {
+          outpoint: OutPoint {
+            txid,
+            vout: u32::try_from(vout)?,
+          },
+          block_height: height,
+          txid,
+          rune_id: id,
+          amount: balance.n(),
+        });
+      }
+    }
+
+    for (id, amount) in burned {
+      events.push(Event::RuneBurned {
+        block_height: height,
+        txid,
+        rune_id: id,
+        amount: amount.n(),
+      });
+    }
+  }
+
+  Ok(events)
+}
+
+fn inscription_events_in_block(
+  index: &Index,
+  block: &Block,
+  events: Vec<(Txid, SatPoint, u32, Event)>,
+) -> Result<Vec<Event>> {
+  let coinbase = u32::try_from(block.txdata.len())?;
+
+  let transactions = block
+    .txdata
+    .iter()
+    .enumerate()
+    .map(|(i, tx)| {
+      Ok((
+        tx.txid(),
+        (if i == 0 { coinbase } else { u32::try_from(i)? }, tx),
+      ))
+    })
+    .collect::<Result<HashMap<Txid, (u32, &Transaction)>>>()?;
+
+  let mut positioned = events
+    .into_iter()
+    .map(|(txid, location, sequence_number, event)| {
+      let position = |txid: Txid| -> Result<u32> {
+        Ok(
+          transactions
+            .get(&txid)
+            .ok_or_else(|| anyhow!("transaction {txid} not found in block"))?
+            .0,
+        )
+      };
+
+      let position: Position = if location.outpoint == OutPoint::null() {
+        (coinbase, u32::MAX, location.offset, sequence_number)
+      } else if location.outpoint == unbound_outpoint() {
+        (position(txid)?, u32::MAX, location.offset, sequence_number)
+      } else {
+        (
+          position(location.outpoint.txid)?,
+          location.outpoint.vout,
+          location.offset,
+          sequence_number,
+        )
+      };
+
+      Ok((position, event))
+    })
+    .collect::<Result<Vec<(Position, Event)>>>()?;
+
+  positioned.sort_by_key(|(position, _event)| *position);
+
+  positioned
+    .into_iter()
+    .map(|(_position, mut event)| {
+      if let Event::InscriptionCreated {
+        details: Some(details),
+        inscription_id,
+        location,
+        ..
+      } = &mut event
+      {
+        let (_position, tx) = transactions
+          .get(&inscription_id.txid)
+          .ok_or_else(|| anyhow!("transaction {} not found in block", inscription_id.txid))?;
+
+        let envelope = ParsedEnvelope::from_transaction(tx)
+          .into_iter()
+          .nth(usize::try_from(inscription_id.index)?)
+          .ok_or_else(|| anyhow!("inscription {inscription_id} not found in transaction"))?;
+
+        let payload = &envelope.payload;
+
+        **details = InscriptionDetails {
+          address: location
+            .and_then(|location| {
+              transactions
+                .get(&location.outpoint.txid)
+                .and_then(|(_position, tx)| {
+                  tx.output.get(usize::try_from(location.outpoint.vout).ok()?)
+                })
+            })
+            .and_then(|txout| {
+              index
+                .settings
+                .chain()
+                .address_from_script(&txout.script_pubkey)
+                .ok()
+            })
+            .map(|address| address.to_string()),
+          body: payload
+            .body()
+            .filter(|body| {
+              index
+                .settings
+                .event_body_limit()
+                .is_some_and(|limit| body.len() <= limit)
+            })
+            .map(hex::encode),
+          content_length: payload.content_length(),
+          content_type: payload.content_type().map(str::to_string),
+          delegate: payload.delegate(),
+          metadata: payload.metadata.as_ref().map(hex::encode),
+          metaprotocol: payload.metaprotocol().map(str::to_string),
+          ..**details
+        };
+      }
+
+      Ok(event)
+    })
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use {super::*, crate::index::testing::Context};
+
+  const RUNE: u128 = 99246114928149462;
+
+  fn replay_blocks(index: &Index, from_height: u32, to_height: u32) -> Result<Vec<Vec<Event>>> {
+    let mut blocks = Vec::new();
+
+    replay(index, from_height, to_height, |events| {
+      blocks.push(events);
+      Ok(())
+    })?;
+
+    Ok(blocks)
+  }
+
+  #[track_caller]
+  fn assert_replay_matches_outbox(context: &Context, from_height: u32) {
+    let expected = context
+      .index
+      .pending_events(usize::MAX)
+      .unwrap()
+      .into_iter()
+      .map(|(_event_id, event)| event)
+      .filter(|event| {
+        event.block_height() >= from_height && !matches!(event, Event::MetaprotocolEvent { .. })
+      })
+      .collect::<Vec<Event>>();
+
+    let to_height = context.index.block_count().unwrap() - 1;
+
+    let blocks = replay_blocks(&context.index, from_height, to_height).unwrap();
+
+    assert_eq!(blocks.len(), (from_height..=to_height).count());
+
+    for (height, events) in (from_height..).zip(&blocks) {
+      assert!(matches!(
+        events.first(),
+        Some(Event::BlockStarted { block_height, .. }) if *block_height == height
+      ));
+      assert!(matches!(
+        events.last(),
+        Some(Event::BlockCommitted { block_height, .. }) if *block_height == height
+      ));
+    }
+
+    pretty_assert_eq!(blocks.concat(), expected);
+  }
+
+  #[test]
+  fn replayed_events_match_recorded_events() {
+    let context = Context::builder()
+      .arg("--index-transfers")
+      .arg("--index-runes")
+      .arg("--rich-events")
+      .arg("--event-body-limit=100")
+      .event_outbox()
+      .build();
+
+    context.mine_blocks(1);
+
+    let parent = context.core.broadcast_tx(TransactionTemplate {
+      inputs: &[(1, 0, 0, inscription("text/plain", "parent").to_witness())],
+      ..default()
+    });
+
+    context.mine_blocks(1);
+
+    let parent = InscriptionId {
+      txid: parent,
+      index: 0,
+    };
+
+    context.core.broadcast_tx(TransactionTemplate {
+      inputs: &[
+        (
+          2,
+          0,
+          0,
+          Inscription {
+            content_type: Some("text/plain".into()),
+            body: Some("child".into()),
+            parents: vec![parent.value()],
+            ..default()
+          }
+          .to_witness(),
+        ),
+        (2, 1, 0, Default::default()),
+      ],
+      outputs: 2,
+      ..default()
+    });
+
+    context.mine_blocks(1);
+
+    context.core.broadcast_tx(TransactionTemplate {
+      inputs: &[(3, 1, 0, Default::default())],
+      fee: 50 * COIN_VALUE,
+      ..default()
+    });
+
+    context.core.broadcast_tx(TransactionTemplate {
+      inputs: &[(3, 0, 0, inscription("text/plain", "burned").to_witness())],
+      op_return: Some(ScriptBuf::new()),
+      op_return_index: Some(0),
+      op_return_value: Some(50 * COIN_VALUE),
+      outputs: 0,
+      ..default()
+    });
+
+    context.mine_blocks(1);
+
+    let (_txid, id) = context.etch(
+      Runestone {
+        etching: Some(Etching {
+          rune: Some(Rune(RUNE)),
+          premine: Some(1000),
+          terms: Some(Terms {
+            amount: Some(100),
+            cap: Some(1),
+            ..default()
+          }),
+          ..default()
+        }),
+        ..default()
+      },
+      1,
+    );
+
+    let etched = usize::try_from(id.block).unwrap();
+
+    // the second mint exceeds the cap, so only the first succeeds
+    let mints = [etched - 1, etched - 2].map(|block| {
+      context.core.broadcast_tx(TransactionTemplate {
+        inputs: &[(block, 0, 0, Witness::new())],
+        op_return: Some(
+          Runestone {
+            mint: Some(id),
+            ..default()
+          }
+          .encipher(),
+        ),
+        ..default()
+      })
+    });
+
+    context.mine_blocks(1);
+
+    context.core.broadcast_tx(TransactionTemplate {
+      inputs: &[
+        (etched, 1, 0, Witness::new()),
+        (etched + 1, 1, 0, Witness::new()),
+      ],
+      op_return: Some(
+        Runestone {
+          edicts: vec![Edict {
+            id,
+            amount: 111,
+            output: 0,
+          }],
+          ..default()
+        }
+        .encipher(),
+      ),
+      op_return_index: Some(0),
+      ..default()
+    });
+
+    context.mine_blocks(1);
+
+    let events = context
+      .index
+      .pending_events(usize::MAX)
+      .unwrap()
+      .into_iter()
+      .map(|(_event_id, event)| event)
+      .collect::<Vec<Event>>();
+
+    assert!(events
+      .iter()
+      .any(|event| matches!(event, Event::RuneTransferred { amount: 989, .. })));
+
+    assert!(events
+      .iter()
+      .any(|event| matches!(event, Event::RuneBurned { amount: 111, .. })));
+
+    assert_eq!(
+      events
+        .iter()
+        .filter(|event| matches!(event, Event::RuneMinted { .. }))
+        .collect::<Vec<&Event>>(),
+      [&Event::RuneMinted {
+        amount: 100,
+        block_height: u32::try_from(id.block).unwrap() + 1,
+        rune_id: id,
+        txid: mints[0],
+      }],
+    );
+
+    assert_replay_matches_outbox(&context, 0);
+    assert_replay_matches_outbox(&context, 3);
+  }
+
+  #[test]
+  fn inscription_events_require_transfer_index() {
+    let context = Context::builder().build();
+
+    context.mine_blocks(1);
+
+    replay_blocks(&context.index, 0, 1).unwrap();
+
+    context.core.broadcast_tx(TransactionTemplate {
+      inputs: &[(1, 0, 0, inscription("text/plain", "foo").to_witness())],
+      ..default()
+    });
+
+    context.mine_blocks(1);
+
+    assert_eq!(
+      replay_blocks(&context.index, 0, 0).unwrap().concat().len(),
+      2
+    );
+
+    assert_eq!(
+      replay_blocks(&context.index, 0, 2).unwrap_err().to_string(),
+      "replaying inscription events requires an index built with `--index-transfers`",
+    );
+  }
+
+  #[test]
+  fn range_must_be_indexed() {
+    let context = Context::builder().build();
+
+    context.mine_blocks(1);
+
+    assert_eq!(
+      replay_blocks(&context.index, 0, 5).unwrap_err().to_string(),
+      "cannot replay events up to height 5, index is at height 1",
+    );
+  }
+
+  #[test]
+  fn rune_events_require_transfer_index() {
+    let context = Context::builder().arg("--index-runes").build();
+
+    assert_eq!(
+      replay_blocks(&context.index, 0, 0).unwrap_err().to_string(),
+      "replaying rune events requires an index built with `--index-transfers`",
+    );
+  }
+}
@@ -0,0 +1,497 @@
+use {
+  super::*,
+  bitcoin::hashes::sha256,
+  brotli::{CompressorWriter, Decompressor},
+  std::{
+    fs::File,
+    io::{BufReader, BufWriter, Seek, SeekFrom},
+  },
+  tempfile::NamedTempFile,
+};
+
+const BUFFER_SIZE: usize = 1 << 20;
+const MAGIC: &[u8; 8] = b"ordsnap\0";
+const QUALITY: u32 = 5;
+const WINDOW: u32 = 22;
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Manifest {
+  pub block_hash: BlockHash,
+  pub chain: Chain,
+  pub checksum: sha256::Hash,
+  pub height: u32,
+  pub index_address_history: bool,
+  pub index_addresses: bool,
+  pub index_content: bool,
+  pub index_full_text: bool,
+  pub index_runes: bool,
+  pub index_sats: bool,
+  pub index_search: bool,
+  pub index_spent_sats: bool,
+  pub index_transactions: bool,
+  pub index_transfers: bool,
+  pub metaprotocols: Vec<String>,
+  pub reorg_rollback: Rollback,
+  pub schema_version: u64,
+  pub size: u64,
+}
+
+impl Manifest {
+  fn describe(database: &Database, chain: Chain) -> Result<Self> {
+    let rtx = rtx::Rtx(database.begin_read()?);
+
+    let Some(Height(height)) = rtx.block_height()? else {
+      bail!("index contains no blocks");
+    };
+
+    let block_hash = rtx.block_hash(Some(height))?.unwrap();
+
+    let statistics = rtx.0.open_table(STATISTIC_TO_COUNT)?;
+
+    Ok(Self {
+      block_hash,
+      chain,
+      checksum: sha256::Hash::all_zeros(),
+      height,
+      index_address_history: Index::is_statistic_set(&statistics, Statistic::IndexAddressHistory)?,
+      index_addresses: Index::is_statistic_set(&statistics, Statistic::IndexAddresses)?,
+      index_content: Index::is_statistic_set(&statistics, Statistic::IndexContent)?,
+      index_full_text: Index::is_statistic_set(&statistics, Statistic::IndexFullText)?,
+      index_runes: Index::is_statistic_set(&statistics, Statistic::IndexRunes)?,
+      index_sats: Index::is_statistic_set(&statistics, Statistic::IndexSats)?,
+      index_search: Index::is_statistic_set(&statistics, Statistic::IndexSearch)?,
+      index_spent_sats: Index::is_statistic_set(&statistics, Statistic::IndexSpentSats)?,
+      index_transactions: Index::is_statistic_set(&statistics, Statistic::IndexTransactions)?,
+      index_transfers: Index::is_statistic_set(&statistics, Statistic::IndexTransfers)?,
+      metaprotocols: rtx
+        .0
+        .open_table(METAPROTOCOLS)?
+        .iter()?
+        .map(|entry| Ok(entry?.0.value().to_string()))
+        .collect::<Result<Vec<String>>>()?,
+      reorg_rollback: Rollback::from_statistic(
+        statistics
+          .get(&Statistic::ReorgRollback.key())?
+          .map(|rollback| rollback.value())
+          .unwrap_or_default(),
+      )?,
+      schema_version: statistics
+        .get(&Statistic::Schema.key())?
+        .map(|x| x.value())
+        .unwrap_or(0),
+      size: 0,
+    })
+  }
+}
+
+pub(crate) fn create(settings: &Settings, path: &Path) -> Result<Manifest> {
+  ensure!(
+    !path.exists(),
+    "snapshot `{}` already exists",
+    path.display()
+  );
+
+  let manifest = {
+    let index = Index::open(settings)?;
+    index.update()?;
+    Manifest::describe(&index.database, settings.chain())?
+  };
+
+  // the index is closed while it's read, so detect concurrent writes by
+  // checking that the file is unchanged
+  let modified = |file: &File| -> Result<(SystemTime, u64)> {
+    let metadata = file.metadata()?;
+    Ok((metadata.modified()?, metadata.len()))
+  };
+
+  let mut database = File::open(settings.index())?;
+
+  let before = modified(&database)?;
+
+  let mut engine = sha256::Hash::engine();
+
+  let size = io::copy(
+    &mut BufReader::with_capacity(BUFFER_SIZE, &database),
+    &mut engine,
+  )?;
+
+  let manifest = Manifest {
+    checksum: sha256::Hash::from_engine(engine),
+    size,
+    ..manifest
+  };
+
+  let mut output = BufWriter::new(File::create(path)?);
+
+  let json = serde_json::to_vec(&manifest)?;
+
+  output.write_all(MAGIC)?;
+  output.write_all(&u32::try_from(json.len())?.to_le_bytes())?;
+  output.write_all(&json)?;
+
+  database.seek(SeekFrom::Start(0))?;
+
+  let mut compressor = CompressorWriter::new(output, BUFFER_SIZE, QUALITY, WINDOW);
+
+  io::copy(
+    &mut BufReader::with_capacity(BUFFER_SIZE, &database),
+    &mut compressor,
+  )?;
+
+  compressor.into_inner().flush()?;
+
+  ensure!(
+    modified(&database)? == before,
+    "index `{}` was modified while the snapshot was being created",
+    settings.index().display(),
+  );
+
+  Ok(manifest)
+}
+
+pub(crate) fn restore(settings: &Settings, path: &Path, force: bool) -> Result<Manifest> {
+  let index = settings.index();
+
+  ensure!(
+    force || !index.exists(),
+    "index `{}` already exists, pass `--force` to overwrite it",
+    index.display(),
+  );
+
+  let mut input = BufReader::new(
+    File::open(path).with_context(|| format!("failed to open snapshot `{}`", path.display()))?,
+  );
+
+  let mut magic = [0; 8];
+  input.read_exact(&mut magic)?;
+  ensure!(
+    magic == *MAGIC,
+    "`{}` is not an index snapshot",
+    path.display()
+  );
+
+  let mut len = [0; 4];
+  input.read_exact(&mut len)?;
+  let mut json = vec![0; u32::from_le_bytes(len).try_into().unwrap()];
+  input.read_exact(&mut json)?;
+  let manifest = serde_json::from_slice::<Manifest>(&json)?;
+
+  ensure!(
+    manifest.chain == settings.chain(),
+    "snapshot is for {} but ord is on {}",
+    manifest.chain,
+    settings.chain(),
+  );
+
+  ensure!(
+    manifest.schema_version == SCHEMA_VERSION,
+    "snapshot has index schema {} but ord has index schema {SCHEMA_VERSION}",
+    manifest.schema_version,
+  );
+
+  match block_source::open(settings)?.block_hash(manifest.height)? {
+    Some(hash) if hash == manifest.block_hash => {}
+    Some(hash) => bail!(
+      "snapshot block {} hash {} does not match node block hash {hash}",
+      manifest.height,
+      manifest.block_hash,
+    ),
+    None => bail!("node does not have snapshot block {}", manifest.height),
+  }
+
+  let dir = index.parent().unwrap();
+
+  fs::create_dir_all(dir)?;
+
+  let mut tempfile = NamedTempFile::new_in(dir)?;
+
+  let mut engine = sha256::Hash::engine();
+
+  let size = {
+    let mut writer = BufWriter::new(tempfile.as_file_mut());
+    let mut decompressor = Decompressor::new(input, BUFFER_SIZE);
+    let mut buffer = vec![0; BUFFER_SIZE];
+    let mut size = 0;
+
+    loop {
+      let n = decompressor.read(&mut buffer)?;
+
+      if n == 0 {
+        break;
+      }
+
+      engine.write_all(&buffer[..n])?;
+      writer.write_all(&buffer[..n])?;
+      size += u64::try_from(n).unwrap();
+    }
+
+    writer.flush()?;
+
+    size
+  };
+
+  ensure!(
+    size == manifest.size && sha256::Hash::from_engine(engine) == manifest.checksum,
+    "snapshot checksum mismatch",
+  );
+
+  tempfile.as_file().sync_all()?;
+
+  {
+    let database = Database::builder()
+      .set_repair_callback(|session| session.abort())
+      .open(tempfile.path())
+      .context("snapshot database is not consistent")?;
+
+    ensure!(
+      Manifest {
+        checksum: manifest.checksum,
+        size: manifest.size,
+        ..Manifest::describe(&database, settings.chain())?
+      } == manifest,
+      "snapshot database does not match snapshot manifest",
+    );
+  }
+
+  tempfile.persist(index)?;
+
+  Ok(manifest)
+}
+
+#[cfg(test)]
+mod tests {
+  use {super::*, crate::index::testing::Context};
+
+  fn context() -> (Settings, mockcore::Handle, TempDir, Txid) {
+    let context = Context::builder()
+      .args(["--index-sats", "--index-content", "--index-full-text"])
+      .build();
+
+    context.mine_blocks(1);
+
+    let txid = context.core.broadcast_tx(TransactionTemplate {
+      inputs: &[(1, 0, 0, inscription("text/plain", "hello").to_witness())],
+      ..default()
+    });
+
+    context.mine_blocks(1);
+
+    let Context {
+      index,
+      core,
+      tempdir,
+    } = context;
+
+    (index.settings.clone(), core, tempdir, txid)
+  }
+
+  #[test]
+  fn snapshot_is_restored() {
+    let (settings, _core, tempdir, txid) = context();
+
+    let path = tempdir.path().join("snapshot");
+
+    let created = create(&settings, &path).unwrap();
+
+    assert_eq!(created.height, 2);
+    assert!(created.index_content);
+    assert!(created.index_full_text);
+    assert!(created.index_sats);
+    assert!(!created.index_runes);
+    assert_eq!(created.schema_version, SCHEMA_VERSION);
+
+    let settings = settings.with_index(tempdir.path().join("restored.redb"));
+
+    assert_eq!(restore(&settings, &path, false).unwrap(), created);
+
+    let index = Index::open(&settings).unwrap();
+
+    assert!(index.has_sat_index());
+
+    assert_eq!(index.block_count().unwrap(), 3);
+
+    assert_eq!(
+      index
+        .get_inscription_satpoint_by_id(InscriptionId { txid, index: 0 })
+        .unwrap(),
+      Some(SatPoint {
+        outpoint: OutPoint { txid, vout: 0 },
+        offset: 0,
+      })
+    );
+  }
+
+  #[test]
+  fn snapshot_records_rollback_mode_and_metaprotocols() {
+    let context = Context::builder()
+      .args([
+        "--index-metaprotocol",
+        "token",
+        "--reorg-rollback",
+        "undo-log",
+      ])
+      .build();
+
+    context.mine_blocks(1);
+
+    let Context {
+      index,
+      core: _core,
+      tempdir,
+    } = context;
+
+    let settings = index.settings.clone();
+
+    drop(index);
+
+    let path = tempdir.path().join("snapshot");
+
+    let created = create(&settings, &path).unwrap();
+
+    assert_eq!(created.metaprotocols, ["token"]);
+    assert_eq!(created.reorg_rollback, Rollback::UndoLog);
+
+    let restored = settings.with_index(tempdir.path().join("restored.redb"));
+
+    assert_eq!(restore(&restored, &path, false).unwrap(), created);
+
+    assert_eq!(
+      serde_json::from_str::<Manifest>(&serde_json::to_string(&created).unwrap()).unwrap(),
+      created,
+    );
+
+    Index::open(&restored).unwrap();
+  }
+
+  #[test]
+  fn existing_snapshot_is_not_overwritten() {
+    let (settings, _core, tempdir, _txid) = context();
+
+    let path = tempdir.path().join("snapshot");
+
+    fs::write(&path, "foo").unwrap();
+
+    assert_regex_match!(
+      create(&settings, &path).unwrap_err(),
+      "snapshot `.*snapshot` already exists",
+    );
+  }
+
+  #[test]
+  fn existing_index_is_only_overwritten_with_force() {
+    let (settings, _core, tempdir, _txid) = context();
+
+    let path = tempdir.path().join("snapshot");
+
+    create(&settings, &path).unwrap();
+
+    assert_regex_match!(
+      restore(&settings, &path, false).unwrap_err(),
+      "index `.*` already exists, pass `--force` to overwrite it",
+    );
+
+    restore(&settings, &path, true).unwrap();
+  }
+
+  #[test]
+  fn checksum_is_verified() {
+    let (settings, _core, tempdir, _txid) = context();
+
+    let path = tempdir.path().join("snapshot");
+
+    let manifest = create(&settings, &path).unwrap();
+
+    let snapshot = fs::read(&path).unwrap();
+
+    let checksum = manifest.checksum.to_string();
+
+    let position = snapshot
+      .windows(checksum.len())
+      .position(|window| window == checksum.as_bytes())
+      .unwrap();
+
+    let mut corrupted = snapshot.clone();
+
+    corrupted[position..position + checksum.len()]
+      .copy_from_slice(sha256::Hash::all_zeros().to_string().as_bytes());
+
+    fs::write(&path, corrupted).unwrap();
+
+    assert_eq!(
+      restore(
+        &settings
+          .clone()
+          .with_index(tempdir.path().join("restored.redb")),
+        &path,
+        false
+      )
+      .unwrap_err()
+      .to_string(),
+      "snapshot checksum mismatch",
+    );
+  }
+
+  #[test]
+  fn block_hash_is_validated_against_node() {
+    let (settings, core, tempdir, _txid) = context();
+
+    let path = tempdir.path().join("snapshot");
+
+    create(&settings, &path).unwrap();
+
+    core.invalidate_tip();
+
+    core.mine_blocks(1);
+
+    assert_regex_match!(
+      restore(
+        &settings
+          .clone()
+          .with_index(tempdir.path().join("restored.redb")),
+        &path,
+        false
+      )
+      .unwrap_err(),
+      "snapshot block 2 hash [[:xdigit:]]{64} does not match node block hash [[:xdigit:]]{64}",
+    );
+
+    core.invalidate_tip();
+
+    core.invalidate_tip();
+
+    assert_eq!(
+      restore(
+        &settings
+          .clone()
+          .with_index(tempdir.path().join("restored.redb")),
+        &path,
+        false
+      )
+      .unwrap_err()
+      .to_string(),
+      "node does not have snapshot block 2",
+    );
+  }
+
+  #[test]
+  fn invalid_snapshot() {
+    let (settings, _core, tempdir, _txid) = context();
+
+    let path = tempdir.path().join("snapshot");
+
+    fs::write(&path, "not a snapshot").unwrap();
+
+    assert_regex_match!(
+      restore(
+        &settings
+          .clone()
+          .with_index(tempdir.path().join("restored.redb")),
+        &path,
+        false
+      )
+      .unwrap_err(),
+      "`.*snapshot` is not an index snapshot",
+    );
+  }
+}
@@ -3,6 +3,7 @@ use {super::*, bitcoin::script::PushBytes, std::ffi::OsString, tempfile::TempDir
 pub(crate) struct ContextBuilder {
   args: Vec<OsString>,
   chain: Chain,
+  event_outbox: bool,
   event_sender: Option<tokio::sync::mpsc::Sender<Event>>,
   tempdir: Option<TempDir>,
 }
@@ -31,10 +32,12 @@ impl ContextBuilder {
     ];
 
     let options = Options::try_parse_from(command.into_iter().chain(self.args)).unwrap();
-    let index = Index::open_with_event_sender(
-      &Settings::from_options(options).or_defaults().unwrap(),
-      self.event_sender,
-    )?;
+    let settings = Settings::from_options(options).or_defaults().unwrap();
+    let index = if self.event_outbox {
+      Index::open_with_event_outbox(&settings, EventConsumer::Sink)?
+    } else {
+      Index::open_with_event_sender(&settings, self.event_sender)?
+    };
     index.update().unwrap();
 
     Ok(Context {
@@ -64,6 +67,11 @@ impl ContextBuilder {
     self
   }
 
+  pub(crate) fn event_outbox(mut self) -> Self {
+    self.event_outbox = true;
+    self
+  }
+
   pub(crate) fn event_sender(mut self, sender: tokio::sync::mpsc::Sender<Event>) -> Self {
     self.event_sender = Some(sender);
     self
@@ -82,6 +90,7 @@ impl Context {
     ContextBuilder {
       args: Vec::new(),
       chain: Chain::Regtest,
+      event_outbox: false,
       event_sender: None,
       tempdir: None,
     }
@@ -136,7 +145,7 @@ impl Context {
 
     let mut outstanding: HashMap<RuneId, u128> = HashMap::new();
 
-    for (_, balances) in balances {
+    for (_, balances) in balances.iter() {
       for (id, balance) in balances {
         *outstanding.entry(*id).or_default() += *balance;
       }
@@ -148,6 +157,98 @@ impl Context {
         entry.supply() - entry.burned
       );
     }
+
+    self.assert_rune_holders(balances);
+  }
+
+  fn assert_rune_holders(&self, balances: &[(OutPoint, Vec<(RuneId, u128)>)]) {
+    let rtx = self.index.database.begin_read().unwrap();
+
+    let outpoint_to_holder = rtx.open_table(OUTPOINT_TO_RUNE_HOLDER).unwrap();
+
+    pretty_assert_eq!(outpoint_to_holder.len().unwrap(), balances.len() as u64);
+
+    let mut expected: BTreeMap<(RuneId, ScriptBuf), u128> = BTreeMap::new();
+
+    for (outpoint, balances) in balances {
+      let script_pubkey = ScriptBuf::from_bytes(
+        outpoint_to_holder
+          .get(&outpoint.store())
+          .unwrap()
+          .unwrap()
+          .value()
+          .to_vec(),
+      );
+
+      for (id, balance) in balances {
+        *expected.entry((*id, script_pubkey.clone())).or_default() += *balance;
+      }
+    }
+
+    expected.retain(|_, balance| *balance > 0);
+
+    let mut holders = BTreeMap::new();
+
+    for result in rtx
+      .open_table(RUNE_HOLDER_TO_BALANCE)
+      .unwrap()
+      .iter()
+      .unwrap()
+    {
+      let (key, balance) = result.unwrap();
+      let (id, script_pubkey) = key.value();
+      holders.insert(
+        (
+          RuneId::load(id),
+          ScriptBuf::from_bytes(script_pubkey.to_vec()),
+        ),
+        balance.value(),
+      );
+    }
+
+    pretty_assert_eq!(holders, expected);
+
+    let mut by_balance = BTreeMap::new();
+
+    for result in rtx
+      .open_multimap_table(RUNE_BALANCE_TO_HOLDER)
+      .unwrap()
+      .iter()
+      .unwrap()
+    {
+      let (key, scripts) = result.unwrap();
+      let (id, balance) = key.value();
+      for script_pubkey in scripts {
+        by_balance.insert(
+          (
+            RuneId::load(id),
+            ScriptBuf::from_bytes(script_pubkey.unwrap().value().to_vec()),
+          ),
+          balance,
+        );
+      }
+    }
+
+    pretty_assert_eq!(by_balance, expected);
+
+    let mut counts: BTreeMap<RuneId, u64> = BTreeMap::new();
+
+    for (id, _script_pubkey) in expected.keys() {
+      *counts.entry(*id).or_default() += 1;
+    }
+
+    for (id, count) in &counts {
+      pretty_assert_eq!(self.index.get_rune_holder_count(*id).unwrap(), *count);
+    }
+
+    pretty_assert_eq!(
+      rtx
+        .open_table(RUNE_ID_TO_HOLDER_COUNT)
+        .unwrap()
+        .len()
+        .unwrap(),
+      counts.len() as u64
+    );
   }
 
   pub(crate) fn etch(&self, runestone: Runestone, outputs: usize) -> (Txid, RuneId) {
@@ -0,0 +1,487 @@
+use {
+  super::*,
+  redb::{AccessGuard, Key, MultimapValue, Value},
+  std::{borrow::Borrow, cell::RefCell, ops::Deref, rc::Rc},
+};
+
+const INSERT: u8 = 0;
+const REMOVE_KEY: u8 = 1;
+const REMOVE_VALUE: u8 = 2;
+
+#[derive(Debug, PartialEq)]
+enum Undo {
+  Insert { key: Vec<u8>, value: Vec<u8> },
+  RemoveKey { key: Vec<u8> },
+  RemoveValue { key: Vec<u8>, value: Vec<u8> },
+}
+
+impl Undo {
+  fn encode(&self, buffer: &mut Vec<u8>) {
+    let (tag, key, value) = match self {
+      Self::Insert { key, value } => (INSERT, key, Some(value)),
+      Self::RemoveKey { key } => (REMOVE_KEY, key, None),
+      Self::RemoveValue { key, value } => (REMOVE_VALUE, key, Some(value)),
+    };
+
+    buffer.push(tag);
+
+    for bytes in [Some(key), value].into_iter().flatten() {
+      varint::encode_to_vec(bytes.len().try_into().unwrap(), buffer);
+      buffer.extend_from_slice(bytes);
+    }
+  }
+
+  fn decode(mut buffer: &[u8]) -> Result<Vec<Self>> {
+    fn next(buffer: &mut &[u8]) -> Result<Vec<u8>> {
+      let (len, n) = varint::decode(buffer)?;
+      let end = usize::try_from(len)?
+        .checked_add(n)
+        .filter(|end| *end <= buffer.len())
+        .ok_or_else(|| anyhow!("truncated undo log"))?;
+      let bytes = buffer[n..end].to_vec();
+      *buffer = &buffer[end..];
+      Ok(bytes)
+    }
+
+    let mut undos = Vec::new();
+
+    while let Some((tag, rest)) = buffer.split_first() {
+      buffer = rest;
+
+      undos.push(match *tag {
+        INSERT => Self::Insert {
+          key: next(&mut buffer)?,
+          value: next(&mut buffer)?,
+        },
+        REMOVE_KEY => Self::RemoveKey {
+          key: next(&mut buffer)?,
+        },
+        REMOVE_VALUE => Self::RemoveValue {
+          key: next(&mut buffer)?,
+          value: next(&mut buffer)?,
+        },
+        _ => bail!("invalid undo log entry"),
+      });
+    }
+
+    Ok(undos)
+  }
+}
+
+/// Records the inverse of every write made to tables opened through it, so
+/// that a block can later be rolled back exactly.
+#[derive(Clone, Default)]
+pub(crate) struct UndoLog(Option<Rc<RefCell<BTreeMap<String, Vec<u8>>>>>);
+
+impl UndoLog {
+  pub(crate) fn new(enabled: bool) -> Self {
+    Self(enabled.then(Default::default))
+  }
+
+  fn enabled(&self) -> bool {
+    self.0.is_some()
+  }
+
+  fn record(&self, table: &str, undo: Undo) {
+    if let Some(log) = &self.0 {
+      let mut log = log.borrow_mut();
+
+      let buffer = match log.get_mut(table) {
+        Some(buffer) => buffer,
+        None => log.entry(table.into()).or_default(),
+      };
+
+      undo.encode(buffer);
+    }
+  }
+
+  pub(crate) fn table<'tx, K: Key + 'static, V: Value + 'static>(
+    &self,
+    wtx: &'tx WriteTransaction,
+    definition: TableDefinition<K, V>,
+  ) -> Result<LoggedTable<'tx, K, V>> {
+    Ok(LoggedTable {
+      log: self.clone(),
+      name: definition.name().into(),
+      table: wtx.open_table(definition)?,
+    })
+  }
+
+  pub(crate) fn multimap_table<'tx, K: Key + 'static, V: Key + 'static>(
+    &self,
+    wtx: &'tx WriteTransaction,
+    definition: MultimapTableDefinition<K, V>,
+  ) -> Result<LoggedMultimapTable<'tx, K, V>> {
+    Ok(LoggedMultimapTable {
+      log: self.clone(),
+      table: wtx.open_multimap_table(definition)?,
+    })
+  }
+
+  /// Saves the log for the block at `height` and drops logs of blocks that
+  /// are `depth` or more blocks below it.
+  pub(crate) fn save(self, wtx: &WriteTransaction, height: u32, depth: u32) -> Result {
+    let mut height_to_undo_log = wtx.open_table(HEIGHT_TO_UNDO_LOG)?;
+
+    if let Some(log) = self.0 {
+      for (table, undos) in log.take() {
+        height_to_undo_log.insert((height, table.as_str()), undos.as_slice())?;
+      }
+    }
+
+    height_to_undo_log.retain_in(..((height + 1).saturating_sub(depth), ""), |_, _| false)?;
+
+    Ok(())
+  }
+
+  /// Returns the number of consecutive blocks below `height` with undo logs.
+  pub(crate) fn blocks(rtx: &rtx::Rtx, height: u32) -> Result<u32> {
+    let height_to_undo_log = rtx.0.open_table(HEIGHT_TO_UNDO_LOG)?;
+
+    let mut blocks = 0;
+    let mut lowest = height;
+
+    for entry in height_to_undo_log.range(..(height, ""))?.rev() {
+      let logged = entry?.0.value().0;
+
+      if logged + 1 == lowest {
+        blocks += 1;
+        lowest = logged;
+      } else if logged != lowest {
+        break;
+      }
+    }
+
+    Ok(blocks)
+  }
+
+  /// Rolls back the blocks at heights `from` and above, using and removing
+  /// their undo logs.
+  pub(crate) fn rollback(wtx: &WriteTransaction, from: u32) -> Result {
+    let logs = wtx
+      .open_table(HEIGHT_TO_UNDO_LOG)?
+      .extract_from_if((from, "").., |_, _| true)?
+      .map(|entry| {
+        entry.map(|(key, undos)| {
+          let (height, table) = key.value();
+          (height, table.to_string(), undos.value().to_vec())
+        })
+      })
+      .collect::<Result<Vec<(u32, String, Vec<u8>)>, StorageError>>()?;
+
+    for (height, table, undos) in logs.into_iter().rev() {
+      log::debug!("rolling back {table} at height {height}");
+      Self::undo(wtx, &table, Undo::decode(&undos)?)?;
+    }
+
+    Ok(())
+  }
+
+  fn undo(wtx: &WriteTransaction, table: &str, undos: Vec<Undo>) -> Result {
+    macro_rules! undo {
+      (tables: [$($table:ident),* $(,)?], multimap_tables: [$($multimap_table:ident),* $(,)?] $(,)?) => {
+        $(
+          if table == $table.name() {
+            return Self::undo_table(&mut wtx.open_table($table)?, undos);
+          }
+        )*
+
+        $(
+          if table == $multimap_table.name() {
+            return Self::undo_multimap_table(&mut wtx.open_multimap_table($multimap_table)?, undos);
+          }
+        )*
+      };
+    }
+
+    undo! {
+      tables: [
+        CONTENT_HASH_TO_BODY,
+        CONTENT_TYPE_TO_COUNT,
+        FEE_TO_SEQUENCE_NUMBER,
+        HEIGHT_TO_BLOCK_HEADER,
+        HEIGHT_TO_LAST_SEQUENCE_NUMBER,
+        HOME_INSCRIPTIONS,
+        INSCRIPTION_ID_TO_SEQUENCE_NUMBER,
+        INSCRIPTION_NUMBER_TO_SEQUENCE_NUMBER,
+        OUTPOINT_TO_RUNE_BALANCES,
+        OUTPOINT_TO_RUNE_HOLDER,
+        OUTPOINT_TO_SPENT_RUNE_BALANCES,
+        OUTPOINT_TO_SAT_RANGES,
+        OUTPOINT_TO_TXOUT,
+        RUNE_HOLDER_TO_BALANCE,
+        RUNE_ID_TO_HOLDER_COUNT,
+        RUNE_ID_TO_RUNE_ENTRY,
+        RUNE_TO_RUNE_ID,
+        SAT_TO_SATPOINT,
+        SCRIPT_PUBKEY_TO_RUNE_BALANCE,
+        SCRIPT_PUBKEY_TO_TRANSACTION,
+        SEQUENCE_NUMBER_TO_CONTENT,
+        SEQUENCE_NUMBER_TO_INSCRIPTION_ENTRY,
+        SEQUENCE_NUMBER_TO_INSCRIPTION_PROPERTIES,
+        SEQUENCE_NUMBER_TO_RUNE_ID,
+        SEQUENCE_NUMBER_TO_SATPOINT,
+        SEQUENCE_NUMBER_TO_TEXT,
+        SEQUENCE_NUMBER_TO_TRANSFER,
+        SIZE_TO_SEQUENCE_NUMBER,
+        STATISTIC_TO_COUNT,
+        TRANSACTION_ID_TO_RUNE,
+        TRANSACTION_ID_TO_RUNE_MINT,
+        TRANSACTION_ID_TO_TRANSACTION,
+      ],
+      multimap_tables: [
+        CONTENT_HASH_TO_SEQUENCE_NUMBER,
+        HEIGHT_TO_TRANSFER,
+        RUNE_BALANCE_TO_HOLDER,
+        SATPOINT_TO_SEQUENCE_NUMBER,
+        SAT_TO_SEQUENCE_NUMBER,
+        SCRIPT_PUBKEY_TO_OUTPOINT,
+        SCRIPT_PUBKEY_TO_SEQUENCE_NUMBER,
+        SEQUENCE_NUMBER_TO_CHILDREN,
+        TERM_TO_SEQUENCE_NUMBER,
+      ],
+    }
+
+    if table.starts_with(metaprotocol::TABLE_PREFIX) {
+      return Self::undo_table(
+        &mut wtx.open_table(TableDefinition::<&[u8], &[u8]>::new(table))?,
+        undos,
+      );
+    }
+
+    bail!("undo log references unknown table `{table}`")
+  }
+
+  fn undo_table<K: Key + 'static, V: Value + 'static>(
+    table: &mut Table<K, V>,
+    undos: Vec<Undo>,
+  ) -> Result {
+    for undo in undos.into_iter().rev() {
+      match undo {
+        Undo::Insert { key, value } => {
+          table.insert(K::from_bytes(&key), V::from_bytes(&value))?;
+        }
+        Undo::RemoveKey { key } => {
+          table.remove(K::from_bytes(&key))?;
+        }
+        Undo::RemoveValue { .. } => bail!("invalid undo log entry for `{}`", table.name()),
+      }
+    }
+
+    Ok(())
+  }
+
+  fn undo_multimap_table<K: Key + 'static, V: Key + 'static>(
+    table: &mut MultimapTable<K, V>,
+    undos: Vec<Undo>,
+  ) -> Result {
+    for undo in undos.into_iter().rev() {
+      match undo {
+        Undo::Insert { key, value } => {
+          table.insert(K::from_bytes(&key), V::from_bytes(&value))?;
+        }
+        Undo::RemoveValue { key, value } => {
+          table.remove(K::from_bytes(&key), V::from_bytes(&value))?;
+        }
+        Undo::RemoveKey { .. } => bail!("invalid undo log entry for `{}`", table.name()),
+      }
+    }
+
+    Ok(())
+  }
+}
+
+pub(crate) struct LoggedTable<'tx, K: Key + 'static, V: Value + 'static> {
+  log: UndoLog,
+  name: String,
+  table: Table<'tx, K, V>,
+}
+
+impl<'tx, K: Key + 'static, V: Value + 'static> LoggedTable<'tx, K, V> {
+  pub(crate) fn insert<'k, 'v>(
+    &mut self,
+    key: impl Borrow<K::SelfType<'k>>,
+    value: impl Borrow<V::SelfType<'v>>,
+  ) -> Result<Option<AccessGuard<'_, V>>, StorageError> {
+    let key_bytes = self
+      .log
+      .enabled()
+      .then(|| K::as_bytes(key.borrow()).as_ref().to_vec());
+
+    let old = self.table.insert(key, value)?;
+
+    if let Some(key) = key_bytes {
+      self.log.record(
+        &self.name,
+        match &old {
+          Some(old) => Undo::Insert {
+            key,
+            value: V::as_bytes(&old.value()).as_ref().to_vec(),
+          },
+          None => Undo::RemoveKey { key },
+        },
+      );
+    }
+
+    Ok(old)
+  }
+
+  pub(crate) fn remove<'k>(
+    &mut self,
+    key: impl Borrow<K::SelfType<'k>>,
+  ) -> Result<Option<AccessGuard<'_, V>>, StorageError> {
+    let key_bytes = self
+      .log
+      .enabled()
+      .then(|| K::as_bytes(key.borrow()).as_ref().to_vec());
+
+    let old = self.table.remove(key)?;
+
+    if let (Some(key), Some(old)) = (key_bytes, &old) {
+      self.log.record(
+        &self.name,
+        Undo::Insert {
+          key,
+          value: V::as_bytes(&old.value()).as_ref().to_vec(),
+        },
+      );
+    }
+
+    Ok(old)
+  }
+
+  pub(crate) fn pop_first(
+    &mut self,
+  ) -> Result<Option<(AccessGuard<'_, K>, AccessGuard<'_, V>)>, StorageError> {
+    let first = self.table.pop_first()?;
+
+    if let Some((key, value)) = &first {
+      self.log.record(
+        &self.name,
+        Undo::Insert {
+          key: K::as_bytes(&key.value()).as_ref().to_vec(),
+          value: V::as_bytes(&value.value()).as_ref().to_vec(),
+        },
+      );
+    }
+
+    Ok(first)
+  }
+}
+
+impl<'tx, K: Key + 'static, V: Value + 'static> Deref for LoggedTable<'tx, K, V> {
+  type Target = Table<'tx, K, V>;
+
+  fn deref(&self) -> &Self::Target {
+    &self.table
+  }
+}
+
+pub(crate) struct LoggedMultimapTable<'tx, K: Key + 'static, V: Key + 'static> {
+  log: UndoLog,
+  table: MultimapTable<'tx, K, V>,
+}
+
+impl<'tx, K: Key + 'static, V: Key + 'static> LoggedMultimapTable<'tx, K, V> {
+  pub(crate) fn insert<'k, 'v>(
+    &mut self,
+    key: impl Borrow<K::SelfType<'k>>,
+    value: impl Borrow<V::SelfType<'v>>,
+  ) -> Result<bool, StorageError> {
+    let undo = self.log.enabled().then(|| Undo::RemoveValue {
+      key: K::as_bytes(key.borrow()).as_ref().to_vec(),
+      value: V::as_bytes(value.borrow()).as_ref().to_vec(),
+    });
+
+    let present = self.table.insert(key, value)?;
+
+    if let Some(undo) = undo.filter(|_| !present) {
+      self.log.record(self.table.name(), undo);
+    }
+
+    Ok(present)
+  }
+
+  pub(crate) fn remove<'k, 'v>(
+    &mut self,
+    key: impl Borrow<K::SelfType<'k>>,
+    value: impl Borrow<V::SelfType<'v>>,
+  ) -> Result<bool, StorageError> {
+    let undo = self.log.enabled().then(|| Undo::Insert {
+      key: K::as_bytes(key.borrow()).as_ref().to_vec(),
+      value: V::as_bytes(value.borrow()).as_ref().to_vec(),
+    });
+
+    let present = self.table.remove(key, value)?;
+
+    if let Some(undo) = undo.filter(|_| present) {
+      self.log.record(self.table.name(), undo);
+    }
+
+    Ok(present)
+  }
+
+  pub(crate) fn remove_all<'k>(
+    &mut self,
+    key: impl Borrow<K::SelfType<'k>>,
+  ) -> Result<MultimapValue<'_, V>, StorageError> {
+    if self.log.enabled() {
+      let key_bytes = K::as_bytes(key.borrow()).as_ref().to_vec();
+
+      for value in self.table.get(key.borrow())? {
+        self.log.record(
+          self.table.name(),
+          Undo::Insert {
+            key: key_bytes.clone(),
+            value: V::as_bytes(&value?.value()).as_ref().to_vec(),
+          },
+        );
+      }
+    }
+
+    self.table.remove_all(key)
+  }
+}
+
+impl<'tx, K: Key + 'static, V: Key + 'static> Deref for LoggedMultimapTable<'tx, K, V> {
+  type Target = MultimapTable<'tx, K, V>;
+
+  fn deref(&self) -> &Self::Target {
+    &self.table
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn undos_round_trip() {
+    let undos = vec![
+      Undo::Insert {
+        key: vec![1, 2],
+        value: vec![3; 300],
+      },
+      Undo::RemoveKey { key: Vec::new() },
+      Undo::RemoveValue {
+        key: vec![4],
+        value: vec![5, 6, 7],
+      },
+    ];
+
+    let mut buffer = Vec::new();
+
+    for undo in &undos {
+      undo.encode(&mut buffer);
+    }
+
+    assert_eq!(Undo::decode(&buffer).unwrap(), undos);
+
+    assert_eq!(
+      Undo::decode(&buffer[..buffer.len() - 1])
+        .unwrap_err()
+        .to_string(),
+      "truncated undo log",
+    );
+  }
+}
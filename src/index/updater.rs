@@ -1,6 +1,10 @@
 use {
-  self::{inscription_updater::InscriptionUpdater, rune_updater::RuneUpdater},
-  super::{fetcher::Fetcher, *},
+  self::{
+    address_history::AddressHistory, content_store::ContentStore,
+    full_text_indexer::FullTextIndexer, inscription_updater::InscriptionUpdater,
+    metaprotocols::Metaprotocols, rune_updater::RuneUpdater,
+  },
+  super::*,
   futures::future::try_join_all,
   tokio::sync::{
     broadcast::{self, error::TryRecvError},
@@ -8,9 +12,15 @@ use {
   },
 };
 
+mod address_history;
+mod content_store;
+mod full_text_indexer;
 mod inscription_updater;
+mod metaprotocols;
 mod rune_updater;
 
+pub(super) use self::rune_updater::allocate;
+
 pub(crate) struct BlockData {
   pub(crate) header: Header,
   pub(crate) txdata: Vec<(Transaction, Txid)>,
@@ -32,6 +42,53 @@ impl From<Block> for BlockData {
   }
 }
 
+pub(super) struct EventOutbox<'tx> {
+  counts: BlockEventCounts,
+  next_event_id: u64,
+  table: Table<'tx, u64, &'static [u8]>,
+}
+
+impl<'tx> EventOutbox<'tx> {
+  pub(super) fn new(table: Table<'tx, u64, &'static [u8]>, next_event_id: u64) -> Self {
+    Self {
+      counts: BlockEventCounts::default(),
+      next_event_id,
+      table,
+    }
+  }
+
+  fn start_block(&mut self, height: u32, header: &Header) -> Result {
+    self.counts = BlockEventCounts::default();
+    self.push(Event::BlockStarted {
+      block_hash: header.block_hash(),
+      block_height: height,
+      prev_block_hash: header.prev_blockhash,
+      timestamp: header.time,
+    })
+  }
+
+  fn commit_block(&mut self, height: u32, header: &Header) -> Result {
+    self.push(Event::BlockCommitted {
+      block_hash: header.block_hash(),
+      block_height: height,
+      counts: self.counts,
+    })
+  }
+
+  pub(super) fn next_event_id(&self) -> u64 {
+    self.next_event_id
+  }
+
+  pub(super) fn push(&mut self, event: Event) -> Result {
+    self.counts.record(&event);
+    self
+      .table
+      .insert(self.next_event_id, serde_json::to_vec(&event)?.as_slice())?;
+    self.next_event_id += 1;
+    Ok(())
+  }
+}
+
 pub(crate) struct Updater<'index> {
   pub(super) height: u32,
   pub(super) index: &'index Index,
@@ -40,14 +97,26 @@ pub(crate) struct Updater<'index> {
   pub(super) outputs_traversed: u64,
   pub(super) range_cache: HashMap<OutPointValue, Vec<u8>>,
   pub(super) sat_ranges_since_flush: u64,
+  pub(super) undo_log: UndoLog,
+  pub(super) undo_log_height: Option<u32>,
 }
 
 impl<'index> Updater<'index> {
   pub(crate) fn update_index(&mut self, mut wtx: WriteTransaction) -> Result {
     let start = Instant::now();
-    let starting_height = u32::try_from(self.index.client.get_block_count()?).unwrap() + 1;
+    let starting_height = self.index.block_source.block_count()? + 1;
+    metrics::NODE_HEIGHT.set((starting_height - 1).into());
     let starting_index_height = self.height;
 
+    // blocks within `undo_log_depth` of the chain tip are committed one at a
+    // time, each with an undo log
+    self.undo_log_height = match self.index.reorg_rollback {
+      Rollback::Savepoints => None,
+      Rollback::UndoLog => {
+        Some(starting_height.saturating_sub(self.index.settings.undo_log_depth()))
+      }
+    };
+
     wtx
       .open_table(WRITE_TRANSACTION_STARTING_BLOCK_COUNT_TO_TIMESTAMP)?
       .insert(
@@ -76,7 +145,7 @@ impl<'index> Updater<'index> {
     let rx = Self::fetch_blocks_from(self.index, self.height, self.index.index_sats)?;
 
     let (mut output_sender, mut txout_receiver, mut address_txout_receiver) =
-      Self::spawn_fetcher(&self.index.settings)?;
+      Self::spawn_fetcher(self.index)?;
 
     let mut uncommitted = 0;
     let mut utxo_cache = HashMap::new();
@@ -90,12 +159,15 @@ impl<'index> Updater<'index> {
         &mut utxo_cache,
       )?;
 
+      metrics::RANGE_CACHE_ENTRIES.set(self.range_cache.len().try_into()?);
+      metrics::UTXO_CACHE_ENTRIES.set(utxo_cache.len().try_into()?);
+
       if let Some(progress_bar) = &mut progress_bar {
         progress_bar.inc(1);
 
         if progress_bar.position() > progress_bar.length().unwrap() {
-          if let Ok(count) = self.index.client.get_block_count() {
-            progress_bar.set_length(count + 1);
+          if let Ok(count) = self.index.block_source.block_count() {
+            progress_bar.set_length((count + 1).into());
           } else {
             log::warn!("Failed to fetch latest block height");
           }
@@ -104,7 +176,7 @@ impl<'index> Updater<'index> {
 
       uncommitted += 1;
 
-      if uncommitted == self.index.settings.commit_interval() {
+      if uncommitted == self.index.settings.commit_interval() || self.undo_logging() {
         self.commit(wtx, utxo_cache)?;
         utxo_cache = HashMap::new();
         uncommitted = 0;
@@ -163,29 +235,67 @@ impl<'index> Updater<'index> {
 
     let height_limit = index.height_limit;
 
-    let client = index.settings.bitcoin_rpc_client(None)?;
+    let block_source = index.block_source.clone();
 
     let first_inscription_height = index.first_inscription_height;
 
-    thread::spawn(move || loop {
-      if let Some(height_limit) = height_limit {
-        if height >= height_limit {
-          break;
+    let settings = index.settings.clone();
+
+    thread::spawn(move || {
+      if settings.fast_sync() {
+        match Self::open_block_files(&*block_source, &settings, height, height_limit) {
+          Ok(Some(block_files)) => {
+            for block in block_files {
+              let mut block = match block {
+                Ok(block) => block,
+                Err(err) => {
+                  log::warn!("failed to read block {height} from block files: {err}");
+                  break;
+                }
+              };
+
+              if !index_sats && height < first_inscription_height {
+                block.txdata.clear();
+              }
+
+              if let Err(err) = tx.send(block.into()) {
+                log::info!("Block receiver disconnected: {err}");
+                return;
+              }
+
+              height += 1;
+            }
+          }
+          Ok(None) => {}
+          Err(err) => log::warn!("not reading blocks from block files: {err}"),
         }
       }
 
-      match Self::get_block_with_retries(&client, height, index_sats, first_inscription_height) {
-        Ok(Some(block)) => {
-          if let Err(err) = tx.send(block.into()) {
-            log::info!("Block receiver disconnected: {err}");
+      loop {
+        if let Some(height_limit) = height_limit {
+          if height >= height_limit {
             break;
           }
-          height += 1;
         }
-        Ok(None) => break,
-        Err(err) => {
-          log::error!("failed to fetch block {height}: {err}");
-          break;
+
+        match Self::get_block_with_retries(
+          &*block_source,
+          height,
+          index_sats,
+          first_inscription_height,
+        ) {
+          Ok(Some(block)) => {
+            if let Err(err) = tx.send(block.into()) {
+              log::info!("Block receiver disconnected: {err}");
+              break;
+            }
+            height += 1;
+          }
+          Ok(None) => break,
+          Err(err) => {
+            log::error!("failed to fetch block {height}: {err}");
+            break;
+          }
         }
       }
     });
@@ -193,37 +303,76 @@ impl<'index> Updater<'index> {
     Ok(rx)
   }
 
+  fn open_block_files(
+    block_source: &dyn BlockSource,
+    settings: &Settings,
+    height: u32,
+    height_limit: Option<u32>,
+  ) -> Result<Option<block_source::Chain>> {
+    let Some(mut end) = block_source
+      .block_count()?
+      .checked_sub(settings.chain_tip_distance())
+    else {
+      return Ok(None);
+    };
+
+    if let Some(height_limit) = height_limit {
+      let Some(last) = height_limit.checked_sub(1) else {
+        return Ok(None);
+      };
+
+      end = end.min(last);
+    }
+
+    if end < height {
+      return Ok(None);
+    }
+
+    let end_hash = block_source
+      .block_hash(end)?
+      .ok_or_else(|| anyhow!("block {end} not found"))?;
+
+    let block_files = block_source::Files::new(settings)?.chain(height, end, end_hash)?;
+
+    log::info!(
+      "Reading {} blocks from block files in {}",
+      block_files.len(),
+      settings.bitcoin_blocks_dir().display(),
+    );
+
+    Ok(Some(block_files))
+  }
+
   fn get_block_with_retries(
-    client: &Client,
+    block_source: &dyn BlockSource,
     height: u32,
     index_sats: bool,
     first_inscription_height: u32,
   ) -> Result<Option<Block>> {
+    let _timer = metrics::rpc_timer("block");
     let mut errors = 0;
     loop {
-      match client
-        .get_block_hash(height.into())
-        .into_option()
-        .and_then(|option| {
-          option
-            .map(|hash| {
-              if index_sats || height >= first_inscription_height {
-                Ok(client.get_block(&hash)?)
-              } else {
-                Ok(Block {
-                  header: client.get_block_header(&hash)?,
-                  txdata: Vec::new(),
-                })
-              }
-            })
-            .transpose()
-        }) {
+      match block_source.block_hash(height).and_then(|option| {
+        option
+          .map(|hash| {
+            if index_sats || height >= first_inscription_height {
+              block_source.block(hash)
+            } else {
+              Ok(Block {
+                header: block_source.block_header(hash)?,
+                txdata: Vec::new(),
+              })
+            }
+          })
+          .transpose()
+      }) {
         Err(err) => {
           if cfg!(test) {
             return Err(err);
           }
 
           errors += 1;
+          metrics::rpc_retry("block");
           let seconds = 1 << errors;
           log::warn!("failed to fetch block {height}, retrying in {seconds}s: {err}");
 
@@ -240,13 +389,15 @@ impl<'index> Updater<'index> {
   }
 
   fn spawn_fetcher(
-    settings: &Settings,
+    index: &Index,
   ) -> Result<(
     mpsc::Sender<OutPoint>,
     broadcast::Receiver<TxOut>,
     Option<broadcast::Receiver<TxOut>>,
   )> {
-    let fetcher = Fetcher::new(settings)?;
+    let block_source = index.block_source.clone();
+
+    let settings = &index.settings;
 
     // A block probably has no more than 20k inputs
     const CHANNEL_BUFFER_SIZE: usize = 20_000;
@@ -296,10 +447,12 @@ impl<'index> Updater<'index> {
           let mut futs = Vec::with_capacity(parallel_requests);
           for chunk in outpoints.chunks(chunk_size) {
             let txids = chunk.iter().map(|outpoint| outpoint.txid).collect();
-            let fut = fetcher.get_transactions(txids);
+            let fut = block_source.transactions(txids);
             futs.push(fut);
           }
 
+          let timer = metrics::rpc_timer("transactions");
+
           let txs = match try_join_all(futs).await {
             Ok(txs) => txs,
             Err(e) => {
@@ -308,6 +461,8 @@ impl<'index> Updater<'index> {
             }
           };
 
+          timer.observe_duration();
+
           // Send all tx outputs back in order
           for (i, tx) in txs.iter().flatten().enumerate() {
             let Ok(_) =
@@ -335,6 +490,12 @@ impl<'index> Updater<'index> {
   ) -> Result<()> {
     Reorg::detect_reorg(&block, self.height, self.index)?;
 
+    let _timer = metrics::BLOCK_INDEX_DURATION.start_timer();
+
+    self.undo_log = UndoLog::new(self.undo_logging());
+
+    let undo_log = self.undo_log.clone();
+
     let start = Instant::now();
     let mut sat_ranges_written = 0;
     let mut outputs_in_block = 0;
@@ -346,7 +507,7 @@ impl<'index> Updater<'index> {
       block.txdata.len()
     );
 
-    let mut outpoint_to_txout = wtx.open_table(OUTPOINT_TO_TXOUT)?;
+    let mut outpoint_to_txout = undo_log.table(wtx, OUTPOINT_TO_TXOUT)?;
 
     let index_inscriptions = self.height >= self.index.first_inscription_height
       && self.index.settings.index_inscriptions();
@@ -401,8 +562,14 @@ impl<'index> Updater<'index> {
       }
     }
 
+    let mut address_history = self
+      .index
+      .index_address_history
+      .then(|| AddressHistory::new(self.height));
+
     if let Some(address_txout_receiver) = address_txout_receiver {
-      let mut script_pubkey_to_outpoint = wtx.open_multimap_table(SCRIPT_PUBKEY_TO_OUTPOINT)?;
+      let mut script_pubkey_to_outpoint =
+        undo_log.multimap_table(wtx, SCRIPT_PUBKEY_TO_OUTPOINT)?;
       for (tx, txid) in &block.txdata {
         self.index_transaction_output_script_pubkeys(
           tx,
@@ -411,27 +578,54 @@ impl<'index> Updater<'index> {
           utxo_cache,
           &mut script_pubkey_to_outpoint,
           &mut outpoint_to_txout,
+          address_history.as_mut(),
           index_inscriptions,
         )?;
       }
     };
 
-    let mut content_type_to_count = wtx.open_table(CONTENT_TYPE_TO_COUNT)?;
-    let mut height_to_block_header = wtx.open_table(HEIGHT_TO_BLOCK_HEADER)?;
-    let mut height_to_last_sequence_number = wtx.open_table(HEIGHT_TO_LAST_SEQUENCE_NUMBER)?;
-    let mut home_inscriptions = wtx.open_table(HOME_INSCRIPTIONS)?;
+    let mut content_store = self.index.index_content.then(ContentStore::new);
+    let mut content_type_to_count = undo_log.table(wtx, CONTENT_TYPE_TO_COUNT)?;
+    let event_id_to_event = if self.index.event_outbox {
+      Some(wtx.open_table(EVENT_ID_TO_EVENT)?)
+    } else {
+      None
+    };
+    let mut fee_to_sequence_number = undo_log.table(wtx, FEE_TO_SEQUENCE_NUMBER)?;
+    let mut full_text_indexer = self.index.index_full_text.then(FullTextIndexer::new);
+    let mut height_to_block_header = undo_log.table(wtx, HEIGHT_TO_BLOCK_HEADER)?;
+    let mut height_to_last_sequence_number = undo_log.table(wtx, HEIGHT_TO_LAST_SEQUENCE_NUMBER)?;
+    let mut height_to_transfer = undo_log.multimap_table(wtx, HEIGHT_TO_TRANSFER)?;
+    let mut home_inscriptions = undo_log.table(wtx, HOME_INSCRIPTIONS)?;
     let mut inscription_id_to_sequence_number =
-      wtx.open_table(INSCRIPTION_ID_TO_SEQUENCE_NUMBER)?;
+      undo_log.table(wtx, INSCRIPTION_ID_TO_SEQUENCE_NUMBER)?;
     let mut inscription_number_to_sequence_number =
-      wtx.open_table(INSCRIPTION_NUMBER_TO_SEQUENCE_NUMBER)?;
-    let mut sat_to_sequence_number = wtx.open_multimap_table(SAT_TO_SEQUENCE_NUMBER)?;
-    let mut satpoint_to_sequence_number = wtx.open_multimap_table(SATPOINT_TO_SEQUENCE_NUMBER)?;
-    let mut sequence_number_to_children = wtx.open_multimap_table(SEQUENCE_NUMBER_TO_CHILDREN)?;
+      undo_log.table(wtx, INSCRIPTION_NUMBER_TO_SEQUENCE_NUMBER)?;
+    let mut metaprotocols = if self.index.metaprotocols().is_empty() {
+      None
+    } else {
+      Some(Metaprotocols::new(
+        self.index.metaprotocols(),
+        &undo_log,
+        wtx,
+      )?)
+    };
+    let mut sat_to_sequence_number = undo_log.multimap_table(wtx, SAT_TO_SEQUENCE_NUMBER)?;
+    let mut satpoint_to_sequence_number =
+      undo_log.multimap_table(wtx, SATPOINT_TO_SEQUENCE_NUMBER)?;
+    let mut script_pubkey_to_sequence_number =
+      undo_log.multimap_table(wtx, SCRIPT_PUBKEY_TO_SEQUENCE_NUMBER)?;
+    let mut sequence_number_to_children =
+      undo_log.multimap_table(wtx, SEQUENCE_NUMBER_TO_CHILDREN)?;
     let mut sequence_number_to_inscription_entry =
-      wtx.open_table(SEQUENCE_NUMBER_TO_INSCRIPTION_ENTRY)?;
-    let mut sequence_number_to_satpoint = wtx.open_table(SEQUENCE_NUMBER_TO_SATPOINT)?;
-    let mut statistic_to_count = wtx.open_table(STATISTIC_TO_COUNT)?;
-    let mut transaction_id_to_transaction = wtx.open_table(TRANSACTION_ID_TO_TRANSACTION)?;
+      undo_log.table(wtx, SEQUENCE_NUMBER_TO_INSCRIPTION_ENTRY)?;
+    let mut sequence_number_to_inscription_properties =
+      undo_log.table(wtx, SEQUENCE_NUMBER_TO_INSCRIPTION_PROPERTIES)?;
+    let mut sequence_number_to_satpoint = undo_log.table(wtx, SEQUENCE_NUMBER_TO_SATPOINT)?;
+    let mut sequence_number_to_transfer = undo_log.table(wtx, SEQUENCE_NUMBER_TO_TRANSFER)?;
+    let mut size_to_sequence_number = undo_log.table(wtx, SIZE_TO_SEQUENCE_NUMBER)?;
+    let mut statistic_to_count = undo_log.table(wtx, STATISTIC_TO_COUNT)?;
+    let mut transaction_id_to_transaction = undo_log.table(wtx, TRANSACTION_ID_TO_TRANSACTION)?;
 
     let mut lost_sats = statistic_to_count
       .get(&Statistic::LostSats.key())?
@@ -453,6 +647,22 @@ impl<'index> Updater<'index> {
       .map(|unbound_inscriptions| unbound_inscriptions.value())
       .unwrap_or(0);
 
+    let mut event_outbox = event_id_to_event
+      .map(|table| -> Result<EventOutbox> {
+        Ok(EventOutbox::new(
+          table,
+          statistic_to_count
+            .get(&Statistic::Events.key())?
+            .map(|count| count.value())
+            .unwrap_or(0),
+        ))
+      })
+      .transpose()?;
+
+    if let Some(event_outbox) = &mut event_outbox {
+      event_outbox.start_block(self.height, &block.header)?;
+    }
+
     let next_sequence_number = sequence_number_to_inscription_entry
       .iter()?
       .next_back()
@@ -463,27 +673,57 @@ impl<'index> Updater<'index> {
     let home_inscription_count = home_inscriptions.len()?;
 
     let mut inscription_updater = InscriptionUpdater {
+      address_history: address_history.as_mut(),
       blessed_inscription_count,
       chain: self.index.settings.chain(),
+      content_store: content_store.as_mut(),
+      full_text_indexer: full_text_indexer.as_mut(),
       content_type_to_count: &mut content_type_to_count,
       cursed_inscription_count,
-      event_sender: self.index.event_sender.as_ref(),
+      event_body_limit: self.index.settings.event_body_limit(),
+      event_outbox: event_outbox.as_mut(),
+      fee_to_sequence_number: self
+        .index
+        .index_search
+        .then_some(&mut fee_to_sequence_number),
       flotsam: Vec::new(),
       height: self.height,
+      height_to_transfer: self
+        .index
+        .index_transfers
+        .then_some(&mut height_to_transfer),
       home_inscription_count,
       home_inscriptions: &mut home_inscriptions,
       id_to_sequence_number: &mut inscription_id_to_sequence_number,
       index_transactions: self.index.index_transactions,
       inscription_number_to_sequence_number: &mut inscription_number_to_sequence_number,
       lost_sats,
+      metaprotocols: metaprotocols.as_mut(),
       next_sequence_number,
       outpoint_to_txout: &mut outpoint_to_txout,
       reward: Height(self.height).subsidy(),
+      rich_events: self.index.settings.rich_events(),
       sat_to_sequence_number: &mut sat_to_sequence_number,
       satpoint_to_sequence_number: &mut satpoint_to_sequence_number,
+      script_pubkey_to_sequence_number: self
+        .index
+        .index_addresses
+        .then_some(&mut script_pubkey_to_sequence_number),
       sequence_number_to_children: &mut sequence_number_to_children,
       sequence_number_to_entry: &mut sequence_number_to_inscription_entry,
+      sequence_number_to_properties: self
+        .index
+        .index_search
+        .then_some(&mut sequence_number_to_inscription_properties),
       sequence_number_to_satpoint: &mut sequence_number_to_satpoint,
+      sequence_number_to_transfer: self
+        .index
+        .index_transfers
+        .then_some(&mut sequence_number_to_transfer),
+      size_to_sequence_number: self
+        .index
+        .index_search
+        .then_some(&mut size_to_sequence_number),
       timestamp: block.header.time,
       transaction_buffer: Vec::new(),
       transaction_id_to_transaction: &mut transaction_id_to_transaction,
@@ -493,8 +733,8 @@ impl<'index> Updater<'index> {
     };
 
     if self.index.index_sats {
-      let mut sat_to_satpoint = wtx.open_table(SAT_TO_SATPOINT)?;
-      let mut outpoint_to_sat_ranges = wtx.open_table(OUTPOINT_TO_SAT_RANGES)?;
+      let mut sat_to_satpoint = undo_log.table(wtx, SAT_TO_SATPOINT)?;
+      let mut outpoint_to_sat_ranges = undo_log.table(wtx, OUTPOINT_TO_SAT_RANGES)?;
 
       let mut coinbase_inputs = VecDeque::new();
 
@@ -625,11 +865,19 @@ impl<'index> Updater<'index> {
     )?;
 
     if self.index.index_runes && self.height >= self.index.settings.first_rune_height() {
-      let mut outpoint_to_rune_balances = wtx.open_table(OUTPOINT_TO_RUNE_BALANCES)?;
-      let mut rune_id_to_rune_entry = wtx.open_table(RUNE_ID_TO_RUNE_ENTRY)?;
-      let mut rune_to_rune_id = wtx.open_table(RUNE_TO_RUNE_ID)?;
-      let mut sequence_number_to_rune_id = wtx.open_table(SEQUENCE_NUMBER_TO_RUNE_ID)?;
-      let mut transaction_id_to_rune = wtx.open_table(TRANSACTION_ID_TO_RUNE)?;
+      let mut outpoint_to_rune_balances = undo_log.table(wtx, OUTPOINT_TO_RUNE_BALANCES)?;
+      let mut outpoint_to_rune_holder = undo_log.table(wtx, OUTPOINT_TO_RUNE_HOLDER)?;
+      let mut outpoint_to_spent_rune_balances =
+        undo_log.table(wtx, OUTPOINT_TO_SPENT_RUNE_BALANCES)?;
+      let mut rune_balance_to_holder = undo_log.multimap_table(wtx, RUNE_BALANCE_TO_HOLDER)?;
+      let mut rune_holder_to_balance = undo_log.table(wtx, RUNE_HOLDER_TO_BALANCE)?;
+      let mut rune_id_to_holder_count = undo_log.table(wtx, RUNE_ID_TO_HOLDER_COUNT)?;
+      let mut rune_id_to_rune_entry = undo_log.table(wtx, RUNE_ID_TO_RUNE_ENTRY)?;
+      let mut script_pubkey_to_rune_balance = undo_log.table(wtx, SCRIPT_PUBKEY_TO_RUNE_BALANCE)?;
+      let mut rune_to_rune_id = undo_log.table(wtx, RUNE_TO_RUNE_ID)?;
+      let mut sequence_number_to_rune_id = undo_log.table(wtx, SEQUENCE_NUMBER_TO_RUNE_ID)?;
+      let mut transaction_id_to_rune = undo_log.table(wtx, TRANSACTION_ID_TO_RUNE)?;
+      let mut transaction_id_to_rune_mint = undo_log.table(wtx, TRANSACTION_ID_TO_RUNE_MINT)?;
 
       let runes = statistic_to_count
         .get(&Statistic::Runes.into())?
@@ -637,22 +885,40 @@ impl<'index> Updater<'index> {
         .unwrap_or(0);
 
       let mut rune_updater = RuneUpdater {
-        event_sender: self.index.event_sender.as_ref(),
+        address_history: address_history.as_mut(),
+        balance_to_holder: &mut rune_balance_to_holder,
         block_time: block.header.time,
         burned: HashMap::new(),
         client: &self.index.client,
+        event_outbox: event_outbox.as_mut(),
         height: self.height,
+        holder_to_balance: &mut rune_holder_to_balance,
+        holders: HashMap::new(),
         id_to_entry: &mut rune_id_to_rune_entry,
+        id_to_holder_count: &mut rune_id_to_holder_count,
         inscription_id_to_sequence_number: &mut inscription_id_to_sequence_number,
         minimum: Rune::minimum_at_height(
           self.index.settings.chain().network(),
           Height(self.height),
         ),
         outpoint_to_balances: &mut outpoint_to_rune_balances,
+        outpoint_to_holder: &mut outpoint_to_rune_holder,
+        outpoint_to_spent_balances: self
+          .index
+          .index_transfers
+          .then_some(&mut outpoint_to_spent_rune_balances),
         rune_to_id: &mut rune_to_rune_id,
         runes,
+        script_pubkey_to_balance: self
+          .index
+          .index_addresses
+          .then_some(&mut script_pubkey_to_rune_balance),
         sequence_number_to_rune_id: &mut sequence_number_to_rune_id,
         statistic_to_count: &mut statistic_to_count,
+        transaction_id_to_mint: self
+          .index
+          .index_transfers
+          .then_some(&mut transaction_id_to_rune_mint),
         transaction_id_to_rune: &mut transaction_id_to_rune,
       };
 
@@ -663,6 +929,36 @@ impl<'index> Updater<'index> {
       rune_updater.update()?;
     }
 
+    if let Some(content_store) = content_store {
+      content_store.commit(
+        &inscription_id_to_sequence_number,
+        &mut undo_log.table(wtx, CONTENT_HASH_TO_BODY)?,
+        &mut undo_log.multimap_table(wtx, CONTENT_HASH_TO_SEQUENCE_NUMBER)?,
+        &mut undo_log.table(wtx, SEQUENCE_NUMBER_TO_CONTENT)?,
+      )?;
+    }
+
+    if let Some(full_text_indexer) = full_text_indexer {
+      full_text_indexer.commit(
+        &inscription_id_to_sequence_number,
+        &mut undo_log.table(wtx, SEQUENCE_NUMBER_TO_TEXT)?,
+        &mut statistic_to_count,
+        &mut undo_log.multimap_table(wtx, TERM_TO_SEQUENCE_NUMBER)?,
+      )?;
+    }
+
+    if let Some(address_history) = address_history {
+      address_history.commit(
+        &mut undo_log.table(wtx, SCRIPT_PUBKEY_TO_TRANSACTION)?,
+        &block.txdata,
+      )?;
+    }
+
+    if let Some(mut event_outbox) = event_outbox {
+      event_outbox.commit_block(self.height, &block.header)?;
+      statistic_to_count.insert(&Statistic::Events.key(), &event_outbox.next_event_id())?;
+    }
+
     height_to_block_header.insert(&self.height, &block.header.store())?;
 
     self.height += 1;
@@ -682,8 +978,9 @@ impl<'index> Updater<'index> {
     txid: &Txid,
     txout_receiver: &mut broadcast::Receiver<TxOut>,
     utxo_cache: &mut HashMap<OutPoint, TxOut>,
-    script_pubkey_to_outpoint: &mut MultimapTable<&[u8], OutPointValue>,
-    outpoint_to_txout: &mut Table<&OutPointValue, TxOutValue>,
+    script_pubkey_to_outpoint: &mut LoggedMultimapTable<&[u8], OutPointValue>,
+    outpoint_to_txout: &mut LoggedTable<&OutPointValue, TxOutValue>,
+    mut address_history: Option<&mut AddressHistory>,
     index_inscriptions: bool,
   ) -> Result {
     for txin in &tx.input {
@@ -713,6 +1010,10 @@ impl<'index> Updater<'index> {
       }
 
       script_pubkey_to_outpoint.remove(&txout.script_pubkey.as_bytes(), output.store())?;
+
+      if let Some(address_history) = address_history.as_mut() {
+        address_history.send(&txout.script_pubkey, *txid, txout.value);
+      }
     }
 
     for (vout, txout) in tx.output.iter().enumerate() {
@@ -722,6 +1023,13 @@ impl<'index> Updater<'index> {
         OutPoint { txid: *txid, vout }.store(),
       )?;
 
+      if let Some(address_history) = address_history
+        .as_mut()
+        .filter(|_| !txout.script_pubkey.is_op_return())
+      {
+        address_history.receive(&txout.script_pubkey, *txid, txout.value);
+      }
+
       utxo_cache.insert(OutPoint { txid: *txid, vout }, txout.clone());
     }
 
@@ -732,7 +1040,7 @@ impl<'index> Updater<'index> {
     &mut self,
     tx: &Transaction,
     txid: Txid,
-    sat_to_satpoint: &mut Table<u64, &SatPointValue>,
+    sat_to_satpoint: &mut LoggedTable<u64, &SatPointValue>,
     input_sat_ranges: &mut VecDeque<(u64, u64)>,
     sat_ranges_written: &mut u64,
     outputs_traversed: &mut u64,
@@ -795,6 +1103,8 @@ impl<'index> Updater<'index> {
   }
 
   fn commit(&mut self, wtx: WriteTransaction, utxo_cache: HashMap<OutPoint, TxOut>) -> Result {
+    let timer = metrics::COMMIT_DURATION.start_timer();
+
     log::info!(
       "Committing at block height {}, {} outputs traversed, {} in map, {} cached",
       self.height,
@@ -811,7 +1121,7 @@ impl<'index> Updater<'index> {
         self.outputs_inserted_since_flush,
       );
 
-      let mut outpoint_to_sat_ranges = wtx.open_table(OUTPOINT_TO_SAT_RANGES)?;
+      let mut outpoint_to_sat_ranges = self.undo_log.table(&wtx, OUTPOINT_TO_SAT_RANGES)?;
 
       for (outpoint, sat_ranges) in self.range_cache.drain() {
         outpoint_to_sat_ranges.insert(&outpoint, sat_ranges.as_slice())?;
@@ -823,22 +1133,58 @@ impl<'index> Updater<'index> {
     {
       log::info!("Flushing utxo cache with {} entries", utxo_cache.len());
 
-      let mut outpoint_to_txout = wtx.open_table(OUTPOINT_TO_TXOUT)?;
+      let mut outpoint_to_txout = self.undo_log.table(&wtx, OUTPOINT_TO_TXOUT)?;
 
       for (outpoint, txout) in utxo_cache {
         outpoint_to_txout.insert(&outpoint.store(), txout.store())?;
       }
     }
 
-    Index::increment_statistic(&wtx, Statistic::OutputsTraversed, self.outputs_traversed)?;
+    {
+      let mut statistic_to_count = self.undo_log.table(&wtx, STATISTIC_TO_COUNT)?;
+
+      for (statistic, n) in [
+        (Statistic::OutputsTraversed, self.outputs_traversed),
+        (Statistic::SatRanges, self.sat_ranges_since_flush),
+      ] {
+        let count = statistic_to_count
+          .get(&statistic.key())?
+          .map(|count| count.value())
+          .unwrap_or_default();
+
+        statistic_to_count.insert(&statistic.key(), &(count + n))?;
+      }
+    }
+
     self.outputs_traversed = 0;
-    Index::increment_statistic(&wtx, Statistic::SatRanges, self.sat_ranges_since_flush)?;
     self.sat_ranges_since_flush = 0;
+
+    if self.index.reorg_rollback == Rollback::UndoLog {
+      mem::take(&mut self.undo_log).save(
+        &wtx,
+        self.height - 1,
+        self.index.settings.undo_log_depth(),
+      )?;
+    }
+
     Index::increment_statistic(&wtx, Statistic::Commits, 1)?;
     wtx.commit()?;
 
+    timer.observe_duration();
+
+    metrics::RANGE_CACHE_ENTRIES.set(0);
+    metrics::UTXO_CACHE_ENTRIES.set(0);
+
     Reorg::update_savepoints(self.index, self.height)?;
 
+    self.index.dispatch_events()?;
+
     Ok(())
   }
+
+  fn undo_logging(&self) -> bool {
+    self
+      .undo_log_height
+      .is_some_and(|undo_log_height| self.height >= undo_log_height)
+  }
 }
@@ -0,0 +1,94 @@
+use super::*;
+
+pub(super) struct AddressHistory {
+  entries: HashMap<(ScriptBuf, Txid), AddressTransactionEntry>,
+  height: u32,
+}
+
+impl AddressHistory {
+  pub(super) fn new(height: u32) -> Self {
+    Self {
+      entries: HashMap::new(),
+      height,
+    }
+  }
+
+  fn entry(&mut self, script_pubkey: &Script, txid: Txid) -> &mut AddressTransactionEntry {
+    let height = self.height;
+
+    self
+      .entries
+      .entry((script_pubkey.into(), txid))
+      .or_insert_with(|| AddressTransactionEntry {
+        height,
+        inscriptions: Vec::new(),
+        received: 0,
+        runes: Vec::new(),
+        sent: 0,
+        txid,
+      })
+  }
+
+  pub(super) fn inscription(
+    &mut self,
+    script_pubkey: &Script,
+    txid: Txid,
+    inscription_id: InscriptionId,
+  ) {
+    let entry = self.entry(script_pubkey, txid);
+
+    if !entry.inscriptions.contains(&inscription_id) {
+      entry.inscriptions.push(inscription_id);
+    }
+  }
+
+  pub(super) fn receive(&mut self, script_pubkey: &Script, txid: Txid, value: u64) {
+    self.entry(script_pubkey, txid).received += value;
+  }
+
+  pub(super) fn rune(
+    &mut self,
+    script_pubkey: &Script,
+    txid: Txid,
+    id: RuneId,
+    received: u128,
+    sent: u128,
+  ) {
+    let entry = self.entry(script_pubkey, txid);
+
+    match entry.runes.iter_mut().find(|(rune, _, _)| *rune == id) {
+      Some((_, total_received, total_sent)) => {
+        *total_received = total_received.checked_add(received).unwrap();
+        *total_sent = total_sent.checked_add(sent).unwrap();
+      }
+      None => entry.runes.push((id, received, sent)),
+    }
+  }
+
+  pub(super) fn send(&mut self, script_pubkey: &Script, txid: Txid, value: u64) {
+    self.entry(script_pubkey, txid).sent += value;
+  }
+
+  pub(super) fn commit(
+    self,
+    table: &mut LoggedTable<(&[u8], u32, u32), AddressTransactionEntryValue>,
+    txdata: &[(Transaction, Txid)],
+  ) -> Result {
+    let positions = txdata
+      .iter()
+      .enumerate()
+      .map(|(i, (_, txid))| (*txid, u32::try_from(i).unwrap()))
+      .collect::<HashMap<Txid, u32>>();
+
+    for ((script_pubkey, txid), mut entry) in self.entries {
+      entry.runes.sort_by_key(|(id, _, _)| *id);
+
+      table.insert(
+        (script_pubkey.as_bytes(), self.height, positions[&txid]),
+        entry.store(),
+      )?;
+    }
+
+    Ok(())
+  }
+}
@@ -0,0 +1,66 @@
+use {super::*, bitcoin::hashes::sha256};
+
+pub(super) struct ContentStore {
+  inscriptions: Vec<(InscriptionId, Inscription)>,
+}
+
+impl ContentStore {
+  pub(super) fn new() -> Self {
+    Self {
+      inscriptions: Vec::new(),
+    }
+  }
+
+  pub(super) fn inscription(&mut self, inscription_id: InscriptionId, inscription: &Inscription) {
+    self
+      .inscriptions
+      .push((inscription_id, inscription.clone()));
+  }
+
+  pub(super) fn commit(
+    self,
+    inscription_id_to_sequence_number: &LoggedTable<'_, InscriptionIdValue, u32>,
+    content_hash_to_body: &mut LoggedTable<'_, &'static [u8; 32], &'static [u8]>,
+    content_hash_to_sequence_number: &mut LoggedMultimapTable<'_, &'static [u8; 32], u32>,
+    sequence_number_to_content: &mut LoggedTable<
+      '_,
+      u32,
+      (Option<&'static [u8; 32]>, &'static [u8]),
+    >,
+  ) -> Result {
+    let mut buffer = Vec::new();
+
+    for (inscription_id, mut inscription) in self.inscriptions {
+      let sequence_number = inscription_id_to_sequence_number
+        .get(&inscription_id.store())?
+        .unwrap()
+        .value();
+
+      let hash = inscription.body.take().map(|body| {
+        let hash = sha256::Hash::hash(&body).to_byte_array();
+        (hash, body)
+      });
+
+      if let Some((hash, body)) = &hash {
+        if content_hash_to_body.get(hash)?.is_none() {
+          content_hash_to_body.insert(hash, body.as_slice())?;
+        }
+
+        content_hash_to_sequence_number.insert(hash, sequence_number)?;
+
+        // keep `Some` to distinguish empty bodies from missing ones
+        inscription.body = Some(Vec::new());
+      }
+
+      buffer.clear();
+      ciborium::into_writer(&inscription, &mut buffer)?;
+
+      sequence_number_to_content.insert(
+        sequence_number,
+        (hash.as_ref().map(|(hash, _body)| hash), buffer.as_slice()),
+      )?;
+    }
+
+    Ok(())
+  }
+}
@@ -0,0 +1,63 @@
+use {super::*, crate::index::full_text};
+
+pub(super) struct FullTextIndexer {
+  documents: Vec<(InscriptionId, String)>,
+}
+
+impl FullTextIndexer {
+  pub(super) fn new() -> Self {
+    Self {
+      documents: Vec::new(),
+    }
+  }
+
+  pub(super) fn inscription(&mut self, inscription_id: InscriptionId, inscription: &Inscription) {
+    if let Some(text) = full_text::document(inscription) {
+      self.documents.push((inscription_id, text));
+    }
+  }
+
+  pub(super) fn commit(
+    self,
+    inscription_id_to_sequence_number: &LoggedTable<'_, InscriptionIdValue, u32>,
+    sequence_number_to_text: &mut LoggedTable<'_, u32, &'static str>,
+    statistic_to_count: &mut LoggedTable<'_, u64, u64>,
+    term_to_sequence_number: &mut LoggedMultimapTable<'_, &'static str, (u32, u32, u32)>,
+  ) -> Result {
+    let mut total_terms = 0;
+
+    for (inscription_id, text) in self.documents {
+      let sequence_number = inscription_id_to_sequence_number
+        .get(&inscription_id.store())?
+        .unwrap()
+        .value();
+
+      let mut counts = BTreeMap::<String, u32>::new();
+
+      for term in full_text::terms(&text) {
+        *counts.entry(term).or_default() += 1;
+      }
+
+      let length = counts.values().sum::<u32>();
+
+      // document length is stored in each posting, so that ranking doesn't
+      // read the document's text
+      for (term, count) in &counts {
+        term_to_sequence_number.insert(term.as_str(), (sequence_number, *count, length))?;
+      }
+
+      sequence_number_to_text.insert(sequence_number, text.as_str())?;
+
+      total_terms += u64::from(length);
+    }
+
+    let terms = statistic_to_count
+      .get(&Statistic::FullTextTerms.key())?
+      .map(|count| count.value())
+      .unwrap_or_default();
+
+    statistic_to_count.insert(&Statistic::FullTextTerms.key(), &(terms + total_terms))?;
+
+    Ok(())
+  }
+}
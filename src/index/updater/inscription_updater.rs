@@ -1,4 +1,4 @@
-use super::*;
+use {super::*, crate::index::metaprotocol::Transfer};
 
 #[derive(Debug, PartialEq, Copy, Clone)]
 enum Curse {
@@ -24,43 +24,63 @@ pub(super) struct Flotsam {
 enum Origin {
   New {
     cursed: bool,
+    details: Option<Box<InscriptionDetails>>,
     fee: u64,
     hidden: bool,
     parents: Vec<InscriptionId>,
     pointer: Option<u64>,
+    properties: InscriptionProperties,
     reinscription: bool,
     unbound: bool,
     vindicated: bool,
   },
   Old {
     old_satpoint: SatPoint,
+    old_script_pubkey: Option<ScriptBuf>,
   },
 }
 
 pub(super) struct InscriptionUpdater<'a, 'tx> {
+  pub(super) address_history: Option<&'a mut AddressHistory>,
   pub(super) blessed_inscription_count: u64,
   pub(super) chain: Chain,
-  pub(super) content_type_to_count: &'a mut Table<'tx, Option<&'static [u8]>, u64>,
+  pub(super) content_store: Option<&'a mut ContentStore>,
+  pub(super) full_text_indexer: Option<&'a mut FullTextIndexer>,
+  pub(super) content_type_to_count: &'a mut LoggedTable<'tx, Option<&'static [u8]>, u64>,
   pub(super) cursed_inscription_count: u64,
-  pub(super) event_sender: Option<&'a mpsc::Sender<Event>>,
+  pub(super) event_body_limit: Option<usize>,
+  pub(super) event_outbox: Option<&'a mut EventOutbox<'tx>>,
+  pub(super) fee_to_sequence_number: Option<&'a mut LoggedTable<'tx, (u64, u32), ()>>,
   pub(super) flotsam: Vec<Flotsam>,
   pub(super) height: u32,
+  pub(super) height_to_transfer: Option<&'a mut LoggedMultimapTable<'tx, u32, (u32, u32)>>,
   pub(super) home_inscription_count: u64,
-  pub(super) home_inscriptions: &'a mut Table<'tx, u32, InscriptionIdValue>,
-  pub(super) id_to_sequence_number: &'a mut Table<'tx, InscriptionIdValue, u32>,
+  pub(super) home_inscriptions: &'a mut LoggedTable<'tx, u32, InscriptionIdValue>,
+  pub(super) id_to_sequence_number: &'a mut LoggedTable<'tx, InscriptionIdValue, u32>,
   pub(super) index_transactions: bool,
-  pub(super) inscription_number_to_sequence_number: &'a mut Table<'tx, i32, u32>,
+  pub(super) inscription_number_to_sequence_number: &'a mut LoggedTable<'tx, i32, u32>,
   pub(super) lost_sats: u64,
+  pub(super) metaprotocols: Option<&'a mut Metaprotocols<'tx>>,
   pub(super) next_sequence_number: u32,
-  pub(super) outpoint_to_txout: &'a mut Table<'tx, &'static OutPointValue, TxOutValue>,
+  pub(super) outpoint_to_txout: &'a mut LoggedTable<'tx, &'static OutPointValue, TxOutValue>,
   pub(super) reward: u64,
+  pub(super) rich_events: bool,
   pub(super) transaction_buffer: Vec<u8>,
-  pub(super) transaction_id_to_transaction: &'a mut Table<'tx, &'static TxidValue, &'static [u8]>,
-  pub(super) sat_to_sequence_number: &'a mut MultimapTable<'tx, u64, u32>,
-  pub(super) satpoint_to_sequence_number: &'a mut MultimapTable<'tx, &'static SatPointValue, u32>,
-  pub(super) sequence_number_to_children: &'a mut MultimapTable<'tx, u32, u32>,
-  pub(super) sequence_number_to_entry: &'a mut Table<'tx, u32, InscriptionEntryValue>,
-  pub(super) sequence_number_to_satpoint: &'a mut Table<'tx, u32, &'static SatPointValue>,
+  pub(super) transaction_id_to_transaction:
+    &'a mut LoggedTable<'tx, &'static TxidValue, &'static [u8]>,
+  pub(super) sat_to_sequence_number: &'a mut LoggedMultimapTable<'tx, u64, u32>,
+  pub(super) satpoint_to_sequence_number:
+    &'a mut LoggedMultimapTable<'tx, &'static SatPointValue, u32>,
+  pub(super) script_pubkey_to_sequence_number:
+    Option<&'a mut LoggedMultimapTable<'tx, &'static [u8], u32>>,
+  pub(super) sequence_number_to_children: &'a mut LoggedMultimapTable<'tx, u32, u32>,
+  pub(super) sequence_number_to_entry: &'a mut LoggedTable<'tx, u32, InscriptionEntryValue>,
+  pub(super) sequence_number_to_properties:
+    Option<&'a mut LoggedTable<'tx, u32, InscriptionPropertiesValue>>,
+  pub(super) sequence_number_to_satpoint: &'a mut LoggedTable<'tx, u32, &'static SatPointValue>,
+  pub(super) sequence_number_to_transfer:
+    Option<&'a mut LoggedTable<'tx, (u32, u32), TransferEntryValue>>,
+  pub(super) size_to_sequence_number: Option<&'a mut LoggedTable<'tx, (u64, u32), ()>>,
   pub(super) timestamp: u32,
   pub(super) unbound_inscriptions: u64,
   pub(super) utxo_cache: &'a mut HashMap<OutPoint, TxOut>,
@@ -93,25 +113,11 @@ impl<'a, 'tx> InscriptionUpdater<'a, 'tx> {
       }
 
       // find existing inscriptions on input (transfers of inscriptions)
-      for (old_satpoint, inscription_id) in Index::inscriptions_on_output(
-        self.satpoint_to_sequence_number,
-        self.sequence_number_to_entry,
+      let transferred = Index::inscriptions_on_output(
+        &**self.satpoint_to_sequence_number,
+        &**self.sequence_number_to_entry,
         txin.previous_output,
-      )? {
-        let offset = total_input_value + old_satpoint.offset;
-        floating_inscriptions.push(Flotsam {
-          offset,
-          inscription_id,
-          origin: Origin::Old { old_satpoint },
-        });
-
-        inscribed_offsets
-          .entry(offset)
-          .or_insert((inscription_id, 0))
-          .1 += 1;
-      }
-
-      let offset = total_input_value;
+      )?;
 
       // multi-level cache for UTXO set to get to the input amount
       let txout = if let Some(txout) = self.utxo_cache.remove(&txin.previous_output) {
@@ -130,6 +136,29 @@ impl<'a, 'tx> InscriptionUpdater<'a, 'tx> {
         })?
       };
 
+      for (old_satpoint, inscription_id) in transferred {
+        let offset = total_input_value + old_satpoint.offset;
+        floating_inscriptions.push(Flotsam {
+          offset,
+          inscription_id,
+          origin: Origin::Old {
+            old_satpoint,
+            old_script_pubkey: (self.address_history.is_some()
+              || self.metaprotocols.is_some()
+              || self.script_pubkey_to_sequence_number.is_some()
+              || self.sequence_number_to_transfer.is_some())
+            .then(|| txout.script_pubkey.clone()),
+          },
+        });
+
+        inscribed_offsets
+          .entry(offset)
+          .or_insert((inscription_id, 0))
+          .1 += 1;
+      }
+
+      let offset = total_input_value;
+
       total_input_value += txout.value;
 
       // go through all inscriptions in this input
@@ -193,6 +222,18 @@ impl<'a, 'tx> InscriptionUpdater<'a, 'tx> {
           .filter(|&pointer| pointer < total_output_value)
           .unwrap_or(offset);
 
+        if let Some(content_store) = self.content_store.as_mut() {
+          content_store.inscription(inscription_id, &inscription.payload);
+        }
+
+        if let Some(full_text_indexer) = self.full_text_indexer.as_mut() {
+          full_text_indexer.inscription(inscription_id, &inscription.payload);
+        }
+
+        if let Some(metaprotocols) = self.metaprotocols.as_mut() {
+          metaprotocols.inscription(inscription_id, &inscription.payload);
+        }
+
         let content_type = inscription.payload.content_type.as_deref();
 
         let content_type_count = self
@@ -205,15 +246,38 @@ impl<'a, 'tx> InscriptionUpdater<'a, 'tx> {
           .content_type_to_count
           .insert(content_type, content_type_count + 1)?;
 
+        let details = (self.rich_events && self.event_outbox.is_some()).then(|| {
+          let payload = &inscription.payload;
+
+          Box::new(InscriptionDetails {
+            body: payload
+              .body()
+              .filter(|body| {
+                self
+                  .event_body_limit
+                  .is_some_and(|limit| body.len() <= limit)
+              })
+              .map(hex::encode),
+            content_length: payload.content_length(),
+            content_type: payload.content_type().map(str::to_string),
+            delegate: payload.delegate(),
+            metadata: payload.metadata.as_ref().map(hex::encode),
+            metaprotocol: payload.metaprotocol().map(str::to_string),
+            ..default()
+          })
+        });
+
         floating_inscriptions.push(Flotsam {
           inscription_id,
           offset,
           origin: Origin::New {
             cursed: curse.is_some() && !jubilant,
+            details,
             fee: 0,
             hidden: inscription.payload.hidden(),
             parents: inscription.payload.parents(),
             pointer: inscription.payload.pointer(),
+            properties: InscriptionProperties::from(&inscription.payload),
             reinscription: inscribed_offsets.contains_key(&offset),
             unbound: txout.value == 0
               || curse == Some(Curse::UnrecognizedEvenField)
@@ -326,6 +390,12 @@ impl<'a, 'tx> InscriptionUpdater<'a, 'tx> {
       );
     }
 
+    let fee = if is_coinbase {
+      0
+    } else {
+      total_input_value - output_value
+    };
+
     for (new_satpoint, mut flotsam, op_return) in new_locations.into_iter() {
       let new_satpoint = match flotsam.origin {
         Origin::New {
@@ -348,7 +418,15 @@ impl<'a, 'tx> InscriptionUpdater<'a, 'tx> {
         _ => new_satpoint,
       };
 
-      self.update_inscription_location(input_sat_ranges, flotsam, new_satpoint, op_return)?;
+      self.update_inscription_location(
+        input_sat_ranges,
+        flotsam,
+        new_satpoint,
+        op_return,
+        txid,
+        fee,
+        is_coinbase,
+      )?;
     }
 
     if is_coinbase {
@@ -357,7 +435,15 @@ impl<'a, 'tx> InscriptionUpdater<'a, 'tx> {
           outpoint: OutPoint::null(),
           offset: self.lost_sats + flotsam.offset - output_value,
         };
-        self.update_inscription_location(input_sat_ranges, flotsam, new_satpoint, false)?;
+        self.update_inscription_location(
+          input_sat_ranges,
+          flotsam,
+          new_satpoint,
+          false,
+          txid,
+          0,
+          true,
+        )?;
       }
       self.lost_sats += self.reward - output_value;
       Ok(())
@@ -396,10 +482,16 @@ impl<'a, 'tx> InscriptionUpdater<'a, 'tx> {
     flotsam: Flotsam,
     new_satpoint: SatPoint,
     op_return: bool,
+    txid: Txid,
+    fee: u64,
+    spent_as_fee: bool,
   ) -> Result {
     let inscription_id = flotsam.inscription_id;
     let (unbound, sequence_number) = match flotsam.origin {
-      Origin::Old { old_satpoint } => {
+      Origin::Old {
+        old_satpoint,
+        old_script_pubkey,
+      } => {
         self
           .satpoint_to_sequence_number
           .remove_all(&old_satpoint.store())?;
@@ -410,6 +502,49 @@ impl<'a, 'tx> InscriptionUpdater<'a, 'tx> {
           .unwrap()
           .value();
 
+        if let (Some(script_pubkey_to_sequence_number), Some(old_script_pubkey)) = (
+          self.script_pubkey_to_sequence_number.as_mut(),
+          &old_script_pubkey,
+        ) {
+          script_pubkey_to_sequence_number.remove(old_script_pubkey.as_bytes(), sequence_number)?;
+        }
+
+        if let (Some(address_history), Some(old_script_pubkey)) =
+          (self.address_history.as_mut(), &old_script_pubkey)
+        {
+          address_history.inscription(old_script_pubkey, txid, inscription_id);
+        }
+
+        if let Some(sequence_number_to_transfer) = self.sequence_number_to_transfer.as_mut() {
+          let next = sequence_number_to_transfer
+            .range((sequence_number, 0)..=(sequence_number, u32::MAX))?
+            .next_back()
+            .transpose()?
+            .map(|(key, _entry)| key.value().1 + 1)
+            .unwrap_or_default();
+
+          if let Some(height_to_transfer) = self.height_to_transfer.as_mut() {
+            height_to_transfer.insert(self.height, (sequence_number, next))?;
+          }
+
+          sequence_number_to_transfer.insert(
+            (sequence_number, next),
+            TransferEntry {
+              fee,
+              height: self.height,
+              new_satpoint,
+              old_satpoint,
+              receiver: self
+                .utxo_cache
+                .get(&new_satpoint.outpoint)
+                .map(|txout| txout.script_pubkey.clone()),
+              sender: old_script_pubkey.clone(),
+              txid,
+            }
+            .store(),
+          )?;
+        }
+
         if op_return {
           let entry = InscriptionEntry::load(
             self
@@ -428,8 +563,8 @@ impl<'a, 'tx> InscriptionUpdater<'a, 'tx> {
           )?;
         }
 
-        if let Some(sender) = self.event_sender {
-          sender.blocking_send(Event::InscriptionTransferred {
+        if let Some(event_outbox) = &mut self.event_outbox {
+          event_outbox.push(Event::InscriptionTransferred {
             block_height: self.height,
             inscription_id,
             new_location: new_satpoint,
@@ -438,14 +573,32 @@ impl<'a, 'tx> InscriptionUpdater<'a, 'tx> {
           })?;
         }
 
+        if let Some(metaprotocols) = self.metaprotocols.as_mut() {
+          metaprotocols.transferred(
+            self.event_outbox.as_deref_mut(),
+            &Transfer {
+              height: self.height,
+              inscription_id,
+              receiver: self
+                .utxo_cache
+                .get(&new_satpoint.outpoint)
+                .map(|txout| txout.script_pubkey.as_script()),
+              sequence_number,
+              spent_as_fee,
+            },
+          )?;
+        }
+
         (false, sequence_number)
       }
       Origin::New {
         cursed,
+        details,
         fee,
         hidden,
         parents,
         pointer: _,
+        properties,
         reinscription,
         unbound,
         vindicated,
@@ -524,10 +677,25 @@ impl<'a, 'tx> InscriptionUpdater<'a, 'tx> {
           })
           .collect::<Result<Vec<u32>>>()?;
 
-        if let Some(sender) = self.event_sender {
-          sender.blocking_send(Event::InscriptionCreated {
+        if let Some(event_outbox) = &mut self.event_outbox {
+          let details = details.map(|details| {
+            Box::new(InscriptionDetails {
+              address: self
+                .utxo_cache
+                .get(&new_satpoint.outpoint)
+                .and_then(|txout| self.chain.address_from_script(&txout.script_pubkey).ok())
+                .map(|address| address.to_string()),
+              fee,
+              inscription_number,
+              sat,
+              ..*details
+            })
+          });
+
+          event_outbox.push(Event::InscriptionCreated {
             block_height: self.height,
             charms,
+            details,
             inscription_id,
             location: (!unbound).then_some(new_satpoint),
             parent_inscription_ids: parents,
@@ -535,6 +703,20 @@ impl<'a, 'tx> InscriptionUpdater<'a, 'tx> {
           })?;
         }
 
+        if let Some(metaprotocols) = self.metaprotocols.as_mut() {
+          metaprotocols.created(
+            self.event_outbox.as_deref_mut(),
+            self.height,
+            inscription_id,
+            inscription_number,
+            (!unbound)
+              .then(|| self.utxo_cache.get(&new_satpoint.outpoint))
+              .flatten()
+              .map(|txout| txout.script_pubkey.as_script()),
+            sequence_number,
+          )?;
+        }
+
         self.sequence_number_to_entry.insert(
           sequence_number,
           &InscriptionEntry {
@@ -555,6 +737,18 @@ impl<'a, 'tx> InscriptionUpdater<'a, 'tx> {
           .id_to_sequence_number
           .insert(&inscription_id.store(), sequence_number)?;
 
+        if let Some(fee_to_sequence_number) = self.fee_to_sequence_number.as_mut() {
+          fee_to_sequence_number.insert((fee, sequence_number), ())?;
+        }
+
+        if let Some(size_to_sequence_number) = self.size_to_sequence_number.as_mut() {
+          size_to_sequence_number.insert((properties.content_length, sequence_number), ())?;
+        }
+
+        if let Some(sequence_number_to_properties) = self.sequence_number_to_properties.as_mut() {
+          sequence_number_to_properties.insert(sequence_number, properties.store())?;
+        }
+
         if !hidden {
           self
             .home_inscriptions
@@ -579,6 +773,25 @@ impl<'a, 'tx> InscriptionUpdater<'a, 'tx> {
       self.unbound_inscriptions += 1;
       new_unbound_satpoint.store()
     } else {
+      if self.address_history.is_some() || self.script_pubkey_to_sequence_number.is_some() {
+        if let Some(txout) = self
+          .utxo_cache
+          .get(&new_satpoint.outpoint)
+          .filter(|txout| !txout.script_pubkey.is_op_return())
+        {
+          if let Some(address_history) = self.address_history.as_mut() {
+            address_history.inscription(&txout.script_pubkey, txid, inscription_id);
+          }
+
+          if let Some(script_pubkey_to_sequence_number) =
+            self.script_pubkey_to_sequence_number.as_mut()
+          {
+            script_pubkey_to_sequence_number
+              .insert(txout.script_pubkey.as_bytes(), sequence_number)?;
+          }
+        }
+      }
+
       new_satpoint.store()
     };
 
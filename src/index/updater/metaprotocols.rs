@@ -0,0 +1,110 @@
+use {
+  super::*,
+  crate::index::metaprotocol::{
+    Creation, MetaprotocolIndexer, MetaprotocolStore, MetaprotocolTables, Transfer,
+  },
+};
+
+pub(super) struct Metaprotocols<'tx> {
+  indexers: Vec<(&'static dyn MetaprotocolIndexer, MetaprotocolTables<'tx>)>,
+  inscriptions: HashMap<InscriptionId, Inscription>,
+}
+
+impl<'tx> Metaprotocols<'tx> {
+  pub(super) fn new(
+    indexers: &[&'static dyn MetaprotocolIndexer],
+    undo_log: &UndoLog,
+    wtx: &'tx WriteTransaction,
+  ) -> Result<Self> {
+    Ok(Self {
+      indexers: indexers
+        .iter()
+        .map(|indexer| {
+          Ok((
+            *indexer,
+            indexer
+              .tables()
+              .iter()
+              .map(|table| Ok((*table, undo_log.table(wtx, TableDefinition::new(table))?)))
+              .collect::<Result<MetaprotocolTables>>()?,
+          ))
+        })
+        .collect::<Result<_>>()?,
+      inscriptions: HashMap::new(),
+    })
+  }
+
+  pub(super) fn inscription(&mut self, inscription_id: InscriptionId, inscription: &Inscription) {
+    self
+      .inscriptions
+      .insert(inscription_id, inscription.clone());
+  }
+
+  pub(super) fn created(
+    &mut self,
+    event_outbox: Option<&mut EventOutbox>,
+    height: u32,
+    inscription_id: InscriptionId,
+    inscription_number: i32,
+    owner: Option<&Script>,
+    sequence_number: u32,
+  ) -> Result {
+    let Some(inscription) = self.inscriptions.remove(&inscription_id) else {
+      return Ok(());
+    };
+
+    let creation = Creation {
+      height,
+      inscription: &inscription,
+      inscription_id,
+      inscription_number,
+      owner,
+      sequence_number,
+    };
+
+    self.call(event_outbox, height, inscription_id, |indexer, store| {
+      indexer.inscription_created(store, &creation)
+    })
+  }
+
+  pub(super) fn transferred(
+    &mut self,
+    event_outbox: Option<&mut EventOutbox>,
+    transfer: &Transfer,
+  ) -> Result {
+    self.call(
+      event_outbox,
+      transfer.height,
+      transfer.inscription_id,
+      |indexer, store| indexer.inscription_transferred(store, transfer),
+    )
+  }
+
+  fn call(
+    &mut self,
+    mut event_outbox: Option<&mut EventOutbox>,
+    height: u32,
+    inscription_id: InscriptionId,
+    f: impl Fn(&dyn MetaprotocolIndexer, &mut MetaprotocolStore) -> Result,
+  ) -> Result {
+    for (indexer, tables) in &mut self.indexers {
+      let mut store = MetaprotocolStore::new(tables);
+
+      f(*indexer, &mut store)?;
+
+      if let Some(event_outbox) = event_outbox.as_mut() {
+        for (kind, data) in store.events {
+          event_outbox.push(Event::MetaprotocolEvent {
+            block_height: height,
+            data,
+            inscription_id,
+            kind,
+            protocol: indexer.name().into(),
+          })?;
+        }
+      }
+    }
+
+    Ok(())
+  }
+}
@@ -1,37 +1,190 @@
 use super::*;
 
 pub(super) struct RuneUpdater<'a, 'tx, 'client> {
+  pub(super) address_history: Option<&'a mut AddressHistory>,
+  pub(super) balance_to_holder:
+    &'a mut LoggedMultimapTable<'tx, (RuneIdValue, u128), &'static [u8]>,
   pub(super) block_time: u32,
   pub(super) burned: HashMap<RuneId, Lot>,
   pub(super) client: &'client Client,
-  pub(super) event_sender: Option<&'a mpsc::Sender<Event>>,
+  pub(super) event_outbox: Option<&'a mut EventOutbox<'tx>>,
   pub(super) height: u32,
-  pub(super) id_to_entry: &'a mut Table<'tx, RuneIdValue, RuneEntryValue>,
-  pub(super) inscription_id_to_sequence_number: &'a Table<'tx, InscriptionIdValue, u32>,
+  pub(super) holder_to_balance: &'a mut LoggedTable<'tx, (RuneIdValue, &'static [u8]), u128>,
+  /// amounts received and sent by each holder in the current block
+  pub(super) holders: HashMap<(RuneId, ScriptBuf), (Lot, Lot)>,
+  pub(super) id_to_entry: &'a mut LoggedTable<'tx, RuneIdValue, RuneEntryValue>,
+  pub(super) id_to_holder_count: &'a mut LoggedTable<'tx, RuneIdValue, u64>,
+  pub(super) inscription_id_to_sequence_number: &'a LoggedTable<'tx, InscriptionIdValue, u32>,
   pub(super) minimum: Rune,
-  pub(super) outpoint_to_balances: &'a mut Table<'tx, &'static OutPointValue, &'static [u8]>,
-  pub(super) rune_to_id: &'a mut Table<'tx, u128, RuneIdValue>,
+  pub(super) outpoint_to_balances: &'a mut LoggedTable<'tx, &'static OutPointValue, &'static [u8]>,
+  pub(super) outpoint_to_holder: &'a mut LoggedTable<'tx, &'static OutPointValue, &'static [u8]>,
+  pub(super) outpoint_to_spent_balances:
+    Option<&'a mut LoggedTable<'tx, &'static OutPointValue, &'static [u8]>>,
+  pub(super) rune_to_id: &'a mut LoggedTable<'tx, u128, RuneIdValue>,
   pub(super) runes: u64,
-  pub(super) sequence_number_to_rune_id: &'a mut Table<'tx, u32, RuneIdValue>,
-  pub(super) statistic_to_count: &'a mut Table<'tx, u64, u64>,
-  pub(super) transaction_id_to_rune: &'a mut Table<'tx, &'static TxidValue, u128>,
+  pub(super) script_pubkey_to_balance:
+    Option<&'a mut LoggedTable<'tx, (&'static [u8], RuneIdValue), u128>>,
+  pub(super) sequence_number_to_rune_id: &'a mut LoggedTable<'tx, u32, RuneIdValue>,
+  pub(super) statistic_to_count: &'a mut LoggedTable<'tx, u64, u64>,
+  pub(super) transaction_id_to_mint: Option<&'a mut LoggedTable<'tx, &'static TxidValue, u128>>,
+  pub(super) transaction_id_to_rune: &'a mut LoggedTable<'tx, &'static TxidValue, u128>,
+}
+
+/// Allocates the `unallocated` runes of `tx`, which include its inputs, mint,
+/// and premine, to its outputs, returning the balance of each output and the
+/// runes that were burned. Runes allocated to `OP_RETURN` outputs are burned.
+pub(crate) fn allocate(
+  tx: &Transaction,
+  artifact: Option<&Artifact>,
+  etched: Option<RuneId>,
+  mut unallocated: HashMap<RuneId, Lot>,
+) -> (Vec<HashMap<RuneId, Lot>>, BTreeMap<RuneId, Lot>) {
+  let mut allocated: Vec<HashMap<RuneId, Lot>> = vec![HashMap::new(); tx.output.len()];
+
+  if let Some(Artifact::Runestone(runestone)) = artifact {
+    for Edict { id, amount, output } in runestone.edicts.iter().copied() {
+      let amount = Lot(amount);
+
+      // edicts with output values greater than the number of outputs
+      // should never be produced by the edict parser
+      let output = usize::try_from(output).unwrap();
+      assert!(output <= tx.output.len());
+
+      let id = if id == RuneId::default() {
+        let Some(id) = etched else {
+          continue;
+        };
+
+        id
+      } else {
+        id
+      };
+
+      let Some(balance) = unallocated.get_mut(&id) else {
+        continue;
+      };
+
+      let mut allocate = |balance: &mut Lot, amount: Lot, output: usize| {
+        if amount > 0 {
+          *balance -= amount;
+          *allocated[output].entry(id).or_default() += amount;
+        }
+      };
+
+      if output == tx.output.len() {
+        // find non-OP_RETURN outputs
+        let destinations = tx
+          .output
+          .iter()
+          .enumerate()
+          .filter_map(|(output, tx_out)| (!tx_out.script_pubkey.is_op_return()).then_some(output))
+          .collect::<Vec<usize>>();
+
+        if !destinations.is_empty() {
+          if amount == 0 {
+            // if amount is zero, divide balance between eligible outputs
+            let amount = *balance / destinations.len() as u128;
+            let remainder = usize::try_from(*balance % destinations.len() as u128).unwrap();
+
+            for (i, output) in destinations.iter().enumerate() {
+              allocate(
+                balance,
+                if i < remainder { amount + 1 } else { amount },
+                *output,
+              );
+            }
+          } else {
+            // if amount is non-zero, distribute amount to eligible outputs
+            for output in destinations {
+              allocate(balance, amount.min(*balance), output);
+            }
+          }
+        }
+      } else {
+        // Get the allocatable amount
+        let amount = if amount == 0 {
+          *balance
+        } else {
+          amount.min(*balance)
+        };
+
+        allocate(balance, amount, output);
+      }
+    }
+  }
+
+  let mut burned: BTreeMap<RuneId, Lot> = BTreeMap::new();
+
+  if let Some(Artifact::Cenotaph(_)) = artifact {
+    for (id, balance) in unallocated {
+      *burned.entry(id).or_default() += balance;
+    }
+  } else {
+    let pointer = artifact
+      .map(|artifact| match artifact {
+        Artifact::Runestone(runestone) => runestone.pointer,
+        Artifact::Cenotaph(_) => unreachable!(),
+      })
+      .unwrap_or_default();
+
+    // assign all un-allocated runes to the default output, or the first non
+    // OP_RETURN output if there is no default
+    if let Some(vout) = pointer
+      .map(|pointer| pointer.into_usize())
+      .inspect(|&pointer| assert!(pointer < allocated.len()))
+      .or_else(|| {
+        tx.output
+          .iter()
+          .enumerate()
+          .find(|(_vout, tx_out)| !tx_out.script_pubkey.is_op_return())
+          .map(|(vout, _tx_out)| vout)
+      })
+    {
+      for (id, balance) in unallocated {
+        if balance > 0 {
+          *allocated[vout].entry(id).or_default() += balance;
+        }
+      }
+    } else {
+      for (id, balance) in unallocated {
+        if balance > 0 {
+          *burned.entry(id).or_default() += balance;
+        }
+      }
+    }
+  }
+
+  // burn balances allocated to OP_RETURN outputs
+  for (vout, balances) in allocated.iter_mut().enumerate() {
+    if tx.output[vout].script_pubkey.is_op_return() {
+      for (id, balance) in balances.drain() {
+        *burned.entry(id).or_default() += balance;
+      }
+    }
+  }
+
+  (allocated, burned)
 }
 
 impl<'a, 'tx, 'client> RuneUpdater<'a, 'tx, 'client> {
   pub(super) fn index_runes(&mut self, tx_index: u32, tx: &Transaction, txid: Txid) -> Result<()> {
     let artifact = Runestone::decipher(tx);
 
-    let mut unallocated = self.unallocated(tx)?;
+    let mut unallocated = self.unallocated(tx, txid)?;
 
-    let mut allocated: Vec<HashMap<RuneId, Lot>> = vec![HashMap::new(); tx.output.len()];
+    let mut etched = None;
 
     if let Some(artifact) = &artifact {
       if let Some(id) = artifact.mint() {
         if let Some(amount) = self.mint(id)? {
           *unallocated.entry(id).or_default() += amount;
 
-          if let Some(sender) = self.event_sender {
-            sender.blocking_send(Event::RuneMinted {
+          if let Some(transaction_id_to_mint) = self.transaction_id_to_mint.as_mut() {
+            transaction_id_to_mint.insert(&txid.store(), amount.n())?;
+          }
+
+          if let Some(event_outbox) = &mut self.event_outbox {
+            event_outbox.push(Event::RuneMinted {
               block_height: self.height,
               txid,
               rune_id: id,
@@ -41,131 +194,23 @@ impl<'a, 'tx, 'client> RuneUpdater<'a, 'tx, 'client> {
         }
       }
 
-      let etched = self.etched(tx_index, tx, artifact)?;
-
-      if let Artifact::Runestone(runestone) = artifact {
-        if let Some((id, ..)) = etched {
-          *unallocated.entry(id).or_default() +=
-            runestone.etching.unwrap().premine.unwrap_or_default();
-        }
-
-        for Edict { id, amount, output } in runestone.edicts.iter().copied() {
-          let amount = Lot(amount);
-
-          // edicts with output values greater than the number of outputs
-          // should never be produced by the edict parser
-          let output = usize::try_from(output).unwrap();
-          assert!(output <= tx.output.len());
-
-          let id = if id == RuneId::default() {
-            let Some((id, ..)) = etched else {
-              continue;
-            };
-
-            id
-          } else {
-            id
-          };
+      etched = self.etched(tx_index, tx, artifact)?;
 
-          let Some(balance) = unallocated.get_mut(&id) else {
-            continue;
-          };
-
-          let mut allocate = |balance: &mut Lot, amount: Lot, output: usize| {
-            if amount > 0 {
-              *balance -= amount;
-              *allocated[output].entry(id).or_default() += amount;
-            }
-          };
-
-          if output == tx.output.len() {
-            // find non-OP_RETURN outputs
-            let destinations = tx
-              .output
-              .iter()
-              .enumerate()
-              .filter_map(|(output, tx_out)| {
-                (!tx_out.script_pubkey.is_op_return()).then_some(output)
-              })
-              .collect::<Vec<usize>>();
-
-            if !destinations.is_empty() {
-              if amount == 0 {
-                // if amount is zero, divide balance between eligible outputs
-                let amount = *balance / destinations.len() as u128;
-                let remainder = usize::try_from(*balance % destinations.len() as u128).unwrap();
-
-                for (i, output) in destinations.iter().enumerate() {
-                  allocate(
-                    balance,
-                    if i < remainder { amount + 1 } else { amount },
-                    *output,
-                  );
-                }
-              } else {
-                // if amount is non-zero, distribute amount to eligible outputs
-                for output in destinations {
-                  allocate(balance, amount.min(*balance), output);
-                }
-              }
-            }
-          } else {
-            // Get the allocatable amount
-            let amount = if amount == 0 {
-              *balance
-            } else {
-              amount.min(*balance)
-            };
-
-            allocate(balance, amount, output);
-          }
-        }
-      }
-
-      if let Some((id, rune)) = etched {
-        self.create_rune_entry(txid, artifact, id, rune)?;
+      if let (Artifact::Runestone(runestone), Some((id, ..))) = (artifact, etched) {
+        *unallocated.entry(id).or_default() +=
+          runestone.etching.unwrap().premine.unwrap_or_default();
       }
     }
 
-    let mut burned: HashMap<RuneId, Lot> = HashMap::new();
-
-    if let Some(Artifact::Cenotaph(_)) = artifact {
-      for (id, balance) in unallocated {
-        *burned.entry(id).or_default() += balance;
-      }
-    } else {
-      let pointer = artifact
-        .map(|artifact| match artifact {
-          Artifact::Runestone(runestone) => runestone.pointer,
-          Artifact::Cenotaph(_) => unreachable!(),
-        })
-        .unwrap_or_default();
+    let (allocated, burned) = allocate(
+      tx,
+      artifact.as_ref(),
+      etched.map(|(id, _rune)| id),
+      unallocated,
+    );
 
-      // assign all un-allocated runes to the default output, or the first non
-      // OP_RETURN output if there is no default
-      if let Some(vout) = pointer
-        .map(|pointer| pointer.into_usize())
-        .inspect(|&pointer| assert!(pointer < allocated.len()))
-        .or_else(|| {
-          tx.output
-            .iter()
-            .enumerate()
-            .find(|(_vout, tx_out)| !tx_out.script_pubkey.is_op_return())
-            .map(|(vout, _tx_out)| vout)
-        })
-      {
-        for (id, balance) in unallocated {
-          if balance > 0 {
-            *allocated[vout].entry(id).or_default() += balance;
-          }
-        }
-      } else {
-        for (id, balance) in unallocated {
-          if balance > 0 {
-            *burned.entry(id).or_default() += balance;
-          }
-        }
-      }
+    if let (Some(artifact), Some((id, rune))) = (&artifact, etched) {
+      self.create_rune_entry(txid, artifact, id, rune)?;
     }
 
     // update outpoint balances
@@ -175,14 +220,6 @@ impl<'a, 'tx, 'client> RuneUpdater<'a, 'tx, 'client> {
         continue;
       }
 
-      // increment burned balances
-      if tx.output[vout].script_pubkey.is_op_return() {
-        for (id, balance) in &balances {
-          *burned.entry(*id).or_default() += *balance;
-        }
-        continue;
-      }
-
       buffer.clear();
 
       let mut balances = balances.into_iter().collect::<Vec<(RuneId, Lot)>>();
@@ -195,11 +232,23 @@ impl<'a, 'tx, 'client> RuneUpdater<'a, 'tx, 'client> {
         vout: vout.try_into().unwrap(),
       };
 
+      let script_pubkey = &tx.output[vout].script_pubkey;
+
       for (id, balance) in balances {
         Index::encode_rune_balance(id, balance.n(), &mut buffer);
 
-        if let Some(sender) = self.event_sender {
-          sender.blocking_send(Event::RuneTransferred {
+        self
+          .holders
+          .entry((id, script_pubkey.clone()))
+          .or_default()
+          .0 += balance;
+
+        if let Some(address_history) = self.address_history.as_mut() {
+          address_history.rune(script_pubkey, txid, id, balance.n(), 0);
+        }
+
+        if let Some(event_outbox) = &mut self.event_outbox {
+          event_outbox.push(Event::RuneTransferred {
             outpoint,
             block_height: self.height,
             txid,
@@ -212,14 +261,18 @@ impl<'a, 'tx, 'client> RuneUpdater<'a, 'tx, 'client> {
       self
         .outpoint_to_balances
         .insert(&outpoint.store(), buffer.as_slice())?;
+
+      self
+        .outpoint_to_holder
+        .insert(&outpoint.store(), script_pubkey.as_bytes())?;
     }
 
     // increment entries with burned runes
     for (id, amount) in burned {
       *self.burned.entry(id).or_default() += amount;
 
-      if let Some(sender) = self.event_sender {
-        sender.blocking_send(Event::RuneBurned {
+      if let Some(event_outbox) = &mut self.event_outbox {
+        event_outbox.push(Event::RuneBurned {
           block_height: self.height,
           txid,
           rune_id: id,
@@ -231,13 +284,84 @@ impl<'a, 'tx, 'client> RuneUpdater<'a, 'tx, 'client> {
     Ok(())
   }
 
-  pub(super) fn update(self) -> Result {
+  pub(super) fn update(mut self) -> Result {
     for (rune_id, burned) in self.burned {
       let mut entry = RuneEntry::load(self.id_to_entry.get(&rune_id.store())?.unwrap().value());
       entry.burned = entry.burned.checked_add(burned.n()).unwrap();
       self.id_to_entry.insert(&rune_id.store(), entry.store())?;
     }
 
+    let mut holder_counts: HashMap<RuneId, (u64, u64)> = HashMap::new();
+
+    for ((id, script_pubkey), (received, sent)) in self.holders {
+      let key = (id.store(), script_pubkey.as_bytes());
+
+      let old = self
+        .holder_to_balance
+        .get(&key)?
+        .map(|balance| balance.value())
+        .unwrap_or_default();
+
+      let new = if received >= sent {
+        old.checked_add((received - sent).n()).unwrap()
+      } else {
+        old.checked_sub((sent - received).n()).unwrap()
+      };
+
+      if new == old {
+        continue;
+      }
+
+      if old > 0 {
+        self
+          .balance_to_holder
+          .remove(&(id.store(), old), script_pubkey.as_bytes())?;
+      }
+
+      if new > 0 {
+        self.holder_to_balance.insert(&key, new)?;
+        self
+          .balance_to_holder
+          .insert(&(id.store(), new), script_pubkey.as_bytes())?;
+      } else {
+        self.holder_to_balance.remove(&key)?;
+      }
+
+      if let Some(script_pubkey_to_balance) = self.script_pubkey_to_balance.as_mut() {
+        let key = (script_pubkey.as_bytes(), id.store());
+
+        if new > 0 {
+          script_pubkey_to_balance.insert(&key, new)?;
+        } else {
+          script_pubkey_to_balance.remove(&key)?;
+        }
+      }
+
+      let (added, removed) = holder_counts.entry(id).or_default();
+
+      if old == 0 {
+        *added += 1;
+      } else if new == 0 {
+        *removed += 1;
+      }
+    }
+
+    for (id, (added, removed)) in holder_counts {
+      let count = self
+        .id_to_holder_count
+        .get(&id.store())?
+        .map(|count| count.value())
+        .unwrap_or_default();
+
+      let count = (count + added).checked_sub(removed).unwrap();
+
+      if count > 0 {
+        self.id_to_holder_count.insert(&id.store(), count)?;
+      } else {
+        self.id_to_holder_count.remove(&id.store())?;
+      }
+    }
+
     Ok(())
   }
 
@@ -308,8 +432,8 @@ impl<'a, 'tx, 'client> RuneUpdater<'a, 'tx, 'client> {
 
     self.id_to_entry.insert(id.store(), entry.store())?;
 
-    if let Some(sender) = self.event_sender {
-      sender.blocking_send(Event::RuneEtched {
+    if let Some(event_outbox) = &mut self.event_outbox {
+      event_outbox.push(Event::RuneEtched {
         block_height: self.height,
         txid,
         rune_id: id,
@@ -466,7 +590,7 @@ impl<'a, 'tx, 'client> RuneUpdater<'a, 'tx, 'client> {
     Ok(false)
   }
 
-  fn unallocated(&mut self, tx: &Transaction) -> Result<HashMap<RuneId, Lot>> {
+  fn unallocated(&mut self, tx: &Transaction, txid: Txid) -> Result<HashMap<RuneId, Lot>> {
     // map of rune ID to un-allocated balance of that rune
     let mut unallocated: HashMap<RuneId, Lot> = HashMap::new();
 
@@ -476,12 +600,35 @@ impl<'a, 'tx, 'client> RuneUpdater<'a, 'tx, 'client> {
         .outpoint_to_balances
         .remove(&input.previous_output.store())?
       {
+        let script_pubkey = ScriptBuf::from_bytes(
+          self
+            .outpoint_to_holder
+            .remove(&input.previous_output.store())?
+            .unwrap()
+            .value()
+            .to_vec(),
+        );
+
         let buffer = guard.value();
+
+        if let Some(outpoint_to_spent_balances) = self.outpoint_to_spent_balances.as_mut() {
+          outpoint_to_spent_balances.insert(&input.previous_output.store(), buffer)?;
+        }
+
         let mut i = 0;
         while i < buffer.len() {
           let ((id, balance), len) = Index::decode_rune_balance(&buffer[i..]).unwrap();
           i += len;
           *unallocated.entry(id).or_default() += balance;
+          self
+            .holders
+            .entry((id, script_pubkey.clone()))
+            .or_default()
+            .1 += balance;
+
+          if let Some(address_history) = self.address_history.as_mut() {
+            address_history.rune(&script_pubkey, txid, id, 0, balance);
+          }
         }
       }
     }
@@ -0,0 +1,630 @@
+use {super::*, redb::ReadTransaction};
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub enum Check {
+  InscriptionNumbers,
+  RuneSupply,
+  SatRanges,
+  Satpoints,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub enum Discrepancy {
+  InscriptionNumberGap {
+    start: i32,
+    end: i32,
+  },
+  MissingSatpointEntry {
+    sequence_number: u32,
+    satpoint: SatPoint,
+  },
+  OrphanedSatpointEntry {
+    sequence_number: u32,
+    satpoint: SatPoint,
+    expected: Option<SatPoint>,
+  },
+  OverlappingSatRanges {
+    first: OutPoint,
+    first_range: (u64, u64),
+    second: OutPoint,
+    second_range: (u64, u64),
+  },
+  RuneSupplyMismatch {
+    rune: SpacedRune,
+    supply: u128,
+    balances: u128,
+    burned: u128,
+  },
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct Report {
+  pub checked: Vec<Check>,
+  pub discrepancies: Vec<Discrepancy>,
+  pub height: Option<u32>,
+  pub skipped: Vec<Check>,
+}
+
+pub(crate) fn verify(index: &Index) -> Result<Report> {
+  let rtx = index.begin_read()?;
+
+  let mut report = Report {
+    checked: Vec::new(),
+    discrepancies: Vec::new(),
+    height: rtx.block_height()?.map(|height| height.n()),
+    skipped: Vec::new(),
+  };
+
+  let rtx = rtx.0;
+
+  satpoints(&rtx, &mut report.discrepancies)?;
+  report.checked.push(Check::Satpoints);
+
+  inscription_numbers(&rtx, &mut report.discrepancies)?;
+  report.checked.push(Check::InscriptionNumbers);
+
+  if index.index_runes {
+    rune_supply(&rtx, &mut report.discrepancies)?;
+    report.checked.push(Check::RuneSupply);
+  } else {
+    report.skipped.push(Check::RuneSupply);
+  }
+
+  // spent outputs keep their sat ranges with `--index-spent-sats`, so ranges
+  // are expected to overlap
+  if index.index_sats && !index.index_spent_sats {
+    sat_ranges(&rtx, &mut report.discrepancies)?;
+    report.checked.push(Check::SatRanges);
+  } else {
+    report.skipped.push(Check::SatRanges);
+  }
+
+  Ok(report)
+}
+
+fn satpoints(rtx: &ReadTransaction, discrepancies: &mut Vec<Discrepancy>) -> Result {
+  let sequence_number_to_satpoint = rtx.open_table(SEQUENCE_NUMBER_TO_SATPOINT)?;
+  let satpoint_to_sequence_number = rtx.open_multimap_table(SATPOINT_TO_SEQUENCE_NUMBER)?;
+
+  for entry in sequence_number_to_satpoint.iter()? {
+    let (sequence_number, satpoint) = entry?;
+
+    let sequence_number = sequence_number.value();
+
+    let indexed = satpoint_to_sequence_number
+      .get(satpoint.value())?
+      .map(|result| result.map(|guard| guard.value()))
+      .collect::<Result<Vec<u32>, StorageError>>()?;
+
+    if !indexed.contains(&sequence_number) {
+      discrepancies.push(Discrepancy::MissingSatpointEntry {
+        sequence_number,
+        satpoint: SatPoint::load(*satpoint.value()),
+      });
+    }
+  }
+
+  for entry in satpoint_to_sequence_number.iter()? {
+    let (satpoint, sequence_numbers) = entry?;
+
+    let satpoint = SatPoint::load(*satpoint.value());
+
+    for sequence_number in sequence_numbers {
+      let sequence_number = sequence_number?.value();
+
+      let expected = sequence_number_to_satpoint
+        .get(sequence_number)?
+        .map(|satpoint| SatPoint::load(*satpoint.value()));
+
+      if expected != Some(satpoint) {
+        discrepancies.push(Discrepancy::OrphanedSatpointEntry {
+          sequence_number,
+          satpoint,
+          expected,
+        });
+      }
+    }
+  }
+
+  Ok(())
+}
+
+fn inscription_numbers(rtx: &ReadTransaction, discrepancies: &mut Vec<Discrepancy>) -> Result {
+  let inscription_number_to_sequence_number =
+    rtx.open_table(INSCRIPTION_NUMBER_TO_SEQUENCE_NUMBER)?;
+
+  // cursed numbers count down from -1 and blessed numbers count up from 0
+  if let Some((first, _sequence_number)) = inscription_number_to_sequence_number
+    .range(..0)?
+    .next()
+    .transpose()?
+  {
+    number_gaps(
+      first.value(),
+      inscription_number_to_sequence_number
+        .range(..0)?
+        .map(|entry| Ok(entry?.0.value())),
+      Some(-1),
+      discrepancies,
+    )?;
+  }
+
+  number_gaps(
+    0,
+    inscription_number_to_sequence_number
+      .range(0..)?
+      .map(|entry| Ok(entry?.0.value())),
+    None,
+    discrepancies,
+  )
+}
+
+fn number_gaps(
+  mut expected: i32,
+  numbers: impl Iterator<Item = Result<i32>>,
+  end: Option<i32>,
+  discrepancies: &mut Vec<Discrepancy>,
+) -> Result {
+  for number in numbers {
+    let number = number?;
+
+    if number > expected {
+      discrepancies.push(Discrepancy::InscriptionNumberGap {
+        start: expected,
+        end: number - 1,
+      });
+    }
+
+    expected = number + 1;
+  }
+
+  if let Some(end) = end {
+    if expected <= end {
+      discrepancies.push(Discrepancy::InscriptionNumberGap {
+        start: expected,
+        end,
+      });
+    }
+  }
+
+  Ok(())
+}
+
+fn rune_supply(rtx: &ReadTransaction, discrepancies: &mut Vec<Discrepancy>) -> Result {
+  let mut balances = HashMap::<RuneId, u128>::new();
+
+  for entry in rtx.open_table(OUTPOINT_TO_RUNE_BALANCES)?.iter()? {
+    let (_outpoint, buffer) = entry?;
+
+    let buffer = buffer.value();
+
+    let mut i = 0;
+    while i < buffer.len() {
+      let ((id, balance), length) = Index::decode_rune_balance(&buffer[i..])?;
+      i += length;
+
+      let total = balances.entry(id).or_default();
+      *total = total.saturating_add(balance);
+    }
+  }
+
+  for entry in rtx.open_table(RUNE_ID_TO_RUNE_ENTRY)?.iter()? {
+    let (id, entry) = entry?;
+
+    let entry = RuneEntry::load(entry.value());
+
+    let balances = balances
+      .get(&RuneId::load(id.value()))
+      .copied()
+      .unwrap_or_default();
+
+    if balances.checked_add(entry.burned) != Some(entry.supply()) {
+      discrepancies.push(Discrepancy::RuneSupplyMismatch {
+        rune: entry.spaced_rune,
+        supply: entry.supply(),
+        balances,
+        burned: entry.burned,
+      });
+    }
+  }
+
+  Ok(())
+}
+
+/// Number of outpoints whose sat ranges are checked in a single pass. Sat
+/// ranges are only sorted within a pass, so memory use is bounded by the number
+/// of ranges in one pass, at the cost of reading the table once per pass.
+const OUTPOINTS_PER_PASS: u64 = 1 << 22;
+
+fn sat_ranges(rtx: &ReadTransaction, discrepancies: &mut Vec<Discrepancy>) -> Result {
+  let passes = rtx
+    .open_table(OUTPOINT_TO_SAT_RANGES)?
+    .len()?
+    .div_ceil(OUTPOINTS_PER_PASS)
+    .max(1);
+
+  sat_ranges_in_passes(rtx, passes, discrepancies)
+}
+
+/// Each pass checks the ranges starting in one slice of the sat supply. The
+/// last range of each pass is carried into the next, so overlaps across slice
+/// boundaries are still found.
+fn sat_ranges_in_passes(
+  rtx: &ReadTransaction,
+  passes: u64,
+  discrepancies: &mut Vec<Discrepancy>,
+) -> Result {
+  let table = rtx.open_table(OUTPOINT_TO_SAT_RANGES)?;
+
+  let slice = Sat::SUPPLY.div_ceil(passes);
+
+  let mut previous = None;
+
+  for pass in 0..passes {
+    let low = pass * slice;
+    let high = if pass + 1 == passes {
+      u64::MAX
+    } else {
+      low + slice
+    };
+
+    let mut ranges = Vec::new();
+
+    for entry in table.iter()? {
+      let (outpoint, buffer) = entry?;
+
+      let outpoint = OutPoint::load(*outpoint.value());
+
+      for chunk in buffer.value().chunks_exact(11) {
+        let (start, end) = SatRange::load(chunk.try_into().unwrap());
+
+        if (low..high).contains(&start) {
+          ranges.push((start, end, outpoint));
+        }
+      }
+    }
+
+    ranges.sort_unstable();
+
+    for (start, end, outpoint) in ranges {
+      if let Some((first_start, first_end, first)) = previous {
+        if start < first_end {
+          discrepancies.push(Discrepancy::OverlappingSatRanges {
+            first,
+            first_range: (first_start, first_end),
+            second: outpoint,
+            second_range: (start, end),
+          });
+        }
+      }
+
+      previous = Some((start, end, outpoint));
+    }
+  }
+
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use {super::*, crate::index::testing::Context};
+
+  const RUNE: u128 = 99246114928149462;
+
+  fn inscribe(context: &Context, n: usize) {
+    for i in 0..n {
+      context.mine_blocks(1);
+
+      context.core.broadcast_tx(TransactionTemplate {
+        inputs: &[(i + 1, 0, 0, inscription("text/plain", "hello").to_witness())],
+        ..default()
+      });
+    }
+
+    context.mine_blocks(1);
+  }
+
+  #[test]
+  fn consistent_index_has_no_discrepancies() {
+    let context = Context::builder()
+      .args(["--index-runes", "--index-sats"])
+      .build();
+
+    inscribe(&context, 2);
+
+    context.etch(
+      Runestone {
+        edicts: vec![Edict {
+          id: RuneId::default(),
+          amount: 1000,
+          output: 0,
+        }],
+        etching: Some(Etching {
+          rune: Some(Rune(RUNE)),
+          premine: Some(1000),
+          ..default()
+        }),
+        ..default()
+      },
+      1,
+    );
+
+    let report = verify(&context.index).unwrap();
+
+    assert_eq!(
+      report,
+      Report {
+        checked: vec![
+          Check::Satpoints,
+          Check::InscriptionNumbers,
+          Check::RuneSupply,
+          Check::SatRanges,
+        ],
+        discrepancies: Vec::new(),
+        height: Some(context.index.block_height().unwrap().unwrap().n()),
+        skipped: Vec::new(),
+      }
+    );
+  }
+
+  #[test]
+  fn checks_are_skipped_without_indices() {
+    let context = Context::builder().build();
+
+    context.mine_blocks(1);
+
+    let report = verify(&context.index).unwrap();
+
+    assert_eq!(report.skipped, [Check::RuneSupply, Check::SatRanges]);
+    assert!(report.discrepancies.is_empty());
+  }
+
+  #[test]
+  fn sat_ranges_are_skipped_with_spent_sats() {
+    let context = Context::builder()
+      .args(["--index-sats", "--index-spent-sats"])
+      .build();
+
+    context.mine_blocks(1);
+
+    assert_eq!(
+      verify(&context.index).unwrap().skipped,
+      [Check::RuneSupply, Check::SatRanges]
+    );
+  }
+
+  #[test]
+  fn satpoint_discrepancies_are_reported() {
+    let context = Context::builder().build();
+
+    inscribe(&context, 2);
+
+    let wtx = context.index.begin_write().unwrap();
+
+    let (first, second) = {
+      let mut sequence_number_to_satpoint = wtx.open_table(SEQUENCE_NUMBER_TO_SATPOINT).unwrap();
+
+      let first = SatPoint::load(*sequence_number_to_satpoint.get(0).unwrap().unwrap().value());
+      let second = SatPoint::load(
+        *sequence_number_to_satpoint
+          .remove(1)
+          .unwrap()
+          .unwrap()
+          .value(),
+      );
+
+      (first, second)
+    };
+
+    wtx
+      .open_multimap_table(SATPOINT_TO_SEQUENCE_NUMBER)
+      .unwrap()
+      .remove(&first.store(), 0)
+      .unwrap();
+
+    wtx.commit().unwrap();
+
+    assert_eq!(
+      verify(&context.index).unwrap().discrepancies,
+      [
+        Discrepancy::MissingSatpointEntry {
+          sequence_number: 0,
+          satpoint: first,
+        },
+        Discrepancy::OrphanedSatpointEntry {
+          sequence_number: 1,
+          satpoint: second,
+          expected: None,
+        },
+      ]
+    );
+  }
+
+  #[test]
+  fn inscription_number_gaps_are_reported() {
+    let context = Context::builder().build();
+
+    inscribe(&context, 3);
+
+    let wtx = context.index.begin_write().unwrap();
+
+    wtx
+      .open_table(INSCRIPTION_NUMBER_TO_SEQUENCE_NUMBER)
+      .unwrap()
+      .remove(1)
+      .unwrap();
+
+    wtx.commit().unwrap();
+
+    assert_eq!(
+      verify(&context.index).unwrap().discrepancies,
+      [Discrepancy::InscriptionNumberGap { start: 1, end: 1 }]
+    );
+  }
+
+  #[test]
+  fn cursed_number_gaps_are_reported() {
+    let mut discrepancies = Vec::new();
+
+    number_gaps(
+      -5,
+      [-5, -3, -2].into_iter().map(Ok),
+      Some(-1),
+      &mut discrepancies,
+    )
+    .unwrap();
+
+    assert_eq!(
+      discrepancies,
+      [
+        Discrepancy::InscriptionNumberGap { start: -4, end: -4 },
+        Discrepancy::InscriptionNumberGap { start: -1, end: -1 },
+      ]
+    );
+  }
+
+  #[test]
+  fn rune_supply_mismatch_is_reported() {
+    let context = Context::builder().arg("--index-runes").build();
+
+    let (txid, id) = context.etch(
+      Runestone {
+        edicts: vec![Edict {
+          id: RuneId::default(),
+          amount: 1000,
+          output: 0,
+        }],
+        etching: Some(Etching {
+          rune: Some(Rune(RUNE)),
+          premine: Some(1000),
+          ..default()
+        }),
+        ..default()
+      },
+      1,
+    );
+
+    let wtx = context.index.begin_write().unwrap();
+
+    let mut buffer = Vec::new();
+    Index::encode_rune_balance(id, 600, &mut buffer);
+
+    wtx
+      .open_table(OUTPOINT_TO_RUNE_BALANCES)
+      .unwrap()
+      .insert(&OutPoint { txid, vout: 0 }.store(), buffer.as_slice())
+      .unwrap();
+
+    wtx.commit().unwrap();
+
+    assert_eq!(
+      verify(&context.index).unwrap().discrepancies,
+      [Discrepancy::RuneSupplyMismatch {
+        rune: SpacedRune {
+          rune: Rune(RUNE),
+          spacers: 0,
+        },
+        supply: 1000,
+        balances: 600,
+        burned: 0,
+      }]
+    );
+  }
+
+  #[test]
+  fn overlapping_sat_ranges_are_reported() {
+    let context = Context::builder().arg("--index-sats").build();
+
+    let coinbase = context.mine_blocks(1)[0].txdata[0].txid();
+
+    let first = OutPoint {
+      txid: coinbase,
+      vout: 0,
+    };
+
+    let second = OutPoint {
+      txid: coinbase,
+      vout: 1,
+    };
+
+    let first_range = (50 * COIN_VALUE, 100 * COIN_VALUE);
+    let second_range = (75 * COIN_VALUE, 80 * COIN_VALUE);
+
+    let wtx = context.index.begin_write().unwrap();
+
+    wtx
+      .open_table(OUTPOINT_TO_SAT_RANGES)
+      .unwrap()
+      .insert(&second.store(), second_range.store().as_slice())
+      .unwrap();
+
+    wtx.commit().unwrap();
+
+    assert_eq!(
+      verify(&context.index).unwrap().discrepancies,
+      [Discrepancy::OverlappingSatRanges {
+        first,
+        first_range,
+        second,
+        second_range,
+      }]
+    );
+  }
+
+  #[test]
+  fn overlapping_sat_ranges_are_reported_across_passes() {
+    let context = Context::builder().arg("--index-sats").build();
+
+    let coinbase = context.mine_blocks(1)[0].txdata[0].txid();
+
+    let first = OutPoint {
+      txid: coinbase,
+      vout: 1,
+    };
+
+    let second = OutPoint {
+      txid: coinbase,
+      vout: 2,
+    };
+
+    let boundary = Sat::SUPPLY.div_ceil(2);
+
+    let first_range = (boundary - 10, boundary + 10);
+    let second_range = (boundary, boundary + 5);
+
+    let wtx = context.index.begin_write().unwrap();
+
+    {
+      let mut outpoint_to_sat_ranges = wtx.open_table(OUTPOINT_TO_SAT_RANGES).unwrap();
+
+      outpoint_to_sat_ranges
+        .insert(&first.store(), first_range.store().as_slice())
+        .unwrap();
+
+      outpoint_to_sat_ranges
+        .insert(&second.store(), second_range.store().as_slice())
+        .unwrap();
+    }
+
+    wtx.commit().unwrap();
+
+    let mut discrepancies = Vec::new();
+
+    sat_ranges_in_passes(
+      &context.index.begin_read().unwrap().0,
+      2,
+      &mut discrepancies,
+    )
+    .unwrap();
+
+    assert_eq!(
+      discrepancies,
+      [Discrepancy::OverlappingSatRanges {
+        first,
+        first_range,
+        second,
+        second_range,
+      }]
+    );
+  }
+}
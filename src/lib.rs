@@ -114,6 +114,7 @@ pub mod index;
 mod inscriptions;
 mod into_usize;
 mod macros;
+mod metrics;
 mod object;
 pub mod options;
 pub mod outgoing;
@@ -121,6 +122,7 @@ mod re;
 mod representation;
 pub mod runes;
 pub mod settings;
+pub mod sink;
 pub mod subcommand;
 mod tally;
 pub mod templates;
@@ -0,0 +1,139 @@
+use {
+  super::*,
+  prometheus::{
+    register_histogram, register_histogram_vec, register_int_counter_vec, register_int_gauge,
+    Encoder, Histogram, HistogramTimer, HistogramVec, IntCounterVec, IntGauge, TextEncoder,
+  },
+};
+
+lazy_static! {
+  pub(crate) static ref BLOCK_INDEX_DURATION: Histogram = register_histogram!(
+    "ord_block_index_duration_seconds",
+    "Time spent indexing a block",
+  )
+  .unwrap();
+  pub(crate) static ref COMMIT_DURATION: Histogram = register_histogram!(
+    "ord_commit_duration_seconds",
+    "Time spent flushing caches and committing an index write transaction",
+  )
+  .unwrap();
+  pub(crate) static ref EVENT_OUTBOX_BACKLOG: IntGauge = register_int_gauge!(
+    "ord_event_outbox_backlog",
+    "Events recorded in the index but not yet delivered",
+  )
+  .unwrap();
+  pub(crate) static ref HTTP_REQUEST_DURATION: HistogramVec = register_histogram_vec!(
+    "ord_http_request_duration_seconds",
+    "Time spent handling HTTP requests",
+    &["method", "route", "status"],
+  )
+  .unwrap();
+  pub(crate) static ref INDEXED_HEIGHT: IntGauge =
+    register_int_gauge!("ord_indexed_height", "Height of the last indexed block").unwrap();
+  pub(crate) static ref NODE_HEIGHT: IntGauge = register_int_gauge!(
+    "ord_node_height",
+    "Height of the node's chain tip at the start of the last index update",
+  )
+  .unwrap();
+  pub(crate) static ref RANGE_CACHE_ENTRIES: IntGauge = register_int_gauge!(
+    "ord_range_cache_entries",
+    "Sat ranges held in memory and not yet flushed to the index",
+  )
+  .unwrap();
+  pub(crate) static ref REORGS: IntCounterVec = register_int_counter_vec!(
+    "ord_reorgs_total",
+    "Reorgs detected while indexing",
+    &["outcome"],
+  )
+  .unwrap();
+  pub(crate) static ref RPC_DURATION: HistogramVec = register_histogram_vec!(
+    "ord_rpc_duration_seconds",
+    "Time spent fetching data from the block source, including retries",
+    &["request"],
+  )
+  .unwrap();
+  pub(crate) static ref RPC_RETRIES: IntCounterVec = register_int_counter_vec!(
+    "ord_rpc_retries_total",
+    "Failed block source requests that were retried",
+    &["request"],
+  )
+  .unwrap();
+  pub(crate) static ref UTXO_CACHE_ENTRIES: IntGauge = register_int_gauge!(
+    "ord_utxo_cache_entries",
+    "Transaction outputs held in memory and not yet flushed to the index",
+  )
+  .unwrap();
+}
+
+pub(crate) fn rpc_timer(request: &str) -> HistogramTimer {
+  RPC_DURATION.with_label_values(&[request]).start_timer()
+}
+
+pub(crate) fn rpc_retry(request: &str) {
+  RPC_RETRIES.with_label_values(&[request]).inc();
+}
+
+pub(crate) fn render(index: &Index, event_feed_next: Option<u64>) -> Result<String> {
+  INDEXED_HEIGHT.set(
+    index
+      .block_height()?
+      .map(|height| height.n().into())
+      .unwrap_or(-1),
+  );
+
+  // the event feed keeps recent events in the outbox after ingesting them, so
+  // its backlog is the events recorded since it last ingested
+  EVENT_OUTBOX_BACKLOG.set(
+    match event_feed_next {
+      Some(next) => index.next_event_id()?.saturating_sub(next),
+      None => index.event_outbox_len()?,
+    }
+    .try_into()?,
+  );
+
+  let mut buffer = Vec::new();
+
+  TextEncoder::new().encode(&prometheus::gather(), &mut buffer)?;
+
+  Ok(String::from_utf8(buffer)?)
+}
+
+#[cfg(test)]
+mod tests {
+  use {super::*, crate::index::testing::Context};
+
+  #[test]
+  fn render_includes_index_state() {
+    let context = Context::builder().build();
+
+    context.mine_blocks(2);
+
+    let metrics = render(&context.index, None).unwrap();
+
+    assert!(metrics.contains("# TYPE ord_indexed_height gauge\nord_indexed_height 2\n"));
+    assert!(metrics.contains("ord_event_outbox_backlog 0\n"));
+    assert!(metrics.contains("# TYPE ord_block_index_duration_seconds histogram\n"));
+  }
+
+  #[test]
+  fn event_outbox_backlog() {
+    let context = Context::builder().event_outbox().build();
+
+    context.mine_blocks(2);
+
+    let recorded = context.index.event_outbox_len().unwrap();
+
+    assert!(recorded > 2);
+
+    assert!(render(&context.index, None)
+      .unwrap()
+      .contains(&format!("ord_event_outbox_backlog {recorded}\n")));
+
+    assert!(render(
+      &context.index,
+      Some(context.index.next_event_id().unwrap() - 2)
+    )
+    .unwrap()
+    .contains("ord_event_outbox_backlog 2\n"));
+  }
+}
@@ -1,4 +1,8 @@
-use super::*;
+use {
+  super::*,
+  index::{block_source::BlockSourceKind, reorg::Rollback},
+  sink::{EventEncoding, EventSinkKind, KafkaAcks, KafkaKey},
+};
 
 #[derive(Clone, Default, Debug, Parser)]
 #[command(group(
@@ -23,8 +27,24 @@ pub struct Options {
   pub(crate) bitcoin_rpc_username: Option<String>,
   #[arg(long, help = "Max <N> requests in flight. [default: 12]")]
   pub(crate) bitcoin_rpc_limit: Option<u32>,
+  #[arg(
+    long,
+    value_enum,
+    help = "Fetch blocks, headers and prevouts from <BLOCK_SOURCE>. [default: rpc]"
+  )]
+  pub(crate) block_source: Option<BlockSourceKind>,
+  #[arg(
+    long,
+    help = "Replay blocks recorded in <BLOCK_SOURCE_FIXTURE> with the `fixture` block source."
+  )]
+  pub(crate) block_source_fixture: Option<PathBuf>,
   #[arg(long = "chain", value_enum, help = "Use <CHAIN>. [default: mainnet]")]
   pub(crate) chain_argument: Option<Chain>,
+  #[arg(
+    long,
+    help = "Treat blocks within <CHAIN_TIP_DISTANCE> blocks of the chain tip as reorgable. [default: 21]"
+  )]
+  pub(crate) chain_tip_distance: Option<u32>,
   #[arg(
     long,
     help = "Commit to index every <COMMIT_INTERVAL> blocks. [default: 5000]"
@@ -38,6 +58,38 @@ pub struct Options {
   pub(crate) cookie_file: Option<PathBuf>,
   #[arg(long, alias = "datadir", help = "Store index in <DATA_DIR>.")]
   pub(crate) data_dir: Option<PathBuf>,
+  #[arg(
+    long,
+    help = "Include inscription bodies of at most <EVENT_BODY_LIMIT> bytes in rich events."
+  )]
+  pub(crate) event_body_limit: Option<usize>,
+  #[arg(
+    long,
+    value_enum,
+    help = "Encode published events as <EVENT_ENCODING>. [default: json]"
+  )]
+  pub(crate) event_encoding: Option<EventEncoding>,
+  #[arg(
+    long,
+    help = "Write events to <EVENT_FILE> with the `file` event sink."
+  )]
+  pub(crate) event_file: Option<PathBuf>,
+  #[arg(
+    long,
+    value_enum,
+    help = "Send events to <EVENT_SINK>. Events are delivered at least once, in order. [default: kafka]"
+  )]
+  pub(crate) event_sink: Option<EventSinkKind>,
+  #[arg(
+    long,
+    help = "POST events to <EVENT_WEBHOOK_URL> with the `webhook` event sink."
+  )]
+  pub(crate) event_webhook_url: Option<String>,
+  #[arg(
+    long,
+    help = "Read blocks that are too deep to be reorged directly from the blk*.dat files in <BITCOIN_DATA_DIR>."
+  )]
+  pub(crate) fast_sync: bool,
   #[arg(
     long,
     help = "Don't look for inscriptions below <FIRST_INSCRIPTION_HEIGHT>."
@@ -47,6 +99,11 @@ pub struct Options {
   pub(crate) height_limit: Option<u32>,
   #[arg(long, help = "Use index at <INDEX>.")]
   pub(crate) index: Option<PathBuf>,
+  #[arg(
+    long,
+    help = "Keep transaction history of addresses. Implies --index-addresses."
+  )]
+  pub(crate) index_address_history: bool,
   #[arg(long, help = "Track unspent output addresses.")]
   pub(crate) index_addresses: bool,
   #[arg(
@@ -54,6 +111,22 @@ pub struct Options {
     help = "Set index cache size to <INDEX_CACHE_SIZE> bytes. [default: 1/4 available RAM]"
   )]
   pub(crate) index_cache_size: Option<usize>,
+  #[arg(
+    long,
+    help = "Store inscription bodies by content hash, so content is served without fetching transactions from Bitcoin Core."
+  )]
+  pub(crate) index_content: bool,
+  #[arg(
+    long,
+    help = "Index words in text, JSON and markdown inscriptions for full-text search."
+  )]
+  pub(crate) index_full_text: bool,
+  #[arg(
+    long = "index-metaprotocol",
+    value_name = "NAME",
+    help = "Index inscriptions of metaprotocol <NAME>. May be passed multiple times."
+  )]
+  pub(crate) index_metaprotocols: Vec<String>,
   #[arg(
     long,
     help = "Track location of runes. RUNES ARE IN AN UNFINISHED PRE-ALPHA STATE AND SUBJECT TO CHANGE AT ANY TIME."
@@ -61,12 +134,51 @@ pub struct Options {
   pub(crate) index_runes: bool,
   #[arg(long, help = "Track location of all satoshis.")]
   pub(crate) index_sats: bool,
+  #[arg(
+    long,
+    help = "Index inscription properties, fees and sizes for `/r/inscriptions/search`."
+  )]
+  pub(crate) index_search: bool,
   #[arg(long, help = "Keep sat index entries of spent outputs.")]
   pub(crate) index_spent_sats: bool,
   #[arg(long, help = "Store transactions in index.")]
   pub(crate) index_transactions: bool,
+  #[arg(
+    long,
+    help = "Record the transfer history of inscriptions and runes, so that events can be replayed."
+  )]
+  pub(crate) index_transfers: bool,
   #[arg(long, help = "Run in integration test mode.")]
   pub(crate) integration_test: bool,
+  #[arg(
+    long,
+    value_enum,
+    help = "Require <KAFKA_ACKS> acknowledgements for published events. [default: all]"
+  )]
+  pub(crate) kafka_acks: Option<KafkaAcks>,
+  #[arg(long, help = "Publish events to Kafka brokers at <KAFKA_BROKERS>.")]
+  pub(crate) kafka_brokers: Option<String>,
+  #[arg(
+    long,
+    value_enum,
+    help = "Key published events by <KAFKA_KEY>. `id` keys events by inscription or rune ID. [default: id]"
+  )]
+  pub(crate) kafka_key: Option<KafkaKey>,
+  #[arg(
+    long,
+    help = "Publish events to Kafka topic <KAFKA_TOPIC>. [default: ord]"
+  )]
+  pub(crate) kafka_topic: Option<String>,
+  #[arg(
+    long,
+    help = "Keep <MAX_SAVEPOINTS> savepoints to roll back to after a reorg. [default: 2]"
+  )]
+  pub(crate) max_savepoints: Option<u32>,
+  #[arg(
+    long,
+    help = "Emit provisional events for unconfirmed inscription and rune transactions in the mempool."
+  )]
+  pub(crate) mempool_events: bool,
   #[clap(long, short, long, help = "Specify output format. [default: json]")]
   pub(crate) format: Option<OutputFormat>,
   #[arg(
@@ -76,6 +188,22 @@ pub struct Options {
     help = "Do not index inscriptions."
   )]
   pub(crate) no_index_inscriptions: bool,
+  #[arg(
+    long,
+    value_enum,
+    help = "Roll back reorgs with <REORG_ROLLBACK>. `undo-log` rolls back precisely to the fork point. Existing indexes use the mode they were created with. [default: savepoints]"
+  )]
+  pub(crate) reorg_rollback: Option<Rollback>,
+  #[arg(
+    long,
+    help = "Include content type, metadata, fee, sat, and address in inscription events."
+  )]
+  pub(crate) rich_events: bool,
+  #[arg(
+    long,
+    help = "Take a savepoint every <SAVEPOINT_INTERVAL> blocks. [default: 10]"
+  )]
+  pub(crate) savepoint_interval: Option<u32>,
   #[arg(
     long,
     help = "Require basic HTTP authentication with <SERVER_PASSWORD>. Credentials are sent in cleartext. Consider using authentication in conjunction with HTTPS."
@@ -86,6 +214,11 @@ pub struct Options {
     help = "Require basic HTTP authentication with <SERVER_USERNAME>. Credentials are sent in cleartext. Consider using authentication in conjunction with HTTPS."
   )]
   pub(crate) server_username: Option<String>,
+  #[arg(
+    long,
+    help = "Keep undo logs for the last <UNDO_LOG_DEPTH> blocks with `--reorg-rollback undo-log`. [default: 100]"
+  )]
+  pub(crate) undo_log_depth: Option<u32>,
   #[arg(long, short, help = "Use regtest. Equivalent to `--chain regtest`.")]
   pub(crate) regtest: bool,
   #[arg(long, short, help = "Use signet. Equivalent to `--chain signet`.")]
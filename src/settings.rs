@@ -1,4 +1,9 @@
-use {super::*, bitcoincore_rpc::Auth};
+use {
+  super::*,
+  bitcoincore_rpc::Auth,
+  index::{block_source::BlockSourceKind, reorg::Rollback},
+  sink::{EventEncoding, EventSinkKind, KafkaAcks, KafkaKey},
+};
 
 #[derive(Default, Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(default, deny_unknown_fields)]
@@ -8,28 +13,53 @@ pub struct Settings {
   bitcoin_rpc_password: Option<String>,
   bitcoin_rpc_url: Option<String>,
   bitcoin_rpc_username: Option<String>,
+  block_source: Option<BlockSourceKind>,
+  block_source_fixture: Option<PathBuf>,
   chain: Option<Chain>,
+  chain_tip_distance: Option<u32>,
   commit_interval: Option<usize>,
   config: Option<PathBuf>,
   config_dir: Option<PathBuf>,
   cookie_file: Option<PathBuf>,
   data_dir: Option<PathBuf>,
+  event_body_limit: Option<usize>,
+  event_encoding: Option<EventEncoding>,
+  event_file: Option<PathBuf>,
+  event_sink: Option<EventSinkKind>,
+  event_webhook_url: Option<String>,
+  fast_sync: bool,
   first_inscription_height: Option<u32>,
   height_limit: Option<u32>,
   hidden: Option<HashSet<InscriptionId>>,
   http_port: Option<u16>,
   index: Option<PathBuf>,
+  index_address_history: bool,
   index_addresses: bool,
   index_cache_size: Option<usize>,
+  index_content: bool,
+  index_full_text: bool,
+  index_metaprotocols: BTreeSet<String>,
   index_runes: bool,
   index_sats: bool,
+  index_search: bool,
   index_spent_sats: bool,
   index_transactions: bool,
+  index_transfers: bool,
   integration_test: bool,
+  kafka_acks: Option<KafkaAcks>,
+  kafka_brokers: Option<String>,
+  kafka_key: Option<KafkaKey>,
+  kafka_topic: Option<String>,
+  max_savepoints: Option<u32>,
+  mempool_events: bool,
   no_index_inscriptions: bool,
+  reorg_rollback: Option<Rollback>,
+  rich_events: bool,
+  savepoint_interval: Option<u32>,
   server_password: Option<String>,
   server_url: Option<String>,
   server_username: Option<String>,
+  undo_log_depth: Option<u32>,
 }
 
 impl Settings {
@@ -115,12 +145,21 @@ impl Settings {
       bitcoin_rpc_password: self.bitcoin_rpc_password.or(source.bitcoin_rpc_password),
       bitcoin_rpc_url: self.bitcoin_rpc_url.or(source.bitcoin_rpc_url),
       bitcoin_rpc_username: self.bitcoin_rpc_username.or(source.bitcoin_rpc_username),
+      block_source: self.block_source.or(source.block_source),
+      block_source_fixture: self.block_source_fixture.or(source.block_source_fixture),
       chain: self.chain.or(source.chain),
+      chain_tip_distance: self.chain_tip_distance.or(source.chain_tip_distance),
       commit_interval: self.commit_interval.or(source.commit_interval),
       config: self.config.or(source.config),
       config_dir: self.config_dir.or(source.config_dir),
       cookie_file: self.cookie_file.or(source.cookie_file),
       data_dir: self.data_dir.or(source.data_dir),
+      event_body_limit: self.event_body_limit.or(source.event_body_limit),
+      event_encoding: self.event_encoding.or(source.event_encoding),
+      event_file: self.event_file.or(source.event_file),
+      event_sink: self.event_sink.or(source.event_sink),
+      event_webhook_url: self.event_webhook_url.or(source.event_webhook_url),
+      fast_sync: self.fast_sync || source.fast_sync,
       first_inscription_height: self
         .first_inscription_height
         .or(source.first_inscription_height),
@@ -136,17 +175,37 @@ impl Settings {
       ),
       http_port: self.http_port.or(source.http_port),
       index: self.index.or(source.index),
+      index_address_history: self.index_address_history || source.index_address_history,
       index_addresses: self.index_addresses || source.index_addresses,
       index_cache_size: self.index_cache_size.or(source.index_cache_size),
+      index_content: self.index_content || source.index_content,
+      index_full_text: self.index_full_text || source.index_full_text,
+      index_metaprotocols: self
+        .index_metaprotocols
+        .into_iter()
+        .chain(source.index_metaprotocols)
+        .collect(),
       index_runes: self.index_runes || source.index_runes,
       index_sats: self.index_sats || source.index_sats,
+      index_search: self.index_search || source.index_search,
       index_spent_sats: self.index_spent_sats || source.index_spent_sats,
       index_transactions: self.index_transactions || source.index_transactions,
+      index_transfers: self.index_transfers || source.index_transfers,
       integration_test: self.integration_test || source.integration_test,
+      kafka_acks: self.kafka_acks.or(source.kafka_acks),
+      kafka_brokers: self.kafka_brokers.or(source.kafka_brokers),
+      kafka_key: self.kafka_key.or(source.kafka_key),
+      kafka_topic: self.kafka_topic.or(source.kafka_topic),
+      max_savepoints: self.max_savepoints.or(source.max_savepoints),
+      mempool_events: self.mempool_events || source.mempool_events,
       no_index_inscriptions: self.no_index_inscriptions || source.no_index_inscriptions,
+      reorg_rollback: self.reorg_rollback.or(source.reorg_rollback),
+      rich_events: self.rich_events || source.rich_events,
+      savepoint_interval: self.savepoint_interval.or(source.savepoint_interval),
       server_password: self.server_password.or(source.server_password),
       server_url: self.server_url.or(source.server_url),
       server_username: self.server_username.or(source.server_username),
+      undo_log_depth: self.undo_log_depth.or(source.undo_log_depth),
     }
   }
 
@@ -157,33 +216,58 @@ impl Settings {
       bitcoin_rpc_password: options.bitcoin_rpc_password,
       bitcoin_rpc_url: options.bitcoin_rpc_url,
       bitcoin_rpc_username: options.bitcoin_rpc_username,
+      block_source: options.block_source,
+      block_source_fixture: options.block_source_fixture,
       chain: options
         .signet
         .then_some(Chain::Signet)
         .or(options.regtest.then_some(Chain::Regtest))
         .or(options.testnet.then_some(Chain::Testnet))
         .or(options.chain_argument),
+      chain_tip_distance: options.chain_tip_distance,
       commit_interval: options.commit_interval,
       config: options.config,
       config_dir: options.config_dir,
       cookie_file: options.cookie_file,
       data_dir: options.data_dir,
+      event_body_limit: options.event_body_limit,
+      event_encoding: options.event_encoding,
+      event_file: options.event_file,
+      event_sink: options.event_sink,
+      event_webhook_url: options.event_webhook_url,
+      fast_sync: options.fast_sync,
       first_inscription_height: options.first_inscription_height,
       height_limit: options.height_limit,
       hidden: None,
       http_port: None,
       index: options.index,
+      index_address_history: options.index_address_history,
       index_addresses: options.index_addresses,
       index_cache_size: options.index_cache_size,
+      index_content: options.index_content,
+      index_full_text: options.index_full_text,
+      index_metaprotocols: options.index_metaprotocols.into_iter().collect(),
       index_runes: options.index_runes,
       index_sats: options.index_sats,
+      index_search: options.index_search,
       index_spent_sats: options.index_spent_sats,
       index_transactions: options.index_transactions,
+      index_transfers: options.index_transfers,
       integration_test: options.integration_test,
+      kafka_acks: options.kafka_acks,
+      kafka_brokers: options.kafka_brokers,
+      kafka_key: options.kafka_key,
+      kafka_topic: options.kafka_topic,
+      max_savepoints: options.max_savepoints,
+      mempool_events: options.mempool_events,
       no_index_inscriptions: options.no_index_inscriptions,
+      reorg_rollback: options.reorg_rollback,
+      rich_events: options.rich_events,
+      savepoint_interval: options.savepoint_interval,
       server_password: options.server_password,
       server_url: None,
       server_username: options.server_username,
+      undo_log_depth: options.undo_log_depth,
     }
   }
 
@@ -222,6 +306,56 @@ impl Settings {
         })
     };
 
+    let get_event_encoding = |key| {
+      env
+        .get(key)
+        .map(|encoding| encoding.parse::<EventEncoding>())
+        .transpose()
+        .with_context(|| {
+          format!("failed to parse environment variable ORD_{key} as event encoding")
+        })
+    };
+
+    let get_block_source = |key| {
+      env
+        .get(key)
+        .map(|source| source.parse::<BlockSourceKind>())
+        .transpose()
+        .with_context(|| format!("failed to parse environment variable ORD_{key} as block source"))
+    };
+
+    let get_event_sink = |key| {
+      env
+        .get(key)
+        .map(|sink| sink.parse::<EventSinkKind>())
+        .transpose()
+        .with_context(|| format!("failed to parse environment variable ORD_{key} as event sink"))
+    };
+
+    let get_kafka_acks = |key| {
+      env
+        .get(key)
+        .map(|acks| acks.parse::<KafkaAcks>())
+        .transpose()
+        .with_context(|| format!("failed to parse environment variable ORD_{key} as kafka acks"))
+    };
+
+    let get_kafka_key = |key| {
+      env
+        .get(key)
+        .map(|kafka_key| kafka_key.parse::<KafkaKey>())
+        .transpose()
+        .with_context(|| format!("failed to parse environment variable ORD_{key} as kafka key"))
+    };
+
+    let get_rollback = |key| {
+      env
+        .get(key)
+        .map(|rollback| rollback.parse::<Rollback>())
+        .transpose()
+        .with_context(|| format!("failed to parse environment variable ORD_{key} as rollback"))
+    };
+
     let get_u16 = |key| {
       env
         .get(key)
@@ -252,28 +386,55 @@ impl Settings {
       bitcoin_rpc_password: get_string("BITCOIN_RPC_PASSWORD"),
       bitcoin_rpc_url: get_string("BITCOIN_RPC_URL"),
       bitcoin_rpc_username: get_string("BITCOIN_RPC_USERNAME"),
+      block_source: get_block_source("BLOCK_SOURCE")?,
+      block_source_fixture: get_path("BLOCK_SOURCE_FIXTURE"),
       chain: get_chain("CHAIN")?,
+      chain_tip_distance: get_u32("CHAIN_TIP_DISTANCE")?,
       commit_interval: get_usize("COMMIT_INTERVAL")?,
       config: get_path("CONFIG"),
       config_dir: get_path("CONFIG_DIR"),
       cookie_file: get_path("COOKIE_FILE"),
       data_dir: get_path("DATA_DIR"),
+      event_body_limit: get_usize("EVENT_BODY_LIMIT")?,
+      event_encoding: get_event_encoding("EVENT_ENCODING")?,
+      event_file: get_path("EVENT_FILE"),
+      event_sink: get_event_sink("EVENT_SINK")?,
+      event_webhook_url: get_string("EVENT_WEBHOOK_URL"),
+      fast_sync: get_bool("FAST_SYNC"),
       first_inscription_height: get_u32("FIRST_INSCRIPTION_HEIGHT")?,
       height_limit: get_u32("HEIGHT_LIMIT")?,
       hidden: inscriptions("HIDDEN")?,
       http_port: get_u16("HTTP_PORT")?,
       index: get_path("INDEX"),
+      index_address_history: get_bool("INDEX_ADDRESS_HISTORY"),
       index_addresses: get_bool("INDEX_ADDRESSES"),
       index_cache_size: get_usize("INDEX_CACHE_SIZE")?,
+      index_content: get_bool("INDEX_CONTENT"),
+      index_full_text: get_bool("INDEX_FULL_TEXT"),
+      index_metaprotocols: get_string("INDEX_METAPROTOCOLS")
+        .map(|names| names.split_whitespace().map(str::to_string).collect())
+        .unwrap_or_default(),
       index_runes: get_bool("INDEX_RUNES"),
       index_sats: get_bool("INDEX_SATS"),
+      index_search: get_bool("INDEX_SEARCH"),
       index_spent_sats: get_bool("INDEX_SPENT_SATS"),
       index_transactions: get_bool("INDEX_TRANSACTIONS"),
+      index_transfers: get_bool("INDEX_TRANSFERS"),
       integration_test: get_bool("INTEGRATION_TEST"),
+      kafka_acks: get_kafka_acks("KAFKA_ACKS")?,
+      kafka_brokers: get_string("KAFKA_BROKERS"),
+      kafka_key: get_kafka_key("KAFKA_KEY")?,
+      kafka_topic: get_string("KAFKA_TOPIC"),
+      max_savepoints: get_u32("MAX_SAVEPOINTS")?,
+      mempool_events: get_bool("MEMPOOL_EVENTS"),
       no_index_inscriptions: get_bool("NO_INDEX_INSCRIPTIONS"),
+      reorg_rollback: get_rollback("REORG_ROLLBACK")?,
+      rich_events: get_bool("RICH_EVENTS"),
+      savepoint_interval: get_u32("SAVEPOINT_INTERVAL")?,
       server_password: get_string("SERVER_PASSWORD"),
       server_url: get_string("SERVER_URL"),
       server_username: get_string("SERVER_USERNAME"),
+      undo_log_depth: get_u32("UNDO_LOG_DEPTH")?,
     })
   }
 
@@ -284,28 +445,53 @@ impl Settings {
       bitcoin_rpc_url: Some(rpc_url.into()),
       bitcoin_rpc_username: None,
       bitcoin_rpc_limit: None,
+      block_source: None,
+      block_source_fixture: None,
       chain: Some(Chain::Regtest),
+      chain_tip_distance: None,
       commit_interval: None,
       config: None,
       config_dir: None,
       cookie_file: None,
       data_dir: Some(dir.into()),
+      event_body_limit: None,
+      event_encoding: None,
+      event_file: None,
+      event_sink: None,
+      event_webhook_url: None,
+      fast_sync: false,
       first_inscription_height: None,
       height_limit: None,
       hidden: None,
       http_port: None,
       index: None,
+      index_address_history: false,
       index_addresses: true,
       index_cache_size: None,
+      index_content: false,
+      index_full_text: false,
+      index_metaprotocols: BTreeSet::new(),
       index_runes: true,
       index_sats: true,
+      index_search: false,
       index_spent_sats: false,
       index_transactions: false,
+      index_transfers: false,
       integration_test: false,
+      kafka_acks: None,
+      kafka_brokers: None,
+      kafka_key: None,
+      kafka_topic: None,
+      max_savepoints: None,
+      mempool_events: false,
       no_index_inscriptions: false,
+      reorg_rollback: None,
+      rich_events: false,
+      savepoint_interval: None,
       server_password: None,
       server_url: Some(server_url.into()),
       server_username: None,
+      undo_log_depth: None,
     }
   }
 
@@ -342,6 +528,20 @@ impl Settings {
       None => data_dir.join("index.redb"),
     };
 
+    let max_savepoints = self.max_savepoints.unwrap_or(2);
+
+    ensure!(
+      max_savepoints > 0,
+      "max savepoints must be greater than zero"
+    );
+
+    let savepoint_interval = self.savepoint_interval.unwrap_or(10);
+
+    ensure!(
+      savepoint_interval > 0,
+      "savepoint interval must be greater than zero"
+    );
+
     Ok(Self {
       bitcoin_data_dir: Some(bitcoin_data_dir),
       bitcoin_rpc_limit: Some(self.bitcoin_rpc_limit.unwrap_or(12)),
@@ -353,12 +553,21 @@ impl Settings {
           .unwrap_or_else(|| format!("127.0.0.1:{}", chain.default_rpc_port())),
       ),
       bitcoin_rpc_username: self.bitcoin_rpc_username,
+      block_source: Some(self.block_source.unwrap_or_default()),
+      block_source_fixture: self.block_source_fixture,
       chain: Some(chain),
+      chain_tip_distance: Some(self.chain_tip_distance.unwrap_or(21)),
       commit_interval: Some(self.commit_interval.unwrap_or(5000)),
       config: None,
       config_dir: None,
       cookie_file: Some(cookie_file),
       data_dir: Some(data_dir),
+      event_body_limit: self.event_body_limit,
+      event_encoding: Some(self.event_encoding.unwrap_or_default()),
+      event_file: self.event_file,
+      event_sink: Some(self.event_sink.unwrap_or_default()),
+      event_webhook_url: self.event_webhook_url,
+      fast_sync: self.fast_sync,
       first_inscription_height: Some(if self.integration_test {
         0
       } else {
@@ -370,6 +579,7 @@ impl Settings {
       hidden: self.hidden,
       http_port: self.http_port,
       index: Some(index),
+      index_address_history: self.index_address_history,
       index_addresses: self.index_addresses,
       index_cache_size: Some(match self.index_cache_size {
         Some(index_cache_size) => index_cache_size,
@@ -379,18 +589,41 @@ impl Settings {
           usize::try_from(sys.total_memory() / 4)?
         }
       }),
+      index_content: self.index_content,
+      index_full_text: self.index_full_text,
+      index_metaprotocols: self.index_metaprotocols,
       index_runes: self.index_runes,
       index_sats: self.index_sats,
+      index_search: self.index_search,
       index_spent_sats: self.index_spent_sats,
       index_transactions: self.index_transactions,
+      index_transfers: self.index_transfers,
       integration_test: self.integration_test,
+      kafka_acks: Some(self.kafka_acks.unwrap_or_default()),
+      kafka_brokers: self.kafka_brokers,
+      kafka_key: Some(self.kafka_key.unwrap_or_default()),
+      kafka_topic: Some(self.kafka_topic.unwrap_or_else(|| "ord".into())),
+      max_savepoints: Some(max_savepoints),
+      mempool_events: self.mempool_events,
       no_index_inscriptions: self.no_index_inscriptions,
+      reorg_rollback: self.reorg_rollback,
+      rich_events: self.rich_events,
+      savepoint_interval: Some(savepoint_interval),
       server_password: self.server_password,
       server_url: self.server_url,
       server_username: self.server_username,
+      undo_log_depth: Some(self.undo_log_depth.unwrap_or(100)),
     })
   }
 
+  #[cfg(test)]
+  pub(crate) fn with_index(self, index: PathBuf) -> Self {
+    Self {
+      index: Some(index),
+      ..self
+    }
+  }
+
   pub fn default_data_dir() -> Result<PathBuf> {
     Ok(
       dirs::data_dir()
@@ -399,6 +632,13 @@ impl Settings {
     )
   }
 
+  pub fn bitcoin_blocks_dir(&self) -> PathBuf {
+    self
+      .chain()
+      .join_with_data_dir(self.bitcoin_data_dir.as_ref().unwrap())
+      .join("blocks")
+  }
+
   pub fn bitcoin_credentials(&self) -> Result<Auth> {
     if let Some((user, pass)) = &self
       .bitcoin_rpc_username
@@ -472,10 +712,22 @@ impl Settings {
     Ok(client)
   }
 
+  pub fn block_source(&self) -> BlockSourceKind {
+    self.block_source.unwrap()
+  }
+
+  pub fn block_source_fixture(&self) -> Option<&Path> {
+    self.block_source_fixture.as_deref()
+  }
+
   pub fn chain(&self) -> Chain {
     self.chain.unwrap()
   }
 
+  pub fn chain_tip_distance(&self) -> u32 {
+    self.chain_tip_distance.unwrap()
+  }
+
   pub fn commit_interval(&self) -> usize {
     self.commit_interval.unwrap()
   }
@@ -513,6 +765,30 @@ impl Settings {
     self.data_dir.as_ref().unwrap().into()
   }
 
+  pub fn event_body_limit(&self) -> Option<usize> {
+    self.event_body_limit
+  }
+
+  pub fn event_encoding(&self) -> EventEncoding {
+    self.event_encoding.unwrap()
+  }
+
+  pub fn event_file(&self) -> Option<&Path> {
+    self.event_file.as_deref()
+  }
+
+  pub fn event_sink(&self) -> EventSinkKind {
+    self.event_sink.unwrap()
+  }
+
+  pub fn event_webhook_url(&self) -> Option<&str> {
+    self.event_webhook_url.as_deref()
+  }
+
+  pub fn fast_sync(&self) -> bool {
+    self.fast_sync
+  }
+
   pub fn first_inscription_height(&self) -> u32 {
     self.first_inscription_height.unwrap()
   }
@@ -533,8 +809,24 @@ impl Settings {
     self.index.as_ref().unwrap()
   }
 
+  pub fn index_address_history(&self) -> bool {
+    self.index_address_history
+  }
+
   pub fn index_addresses(&self) -> bool {
-    self.index_addresses
+    self.index_addresses || self.index_address_history
+  }
+
+  pub fn index_content(&self) -> bool {
+    self.index_content
+  }
+
+  pub fn index_full_text(&self) -> bool {
+    self.index_full_text
+  }
+
+  pub fn index_metaprotocols(&self) -> &BTreeSet<String> {
+    &self.index_metaprotocols
   }
 
   pub fn index_inscriptions(&self) -> bool {
@@ -545,6 +837,10 @@ impl Settings {
     self.index_runes
   }
 
+  pub fn index_search(&self) -> bool {
+    self.index_search
+  }
+
   pub fn index_cache_size(&self) -> usize {
     self.index_cache_size.unwrap()
   }
@@ -561,10 +857,56 @@ impl Settings {
     self.index_transactions
   }
 
+  pub fn index_transfers(&self) -> bool {
+    self.index_transfers
+  }
+
   pub fn integration_test(&self) -> bool {
     self.integration_test
   }
 
+  pub fn kafka_acks(&self) -> KafkaAcks {
+    self.kafka_acks.unwrap()
+  }
+
+  pub fn kafka_brokers(&self) -> Option<&str> {
+    self.kafka_brokers.as_deref()
+  }
+
+  pub fn kafka_key(&self) -> KafkaKey {
+    self.kafka_key.unwrap()
+  }
+
+  pub fn kafka_topic(&self) -> &str {
+    self.kafka_topic.as_ref().unwrap()
+  }
+
+  pub fn max_savepoints(&self) -> u32 {
+    self.max_savepoints.unwrap()
+  }
+
+  pub fn mempool_events(&self) -> bool {
+    self.mempool_events
+  }
+
+  // `None` unless passed explicitly, since existing indexes use the mode they
+  // were built with
+  pub fn reorg_rollback(&self) -> Option<Rollback> {
+    self.reorg_rollback
+  }
+
+  pub fn rich_events(&self) -> bool {
+    self.rich_events
+  }
+
+  pub fn savepoint_interval(&self) -> u32 {
+    self.savepoint_interval.unwrap()
+  }
+
+  pub fn undo_log_depth(&self) -> u32 {
+    self.undo_log_depth.unwrap()
+  }
+
   pub fn is_hidden(&self, inscription_id: InscriptionId) -> bool {
     self
       .hidden
@@ -920,6 +1262,40 @@ mod tests {
     assert_eq!(arguments.options.commit_interval, Some(500));
   }
 
+  #[test]
+  fn savepoint_settings_must_be_positive() {
+    assert_eq!(
+      Settings::from_options(Options::try_parse_from(["ord", "--max-savepoints", "0"]).unwrap())
+        .or_defaults()
+        .unwrap_err()
+        .to_string(),
+      "max savepoints must be greater than zero",
+    );
+
+    assert_eq!(
+      Settings::from_options(
+        Options::try_parse_from(["ord", "--savepoint-interval", "0"]).unwrap()
+      )
+      .or_defaults()
+      .unwrap_err()
+      .to_string(),
+      "savepoint interval must be greater than zero",
+    );
+  }
+
+  #[test]
+  fn reorg_rollback() {
+    assert_eq!(parse(&[]).reorg_rollback(), None);
+    assert_eq!(
+      parse(&["--reorg-rollback", "undo-log"]).reorg_rollback(),
+      Some(Rollback::UndoLog)
+    );
+    assert_eq!(parse(&[]).undo_log_depth(), 100);
+    assert_eq!(parse(&[]).max_savepoints(), 2);
+    assert_eq!(parse(&[]).savepoint_interval(), 10);
+    assert_eq!(parse(&[]).chain_tip_distance(), 21);
+  }
+
   #[test]
   fn index_runes() {
     assert!(parse(&["--chain=signet", "--index-runes"]).index_runes());
@@ -927,6 +1303,14 @@ mod tests {
     assert!(!parse(&[]).index_runes());
   }
 
+  #[test]
+  fn index_address_history_implies_index_addresses() {
+    assert!(parse(&["--index-address-history"]).index_addresses());
+    assert!(parse(&["--index-addresses"]).index_addresses());
+    assert!(!parse(&["--index-addresses"]).index_address_history());
+    assert!(!parse(&[]).index_addresses());
+  }
+
   #[test]
   fn bitcoin_rpc_and_pass_setting() {
     let config = Settings {
@@ -1017,28 +1401,53 @@ mod tests {
       ("BITCOIN_RPC_PASSWORD", "bitcoin password"),
       ("BITCOIN_RPC_URL", "url"),
       ("BITCOIN_RPC_USERNAME", "bitcoin username"),
+      ("BLOCK_SOURCE", "rest"),
+      ("BLOCK_SOURCE_FIXTURE", "fixture.json"),
       ("CHAIN", "signet"),
+      ("CHAIN_TIP_DISTANCE", "5"),
       ("COMMIT_INTERVAL", "1"),
       ("CONFIG", "config"),
       ("CONFIG_DIR", "config dir"),
       ("COOKIE_FILE", "cookie file"),
       ("DATA_DIR", "/data/dir"),
+      ("EVENT_BODY_LIMIT", "1024"),
+      ("EVENT_ENCODING", "protobuf"),
+      ("EVENT_FILE", "events.ndjson"),
+      ("EVENT_SINK", "webhook"),
+      ("EVENT_WEBHOOK_URL", "http://localhost:8000/events"),
+      ("FAST_SYNC", "1"),
       ("FIRST_INSCRIPTION_HEIGHT", "2"),
       ("HEIGHT_LIMIT", "3"),
       ("HIDDEN", "6fb976ab49dcec017f1e201e84395983204ae1a7c2abf7ced0a85d692e442799i0 703e5f7c49d82aab99e605af306b9a30e991e57d42f982908a962a81ac439832i0"),
     ("HTTP_PORT", "8080"),
       ("INDEX", "index"),
       ("INDEX_CACHE_SIZE", "4"),
+      ("INDEX_CONTENT", "1"),
+      ("INDEX_FULL_TEXT", "1"),
+      ("INDEX_METAPROTOCOLS", "token"),
+      ("INDEX_ADDRESS_HISTORY", "1"),
       ("INDEX_ADDRESSES", "1"),
       ("INDEX_RUNES", "1"),
       ("INDEX_SATS", "1"),
+      ("INDEX_SEARCH", "1"),
       ("INDEX_SPENT_SATS", "1"),
       ("INDEX_TRANSACTIONS", "1"),
+      ("INDEX_TRANSFERS", "1"),
       ("INTEGRATION_TEST", "1"),
+      ("KAFKA_ACKS", "leader"),
+      ("KAFKA_BROKERS", "localhost:9092"),
+      ("KAFKA_KEY", "height"),
+      ("KAFKA_TOPIC", "events"),
+      ("MAX_SAVEPOINTS", "6"),
+      ("MEMPOOL_EVENTS", "1"),
       ("NO_INDEX_INSCRIPTIONS", "1"),
+      ("REORG_ROLLBACK", "undo-log"),
+      ("RICH_EVENTS", "1"),
+      ("SAVEPOINT_INTERVAL", "7"),
       ("SERVER_PASSWORD", "server password"),
       ("SERVER_URL", "server url"),
       ("SERVER_USERNAME", "server username"),
+      ("UNDO_LOG_DEPTH", "8"),
     ]
     .into_iter()
     .map(|(key, value)| (key.into(), value.into()))
@@ -1052,12 +1461,21 @@ mod tests {
         bitcoin_rpc_password: Some("bitcoin password".into()),
         bitcoin_rpc_url: Some("url".into()),
         bitcoin_rpc_username: Some("bitcoin username".into()),
+        block_source: Some(BlockSourceKind::Rest),
+        block_source_fixture: Some("fixture.json".into()),
         chain: Some(Chain::Signet),
+        chain_tip_distance: Some(5),
         commit_interval: Some(1),
         config: Some("config".into()),
         config_dir: Some("config dir".into()),
         cookie_file: Some("cookie file".into()),
         data_dir: Some("/data/dir".into()),
+        event_body_limit: Some(1024),
+        event_encoding: Some(EventEncoding::Protobuf),
+        event_file: Some("events.ndjson".into()),
+        event_sink: Some(EventSinkKind::Webhook),
+        event_webhook_url: Some("http://localhost:8000/events".into()),
+        fast_sync: true,
         first_inscription_height: Some(2),
         height_limit: Some(3),
         hidden: Some(
@@ -1074,17 +1492,33 @@ mod tests {
         ),
         http_port: Some(8080),
         index: Some("index".into()),
+        index_address_history: true,
         index_addresses: true,
         index_cache_size: Some(4),
+        index_content: true,
+        index_full_text: true,
+        index_metaprotocols: ["token".into()].into(),
         index_runes: true,
         index_sats: true,
+        index_search: true,
         index_spent_sats: true,
         index_transactions: true,
+        index_transfers: true,
         integration_test: true,
+        kafka_acks: Some(KafkaAcks::Leader),
+        kafka_brokers: Some("localhost:9092".into()),
+        kafka_key: Some(KafkaKey::Height),
+        kafka_topic: Some("events".into()),
+        max_savepoints: Some(6),
+        mempool_events: true,
         no_index_inscriptions: true,
+        reorg_rollback: Some(Rollback::UndoLog),
+        rich_events: true,
+        savepoint_interval: Some(7),
         server_password: Some("server password".into()),
         server_url: Some("server url".into()),
         server_username: Some("server username".into()),
+        undo_log_depth: Some(8),
       }
     );
   }
@@ -1100,25 +1534,50 @@ mod tests {
           "--bitcoin-rpc-password=bitcoin password",
           "--bitcoin-rpc-url=url",
           "--bitcoin-rpc-username=bitcoin username",
+          "--block-source=rest",
+          "--block-source-fixture=fixture.json",
           "--chain=signet",
+          "--chain-tip-distance=5",
           "--commit-interval=1",
           "--config=config",
           "--config-dir=config dir",
           "--cookie-file=cookie file",
           "--datadir=/data/dir",
+          "--event-body-limit=1024",
+          "--event-encoding=protobuf",
+          "--event-file=events.ndjson",
+          "--event-sink=webhook",
+          "--event-webhook-url=http://localhost:8000/events",
+          "--fast-sync",
           "--first-inscription-height=2",
           "--height-limit=3",
+          "--index-address-history",
           "--index-addresses",
           "--index-cache-size=4",
+          "--index-content",
+          "--index-full-text",
+          "--index-metaprotocol=token",
           "--index-runes",
           "--index-sats",
+          "--index-search",
           "--index-spent-sats",
           "--index-transactions",
+          "--index-transfers",
           "--index=index",
           "--integration-test",
+          "--kafka-acks=leader",
+          "--kafka-brokers=localhost:9092",
+          "--kafka-key=height",
+          "--kafka-topic=events",
+          "--max-savepoints=6",
+          "--mempool-events",
           "--no-index-inscriptions",
+          "--reorg-rollback=undo-log",
+          "--rich-events",
+          "--savepoint-interval=7",
           "--server-password=server password",
           "--server-username=server username",
+          "--undo-log-depth=8",
         ])
         .unwrap()
       ),
@@ -1128,28 +1587,53 @@ mod tests {
         bitcoin_rpc_password: Some("bitcoin password".into()),
         bitcoin_rpc_url: Some("url".into()),
         bitcoin_rpc_username: Some("bitcoin username".into()),
+        block_source: Some(BlockSourceKind::Rest),
+        block_source_fixture: Some("fixture.json".into()),
         chain: Some(Chain::Signet),
+        chain_tip_distance: Some(5),
         commit_interval: Some(1),
         config: Some("config".into()),
         config_dir: Some("config dir".into()),
         cookie_file: Some("cookie file".into()),
         data_dir: Some("/data/dir".into()),
+        event_body_limit: Some(1024),
+        event_encoding: Some(EventEncoding::Protobuf),
+        event_file: Some("events.ndjson".into()),
+        event_sink: Some(EventSinkKind::Webhook),
+        event_webhook_url: Some("http://localhost:8000/events".into()),
+        fast_sync: true,
         first_inscription_height: Some(2),
         height_limit: Some(3),
         hidden: None,
         http_port: None,
         index: Some("index".into()),
+        index_address_history: true,
         index_addresses: true,
         index_cache_size: Some(4),
+        index_content: true,
+        index_full_text: true,
+        index_metaprotocols: ["token".into()].into(),
         index_runes: true,
         index_sats: true,
+        index_search: true,
         index_spent_sats: true,
         index_transactions: true,
+        index_transfers: true,
         integration_test: true,
+        kafka_acks: Some(KafkaAcks::Leader),
+        kafka_brokers: Some("localhost:9092".into()),
+        kafka_key: Some(KafkaKey::Height),
+        kafka_topic: Some("events".into()),
+        max_savepoints: Some(6),
+        mempool_events: true,
         no_index_inscriptions: true,
+        reorg_rollback: Some(Rollback::UndoLog),
+        rich_events: true,
+        savepoint_interval: Some(7),
         server_password: Some("server password".into()),
         server_url: None,
         server_username: Some("server username".into()),
+        undo_log_depth: Some(8),
       }
     );
   }
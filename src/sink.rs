@@ -0,0 +1,323 @@
+use {
+  super::*,
+  async_trait::async_trait,
+  clap::ValueEnum,
+  index::event::{Event, EventMessage},
+};
+
+pub use self::kafka::{KafkaAcks, KafkaKey};
+
+mod file;
+mod kafka;
+mod stdout;
+mod webhook;
+
+const BATCH_SIZE: usize = 1000;
+
+#[derive(Default, ValueEnum, Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum EventSinkKind {
+  File,
+  #[default]
+  Kafka,
+  Stdout,
+  Webhook,
+}
+
+impl FromStr for EventSinkKind {
+  type Err = Error;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s {
+      "file" => Ok(Self::File),
+      "kafka" => Ok(Self::Kafka),
+      "stdout" => Ok(Self::Stdout),
+      "webhook" => Ok(Self::Webhook),
+      _ => bail!("invalid event sink `{s}`"),
+    }
+  }
+}
+
+#[derive(Default, ValueEnum, Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum EventEncoding {
+  #[default]
+  Json,
+  Protobuf,
+}
+
+impl EventEncoding {
+  pub(crate) fn content_type(self) -> &'static str {
+    match self {
+      Self::Json => "application/json",
+      Self::Protobuf => "application/x-protobuf",
+    }
+  }
+
+  pub(crate) fn encode(self, message: &EventMessage) -> Result<Vec<u8>> {
+    match self {
+      Self::Json => Ok(serde_json::to_vec(message)?),
+      Self::Protobuf => message.to_protobuf(),
+    }
+  }
+}
+
+impl FromStr for EventEncoding {
+  type Err = Error;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s {
+      "json" => Ok(Self::Json),
+      "protobuf" => Ok(Self::Protobuf),
+      _ => bail!("invalid event encoding `{s}`"),
+    }
+  }
+}
+
+#[async_trait]
+pub trait EventSink: Send {
+  async fn send(&mut self, messages: &[EventMessage]) -> Result;
+
+  async fn flush(&mut self) -> Result {
+    Ok(())
+  }
+}
+
+pub fn open(settings: &Settings) -> Result<Box<dyn EventSink>> {
+  open_kind(settings, settings.event_sink())
+}
+
+pub fn open_kind(settings: &Settings, kind: EventSinkKind) -> Result<Box<dyn EventSink>> {
+  if settings.event_encoding() != EventEncoding::Json && kind != EventSinkKind::Kafka {
+    bail!("the `{kind:?}` event sink only supports JSON encoding, set `--event-encoding json`");
+  }
+
+  Ok(match kind {
+    EventSinkKind::File => Box::new(file::File::new(settings)?),
+    EventSinkKind::Kafka => Box::new(kafka::Kafka::new(settings)?),
+    EventSinkKind::Stdout => Box::new(stdout::Stdout),
+    EventSinkKind::Webhook => Box::new(webhook::Webhook::new(settings)?),
+  })
+}
+
+/// Opens the configured sink for events replayed by `ord index events`.
+pub fn open_replay(settings: &Settings) -> Result<Box<dyn EventSink>> {
+  match settings.event_sink() {
+    EventSinkKind::Kafka => Ok(Box::new(kafka::Kafka::replay(settings)?)),
+    kind => open_kind(settings, kind),
+  }
+}
+
+pub async fn drain(index: &Index, sink: &mut dyn EventSink) -> Result {
+  loop {
+    let messages = index
+      .pending_events(BATCH_SIZE)?
+      .into_iter()
+      .map(|(event_id, event)| EventMessage::new(event_id, event))
+      .collect::<Vec<EventMessage>>();
+
+    let Some(last) = messages.last() else {
+      break;
+    };
+
+    let last = last.event_id;
+
+    sink.send(&messages).await?;
+
+    sink.flush().await?;
+
+    index.mark_events_delivered(last)?;
+  }
+
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use {super::*, crate::index::testing::Context};
+
+  #[derive(Default)]
+  struct Recorder {
+    fail: bool,
+    fail_flush: bool,
+    messages: Vec<EventMessage>,
+  }
+
+  #[async_trait]
+  impl EventSink for Recorder {
+    async fn send(&mut self, messages: &[EventMessage]) -> Result {
+      if self.fail {
+        bail!("failed to send");
+      }
+
+      self.messages.extend_from_slice(messages);
+
+      Ok(())
+    }
+
+    async fn flush(&mut self) -> Result {
+      if self.fail_flush {
+        bail!("failed to flush");
+      }
+
+      Ok(())
+    }
+  }
+
+  #[test]
+  fn from_str() {
+    for kind in [
+      EventSinkKind::File,
+      EventSinkKind::Kafka,
+      EventSinkKind::Stdout,
+      EventSinkKind::Webhook,
+    ] {
+      assert_eq!(
+        serde_json::to_string(&kind)
+          .unwrap()
+          .trim_matches('"')
+          .parse::<EventSinkKind>()
+          .unwrap(),
+        kind
+      );
+    }
+
+    assert_eq!(
+      "foo".parse::<EventSinkKind>().unwrap_err().to_string(),
+      "invalid event sink `foo`"
+    );
+  }
+
+  #[test]
+  fn event_encoding_from_str() {
+    for encoding in [EventEncoding::Json, EventEncoding::Protobuf] {
+      assert_eq!(
+        serde_json::to_string(&encoding)
+          .unwrap()
+          .trim_matches('"')
+          .parse::<EventEncoding>()
+          .unwrap(),
+        encoding
+      );
+    }
+
+    assert_eq!(
+      "foo".parse::<EventEncoding>().unwrap_err().to_string(),
+      "invalid event encoding `foo`"
+    );
+  }
+
+  #[test]
+  fn protobuf_encoding_requires_kafka() {
+    let settings = Settings::from_options(
+      Options::try_parse_from(["ord", "--event-encoding", "protobuf"]).unwrap(),
+    )
+    .or_defaults()
+    .unwrap();
+
+    assert_eq!(
+      open_kind(&settings, EventSinkKind::Stdout)
+        .err()
+        .unwrap()
+        .to_string(),
+      "the `Stdout` event sink only supports JSON encoding, set `--event-encoding json`"
+    );
+  }
+
+  #[test]
+  fn messages_include_schema_version() {
+    let message = EventMessage::new(
+      7,
+      Event::RuneEtched {
+        block_height: 5,
+        rune_id: RuneId { block: 5, tx: 1 },
+        txid: Txid::all_zeros(),
+      },
+    );
+
+    let json = serde_json::to_string(&message).unwrap();
+
+    assert_eq!(
+      json,
+      format!(
+        r#"{{"event_id":7,"schema_version":{},"event":{{"RuneEtched":{{"block_height":5,"rune_id":"5:1","txid":"{}"}}}}}}"#,
+        EventMessage::SCHEMA_VERSION,
+        Txid::all_zeros(),
+      )
+    );
+
+    assert_eq!(
+      serde_json::from_str::<EventMessage>(&json).unwrap(),
+      message
+    );
+  }
+
+  #[test]
+  fn drain_delivers_pending_events() {
+    let context = Context::builder().event_outbox().build();
+
+    context.mine_blocks(1);
+
+    let mut recorder = Recorder::default();
+
+    Runtime::new()
+      .unwrap()
+      .block_on(drain(&context.index, &mut recorder))
+      .unwrap();
+
+    assert_eq!(
+      recorder
+        .messages
+        .iter()
+        .map(|message| (message.event_id, message.event.block_height()))
+        .collect::<Vec<(u64, u32)>>(),
+      [(0, 0), (1, 0), (2, 1), (3, 1)],
+    );
+
+    assert!(context.index.pending_events(usize::MAX).unwrap().is_empty());
+  }
+
+  #[test]
+  fn failed_sends_are_retained() {
+    let context = Context::builder().event_outbox().build();
+
+    context.mine_blocks(1);
+
+    let mut recorder = Recorder {
+      fail: true,
+      ..default()
+    };
+
+    assert!(Runtime::new()
+      .unwrap()
+      .block_on(drain(&context.index, &mut recorder))
+      .is_err());
+
+    assert_eq!(context.index.pending_events(usize::MAX).unwrap().len(), 4);
+  }
+
+  #[test]
+  fn failed_flushes_are_retained() {
+    let context = Context::builder().event_outbox().build();
+
+    context.mine_blocks(1);
+
+    let mut recorder = Recorder {
+      fail_flush: true,
+      ..default()
+    };
+
+    assert_eq!(
+      Runtime::new()
+        .unwrap()
+        .block_on(drain(&context.index, &mut recorder))
+        .unwrap_err()
+        .to_string(),
+      "failed to flush",
+    );
+
+    assert_eq!(recorder.messages.len(), 4);
+
+    assert_eq!(context.index.pending_events(usize::MAX).unwrap().len(), 4);
+  }
+}
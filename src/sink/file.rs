@@ -0,0 +1,232 @@
+use {
+  super::*,
+  std::{
+    io::{BufWriter, Write},
+    time::UNIX_EPOCH,
+  },
+};
+
+pub(crate) struct File {
+  max_size: u64,
+  path: PathBuf,
+  size: u64,
+  writer: BufWriter<fs::File>,
+}
+
+impl File {
+  const MAX_SIZE: u64 = 256 * 1024 * 1024;
+
+  pub(crate) fn new(settings: &Settings) -> Result<Self> {
+    let Some(path) = settings.event_file() else {
+      bail!("no event file configured, set file with `--event-file`");
+    };
+
+    Self::with_max_size(path.into(), Self::MAX_SIZE)
+  }
+
+  fn with_max_size(path: PathBuf, max_size: u64) -> Result<Self> {
+    let (writer, size) = Self::open(&path)?;
+
+    Ok(Self {
+      max_size,
+      path,
+      size,
+      writer,
+    })
+  }
+
+  fn open(path: &Path) -> Result<(BufWriter<fs::File>, u64)> {
+    let file = fs::OpenOptions::new()
+      .create(true)
+      .append(true)
+      .open(path)
+      .with_context(|| format!("failed to open event file `{}`", path.display()))?;
+
+    let size = file.metadata()?.len();
+
+    Ok((BufWriter::new(file), size))
+  }
+
+  fn rotate(&mut self) -> Result {
+    self.writer.flush()?;
+
+    // files rotated within the same millisecond would otherwise overwrite
+    // each other, so advance the suffix until it is unused
+    let mut suffix = SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis();
+
+    let rotated = loop {
+      let mut rotated = self.path.clone().into_os_string();
+      rotated.push(format!(".{suffix}"));
+
+      let rotated = PathBuf::from(rotated);
+
+      if !rotated.try_exists()? {
+        break rotated;
+      }
+
+      suffix += 1;
+    };
+
+    fs::rename(&self.path, &rotated)?;
+
+    (self.writer, self.size) = Self::open(&self.path)?;
+
+    Ok(())
+  }
+}
+
+#[async_trait]
+impl EventSink for File {
+  async fn send(&mut self, messages: &[EventMessage]) -> Result {
+    for message in messages {
+      let mut line = serde_json::to_vec(message)?;
+      line.push(b'\n');
+
+      let len = u64::try_from(line.len()).unwrap();
+
+      if self.size > 0 && self.size + len > self.max_size {
+        self.rotate()?;
+      }
+
+      self.writer.write_all(&line)?;
+      self.size += len;
+    }
+
+    Ok(())
+  }
+
+  async fn flush(&mut self) -> Result {
+    self.writer.flush()?;
+    self.writer.get_ref().sync_data()?;
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use {super::*, tempfile::TempDir};
+
+  fn message(event_id: u64) -> EventMessage {
+    EventMessage::new(
+      event_id,
+      Event::RuneEtched {
+        block_height: 5,
+        rune_id: RuneId { block: 5, tx: 1 },
+        txid: Txid::all_zeros(),
+      },
+    )
+  }
+
+  fn read(path: &Path) -> Vec<u64> {
+    fs::read_to_string(path)
+      .unwrap()
+      .lines()
+      .map(|line| serde_json::from_str::<EventMessage>(line).unwrap().event_id)
+      .collect()
+  }
+
+  #[test]
+  fn file_is_required() {
+    assert_eq!(
+      File::new(&Settings::default()).err().unwrap().to_string(),
+      "no event file configured, set file with `--event-file`"
+    );
+  }
+
+  #[test]
+  fn events_are_appended() {
+    let tempdir = TempDir::new().unwrap();
+
+    let path = tempdir.path().join("events.ndjson");
+
+    let runtime = Runtime::new().unwrap();
+
+    for event_id in 0..2 {
+      let mut file = File::with_max_size(path.clone(), u64::MAX).unwrap();
+      runtime.block_on(file.send(&[message(event_id)])).unwrap();
+      runtime.block_on(file.flush()).unwrap();
+    }
+
+    assert_eq!(read(&path), [0, 1]);
+  }
+
+  #[test]
+  fn files_are_rotated() {
+    let tempdir = TempDir::new().unwrap();
+
+    let path = tempdir.path().join("events.ndjson");
+
+    let len = u64::try_from(serde_json::to_vec(&message(0)).unwrap().len()).unwrap() + 1;
+
+    let mut file = File::with_max_size(path.clone(), len * 2).unwrap();
+
+    let runtime = Runtime::new().unwrap();
+
+    runtime
+      .block_on(file.send(&[message(0), message(1), message(2)]))
+      .unwrap();
+    runtime.block_on(file.flush()).unwrap();
+
+    assert_eq!(read(&path), [2]);
+
+    let rotated = fs::read_dir(tempdir.path())
+      .unwrap()
+      .map(|entry| entry.unwrap().path())
+      .filter(|entry| *entry != path)
+      .collect::<Vec<PathBuf>>();
+
+    assert_eq!(rotated.len(), 1);
+
+    assert!(rotated[0]
+      .file_name()
+      .unwrap()
+      .to_str()
+      .unwrap()
+      .starts_with("events.ndjson."));
+
+    assert_eq!(read(&rotated[0]), [0, 1]);
+  }
+
+  #[test]
+  fn rotated_files_are_not_overwritten() {
+    let tempdir = TempDir::new().unwrap();
+
+    let path = tempdir.path().join("events.ndjson");
+
+    let len = u64::try_from(serde_json::to_vec(&message(0)).unwrap().len()).unwrap() + 1;
+
+    let mut file = File::with_max_size(path.clone(), len).unwrap();
+
+    let runtime = Runtime::new().unwrap();
+
+    runtime
+      .block_on(file.send(&(0..10).map(message).collect::<Vec<EventMessage>>()))
+      .unwrap();
+    runtime.block_on(file.flush()).unwrap();
+
+    let mut rotated = fs::read_dir(tempdir.path())
+      .unwrap()
+      .map(|entry| entry.unwrap().path())
+      .filter(|entry| *entry != path)
+      .collect::<Vec<PathBuf>>();
+
+    rotated.sort_by_key(|path| {
+      path
+        .extension()
+        .unwrap()
+        .to_str()
+        .unwrap()
+        .parse::<u128>()
+        .unwrap()
+    });
+
+    assert_eq!(
+      rotated
+        .iter()
+        .flat_map(|path| read(path))
+        .chain(read(&path))
+        .collect::<Vec<u64>>(),
+      (0..10).collect::<Vec<u64>>(),
+    );
+  }
+}
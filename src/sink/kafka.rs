@@ -0,0 +1,651 @@
+use {
+  super::*,
+  rdkafka::{
+    config::ClientConfig,
+    consumer::{BaseConsumer, Consumer},
+    error::{KafkaError, RDKafkaErrorCode},
+    message::{Header, Headers, OwnedHeaders},
+    producer::{DeliveryFuture, FutureProducer, FutureRecord, Producer},
+    types::RDKafkaRespErr,
+    Message, Offset, TopicPartitionList,
+  },
+};
+
+#[derive(Default, ValueEnum, Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum KafkaAcks {
+  None,
+  Leader,
+  #[default]
+  All,
+}
+
+impl KafkaAcks {
+  fn config_value(self) -> &'static str {
+    match self {
+      Self::None => "0",
+      Self::Leader => "1",
+      Self::All => "all",
+    }
+  }
+}
+
+impl FromStr for KafkaAcks {
+  type Err = Error;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s {
+      "none" => Ok(Self::None),
+      "leader" => Ok(Self::Leader),
+      "all" => Ok(Self::All),
+      _ => bail!("invalid kafka acks `{s}`"),
+    }
+  }
+}
+
+#[derive(Default, ValueEnum, Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum KafkaKey {
+  #[default]
+  Id,
+  Height,
+  None,
+}
+
+impl FromStr for KafkaKey {
+  type Err = Error;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s {
+      "id" => Ok(Self::Id),
+      "height" => Ok(Self::Height),
+      "none" => Ok(Self::None),
+      _ => bail!("invalid kafka key `{s}`"),
+    }
+  }
+}
+
+pub(crate) struct Kafka {
+  encoding: EventEncoding,
+  key: KafkaKey,
+  last_event_id: Option<u64>,
+  producer: FutureProducer,
+  topic: String,
+  transactional: bool,
+}
+
+impl Kafka {
+  const FLUSH_TIMEOUT: Duration = Duration::from_secs(30);
+  const QUEUE_FULL_BACKOFF: Duration = Duration::from_millis(100);
+  const SCAN_WINDOW: i64 = 1024;
+  const TIMEOUT: Duration = Duration::from_secs(30);
+
+  pub(crate) fn new(settings: &Settings) -> Result<Self> {
+    Self::open(settings, false)
+  }
+
+  /// Opens a producer for replayed events, which are numbered independently
+  /// of the outbox. It uses its own transactional ID, so that it doesn't
+  /// fence `ord index publish`, and doesn't skip events already in the topic.
+  pub(crate) fn replay(settings: &Settings) -> Result<Self> {
+    Self::open(settings, true)
+  }
+
+  fn open(settings: &Settings, replay: bool) -> Result<Self> {
+    let Some(brokers) = settings.kafka_brokers() else {
+      bail!("no Kafka brokers configured, set brokers with `--kafka-brokers`");
+    };
+
+    let topic = settings.kafka_topic();
+
+    log::info!("Publishing events to Kafka topic `{topic}` at {brokers}");
+
+    let acks = settings.kafka_acks();
+
+    let mut config = ClientConfig::new();
+
+    config
+      .set("bootstrap.servers", brokers)
+      .set("acks", acks.config_value());
+
+    // Transactions require `acks=all`. Without them, only allow one request
+    // in flight, so that retries can't reorder events.
+    let transactional = acks == KafkaAcks::All;
+
+    if transactional {
+      config.set("enable.idempotence", "true").set(
+        "transactional.id",
+        if replay {
+          format!("ord-{topic}-replay")
+        } else {
+          format!("ord-{topic}")
+        },
+      );
+    } else {
+      config.set("max.in.flight.requests.per.connection", "1");
+    }
+
+    let producer: FutureProducer = config
+      .create()
+      .with_context(|| format!("failed to create Kafka producer for `{brokers}`"))?;
+
+    // Initializing transactions aborts any transaction left open by a
+    // previous producer, so everything in the topic afterwards is committed.
+    if transactional {
+      producer
+        .init_transactions(Self::TIMEOUT)
+        .with_context(|| format!("failed to initialize Kafka transactions for `{brokers}`"))?;
+    }
+
+    let last_event_id = if transactional && !replay {
+      let last_event_id = Self::last_event_id(brokers, topic)?;
+
+      if let Some(last_event_id) = last_event_id {
+        log::info!("Resuming after event {last_event_id} in Kafka topic `{topic}`");
+      }
+
+      last_event_id
+    } else {
+      None
+    };
+
+    Ok(Self {
+      encoding: settings.event_encoding(),
+      key: settings.kafka_key(),
+      last_event_id,
+      producer,
+      topic: topic.into(),
+      transactional,
+    })
+  }
+
+  /// Returns the highest event ID committed to `topic`. Events within a
+  /// partition are in order, so each partition is scanned backwards from its
+  /// end until a message with an `event-id` header is found.
+  fn last_event_id(brokers: &str, topic: &str) -> Result<Option<u64>> {
+    let consumer: BaseConsumer = ClientConfig::new()
+      .set("bootstrap.servers", brokers)
+      .set("group.id", format!("ord-{topic}"))
+      .set("enable.auto.commit", "false")
+      .set("enable.partition.eof", "true")
+      .set("isolation.level", "read_committed")
+      .create()
+      .with_context(|| format!("failed to create Kafka consumer for `{brokers}`"))?;
+
+    let metadata = consumer
+      .fetch_metadata(Some(topic), Self::TIMEOUT)
+      .with_context(|| format!("failed to fetch metadata for Kafka topic `{topic}`"))?;
+
+    let Some(topic_metadata) = metadata.topics().first() else {
+      return Ok(None);
+    };
+
+    match topic_metadata.error() {
+      None => {}
+      Some(RDKafkaRespErr::RD_KAFKA_RESP_ERR_UNKNOWN_TOPIC_OR_PART) => return Ok(None),
+      Some(err) => bail!("failed to fetch metadata for Kafka topic `{topic}`: {err:?}"),
+    }
+
+    let mut last = None;
+
+    for partition in topic_metadata.partitions() {
+      let (low, mut high) = consumer
+        .fetch_watermarks(topic, partition.id(), Self::TIMEOUT)
+        .with_context(|| format!("failed to fetch offsets for Kafka topic `{topic}`"))?;
+
+      while high > low {
+        let start = low.max(high - Self::SCAN_WINDOW);
+
+        let mut assignment = TopicPartitionList::new();
+        assignment.add_partition_offset(topic, partition.id(), Offset::Offset(start))?;
+        consumer.assign(&assignment)?;
+
+        let mut partition_last = None;
+
+        loop {
+          match consumer.poll(Self::TIMEOUT) {
+            None => bail!("timed out reading Kafka topic `{topic}`"),
+            Some(Err(KafkaError::PartitionEOF(_))) => break,
+            Some(Err(err)) => return Err(err.into()),
+            Some(Ok(message)) => {
+              if let Some(event_id) = message.headers().and_then(|headers| {
+                headers
+                  .iter()
+                  .find(|header| header.key == "event-id")
+                  .and_then(|header| header.value)
+                  .and_then(|value| std::str::from_utf8(value).ok())
+                  .and_then(|value| value.parse::<u64>().ok())
+              }) {
+                partition_last = partition_last.max(Some(event_id));
+              }
+            }
+          }
+        }
+
+        if partition_last.is_some() {
+          last = last.max(partition_last);
+          break;
+        }
+
+        high = start;
+      }
+    }
+
+    Ok(last)
+  }
+
+  fn key(&self, event: &Event) -> Option<String> {
+    match self.key {
+      KafkaKey::Id => event
+        .inscription_id()
+        .map(|inscription_id| inscription_id.to_string())
+        .or_else(|| event.rune_id().map(|rune_id| rune_id.to_string())),
+      KafkaKey::Height => Some(event.block_height().to_string()),
+      KafkaKey::None => None,
+    }
+  }
+
+  async fn enqueue(&self, message: &EventMessage) -> Result<DeliveryFuture> {
+    let payload = self.encoding.encode(message)?;
+
+    let key = self.key(&message.event);
+
+    let event_id = message.event_id.to_string();
+
+    let mut record = FutureRecord::<str, [u8]>::to(&self.topic)
+      .payload(&payload)
+      .headers(
+        OwnedHeaders::new()
+          .insert(Header {
+            key: "event-id",
+            value: Some(&event_id),
+          })
+          .insert(Header {
+            key: "content-type",
+            value: Some(self.encoding.content_type()),
+          }),
+      );
+
+    if let Some(key) = &key {
+      record = record.key(key);
+    }
+
+    loop {
+      match self.producer.send_result(record) {
+        Ok(delivery) => return Ok(delivery),
+        Err((KafkaError::MessageProduction(RDKafkaErrorCode::QueueFull), returned)) => {
+          record = returned;
+          tokio::time::sleep(Self::QUEUE_FULL_BACKOFF).await;
+        }
+        Err((err, _record)) => {
+          bail!("failed to publish event to `{}`: {err}", self.topic)
+        }
+      }
+    }
+  }
+
+  /// Enqueues all messages before waiting for any of them to be delivered.
+  /// The producer preserves their order within each partition.
+  async fn publish(&self, messages: &[EventMessage]) -> Result {
+    let mut deliveries = Vec::with_capacity(messages.len());
+
+    for message in messages {
+      deliveries.push(self.enqueue(message).await?);
+    }
+
+    for delivery in deliveries {
+      delivery
+        .await
+        .map_err(|_| anyhow!("failed to publish event to `{}`: canceled", self.topic))?
+        .map_err(|(err, _message)| anyhow!("failed to publish event to `{}`: {err}", self.topic))?;
+    }
+
+    Ok(())
+  }
+}
+
+#[async_trait]
+impl EventSink for Kafka {
+  async fn send(&mut self, messages: &[EventMessage]) -> Result {
+    // skip events that were committed to the topic before a restart, but not
+    // yet marked delivered in the outbox
+    let messages = match self.last_event_id {
+      Some(last_event_id) => {
+        &messages[messages.partition_point(|message| message.event_id <= last_event_id)..]
+      }
+      None => messages,
+    };
+
+    let Some(last) = messages.last() else {
+      return Ok(());
+    };
+
+    let last = last.event_id;
+
+    if !self.transactional {
+      return self.publish(messages).await;
+    }
+
+    self.producer.begin_transaction()?;
+
+    if let Err(err) = self.publish(messages).await {
+      self.producer.abort_transaction(Self::TIMEOUT)?;
+      return Err(err);
+    }
+
+    self
+      .producer
+      .commit_transaction(Self::TIMEOUT)
+      .with_context(|| format!("failed to commit events to `{}`", self.topic))?;
+
+    self.last_event_id = Some(last);
+
+    Ok(())
+  }
+
+  async fn flush(&mut self) -> Result {
+    self.producer.flush(Self::FLUSH_TIMEOUT)?;
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use {
+    super::*,
+    crate::index::testing::Context,
+    rdkafka::{message::BorrowedMessage, mocking::MockCluster},
+  };
+
+  fn settings(args: &[&str]) -> Settings {
+    Settings::from_options(
+      Options::try_parse_from(iter::once("ord").chain(args.iter().copied())).unwrap(),
+    )
+    .or_defaults()
+    .unwrap()
+  }
+
+  fn header(message: &BorrowedMessage, key: &str) -> String {
+    String::from_utf8(
+      message
+        .headers()
+        .unwrap()
+        .iter()
+        .find(|header| header.key == key)
+        .unwrap()
+        .value
+        .unwrap()
+        .into(),
+    )
+    .unwrap()
+  }
+
+  fn consume(brokers: &str, topic: &str) -> Vec<(Option<String>, String, Event)> {
+    let consumer: BaseConsumer = ClientConfig::new()
+      .set("bootstrap.servers", brokers)
+      .set("group.id", "test")
+      .set("enable.partition.eof", "true")
+      .create()
+      .unwrap();
+
+    let mut assignment = TopicPartitionList::new();
+    assignment
+      .add_partition_offset(topic, 0, Offset::Beginning)
+      .unwrap();
+    consumer.assign(&assignment).unwrap();
+
+    let mut messages = Vec::new();
+
+    loop {
+      let message = match consumer
+        .poll(Duration::from_secs(30))
+        .expect("timed out waiting for message")
+      {
+        Err(KafkaError::PartitionEOF(_)) => break,
+        message => message.unwrap(),
+      };
+
+      messages.push((
+        message
+          .key()
+          .map(|key| String::from_utf8(key.into()).unwrap()),
+        header(&message, "event-id"),
+        match header(&message, "content-type").as_str() {
+          "application/json" => {
+            serde_json::from_slice::<EventMessage>(message.payload().unwrap())
+              .unwrap()
+              .event
+          }
+          "application/x-protobuf" => {
+            EventMessage::from_protobuf(message.payload().unwrap())
+              .unwrap()
+              .event
+          }
+          content_type => panic!("unexpected content type {content_type}"),
+        },
+      ));
+    }
+
+    messages
+  }
+
+  #[test]
+  fn settings_are_required() {
+    assert_eq!(
+      Kafka::new(&settings(&[])).err().unwrap().to_string(),
+      "no Kafka brokers configured, set brokers with `--kafka-brokers`"
+    );
+  }
+
+  #[test]
+  fn keys() {
+    let cluster = MockCluster::new(1).unwrap();
+
+    let brokers = cluster.bootstrap_servers();
+
+    let event = Event::RuneEtched {
+      block_height: 5,
+      rune_id: RuneId { block: 5, tx: 1 },
+      txid: Txid::all_zeros(),
+    };
+
+    for (strategy, expected) in [
+      ("id", Some("5:1".to_string())),
+      ("height", Some("5".to_string())),
+      ("none", None),
+    ] {
+      let kafka = Kafka::new(&settings(&[
+        "--kafka-brokers",
+        &brokers,
+        "--kafka-key",
+        strategy,
+      ]))
+      .unwrap();
+
+      assert_eq!(kafka.key(&event), expected);
+    }
+  }
+
+  #[test]
+  fn events_are_published() {
+    let cluster = MockCluster::new(1).unwrap();
+
+    let brokers = cluster.bootstrap_servers();
+
+    cluster.create_topic("events", 1, 1).unwrap();
+
+    let mut kafka = Kafka::new(&settings(&[
+      "--kafka-brokers",
+      &brokers,
+      "--kafka-topic",
+      "events",
+    ]))
+    .unwrap();
+
+    let context = Context::builder().event_outbox().build();
+
+    context.mine_blocks(1);
+
+    context.index.mark_events_delivered(3).unwrap();
+
+    let txid = context.core.broadcast_tx(TransactionTemplate {
+      inputs: &[(1, 0, 0, inscription("text/plain", "foo").to_witness())],
+      ..default()
+    });
+
+    context.mine_blocks(1);
+
+    let inscription_id = InscriptionId { txid, index: 0 };
+
+    assert_eq!(context.index.pending_events(usize::MAX).unwrap().len(), 3);
+
+    Runtime::new()
+      .unwrap()
+      .block_on(drain(&context.index, &mut kafka))
+      .unwrap();
+
+    assert!(context.index.pending_events(usize::MAX).unwrap().is_empty());
+
+    let messages = consume(&brokers, "events");
+
+    assert_eq!(messages.len(), 3);
+
+    assert!(matches!(
+      messages[0],
+      (
+        None,
+        _,
+        Event::BlockStarted {
+          block_height: 2,
+          ..
+        }
+      )
+    ));
+
+    assert!(matches!(
+      messages[2],
+      (
+        None,
+        _,
+        Event::BlockCommitted {
+          block_height: 2,
+          ..
+        }
+      )
+    ));
+
+    assert_eq!(
+      messages[1],
+      (
+        Some(inscription_id.to_string()),
+        "5".into(),
+        Event::InscriptionCreated {
+          block_height: 2,
+          charms: 0,
+          details: None,
+          inscription_id,
+          location: Some(SatPoint {
+            outpoint: OutPoint { txid, vout: 0 },
+            offset: 0,
+          }),
+          parent_inscription_ids: Vec::new(),
+          sequence_number: 0,
+        }
+      )
+    );
+  }
+
+  #[test]
+  fn events_can_be_published_as_protobuf() {
+    let cluster = MockCluster::new(1).unwrap();
+
+    let brokers = cluster.bootstrap_servers();
+
+    cluster.create_topic("events", 1, 1).unwrap();
+
+    let mut kafka = Kafka::new(&settings(&[
+      "--kafka-brokers",
+      &brokers,
+      "--kafka-topic",
+      "events",
+      "--event-encoding",
+      "protobuf",
+    ]))
+    .unwrap();
+
+    let context = Context::builder().event_outbox().build();
+
+    context.mine_blocks(1);
+
+    let expected = context
+      .index
+      .pending_events(usize::MAX)
+      .unwrap()
+      .into_iter()
+      .map(|(_event_id, event)| event)
+      .collect::<Vec<Event>>();
+
+    Runtime::new()
+      .unwrap()
+      .block_on(drain(&context.index, &mut kafka))
+      .unwrap();
+
+    assert_eq!(
+      consume(&brokers, "events")
+        .into_iter()
+        .map(|(_key, _event_id, event)| event)
+        .collect::<Vec<Event>>(),
+      expected,
+    );
+  }
+
+  #[test]
+  fn events_committed_before_restart_are_not_published_again() {
+    let cluster = MockCluster::new(1).unwrap();
+
+    let brokers = cluster.bootstrap_servers();
+
+    cluster.create_topic("events", 1, 1).unwrap();
+
+    let settings = settings(&["--kafka-brokers", &brokers, "--kafka-topic", "events"]);
+
+    let context = Context::builder().event_outbox().build();
+
+    context.mine_blocks(1);
+
+    let messages = context
+      .index
+      .pending_events(usize::MAX)
+      .unwrap()
+      .into_iter()
+      .map(|(event_id, event)| EventMessage::new(event_id, event))
+      .collect::<Vec<EventMessage>>();
+
+    let runtime = Runtime::new().unwrap();
+
+    // simulate being interrupted after the transaction commits, but before
+    // the events are marked delivered
+    runtime
+      .block_on(Kafka::new(&settings).unwrap().send(&messages[..2]))
+      .unwrap();
+
+    let mut kafka = Kafka::new(&settings).unwrap();
+
+    assert_eq!(kafka.last_event_id, Some(messages[1].event_id));
+
+    runtime.block_on(drain(&context.index, &mut kafka)).unwrap();
+
+    assert!(context.index.pending_events(usize::MAX).unwrap().is_empty());
+
+    assert_eq!(
+      consume(&brokers, "events")
+        .into_iter()
+        .map(|(_key, event_id, _event)| event_id.parse::<u64>().unwrap())
+        .collect::<Vec<u64>>(),
+      messages
+        .iter()
+        .map(|message| message.event_id)
+        .collect::<Vec<u64>>(),
+    );
+  }
+}
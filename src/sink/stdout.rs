@@ -0,0 +1,22 @@
+use {super::*, std::io::Write};
+
+pub(crate) struct Stdout;
+
+#[async_trait]
+impl EventSink for Stdout {
+  async fn send(&mut self, messages: &[EventMessage]) -> Result {
+    let mut stdout = io::stdout().lock();
+
+    for message in messages {
+      serde_json::to_writer(&mut stdout, message)?;
+      writeln!(stdout)?;
+    }
+
+    Ok(())
+  }
+
+  async fn flush(&mut self) -> Result {
+    io::stdout().flush()?;
+    Ok(())
+  }
+}
@@ -0,0 +1,241 @@
+use {super::*, reqwest::Client};
+
+pub(crate) struct Webhook {
+  client: Client,
+  url: String,
+}
+
+impl Webhook {
+  const MAX_RETRIES: u32 = 5;
+  const TIMEOUT: Duration = Duration::from_secs(30);
+
+  pub(crate) fn new(settings: &Settings) -> Result<Self> {
+    let Some(url) = settings.event_webhook_url() else {
+      bail!("no webhook URL configured, set URL with `--event-webhook-url`");
+    };
+
+    Ok(Self {
+      client: Self::client(Self::TIMEOUT)?,
+      url: url.into(),
+    })
+  }
+
+  /// Requests that don't complete within `timeout` fail and are retried, so
+  /// that an endpoint that never responds can't stall publishing forever.
+  fn client(timeout: Duration) -> Result<Client> {
+    Ok(Client::builder().timeout(timeout).build()?)
+  }
+
+  async fn try_post(&self, messages: &[EventMessage]) -> Result {
+    self
+      .client
+      .post(&self.url)
+      .json(messages)
+      .send()
+      .await?
+      .error_for_status()?;
+
+    Ok(())
+  }
+}
+
+#[async_trait]
+impl EventSink for Webhook {
+  async fn send(&mut self, messages: &[EventMessage]) -> Result {
+    let mut retries = 0;
+
+    loop {
+      match self.try_post(messages).await {
+        Ok(()) => return Ok(()),
+        Err(err) => {
+          if retries >= Self::MAX_RETRIES {
+            bail!(
+              "failed to post events to `{}` after {} retries: {err}",
+              self.url,
+              Self::MAX_RETRIES
+            );
+          }
+
+          log::info!("failed to post events to `{}`, retrying: {err}", self.url);
+
+          tokio::time::sleep(Duration::from_millis(100 * u64::pow(2, retries))).await;
+          retries += 1;
+        }
+      }
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use {
+    super::*,
+    axum::{extract::State, http::StatusCode, routing::post, Json, Router},
+    std::{
+      net::TcpListener,
+      sync::{Arc, Mutex},
+    },
+  };
+
+  #[derive(Default)]
+  struct Received {
+    failures: usize,
+    messages: Vec<EventMessage>,
+    stalls: usize,
+  }
+
+  async fn receive(
+    State(received): State<Arc<Mutex<Received>>>,
+    Json(messages): Json<Vec<EventMessage>>,
+  ) -> StatusCode {
+    let stall = {
+      let mut received = received.lock().unwrap();
+      let stall = received.stalls > 0;
+      received.stalls = received.stalls.saturating_sub(1);
+      stall
+    };
+
+    if stall {
+      tokio::time::sleep(Duration::from_secs(60)).await;
+      return StatusCode::OK;
+    }
+
+    let mut received = received.lock().unwrap();
+
+    if received.failures > 0 {
+      received.failures -= 1;
+      return StatusCode::SERVICE_UNAVAILABLE;
+    }
+
+    received.messages.extend(messages);
+
+    StatusCode::OK
+  }
+
+  fn webhook(runtime: &Runtime, failures: usize) -> (Webhook, Arc<Mutex<Received>>) {
+    webhook_with(
+      runtime,
+      Received {
+        failures,
+        ..default()
+      },
+    )
+  }
+
+  fn webhook_with(runtime: &Runtime, received: Received) -> (Webhook, Arc<Mutex<Received>>) {
+    let received = Arc::new(Mutex::new(received));
+
+    let _guard = runtime.enter();
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+
+    let port = listener.local_addr().unwrap().port();
+
+    let router = Router::new()
+      .route("/events", post(receive))
+      .with_state(received.clone());
+
+    runtime.spawn(
+      axum::Server::from_tcp(listener)
+        .unwrap()
+        .serve(router.into_make_service()),
+    );
+
+    (
+      Webhook {
+        client: Webhook::client(Duration::from_millis(500)).unwrap(),
+        url: format!("http://127.0.0.1:{port}/events"),
+      },
+      received,
+    )
+  }
+
+  fn messages() -> Vec<EventMessage> {
+    (0..3)
+      .map(|event_id| {
+        EventMessage::new(
+          event_id,
+          Event::RuneEtched {
+            block_height: 5,
+            rune_id: RuneId { block: 5, tx: 1 },
+            txid: Txid::all_zeros(),
+          },
+        )
+      })
+      .collect()
+  }
+
+  #[test]
+  fn url_is_required() {
+    assert_eq!(
+      Webhook::new(&Settings::default())
+        .err()
+        .unwrap()
+        .to_string(),
+      "no webhook URL configured, set URL with `--event-webhook-url`"
+    );
+  }
+
+  #[test]
+  fn events_are_posted() {
+    let runtime = Runtime::new().unwrap();
+
+    let (mut webhook, received) = webhook(&runtime, 0);
+
+    runtime.block_on(webhook.send(&messages())).unwrap();
+
+    assert_eq!(received.lock().unwrap().messages, messages());
+  }
+
+  #[test]
+  fn failed_posts_are_retried() {
+    let runtime = Runtime::new().unwrap();
+
+    let (mut webhook, received) = webhook(&runtime, 2);
+
+    runtime.block_on(webhook.send(&messages())).unwrap();
+
+    assert_eq!(received.lock().unwrap().messages, messages());
+  }
+
+  #[test]
+  fn retries_are_limited() {
+    let runtime = Runtime::new().unwrap();
+
+    let (mut webhook, received) = webhook(&runtime, usize::MAX);
+
+    assert_eq!(
+      runtime
+        .block_on(webhook.send(&messages()))
+        .unwrap_err()
+        .to_string(),
+      format!(
+        "failed to post events to `{}` after 5 retries: HTTP status server error (503 Service Unavailable) for url ({})",
+        webhook.url, webhook.url,
+      )
+    );
+
+    assert!(received.lock().unwrap().messages.is_empty());
+  }
+
+  #[test]
+  fn stalled_posts_time_out_and_are_retried() {
+    let runtime = Runtime::new().unwrap();
+
+    let (mut webhook, received) = webhook_with(
+      &runtime,
+      Received {
+        stalls: 2,
+        ..default()
+      },
+    );
+
+    let start = Instant::now();
+
+    runtime.block_on(webhook.send(&messages())).unwrap();
+
+    assert!(start.elapsed() < Duration::from_secs(10));
+
+    assert_eq!(received.lock().unwrap().messages, messages());
+  }
+}
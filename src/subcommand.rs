@@ -54,19 +54,19 @@ pub(crate) enum Subcommand {
 }
 
 impl Subcommand {
-  pub(crate) fn run(self, settings: Settings) -> SubcommandResult {
+  pub(crate) fn run(self, settings: Settings, format: Option<OutputFormat>) -> SubcommandResult {
     match self {
       Self::Balances => balances::run(settings),
       Self::Decode(decode) => decode.run(settings),
       Self::Env(env) => env.run(),
       Self::Epochs => epochs::run(),
       Self::Find(find) => find.run(settings),
-      Self::Index(index) => index.run(settings),
+      Self::Index(index) => index.run(settings, format),
       Self::List(list) => list.run(settings),
       Self::Parse(parse) => parse.run(),
       Self::Runes => runes::run(settings),
       Self::Server(server) => {
-        let index = Arc::new(Index::open(&settings)?);
+        let index = Arc::new(server.open_index(&settings)?);
         let handle = axum_server::Handle::new();
         LISTENERS.lock().unwrap().push(handle.clone());
         server.run(settings, index, handle)
@@ -1,25 +1,47 @@
 use super::*;
 
+mod events;
 mod export;
 pub mod info;
+mod publish;
+mod schema;
+mod snapshot;
 mod update;
+mod verify;
 
 #[derive(Debug, Parser)]
 pub(crate) enum IndexSubcommand {
+  #[command(about = "Replay events for blocks that have already been indexed")]
+  Events(events::Events),
   #[command(about = "Write inscription numbers and ids to a tab-separated file")]
   Export(export::Export),
   #[command(about = "Print index statistics")]
   Info(info::Info),
+  #[command(
+    about = "Update the index and publish events to the configured event sink, at least once"
+  )]
+  Publish(publish::Publish),
+  #[command(about = "Print the event message schema")]
+  Schema(schema::Schema),
+  #[command(subcommand, about = "Create and restore index snapshots")]
+  Snapshot(snapshot::Snapshot),
   #[command(about = "Update the index", alias = "run")]
   Update,
+  #[command(about = "Check the index for inconsistencies between tables")]
+  Verify,
 }
 
 impl IndexSubcommand {
-  pub(crate) fn run(self, settings: Settings) -> SubcommandResult {
+  pub(crate) fn run(self, settings: Settings, format: Option<OutputFormat>) -> SubcommandResult {
     match self {
+      Self::Events(events) => events.run(settings),
       Self::Export(export) => export.run(settings),
       Self::Info(info) => info.run(settings),
+      Self::Publish(publish) => publish.run(settings),
+      Self::Schema(schema) => schema.run(),
+      Self::Snapshot(snapshot) => snapshot.run(settings),
       Self::Update => update::run(settings),
+      Self::Verify => verify::run(settings, format),
     }
   }
 }
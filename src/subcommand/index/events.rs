@@ -0,0 +1,72 @@
+use {
+  super::*,
+  crate::{
+    index::{event::EventMessage, replay},
+    sink::{self, EventSinkKind},
+  },
+};
+
+#[derive(Debug, Parser)]
+pub(crate) struct Events {
+  #[arg(
+    long,
+    default_value = "0",
+    help = "Replay events for blocks at or above <FROM_HEIGHT>."
+  )]
+  from_height: u32,
+  #[arg(
+    long,
+    help = "Send replayed events to the configured event sink instead of printing them."
+  )]
+  publish: bool,
+  #[arg(
+    long,
+    help = "Replay events for blocks at or below <TO_HEIGHT>. [default: index height]"
+  )]
+  to_height: Option<u32>,
+}
+
+impl Events {
+  pub(crate) fn run(self, settings: Settings) -> SubcommandResult {
+    let index = Index::open(&settings)?;
+
+    let to_height = match self.to_height {
+      Some(to_height) => to_height,
+      None => index
+        .block_height()?
+        .map(|height| height.n())
+        .ok_or_else(|| anyhow!("index has no blocks, run `ord index update` first"))?,
+    };
+
+    if self.from_height > to_height {
+      bail!("`--from-height` must not be greater than `--to-height`");
+    }
+
+    let mut sink = if self.publish {
+      sink::open_replay(&settings)?
+    } else {
+      sink::open_kind(&settings, EventSinkKind::Stdout)?
+    };
+
+    let runtime = Runtime::new()?;
+
+    let mut next_event_id = 0;
+
+    replay::replay(&index, self.from_height, to_height, |events| {
+      let messages = events
+        .into_iter()
+        .map(|event| {
+          let message = EventMessage::new(next_event_id, event);
+          next_event_id += 1;
+          message
+        })
+        .collect::<Vec<EventMessage>>();
+
+      runtime.block_on(sink.send(&messages))
+    })?;
+
+    runtime.block_on(sink.flush())?;
+
+    Ok(None)
+  }
+}
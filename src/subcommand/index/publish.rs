@@ -0,0 +1,42 @@
+use {
+  super::*,
+  crate::{index::EventConsumer, sink},
+};
+
+#[derive(Debug, Parser)]
+pub(crate) struct Publish {
+  #[arg(
+    long,
+    default_value = "5s",
+    help = "Poll Bitcoin Core every <POLLING_INTERVAL>."
+  )]
+  polling_interval: humantime::Duration,
+}
+
+impl Publish {
+  pub(crate) fn run(self, settings: Settings) -> SubcommandResult {
+    let mut sink = sink::open(&settings)?;
+
+    let index = Index::open_with_event_outbox(&settings, EventConsumer::Sink)?;
+
+    let runtime = Runtime::new()?;
+
+    loop {
+      index.update()?;
+
+      runtime.block_on(sink::drain(&index, sink.as_mut()))?;
+
+      if SHUTTING_DOWN.load(atomic::Ordering::Relaxed) {
+        break;
+      }
+
+      thread::sleep(if settings.integration_test() {
+        Duration::from_millis(100)
+      } else {
+        self.polling_interval.into()
+      });
+    }
+
+    Ok(None)
+  }
+}
@@ -0,0 +1,22 @@
+use {super::*, crate::index::event::EventMessage};
+
+#[derive(Debug, Parser)]
+pub(crate) struct Schema {
+  #[arg(
+    long,
+    help = "Print the protobuf definition instead of the JSON Schema."
+  )]
+  protobuf: bool,
+}
+
+impl Schema {
+  pub(crate) fn run(self) -> SubcommandResult {
+    if self.protobuf {
+      print!("{}", EventMessage::PROTOBUF_SCHEMA);
+    } else {
+      print!("{}", EventMessage::json_schema());
+    }
+
+    Ok(None)
+  }
+}
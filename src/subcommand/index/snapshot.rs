@@ -0,0 +1,34 @@
+use {super::*, crate::index::snapshot};
+
+#[derive(Debug, Parser)]
+pub(crate) enum Snapshot {
+  #[command(about = "Update the index and write a compressed snapshot of it")]
+  Create(Create),
+  #[command(about = "Restore the index from a snapshot")]
+  Restore(Restore),
+}
+
+#[derive(Debug, Parser)]
+pub(crate) struct Create {
+  #[arg(help = "Write snapshot to <PATH>.")]
+  path: PathBuf,
+}
+
+#[derive(Debug, Parser)]
+pub(crate) struct Restore {
+  #[arg(long, help = "Overwrite the existing index.")]
+  force: bool,
+  #[arg(help = "Read snapshot from <PATH>.")]
+  path: PathBuf,
+}
+
+impl Snapshot {
+  pub(crate) fn run(self, settings: Settings) -> SubcommandResult {
+    let manifest = match self {
+      Self::Create(create) => snapshot::create(&settings, &create.path)?,
+      Self::Restore(restore) => snapshot::restore(&settings, &restore.path, restore.force)?,
+    };
+
+    Ok(Some(Box::new(manifest)))
+  }
+}
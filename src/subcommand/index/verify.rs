@@ -0,0 +1,23 @@
+use {super::*, crate::index::verify};
+
+pub(crate) fn run(settings: Settings, format: Option<OutputFormat>) -> SubcommandResult {
+  let index = Index::open(&settings)?;
+
+  let report = verify::verify(&index)?;
+
+  if report.discrepancies.is_empty() {
+    return Ok(Some(Box::new(report)));
+  }
+
+  report.print(format.unwrap_or_default());
+
+  bail!(
+    "index has {} discrepanc{}",
+    report.discrepancies.len(),
+    if report.discrepancies.len() == 1 {
+      "y"
+    } else {
+      "ies"
+    },
+  );
+}
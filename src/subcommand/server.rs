@@ -3,24 +3,33 @@ use {
     accept_encoding::AcceptEncoding,
     accept_json::AcceptJson,
     error::{OptionExt, ServerError, ServerResult},
+    event_feed::{Cursor, EventFeed, EventFilter},
   },
   super::*,
+  crate::index::{
+    event::Event,
+    inscription_search::{InscriptionSearch, InscriptionSearchResults, InvalidSearch},
+    EventConsumer,
+  },
   crate::templates::{
     AddressHtml, BlockHtml, BlocksHtml, ChildrenHtml, ClockSvg, CollectionsHtml, HomeHtml,
     InputHtml, InscriptionHtml, InscriptionsBlockHtml, InscriptionsHtml, OutputHtml, PageContent,
     PageHtml, ParentsHtml, PreviewAudioHtml, PreviewCodeHtml, PreviewFontHtml, PreviewImageHtml,
     PreviewMarkdownHtml, PreviewModelHtml, PreviewPdfHtml, PreviewTextHtml, PreviewUnknownHtml,
-    PreviewVideoHtml, RangeHtml, RareTxt, RuneHtml, RunesHtml, SatHtml, TransactionHtml,
+    PreviewVideoHtml, RangeHtml, RareTxt, RuneHtml, RunesHtml, SatHtml, TextSearchHtml,
+    TransactionHtml,
   },
   axum::{
     body,
-    extract::{DefaultBodyLimit, Extension, Json, Path, Query},
-    http::{header, HeaderValue, StatusCode, Uri},
+    extract::{DefaultBodyLimit, Extension, Json, MatchedPath, Path, Query},
+    http::{header, HeaderMap, HeaderValue, Request, StatusCode, Uri},
+    middleware::{self, Next},
     response::{IntoResponse, Redirect, Response},
     routing::{get, post},
     Router,
   },
   axum_server::Handle,
+  bitcoin::hashes::sha256,
   brotli::Decompressor,
   rust_embed::RustEmbed,
   rustls_acme::{
@@ -32,7 +41,10 @@ use {
   std::{cmp::Ordering, str, sync::Arc},
   tokio_stream::StreamExt,
   tower_http::{
-    compression::CompressionLayer,
+    compression::{
+      predicate::{DefaultPredicate, NotForContentType, Predicate},
+      CompressionLayer,
+    },
     cors::{Any, CorsLayer},
     set_header::SetResponseHeaderLayer,
     validate_request::ValidateRequestHeaderLayer,
@@ -44,6 +56,8 @@ pub(crate) use server_config::ServerConfig;
 mod accept_encoding;
 mod accept_json;
 mod error;
+mod event_feed;
+mod graphql;
 pub mod query;
 mod server_config;
 
@@ -58,26 +72,24 @@ struct Search {
   query: String,
 }
 
-#[derive(RustEmbed)]
-#[folder = "static"]
-struct StaticAssets;
-
-struct StaticHtml {
-  title: &'static str,
-  html: &'static str,
+#[derive(Deserialize)]
+struct TextSearchQuery {
+  query: String,
+  page: Option<usize>,
 }
 
-impl PageContent for StaticHtml {
-  fn title(&self) -> String {
-    self.title.into()
-  }
+#[derive(Deserialize)]
+struct EventsQuery {
+  address: Option<String>,
+  cursor: Option<DeserializeFromStr<Cursor>>,
+  parent: Option<DeserializeFromStr<InscriptionId>>,
+  rune: Option<DeserializeFromStr<query::Rune>>,
+  types: Option<String>,
 }
 
-impl Display for StaticHtml {
-  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
-    f.write_str(self.html)
-  }
-}
+#[derive(RustEmbed)]
+#[folder = "static"]
+struct StaticAssets;
 
 #[derive(Debug, Parser, Clone)]
 pub struct Server {
@@ -103,6 +115,20 @@ pub struct Server {
   pub(crate) decompress: bool,
   #[arg(long, help = "Disable JSON API.")]
   pub(crate) disable_json_api: bool,
+  #[arg(long, help = "Stream index events at `/r/events`.")]
+  pub(crate) events: bool,
+  #[arg(
+    long,
+    default_value = "10000",
+    help = "Reject GraphQL queries with a cost above <GRAPHQL_MAX_COST>. Each field costs one, and list fields cost their `limit` times the cost of their selection."
+  )]
+  pub(crate) graphql_max_cost: usize,
+  #[arg(
+    long,
+    default_value = "10",
+    help = "Reject GraphQL queries nested deeper than <GRAPHQL_MAX_DEPTH>."
+  )]
+  pub(crate) graphql_max_depth: usize,
   #[arg(
     long,
     help = "Listen on <HTTP_PORT> for incoming HTTP requests. [default: 80]"
@@ -140,11 +166,27 @@ pub struct Server {
 }
 
 impl Server {
+  pub fn open_index(&self, settings: &Settings) -> Result<Index> {
+    if self.events {
+      Index::open_with_event_outbox(settings, EventConsumer::Feed)
+    } else {
+      Index::open(settings)
+    }
+  }
+
   pub fn run(self, settings: Settings, index: Arc<Index>, handle: Handle) -> SubcommandResult {
     Runtime::new()?.block_on(async {
       let index_clone = index.clone();
       let integration_test = settings.integration_test();
 
+      let event_feed = if self.events {
+        Some(Arc::new(EventFeed::new(&index)?))
+      } else {
+        None
+      };
+
+      let event_feed_clone = event_feed.clone();
+
       let index_thread = thread::spawn(move || loop {
         if SHUTTING_DOWN.load(atomic::Ordering::Relaxed) {
           break;
@@ -156,6 +198,12 @@ impl Server {
           }
         }
 
+        if let Some(event_feed) = &event_feed_clone {
+          if let Err(error) = event_feed.ingest(&index_clone) {
+            log::warn!("Updating event feed: {error}");
+          }
+        }
+
         thread::sleep(if integration_test {
           Duration::from_millis(100)
         } else {
@@ -178,9 +226,37 @@ impl Server {
         json_api_enabled: !self.disable_json_api,
       });
 
+      let graphql_schema = graphql::schema(
+        settings.chain(),
+        index.clone(),
+        self.graphql_max_depth,
+        self.graphql_max_cost,
+      );
+
       let router = Router::new()
         .route("/", get(Self::home))
         .route("/address/:address", get(Self::address))
+        .route(
+          "/address/:address/inscriptions",
+          get(Self::address_inscriptions),
+        )
+        .route(
+          "/address/:address/inscriptions/:page",
+          get(Self::address_inscriptions_paginated),
+        )
+        .route("/address/:address/runes", get(Self::address_runes))
+        .route(
+          "/address/:address/runes/:page",
+          get(Self::address_runes_paginated),
+        )
+        .route(
+          "/address/:address/transactions",
+          get(Self::address_transactions),
+        )
+        .route(
+          "/address/:address/transactions/:page",
+          get(Self::address_transactions_paginated),
+        )
         .route("/block/:query", get(Self::block))
         .route("/blockcount", get(Self::block_count))
         .route("/blockhash", get(Self::block_hash))
@@ -201,6 +277,7 @@ impl Server {
         .route("/faq", get(Self::faq))
         .route("/favicon.ico", get(Self::favicon))
         .route("/feed.xml", get(Self::feed))
+        .route("/graphql", post(Self::graphql))
         .route("/input/:block/:transaction/:input", get(Self::input))
         .route("/inscription/:inscription_query", get(Self::inscription))
         .route(
@@ -219,6 +296,7 @@ impl Server {
           get(Self::inscriptions_in_block_paginated),
         )
         .route("/install.sh", get(Self::install_script))
+        .route("/metrics", get(Self::metrics))
         .route("/ordinal/:sat", get(Self::ordinal))
         .route("/output/:output", get(Self::output))
         .route("/outputs", post(Self::outputs))
@@ -240,6 +318,14 @@ impl Server {
           "/r/inscription/:inscription_id",
           get(Self::inscription_recursive),
         )
+        .route(
+          "/r/inscription/:inscription_id/history",
+          get(Self::inscription_history),
+        )
+        .route(
+          "/r/inscription/:inscription_id/history/:page",
+          get(Self::inscription_history_paginated),
+        )
         .route("/r/children/:inscription_id", get(Self::children_recursive))
         .route(
           "/r/children/:inscription_id/:page",
@@ -253,7 +339,23 @@ impl Server {
           "/r/children/:inscription_id/inscriptions/:page",
           get(Self::child_inscriptions_recursive_paginated),
         )
+        .route(
+          "/r/content-hash/:hash",
+          get(Self::content_hash_inscriptions),
+        )
+        .route(
+          "/r/content-hash/:hash/:page",
+          get(Self::content_hash_inscriptions_paginated),
+        )
+        .route("/r/events", get(Self::events))
+        .route("/r/inscriptions/search", get(Self::inscription_search))
         .route("/r/metadata/:inscription_id", get(Self::metadata))
+        .route("/r/metaprotocol/:protocol/*path", get(Self::metaprotocol))
+        .route("/r/rune/:rune/holders", get(Self::rune_holders))
+        .route(
+          "/r/rune/:rune/holders/:page",
+          get(Self::rune_holders_paginated),
+        )
         .route("/r/parents/:inscription_id", get(Self::parents_recursive))
         .route(
           "/r/parents/:inscription_id/:page",
@@ -277,13 +379,17 @@ impl Server {
         .route("/sat/:sat", get(Self::sat))
         .route("/search", get(Self::search_by_query))
         .route("/search/*query", get(Self::search_by_path))
+        .route("/text-search", get(Self::text_search))
         .route("/static/*path", get(Self::static_asset))
         .route("/status", get(Self::status))
         .route("/tx/:txid", get(Self::transaction))
         .route("/decode/:txid", get(Self::decode))
         .route("/update", get(Self::update))
         .fallback(Self::fallback)
+        .layer(middleware::from_fn(Self::record_request_duration))
         .layer(Extension(index))
+        .layer(Extension(event_feed))
+        .layer(Extension(graphql_schema))
         .layer(Extension(server_config.clone()))
         .layer(Extension(settings.clone()))
         .layer(SetResponseHeaderLayer::if_not_present(
@@ -296,10 +402,13 @@ impl Server {
         ))
         .layer(
           CorsLayer::new()
-            .allow_methods([http::Method::GET])
+            .allow_methods([http::Method::GET, http::Method::POST])
+            .allow_headers([header::CONTENT_TYPE])
             .allow_origin(Any),
         )
-        .layer(CompressionLayer::new())
+        .layer(CompressionLayer::new().compress_when(
+          DefaultPredicate::new().and(NotForContentType::const_new("text/event-stream")),
+        ))
         .with_state(server_config.clone());
 
       let router = if server_config.json_api_enabled {
@@ -666,10 +775,117 @@ impl Server {
     }
   }
 
+  async fn events(
+    Extension(index): Extension<Arc<Index>>,
+    Extension(event_feed): Extension<Option<Arc<EventFeed>>>,
+    Extension(settings): Extension<Arc<Settings>>,
+    headers: HeaderMap,
+    Query(query): Query<EventsQuery>,
+  ) -> ServerResult {
+    let event_feed = event_feed.ok_or_not_found(|| "event feed")?;
+
+    let cursor = match query.cursor {
+      Some(DeserializeFromStr(cursor)) => Some(cursor),
+      None => headers
+        .get("last-event-id")
+        .map(|cursor| {
+          cursor
+            .to_str()
+            .map_err(|err| anyhow!(err))
+            .and_then(str::parse::<Cursor>)
+            .map_err(|err| ServerError::BadRequest(err.to_string()))
+        })
+        .transpose()?,
+    };
+
+    let filter = task::block_in_place(|| {
+      let rune = match query.rune {
+        Some(DeserializeFromStr(rune_query)) => Some(Self::rune_id(&index, rune_query)?),
+        None => None,
+      };
+
+      let parent = match query.parent {
+        Some(DeserializeFromStr(parent)) => Some((
+          parent,
+          index
+            .get_inscription_entry(parent)?
+            .ok_or_not_found(|| format!("inscription {parent}"))?
+            .sequence_number,
+        )),
+        None => None,
+      };
+
+      let script_pubkey = query
+        .address
+        .map(|address| {
+          address
+            .parse::<Address<NetworkUnchecked>>()
+            .map_err(|err| anyhow!(err))
+            .and_then(|address| Ok(address.require_network(settings.chain().network())?))
+            .map(|address| address.script_pubkey())
+            .map_err(|err| ServerError::BadRequest(format!("invalid address: {err}")))
+        })
+        .transpose()?;
+
+      let types = query
+        .types
+        .map(|types| {
+          types
+            .split(',')
+            .map(|name| {
+              if Event::NAMES.contains(&name) {
+                Ok(name.to_string())
+              } else {
+                Err(ServerError::BadRequest(format!(
+                  "invalid event type `{name}`"
+                )))
+              }
+            })
+            .collect::<ServerResult<BTreeSet<String>>>()
+        })
+        .transpose()?;
+
+      Ok::<EventFilter, ServerError>(EventFilter {
+        parent,
+        rune,
+        script_pubkey,
+        types,
+      })
+    })?;
+
+    match task::block_in_place(|| event_feed.stream(index, filter, cursor))? {
+      Ok(stream) => Ok(stream.into_response()),
+      Err(err) => Ok((StatusCode::GONE, err.to_string()).into_response()),
+    }
+  }
+
   async fn rare_txt(Extension(index): Extension<Arc<Index>>) -> ServerResult<RareTxt> {
     task::block_in_place(|| Ok(RareTxt(index.rare_sat_satpoints()?)))
   }
 
+  fn rune_id(index: &Index, rune_query: query::Rune) -> ServerResult<RuneId> {
+    if !index.has_rune_index() {
+      return Err(ServerError::NotFound(
+        "this server has no rune index".to_string(),
+      ));
+    }
+
+    let rune = match rune_query {
+      query::Rune::Id(rune_id) => return Ok(rune_id),
+      query::Rune::Spaced(spaced_rune) => spaced_rune.rune,
+      query::Rune::Number(number) => index
+        .get_rune_by_number(usize::try_from(number).unwrap())?
+        .ok_or_not_found(|| format!("rune number {number}"))?,
+    };
+
+    Ok(
+      index
+        .rune(rune)?
+        .ok_or_not_found(|| format!("rune {rune}"))?
+        .0,
+    )
+  }
+
   async fn rune(
     Extension(server_config): Extension<Arc<ServerConfig>>,
     Extension(index): Extension<Arc<Index>>,
@@ -701,9 +917,12 @@ impl Server {
 
       let mintable = entry.mintable((block_height.n() + 1).into()).is_ok();
 
+      let holders = index.get_rune_holder_count(id)?;
+
       Ok(if accept_json {
         Json(api::Rune {
           entry,
+          holders,
           id,
           mintable,
           parent,
@@ -712,6 +931,7 @@ impl Server {
       } else {
         RuneHtml {
           entry,
+          holders,
           id,
           mintable,
           parent,
@@ -722,6 +942,52 @@ impl Server {
     })
   }
 
+  async fn rune_holders(
+    Extension(settings): Extension<Arc<Settings>>,
+    Extension(index): Extension<Arc<Index>>,
+    Path(rune_query): Path<DeserializeFromStr<query::Rune>>,
+  ) -> ServerResult {
+    Self::rune_holders_paginated(Extension(settings), Extension(index), Path((rune_query, 0))).await
+  }
+
+  async fn rune_holders_paginated(
+    Extension(settings): Extension<Arc<Settings>>,
+    Extension(index): Extension<Arc<Index>>,
+    Path((DeserializeFromStr(rune_query), page)): Path<(DeserializeFromStr<query::Rune>, usize)>,
+  ) -> ServerResult {
+    task::block_in_place(|| {
+      let id = Self::rune_id(&index, rune_query)?;
+
+      index
+        .get_rune_by_id(id)?
+        .ok_or_not_found(|| format!("rune {id}"))?;
+
+      let (holders, more) = index.get_rune_holders_paginated(id, 100, page)?;
+
+      let holders = holders
+        .into_iter()
+        .map(|(script_pubkey, amount)| api::RuneHolder {
+          address: settings
+            .chain()
+            .address_from_script(&script_pubkey)
+            .ok()
+            .map(|address| address.to_string()),
+          amount,
+          script_pubkey,
+        })
+        .collect();
+
+      Ok(
+        Json(api::RuneHolders {
+          holders,
+          more,
+          page,
+        })
+        .into_response(),
+      )
+    })
+  }
+
   async fn runes(
     Extension(server_config): Extension<Arc<ServerConfig>>,
     Extension(index): Extension<Arc<Index>>,
@@ -848,15 +1114,7 @@ impl Server {
     AcceptJson(accept_json): AcceptJson,
   ) -> ServerResult {
     task::block_in_place(|| {
-      if !index.has_address_index() {
-        return Err(ServerError::NotFound(
-          "this server has no address index".to_string(),
-        ));
-      }
-
-      let address = address
-        .require_network(server_config.chain.network())
-        .map_err(|err| ServerError::BadRequest(err.to_string()))?;
+      let address = Self::address_index_address(&index, &server_config, address)?;
 
       let mut outputs = index.get_address_info(&address)?;
 
@@ -864,7 +1122,21 @@ impl Server {
 
       let sat_balance = index.get_sat_balances_for_outputs(&outputs)?;
 
-      let runes_balances = index.get_aggregated_rune_balances_for_outputs(&outputs)?;
+      let runes_balances = index
+        .get_rune_balances_for_address_paginated(&address, usize::MAX, 0)?
+        .0
+        .into_iter()
+        .map(|(spaced_rune, pile)| {
+          (
+            spaced_rune,
+            Decimal {
+              value: pile.amount,
+              scale: pile.divisibility,
+            },
+            pile.symbol,
+          )
+        })
+        .collect();
 
       Ok(if accept_json {
         Json(outputs).into_response()
@@ -881,6 +1153,129 @@ impl Server {
     })
   }
 
+  fn address_index_address(
+    index: &Index,
+    server_config: &ServerConfig,
+    address: Address<NetworkUnchecked>,
+  ) -> ServerResult<Address> {
+    if !index.has_address_index() {
+      return Err(ServerError::NotFound(
+        "this server has no address index".to_string(),
+      ));
+    }
+
+    address
+      .require_network(server_config.chain.network())
+      .map_err(|err| ServerError::BadRequest(err.to_string()))
+  }
+
+  async fn address_inscriptions(
+    Extension(server_config): Extension<Arc<ServerConfig>>,
+    Extension(index): Extension<Arc<Index>>,
+    Path(address): Path<Address<NetworkUnchecked>>,
+  ) -> ServerResult {
+    Self::address_inscriptions_paginated(
+      Extension(server_config),
+      Extension(index),
+      Path((address, 0)),
+    )
+    .await
+  }
+
+  async fn address_inscriptions_paginated(
+    Extension(server_config): Extension<Arc<ServerConfig>>,
+    Extension(index): Extension<Arc<Index>>,
+    Path((address, page)): Path<(Address<NetworkUnchecked>, usize)>,
+  ) -> ServerResult {
+    task::block_in_place(|| {
+      let address = Self::address_index_address(&index, &server_config, address)?;
+
+      let (ids, more) = index.get_inscriptions_for_address_paginated(&address, 100, page)?;
+
+      Ok(Json(api::AddressInscriptions { ids, more, page }).into_response())
+    })
+  }
+
+  async fn address_runes(
+    Extension(server_config): Extension<Arc<ServerConfig>>,
+    Extension(index): Extension<Arc<Index>>,
+    Path(address): Path<Address<NetworkUnchecked>>,
+  ) -> ServerResult {
+    Self::address_runes_paginated(
+      Extension(server_config),
+      Extension(index),
+      Path((address, 0)),
+    )
+    .await
+  }
+
+  async fn address_runes_paginated(
+    Extension(server_config): Extension<Arc<ServerConfig>>,
+    Extension(index): Extension<Arc<Index>>,
+    Path((address, page)): Path<(Address<NetworkUnchecked>, usize)>,
+  ) -> ServerResult {
+    task::block_in_place(|| {
+      let address = Self::address_index_address(&index, &server_config, address)?;
+
+      if !index.has_rune_index() {
+        return Err(ServerError::NotFound(
+          "this server has no rune index".to_string(),
+        ));
+      }
+
+      let (balances, more) = index.get_rune_balances_for_address_paginated(&address, 100, page)?;
+
+      Ok(
+        Json(api::AddressRunes {
+          balances,
+          more,
+          page,
+        })
+        .into_response(),
+      )
+    })
+  }
+
+  async fn address_transactions(
+    Extension(server_config): Extension<Arc<ServerConfig>>,
+    Extension(index): Extension<Arc<Index>>,
+    Path(address): Path<Address<NetworkUnchecked>>,
+  ) -> ServerResult {
+    Self::address_transactions_paginated(
+      Extension(server_config),
+      Extension(index),
+      Path((address, 0)),
+    )
+    .await
+  }
+
+  async fn address_transactions_paginated(
+    Extension(server_config): Extension<Arc<ServerConfig>>,
+    Extension(index): Extension<Arc<Index>>,
+    Path((address, page)): Path<(Address<NetworkUnchecked>, usize)>,
+  ) -> ServerResult {
+    task::block_in_place(|| {
+      let address = Self::address_index_address(&index, &server_config, address)?;
+
+      if !index.has_address_history_index() {
+        return Err(ServerError::NotFound(
+          "this server has no address history index".to_string(),
+        ));
+      }
+
+      let (transactions, more) = index.get_address_transactions_paginated(&address, 100, page)?;
+
+      Ok(
+        Json(api::AddressTransactions {
+          transactions,
+          more,
+          page,
+        })
+        .into_response(),
+      )
+    })
+  }
+
   async fn block(
     Extension(server_config): Extension<Arc<ServerConfig>>,
     Extension(index): Extension<Arc<Index>>,
@@ -1037,6 +1432,97 @@ impl Server {
     })
   }
 
+  async fn metaprotocol(
+    Extension(index): Extension<Arc<Index>>,
+    Path((protocol, path)): Path<(String, String)>,
+  ) -> ServerResult {
+    task::block_in_place(|| {
+      let path = path.split('/').collect::<Vec<&str>>();
+
+      let value = index
+        .metaprotocol_route(&protocol, &path)?
+        .ok_or_else(|| {
+          ServerError::NotFound(format!(
+            "this server has no `{protocol}` metaprotocol index"
+          ))
+        })?
+        .ok_or_not_found(|| format!("{protocol} {}", path.join("/")))?;
+
+      Ok(Json(value).into_response())
+    })
+  }
+
+  async fn graphql(
+    Extension(server_config): Extension<Arc<ServerConfig>>,
+    Extension(schema): Extension<graphql::Schema>,
+    Json(request): Json<async_graphql::Request>,
+  ) -> ServerResult {
+    if !server_config.json_api_enabled {
+      return Ok((StatusCode::NOT_ACCEPTABLE, "JSON API disabled").into_response());
+    }
+
+    Ok(Json(schema.execute(request).await).into_response())
+  }
+
+  async fn inscription_history(
+    Extension(settings): Extension<Arc<Settings>>,
+    Extension(index): Extension<Arc<Index>>,
+    Path(inscription_id): Path<InscriptionId>,
+  ) -> ServerResult {
+    Self::inscription_history_paginated(
+      Extension(settings),
+      Extension(index),
+      Path((inscription_id, 0)),
+    )
+    .await
+  }
+
+  async fn inscription_history_paginated(
+    Extension(settings): Extension<Arc<Settings>>,
+    Extension(index): Extension<Arc<Index>>,
+    Path((inscription_id, page)): Path<(InscriptionId, usize)>,
+  ) -> ServerResult {
+    task::block_in_place(|| {
+      if !index.has_transfer_index() {
+        return Err(ServerError::NotFound(
+          "this server has no transfer index".to_string(),
+        ));
+      }
+
+      let (transfers, more) = index
+        .get_inscription_transfers_paginated(inscription_id, 100, page)?
+        .ok_or_not_found(|| format!("inscription {inscription_id}"))?;
+
+      let address = |script_pubkey: Option<ScriptBuf>| {
+        script_pubkey
+          .and_then(|script_pubkey| settings.chain().address_from_script(&script_pubkey).ok())
+          .map(|address| address.to_string())
+      };
+
+      let transfers = transfers
+        .into_iter()
+        .map(|transfer| api::InscriptionTransfer {
+          fee: transfer.fee,
+          height: transfer.height,
+          new_location: transfer.new_satpoint,
+          old_location: transfer.old_satpoint,
+          receiver: address(transfer.receiver),
+          sender: address(transfer.sender),
+          txid: transfer.txid,
+        })
+        .collect();
+
+      Ok(
+        Json(api::InscriptionHistory {
+          transfers,
+          more,
+          page,
+        })
+        .into_response(),
+      )
+    })
+  }
+
   async fn inscription_recursive(
     Extension(index): Extension<Arc<Index>>,
     Extension(server_config): Extension<Arc<ServerConfig>>,
@@ -1116,6 +1602,41 @@ impl Server {
     })
   }
 
+  async fn metrics(
+    Extension(index): Extension<Arc<Index>>,
+    Extension(event_feed): Extension<Option<Arc<EventFeed>>>,
+  ) -> ServerResult {
+    task::block_in_place(|| {
+      Ok(
+        (
+          [(header::CONTENT_TYPE, prometheus::TEXT_FORMAT)],
+          metrics::render(&index, event_feed.map(|event_feed| event_feed.next()))?,
+        )
+          .into_response(),
+      )
+    })
+  }
+
+  async fn record_request_duration<B>(request: Request<B>, next: Next<B>) -> Response {
+    let start = Instant::now();
+
+    let method = request.method().clone();
+
+    let route = request
+      .extensions()
+      .get::<MatchedPath>()
+      .map(|path| path.as_str().to_owned())
+      .unwrap_or_else(|| "fallback".into());
+
+    let response = next.run(request).await;
+
+    metrics::HTTP_REQUEST_DURATION
+      .with_label_values(&[method.as_str(), &route, response.status().as_str()])
+      .observe(start.elapsed().as_secs_f64());
+
+    response
+  }
+
   async fn search_by_query(
     Extension(index): Extension<Arc<Index>>,
     Query(search): Query<Search>,
@@ -1160,12 +1681,55 @@ impl Server {
         Ok(Redirect::to(&format!("/rune/{rune}")))
       } else if re::ADDRESS.is_match(query) {
         Ok(Redirect::to(&format!("/address/{query}")))
+      } else if index.has_full_text_index() && query.parse::<Sat>().is_err() {
+        Ok(Redirect::to(&format!(
+          "/text-search?query={}",
+          urlencoding::encode(query)
+        )))
       } else {
         Ok(Redirect::to(&format!("/sat/{query}")))
       }
     })
   }
 
+  async fn text_search(
+    Extension(server_config): Extension<Arc<ServerConfig>>,
+    Extension(index): Extension<Arc<Index>>,
+    Query(TextSearchQuery { query, page }): Query<TextSearchQuery>,
+    AcceptJson(accept_json): AcceptJson,
+  ) -> ServerResult {
+    task::block_in_place(|| {
+      if !index.has_full_text_index() {
+        return Err(ServerError::NotFound(
+          "this server has no full-text index".to_string(),
+        ));
+      }
+
+      let page = page.unwrap_or_default();
+
+      let (results, more) = index.search_full_text(&query, 100, page)?;
+
+      Ok(if accept_json {
+        Json(api::TextSearch {
+          query,
+          results,
+          more,
+          page,
+        })
+        .into_response()
+      } else {
+        TextSearchHtml {
+          query,
+          results,
+          prev: page.checked_sub(1),
+          next: more.then_some(page + 1),
+        }
+        .page(server_config)
+        .into_response()
+      })
+    })
+  }
+
   async fn favicon() -> ServerResult {
     Ok(
       Self::static_asset(Path("/favicon.png".to_string()))
@@ -2103,6 +2667,47 @@ impl Server {
     })
   }
 
+  async fn content_hash_inscriptions(
+    Extension(index): Extension<Arc<Index>>,
+    Path(hash): Path<DeserializeFromStr<sha256::Hash>>,
+  ) -> ServerResult<Json<api::ContentHashInscriptions>> {
+    Self::content_hash_inscriptions_paginated(Extension(index), Path((hash, 0))).await
+  }
+
+  async fn content_hash_inscriptions_paginated(
+    Extension(index): Extension<Arc<Index>>,
+    Path((DeserializeFromStr(hash), page)): Path<(DeserializeFromStr<sha256::Hash>, usize)>,
+  ) -> ServerResult<Json<api::ContentHashInscriptions>> {
+    task::block_in_place(|| {
+      if !index.has_content_index() {
+        return Err(ServerError::NotFound(
+          "this server has no content index".to_string(),
+        ));
+      }
+
+      let (ids, more) =
+        index.get_inscriptions_by_content_hash_paginated(hash.to_byte_array(), 100, page)?;
+
+      Ok(Json(api::ContentHashInscriptions { ids, more, page }))
+    })
+  }
+
+  async fn inscription_search(
+    Extension(index): Extension<Arc<Index>>,
+    Query(search): Query<InscriptionSearch>,
+  ) -> ServerResult<Json<api::InscriptionSearch>> {
+    task::block_in_place(|| {
+      search.validate(&index).map_err(|err| match err {
+        InvalidSearch::BadRequest(_) => ServerError::BadRequest(err.to_string()),
+        InvalidSearch::MissingIndex(_) => ServerError::NotFound(err.to_string()),
+      })?;
+
+      let InscriptionSearchResults { ids, next } = index.search_inscriptions(&search)?;
+
+      Ok(Json(api::InscriptionSearch { ids, next }))
+    })
+  }
+
   async fn sat_inscription_at_index(
     Extension(index): Extension<Arc<Index>>,
     Path((DeserializeFromStr(sat), inscription_index)): Path<(DeserializeFromStr<Sat>, isize)>,
@@ -2135,7 +2740,12 @@ impl Server {
 #[cfg(test)]
 mod tests {
   use {
-    super::*, reqwest::Url, serde::de::DeserializeOwned, std::net::TcpListener, tempfile::TempDir,
+    super::*,
+    crate::index::event::EventMessage,
+    reqwest::Url,
+    serde::de::DeserializeOwned,
+    std::{io::BufRead, net::TcpListener},
+    tempfile::TempDir,
   };
 
   const RUNE: u128 = 99246114928149462;
@@ -2267,7 +2877,7 @@ mod tests {
         .or_defaults()
         .unwrap();
 
-      let index = Arc::new(Index::open(&settings).unwrap());
+      let index = Arc::new(server.open_index(&settings).unwrap());
       let ord_server_handle = Handle::new();
 
       {
@@ -2426,6 +3036,23 @@ mod tests {
       response.json().unwrap()
     }
 
+    #[track_caller]
+    fn graphql(&self, query: &str) -> serde_json::Value {
+      if let Err(error) = self.index.update() {
+        log::error!("{error}");
+      }
+
+      let response = reqwest::blocking::Client::new()
+        .post(self.join_url("/graphql"))
+        .json(&serde_json::json!({ "query": query }))
+        .send()
+        .unwrap();
+
+      assert_eq!(response.status(), StatusCode::OK);
+
+      response.json().unwrap()
+    }
+
     fn join_url(&self, url: &str) -> Url {
       self.url.join(url).unwrap()
     }
@@ -3132,6 +3759,8 @@ mod tests {
   <dd>100%</dd>
   <dt>burned</dt>
   <dd>0\u{A0}%</dd>
+  <dt>holders</dt>
+  <dd>1</dd>
   <dt>divisibility</dt>
   <dd>0</dd>
   <dt>symbol</dt>
@@ -3160,6 +3789,97 @@ mod tests {
     );
   }
 
+  #[test]
+  fn rune_holders() {
+    let server = TestServer::builder()
+      .chain(Chain::Regtest)
+      .index_runes()
+      .build();
+
+    server.mine_blocks(1);
+
+    let rune = Rune(RUNE);
+
+    server.assert_response_regex(
+      format!("/r/rune/{rune}/holders"),
+      StatusCode::NOT_FOUND,
+      ".*",
+    );
+
+    let (txid, id) = server.etch(
+      Runestone {
+        edicts: vec![
+          Edict {
+            id: RuneId::default(),
+            amount: 1000,
+            output: 0,
+          },
+          Edict {
+            id: RuneId::default(),
+            amount: 2000,
+            output: 1,
+          },
+        ],
+        etching: Some(Etching {
+          rune: Some(rune),
+          premine: Some(3000),
+          ..default()
+        }),
+        ..default()
+      },
+      2,
+      None,
+    );
+
+    let script_pubkey = server.core.tx_by_id(txid).output[0].script_pubkey.clone();
+
+    let holders = server.get_json::<api::RuneHolders>(format!("/r/rune/{rune}/holders"));
+
+    pretty_assert_eq!(
+      holders,
+      api::RuneHolders {
+        holders: vec![api::RuneHolder {
+          address: Some(server.core.address(OutPoint { txid, vout: 0 }).to_string()),
+          amount: 3000,
+          script_pubkey,
+        }],
+        more: false,
+        page: 0,
+      }
+    );
+
+    pretty_assert_eq!(
+      server.get_json::<api::RuneHolders>(format!("/r/rune/{id}/holders/0")),
+      holders,
+    );
+
+    pretty_assert_eq!(
+      server.get_json::<api::RuneHolders>(format!("/r/rune/{id}/holders/1")),
+      api::RuneHolders {
+        holders: Vec::new(),
+        more: false,
+        page: 1,
+      }
+    );
+
+    assert_eq!(
+      server.get_json::<api::Rune>(format!("/rune/{id}")).holders,
+      1
+    );
+  }
+
+  #[test]
+  fn rune_holders_require_rune_index() {
+    TestServer::builder()
+      .chain(Chain::Regtest)
+      .build()
+      .assert_response(
+        "/r/rune/1:1/holders",
+        StatusCode::NOT_FOUND,
+        "this server has no rune index",
+      );
+  }
+
   #[test]
   fn etched_runes_are_displayed_on_block_page() {
     let server = TestServer::builder()
@@ -3498,6 +4218,34 @@ mod tests {
       .assert_redirect("/", &format!("https://{}/", System::host_name().unwrap()));
   }
 
+  #[test]
+  fn metrics() {
+    let server = TestServer::builder().chain(Chain::Regtest).build();
+
+    server.mine_blocks(2);
+
+    server.assert_response("/blockcount", StatusCode::OK, "3");
+
+    let response = server.get("/metrics");
+
+    assert_eq!(response.status(), StatusCode::OK);
+
+    assert_eq!(
+      response.headers().get(header::CONTENT_TYPE).unwrap(),
+      prometheus::TEXT_FORMAT,
+    );
+
+    let text = response.text().unwrap();
+
+    assert!(text.contains("# TYPE ord_node_height gauge\n"));
+
+    assert!(text.contains("# TYPE ord_commit_duration_seconds histogram\n"));
+
+    assert!(text.contains(
+      "ord_http_request_duration_seconds_count{method=\"GET\",route=\"/blockcount\",status=\"200\"}"
+    ));
+  }
+
   #[test]
   fn status() {
     let server = TestServer::builder().chain(Chain::Regtest).build();
@@ -6125,113 +6873,596 @@ next
   }
 
   #[test]
-  fn sat_recursive_endpoints() {
+  fn inscription_search_recursive_endpoint() {
+    TestServer::builder()
+      .chain(Chain::Regtest)
+      .build()
+      .assert_response(
+        "/r/inscriptions/search",
+        StatusCode::NOT_FOUND,
+        "this server has no search index",
+      );
+
     let server = TestServer::builder()
       .chain(Chain::Regtest)
-      .index_sats()
+      .ord_flag("--index-search")
       .build();
 
-    assert_eq!(
-      server.get_json::<api::SatInscriptions>("/r/sat/5000000000"),
-      api::SatInscriptions {
-        ids: Vec::new(),
-        page: 0,
-        more: false
-      }
-    );
-
-    assert_eq!(
-      server.get_json::<api::SatInscription>("/r/sat/5000000000/at/0"),
-      api::SatInscription { id: None }
-    );
-
-    server.mine_blocks(1);
-
-    let txid = server.core.broadcast_tx(TransactionTemplate {
-      inputs: &[(1, 0, 0, inscription("text/plain", "foo").to_witness())],
-      ..default()
-    });
-
-    server.mine_blocks(1);
+    server.mine_blocks(2);
 
     let mut ids = Vec::new();
-    ids.push(InscriptionId { txid, index: 0 });
 
-    for i in 1..111 {
+    for (i, content_type) in [(1, "text/plain"), (2, "text/html")] {
       let txid = server.core.broadcast_tx(TransactionTemplate {
-        inputs: &[(i + 1, 1, 0, inscription("text/plain", "foo").to_witness())],
+        inputs: &[(i, 0, 0, inscription(content_type, "foo").to_witness())],
         ..default()
       });
 
-      server.mine_blocks(1);
-
       ids.push(InscriptionId { txid, index: 0 });
     }
 
-    let paginated_response = server.get_json::<api::SatInscriptions>("/r/sat/5000000000");
-
-    let equivalent_paginated_response =
-      server.get_json::<api::SatInscriptions>("/r/sat/5000000000/0");
-
-    assert_eq!(paginated_response.ids.len(), 100);
-    assert!(paginated_response.more);
-    assert_eq!(paginated_response.page, 0);
+    server.mine_blocks(1);
 
-    assert_eq!(
-      paginated_response.ids.len(),
-      equivalent_paginated_response.ids.len()
+    pretty_assert_eq!(
+      server.get_json::<api::InscriptionSearch>("/r/inscriptions/search?content-type=text/html"),
+      api::InscriptionSearch {
+        ids: vec![ids[1]],
+        next: None,
+      }
     );
-    assert_eq!(paginated_response.more, equivalent_paginated_response.more);
-    assert_eq!(paginated_response.page, equivalent_paginated_response.page);
 
-    let paginated_response = server.get_json::<api::SatInscriptions>("/r/sat/5000000000/1");
+    let first =
+      server.get_json::<api::InscriptionSearch>("/r/inscriptions/search?order=asc&limit=1");
 
-    assert_eq!(paginated_response.ids.len(), 11);
-    assert!(!paginated_response.more);
-    assert_eq!(paginated_response.page, 1);
+    pretty_assert_eq!(first.ids, [ids[0]]);
 
-    assert_eq!(
-      server
-        .get_json::<api::SatInscription>("/r/sat/5000000000/at/0")
-        .id,
-      Some(ids[0])
+    pretty_assert_eq!(
+      server.get_json::<api::InscriptionSearch>(format!(
+        "/r/inscriptions/search?order=asc&limit=1&cursor={}",
+        first.next.unwrap(),
+      )),
+      api::InscriptionSearch {
+        ids: vec![ids[1]],
+        next: None,
+      }
     );
 
-    assert_eq!(
-      server
-        .get_json::<api::SatInscription>("/r/sat/5000000000/at/-111")
-        .id,
-      Some(ids[0])
+    server.assert_response(
+      "/r/inscriptions/search?sort=fee&cursor=foo",
+      StatusCode::BAD_REQUEST,
+      "invalid cursor `foo`",
     );
 
-    assert_eq!(
-      server
-        .get_json::<api::SatInscription>("/r/sat/5000000000/at/110")
-        .id,
-      Some(ids[110])
+    server.assert_response(
+      "/r/inscriptions/search?min-fee=2&max-fee=1",
+      StatusCode::BAD_REQUEST,
+      "min-fee must not exceed max-fee",
     );
 
-    assert_eq!(
-      server
-        .get_json::<api::SatInscription>("/r/sat/5000000000/at/-1")
-        .id,
-      Some(ids[110])
+    server.assert_response(
+      "/r/inscriptions/search?rarity=uncommon",
+      StatusCode::NOT_FOUND,
+      "this server has no sat index",
     );
-
-    assert!(server
-      .get_json::<api::SatInscription>("/r/sat/5000000000/at/111")
-      .id
-      .is_none());
   }
 
   #[test]
-  fn children_recursive_endpoint() {
-    let server = TestServer::builder().chain(Chain::Regtest).build();
+  fn text_search() {
+    let server = TestServer::builder()
+      .chain(Chain::Regtest)
+      .ord_flag("--index-full-text")
+      .build();
+
     server.mine_blocks(1);
 
-    let parent_txid = server.core.broadcast_tx(TransactionTemplate {
-      inputs: &[(1, 0, 0, inscription("text/plain", "hello").to_witness())],
-      ..default()
+    let txid = server.core.broadcast_tx(TransactionTemplate {
+      inputs: &[(
+        1,
+        0,
+        0,
+        inscription("text/plain", "hello world").to_witness(),
+      )],
+      ..default()
+    });
+
+    let id = InscriptionId { txid, index: 0 };
+
+    server.mine_blocks(1);
+
+    let response = server.get_json::<api::TextSearch>("/text-search?query=world");
+
+    pretty_assert_eq!(response.query, "world");
+    pretty_assert_eq!(response.results.len(), 1);
+    pretty_assert_eq!(response.results[0].id, id);
+    pretty_assert_eq!(response.results[0].number, 0);
+    pretty_assert_eq!(response.results[0].snippet, "hello world");
+    assert!(!response.more);
+    pretty_assert_eq!(response.page, 0);
+
+    server.assert_response_regex(
+      "/text-search?query=world",
+      StatusCode::OK,
+      format!(
+        ".*<title>Search results for “world”</title>.*
+<h1>Search results for “world”</h1>
+<ol class=text-search>
+  <li>
+    <a href=/inscription/{id}>Inscription 0</a>
+    <p>hello world</p>
+  </li>
+</ol>.*"
+      ),
+    );
+
+    server.assert_redirect(
+      "/search?query=hello%20world",
+      "/text-search?query=hello%20world",
+    );
+
+    server.assert_redirect("/search/abc", "/sat/abc");
+  }
+
+  #[test]
+  fn text_search_requires_full_text_index() {
+    TestServer::new().assert_response(
+      "/text-search?query=hello",
+      StatusCode::NOT_FOUND,
+      "this server has no full-text index",
+    );
+
+    TestServer::new().assert_redirect("/search?query=hello%20world", "/sat/hello world");
+  }
+
+  #[test]
+  fn metaprotocol_recursive_endpoint() {
+    let server = TestServer::builder()
+      .chain(Chain::Regtest)
+      .ord_flag("--index-metaprotocol=token")
+      .build();
+
+    server.mine_blocks(1);
+
+    let txid = server.core.broadcast_tx(TransactionTemplate {
+      inputs: &[(
+        1,
+        0,
+        0,
+        inscription(
+          "text/plain",
+          r#"{"p":"token","op":"deploy","tick":"ordi","max":"21000","lim":"1000"}"#,
+        )
+        .to_witness(),
+      )],
+      ..default()
+    });
+
+    server.mine_blocks(1);
+
+    let deployer = server.core.address(OutPoint { txid, vout: 0 });
+
+    pretty_assert_eq!(
+      server.get_json::<serde_json::Value>("/r/metaprotocol/token/token/ORDI"),
+      serde_json::json!({
+        "deployer": deployer.script_pubkey().to_hex_string(),
+        "height": 2,
+        "inscription_id": InscriptionId { txid, index: 0 },
+        "limit": "1000",
+        "max": "21000",
+        "minted": "0",
+        "tick": "ordi",
+      }),
+    );
+
+    pretty_assert_eq!(
+      server.get_json::<serde_json::Value>(format!("/r/metaprotocol/token/balances/{deployer}")),
+      serde_json::json!({}),
+    );
+
+    server.assert_response(
+      "/r/metaprotocol/token/token/foo",
+      StatusCode::NOT_FOUND,
+      "token token/foo not found",
+    );
+
+    server.assert_response(
+      "/r/metaprotocol/foo/tokens",
+      StatusCode::NOT_FOUND,
+      "this server has no `foo` metaprotocol index",
+    );
+  }
+
+  #[test]
+  fn graphql_resolves_nested_queries() {
+    let server = TestServer::builder()
+      .chain(Chain::Regtest)
+      .index_runes()
+      .build();
+
+    server.mine_blocks(1);
+
+    let (rune_txid, _) = server.etch(
+      Runestone {
+        etching: Some(Etching {
+          premine: Some(1000),
+          rune: Some(Rune(RUNE)),
+          ..default()
+        }),
+        ..default()
+      },
+      1,
+      None,
+    );
+
+    let rune_height = usize::try_from(server.index.block_count().unwrap()).unwrap() - 1;
+
+    let parent_txid = server.core.broadcast_tx(TransactionTemplate {
+      inputs: &[(1, 0, 0, inscription("text/plain", "parent").to_witness())],
+      ..default()
+    });
+
+    server.mine_blocks(1);
+
+    let parent = InscriptionId {
+      txid: parent_txid,
+      index: 0,
+    };
+
+    let child_txid = server.core.broadcast_tx(TransactionTemplate {
+      inputs: &[
+        (
+          rune_height + 1,
+          0,
+          0,
+          Inscription {
+            content_type: Some("text/plain".into()),
+            body: Some("child".into()),
+            parents: vec![parent.value()],
+            ..default()
+          }
+          .to_witness(),
+        ),
+        (rune_height + 1, 1, 0, Default::default()),
+        (rune_height, 1, 0, Default::default()),
+      ],
+      ..default()
+    });
+
+    server.mine_blocks(1);
+
+    let child = InscriptionId {
+      txid: child_txid,
+      index: 0,
+    };
+
+    assert_eq!(
+      server
+        .index
+        .get_rune_balances_for_output(OutPoint {
+          txid: rune_txid,
+          vout: 0
+        })
+        .unwrap(),
+      BTreeMap::new(),
+    );
+
+    pretty_assert_eq!(
+      server.graphql(&format!(
+        r#"{{
+          inscription(query: "{child}") {{
+            number
+            parents {{
+              id
+              children {{
+                id
+                output {{
+                  outpoint
+                  runes {{
+                    amount
+                    rune {{
+                      name
+                    }}
+                  }}
+                }}
+              }}
+            }}
+          }}
+        }}"#
+      )),
+      serde_json::json!({
+        "data": {
+          "inscription": {
+            "number": 1,
+            "parents": [{
+              "id": parent.to_string(),
+              "children": [{
+                "id": child.to_string(),
+                "output": {
+                  "outpoint": format!("{child_txid}:0"),
+                  "runes": [{
+                    "amount": "1000",
+                    "rune": {
+                      "name": "AAAAAAAAAAAAA",
+                    },
+                  }],
+                },
+              }],
+            }],
+          },
+        },
+      }),
+    );
+
+    pretty_assert_eq!(
+      server.graphql(r#"{ inscription(query: "0") { id } rune(query: "BBBBBBBBBBBBB") { id } }"#),
+      serde_json::json!({
+        "data": {
+          "inscription": {
+            "id": parent.to_string(),
+          },
+          "rune": null,
+        },
+      }),
+    );
+  }
+
+  #[test]
+  fn graphql_queries_are_limited() {
+    let server = TestServer::builder()
+      .chain(Chain::Regtest)
+      .server_flag("--graphql-max-depth=3")
+      .server_flag("--graphql-max-cost=100")
+      .build();
+
+    server.mine_blocks(1);
+
+    let error = |query: &str| {
+      server.graphql(query)["errors"][0]["message"]
+        .as_str()
+        .unwrap()
+        .to_string()
+    };
+
+    pretty_assert_eq!(
+      server.graphql("{ inscriptions(limit: 5) { parents(limit: 2) { id } } }"),
+      serde_json::json!({
+        "data": {
+          "inscriptions": [],
+        },
+      }),
+    );
+
+    assert_eq!(
+      error("{ inscriptions(limit: 1) { parents(limit: 1) { children(limit: 1) { id } } } }"),
+      "Query is nested too deep.",
+    );
+
+    assert_eq!(
+      error("{ inscriptions(limit: 21) { parents(limit: 5) { id } } }"),
+      "Query is too complex.",
+    );
+
+    assert_eq!(
+      error("{ inscriptions(limit: 4294967296) { parents(limit: 4294967296) { id } } }"),
+      "Query is too complex.",
+    );
+  }
+
+  #[test]
+  fn graphql_errors() {
+    let server = TestServer::builder().chain(Chain::Regtest).build();
+
+    let error = |query: &str| {
+      server.graphql(query)["errors"][0]["message"]
+        .as_str()
+        .unwrap()
+        .to_string()
+    };
+
+    assert_eq!(
+      error("{ block(query: \"0\") { inscriptions(limit: 101) { id } } }"),
+      "limit must be at most 100",
+    );
+
+    assert_eq!(error("{ runes { id } }"), "this server has no rune index");
+
+    assert_eq!(
+      error(
+        "{ address(address: \"bcrt1qs758ursh4q9z627kt3pp5yysm78ddny6txaqgw\") { satBalance } }"
+      ),
+      "this server has no address index",
+    );
+  }
+
+  #[test]
+  fn graphql_is_disabled_with_json_api() {
+    let server = TestServer::builder()
+      .chain(Chain::Regtest)
+      .server_flag("--disable-json-api")
+      .build();
+
+    let response = reqwest::blocking::Client::new()
+      .post(server.join_url("/graphql"))
+      .json(&serde_json::json!({ "query": "{ blockHeight }" }))
+      .send()
+      .unwrap();
+
+    assert_eq!(response.status(), StatusCode::NOT_ACCEPTABLE);
+    assert_eq!(response.text().unwrap(), "JSON API disabled");
+  }
+
+  #[test]
+  fn content_hash_recursive_endpoint() {
+    let server = TestServer::builder()
+      .chain(Chain::Regtest)
+      .ord_flag("--index-content")
+      .build();
+
+    server.mine_blocks(2);
+
+    let mut ids = Vec::new();
+
+    for i in 1..3 {
+      let txid = server.core.broadcast_tx(TransactionTemplate {
+        inputs: &[(i, 0, 0, inscription("text/plain", "foo").to_witness())],
+        ..default()
+      });
+
+      ids.push(InscriptionId { txid, index: 0 });
+    }
+
+    server.mine_blocks(1);
+
+    let hash = bitcoin::hashes::sha256::Hash::hash(b"foo");
+
+    pretty_assert_eq!(
+      server.get_json::<api::ContentHashInscriptions>(format!("/r/content-hash/{hash}")),
+      api::ContentHashInscriptions {
+        ids: ids.clone(),
+        more: false,
+        page: 0,
+      }
+    );
+
+    pretty_assert_eq!(
+      server.get_json::<api::ContentHashInscriptions>(format!("/r/content-hash/{hash}/1")),
+      api::ContentHashInscriptions {
+        ids: Vec::new(),
+        more: false,
+        page: 1,
+      }
+    );
+
+    server.assert_response(format!("/content/{}", ids[1]), StatusCode::OK, "foo");
+
+    server.assert_response_regex(
+      "/r/content-hash/foo",
+      StatusCode::BAD_REQUEST,
+      "Invalid URL: .*",
+    );
+  }
+
+  #[test]
+  fn content_hash_recursive_endpoint_requires_content_index() {
+    let server = TestServer::builder().chain(Chain::Regtest).build();
+
+    server.assert_response(
+      format!(
+        "/r/content-hash/{}",
+        bitcoin::hashes::sha256::Hash::hash(b"foo")
+      ),
+      StatusCode::NOT_FOUND,
+      "this server has no content index",
+    );
+  }
+
+  #[test]
+  fn sat_recursive_endpoints() {
+    let server = TestServer::builder()
+      .chain(Chain::Regtest)
+      .index_sats()
+      .build();
+
+    assert_eq!(
+      server.get_json::<api::SatInscriptions>("/r/sat/5000000000"),
+      api::SatInscriptions {
+        ids: Vec::new(),
+        page: 0,
+        more: false
+      }
+    );
+
+    assert_eq!(
+      server.get_json::<api::SatInscription>("/r/sat/5000000000/at/0"),
+      api::SatInscription { id: None }
+    );
+
+    server.mine_blocks(1);
+
+    let txid = server.core.broadcast_tx(TransactionTemplate {
+      inputs: &[(1, 0, 0, inscription("text/plain", "foo").to_witness())],
+      ..default()
+    });
+
+    server.mine_blocks(1);
+
+    let mut ids = Vec::new();
+    ids.push(InscriptionId { txid, index: 0 });
+
+    for i in 1..111 {
+      let txid = server.core.broadcast_tx(TransactionTemplate {
+        inputs: &[(i + 1, 1, 0, inscription("text/plain", "foo").to_witness())],
+        ..default()
+      });
+
+      server.mine_blocks(1);
+
+      ids.push(InscriptionId { txid, index: 0 });
+    }
+
+    let paginated_response = server.get_json::<api::SatInscriptions>("/r/sat/5000000000");
+
+    let equivalent_paginated_response =
+      server.get_json::<api::SatInscriptions>("/r/sat/5000000000/0");
+
+    assert_eq!(paginated_response.ids.len(), 100);
+    assert!(paginated_response.more);
+    assert_eq!(paginated_response.page, 0);
+
+    assert_eq!(
+      paginated_response.ids.len(),
+      equivalent_paginated_response.ids.len()
+    );
+    assert_eq!(paginated_response.more, equivalent_paginated_response.more);
+    assert_eq!(paginated_response.page, equivalent_paginated_response.page);
+
+    let paginated_response = server.get_json::<api::SatInscriptions>("/r/sat/5000000000/1");
+
+    assert_eq!(paginated_response.ids.len(), 11);
+    assert!(!paginated_response.more);
+    assert_eq!(paginated_response.page, 1);
+
+    assert_eq!(
+      server
+        .get_json::<api::SatInscription>("/r/sat/5000000000/at/0")
+        .id,
+      Some(ids[0])
+    );
+
+    assert_eq!(
+      server
+        .get_json::<api::SatInscription>("/r/sat/5000000000/at/-111")
+        .id,
+      Some(ids[0])
+    );
+
+    assert_eq!(
+      server
+        .get_json::<api::SatInscription>("/r/sat/5000000000/at/110")
+        .id,
+      Some(ids[110])
+    );
+
+    assert_eq!(
+      server
+        .get_json::<api::SatInscription>("/r/sat/5000000000/at/-1")
+        .id,
+      Some(ids[110])
+    );
+
+    assert!(server
+      .get_json::<api::SatInscription>("/r/sat/5000000000/at/111")
+      .id
+      .is_none());
+  }
+
+  #[test]
+  fn children_recursive_endpoint() {
+    let server = TestServer::builder().chain(Chain::Regtest).build();
+    server.mine_blocks(1);
+
+    let parent_txid = server.core.broadcast_tx(TransactionTemplate {
+      inputs: &[(1, 0, 0, inscription("text/plain", "hello").to_witness())],
+      ..default()
     });
 
     let parent_inscription_id = InscriptionId {
@@ -6751,6 +7982,105 @@ next
     assert_eq!(first_child_id, children.ids[0]);
   }
 
+  #[test]
+  fn inscription_history() {
+    let server = TestServer::builder()
+      .chain(Chain::Regtest)
+      .ord_flag("--index-transfers")
+      .build();
+
+    server.mine_blocks(1);
+
+    let create_txid = server.core.broadcast_tx(TransactionTemplate {
+      inputs: &[(1, 0, 0, inscription("text/plain", "foo").to_witness())],
+      ..default()
+    });
+
+    server.mine_blocks(1);
+
+    let id = InscriptionId {
+      txid: create_txid,
+      index: 0,
+    };
+
+    let transfer_txid = server.core.broadcast_tx(TransactionTemplate {
+      inputs: &[(2, 1, 0, Default::default())],
+      fee: 100,
+      ..default()
+    });
+
+    server.mine_blocks(1);
+
+    pretty_assert_eq!(
+      server.get_json::<api::InscriptionHistory>(format!("/r/inscription/{id}/history")),
+      api::InscriptionHistory {
+        transfers: vec![api::InscriptionTransfer {
+          fee: 100,
+          height: 3,
+          new_location: SatPoint {
+            outpoint: OutPoint {
+              txid: transfer_txid,
+              vout: 0
+            },
+            offset: 0
+          },
+          old_location: SatPoint {
+            outpoint: OutPoint {
+              txid: create_txid,
+              vout: 0
+            },
+            offset: 0
+          },
+          receiver: Some(
+            server
+              .core
+              .address(OutPoint {
+                txid: transfer_txid,
+                vout: 0
+              })
+              .to_string()
+          ),
+          sender: Some(
+            server
+              .core
+              .address(OutPoint {
+                txid: create_txid,
+                vout: 0
+              })
+              .to_string()
+          ),
+          txid: transfer_txid,
+        }],
+        more: false,
+        page: 0,
+      }
+    );
+
+    pretty_assert_eq!(
+      server.get_json::<api::InscriptionHistory>(format!("/r/inscription/{id}/history/1")),
+      api::InscriptionHistory {
+        transfers: Vec::new(),
+        more: false,
+        page: 1,
+      }
+    );
+
+    server.assert_response(
+      format!("/r/inscription/{}/history", inscription_id(1)),
+      StatusCode::NOT_FOUND,
+      &format!("inscription {} not found", inscription_id(1)),
+    );
+  }
+
+  #[test]
+  fn inscription_history_requires_transfer_index() {
+    TestServer::new().assert_response(
+      format!("/r/inscription/{}/history", inscription_id(1)),
+      StatusCode::NOT_FOUND,
+      "this server has no transfer index",
+    );
+  }
+
   #[test]
   fn inscription_proxy() {
     let server = TestServer::builder().chain(Chain::Regtest).build();
@@ -7128,4 +8458,85 @@ next
       }
     );
   }
+
+  #[test]
+  fn events_require_event_feed() {
+    TestServer::new().assert_response("/r/events", StatusCode::NOT_FOUND, "event feed not found");
+  }
+
+  #[test]
+  fn events_reject_invalid_queries() {
+    let server = TestServer::builder().server_flag("--events").build();
+
+    server.assert_response(
+      "/r/events?types=Foo",
+      StatusCode::BAD_REQUEST,
+      "invalid event type `Foo`",
+    );
+
+    server.assert_response(
+      "/r/events?address=foo",
+      StatusCode::BAD_REQUEST,
+      "invalid address: base58 address encoding error",
+    );
+
+    assert_eq!(
+      server.get("/r/events?cursor=foo").status(),
+      StatusCode::BAD_REQUEST
+    );
+
+    server.assert_response(
+      "/r/events?cursor=0:100",
+      StatusCode::GONE,
+      "cursor 0:100 is ahead of the event feed",
+    );
+  }
+
+  #[test]
+  fn events_are_streamed() {
+    let server = TestServer::builder().server_flag("--events").build();
+
+    let response = reqwest::blocking::Client::builder()
+      .timeout(Duration::from_secs(10))
+      .build()
+      .unwrap()
+      .get(server.join_url("/r/events?types=BlockCommitted"))
+      .send()
+      .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+      response.headers().get(header::CONTENT_TYPE).unwrap(),
+      "text/event-stream"
+    );
+
+    server.mine_blocks(1);
+
+    let mut id = None;
+
+    for line in io::BufReader::new(response).lines() {
+      let line = line.unwrap();
+
+      if let Some(cursor) = line.strip_prefix("id:") {
+        id = Some(cursor.trim().to_string());
+      }
+
+      if let Some(data) = line.strip_prefix("data:") {
+        let message = serde_json::from_str::<EventMessage>(data.trim()).unwrap();
+
+        let Event::BlockCommitted { block_height, .. } = message.event else {
+          panic!("unexpected event: {:?}", message.event);
+        };
+
+        assert_eq!(
+          id.take().unwrap(),
+          format!("{block_height}:{}", message.event_id)
+        );
+
+        if block_height == 1 {
+          break;
+        }
+      }
+    }
+  }
 }
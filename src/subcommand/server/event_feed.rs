@@ -0,0 +1,979 @@
+use {
+  super::*,
+  crate::index::event::{Event, EventMessage},
+  axum::response::sse::{self, KeepAlive, Sse},
+  std::{
+    collections::VecDeque,
+    convert::Infallible,
+    sync::{Mutex, MutexGuard},
+  },
+  tokio::sync::{broadcast, mpsc},
+  tokio_stream::wrappers::ReceiverStream,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct Cursor {
+  pub(crate) height: u32,
+  pub(crate) sequence: u64,
+}
+
+impl FromStr for Cursor {
+  type Err = Error;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    let Some((height, sequence)) = s.split_once(':') else {
+      bail!("invalid cursor `{s}`, expected `<HEIGHT>:<SEQUENCE>`");
+    };
+
+    Ok(Self {
+      height: height
+        .parse()
+        .with_context(|| format!("invalid cursor height `{height}`"))?,
+      sequence: sequence
+        .parse()
+        .with_context(|| format!("invalid cursor sequence `{sequence}`"))?,
+    })
+  }
+}
+
+impl Display for Cursor {
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    write!(f, "{}:{}", self.height, self.sequence)
+  }
+}
+
+#[derive(Debug, PartialEq)]
+pub(crate) struct FeedEvent {
+  pub(crate) cursor: Cursor,
+  pub(crate) event: Event,
+  pub(crate) script_pubkeys: Vec<ScriptBuf>,
+}
+
+#[derive(Debug, PartialEq)]
+pub(crate) enum ResumeError {
+  Ahead(Cursor),
+  Expired(Cursor),
+  Mismatch(Cursor),
+}
+
+impl Display for ResumeError {
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    match self {
+      Self::Ahead(cursor) => write!(f, "cursor {cursor} is ahead of the event feed"),
+      Self::Expired(cursor) => write!(f, "events after cursor {cursor} are no longer available"),
+      Self::Mismatch(cursor) => write!(f, "cursor {cursor} does not match the event feed"),
+    }
+  }
+}
+
+struct Buffer {
+  events: VecDeque<Arc<FeedEvent>>,
+  first: u64,
+  next: u64,
+}
+
+impl Buffer {
+  fn after(&self, cursor: Cursor) -> Result<Vec<Arc<FeedEvent>>, ResumeError> {
+    if cursor.sequence >= self.next {
+      return Err(ResumeError::Ahead(cursor));
+    }
+
+    if let Some(event) = self
+      .events
+      .iter()
+      .find(|event| event.cursor.sequence == cursor.sequence)
+    {
+      if event.cursor.height != cursor.height {
+        return Err(ResumeError::Mismatch(cursor));
+      }
+    }
+
+    Ok(
+      self
+        .events
+        .iter()
+        .filter(|event| event.cursor.sequence > cursor.sequence)
+        .cloned()
+        .collect(),
+    )
+  }
+}
+
+type Subscription = (Vec<Arc<FeedEvent>>, broadcast::Receiver<Arc<FeedEvent>>);
+
+pub(crate) struct EventFeed {
+  buffer: Mutex<Buffer>,
+  capacity: usize,
+  retention: u64,
+  sender: broadcast::Sender<Arc<FeedEvent>>,
+}
+
+impl EventFeed {
+  const CAPACITY: usize = 10_000;
+  const CHANNEL_CAPACITY: usize = 1024;
+  const RETENTION: u64 = 100_000;
+
+  pub(crate) fn new(index: &Index) -> Result<Self> {
+    Self::with_capacity(index, Self::CAPACITY, Self::RETENTION)
+  }
+
+  fn with_capacity(index: &Index, capacity: usize, retention: u64) -> Result<Self> {
+    let next_event_id = index.next_event_id()?;
+
+    // events retained in the outbox were ingested before the server
+    // restarted, so only the most recent are buffered again, and older
+    // cursors are resumed from the outbox
+    let next = match index.events_from(0, 1)?.first() {
+      Some((first, _event)) => (*first).max(next_event_id.saturating_sub(capacity.try_into()?)),
+      None => next_event_id,
+    };
+
+    Ok(Self {
+      buffer: Mutex::new(Buffer {
+        events: VecDeque::new(),
+        first: next,
+        next,
+      }),
+      capacity,
+      retention,
+      sender: broadcast::channel(Self::CHANNEL_CAPACITY).0,
+    })
+  }
+
+  // the id of the next event to be ingested
+  pub(crate) fn next(&self) -> u64 {
+    self.buffer.lock().unwrap().next
+  }
+
+  pub(crate) fn ingest(&self, index: &Index) -> Result {
+    loop {
+      let events = index.events_from(self.next(), 1000)?;
+
+      let Some((last, _)) = events.last() else {
+        return Ok(());
+      };
+
+      let last = *last;
+
+      let mut transactions = HashMap::new();
+
+      for (event_id, event) in events {
+        let script_pubkeys = Self::script_pubkeys(index, &event, &mut transactions)?;
+        self.push(event_id, event, script_pubkeys);
+      }
+
+      // keep the most recent events in the outbox, so that subscribers can
+      // resume from cursors that are older than the buffer
+      if let Some(expired) = last.checked_sub(self.retention) {
+        index.mark_events_delivered(expired)?;
+      }
+    }
+  }
+
+  // resolve the outputs that the `address` filter matches events against
+  // once, when they are ingested, instead of for each subscriber
+  fn script_pubkeys(
+    index: &Index,
+    event: &Event,
+    transactions: &mut HashMap<Txid, Vec<TxOut>>,
+  ) -> Result<Vec<ScriptBuf>> {
+    let mut outputs = |txid: Txid| -> Result<Vec<TxOut>> {
+      if let Some(outputs) = transactions.get(&txid) {
+        return Ok(outputs.clone());
+      }
+
+      let outputs = index
+        .get_transaction(txid)?
+        .map(|tx| tx.output)
+        .unwrap_or_default();
+
+      transactions.insert(txid, outputs.clone());
+
+      Ok(outputs)
+    };
+
+    let outpoint = match event {
+      Event::InscriptionCreated {
+        location: Some(location),
+        ..
+      } => location.outpoint,
+      Event::InscriptionTransferred { new_location, .. } => new_location.outpoint,
+      Event::RuneTransferred { outpoint, .. } => *outpoint,
+      // pending events are decoded from the transaction alone, so they are
+      // matched against all of the transaction's outputs
+      Event::PendingInscriptionCreated {
+        inscription_id: InscriptionId { txid, .. },
+        ..
+      }
+      | Event::PendingInscriptionTransferred { txid, .. }
+      | Event::PendingRuneTransferred { txid, .. } => {
+        return Ok(
+          outputs(*txid)?
+            .into_iter()
+            .map(|output| output.script_pubkey)
+            .collect(),
+        );
+      }
+      _ => return Ok(Vec::new()),
+    };
+
+    // inscriptions that are lost to fees or unbound have no output
+    if outpoint.txid == Txid::all_zeros() {
+      return Ok(Vec::new());
+    }
+
+    Ok(
+      outputs(outpoint.txid)?
+        .into_iter()
+        .nth(outpoint.vout.into_usize())
+        .map(|output| output.script_pubkey)
+        .into_iter()
+        .collect(),
+    )
+  }
+
+  fn push(&self, event_id: u64, event: Event, script_pubkeys: Vec<ScriptBuf>) {
+    let mut buffer = self.buffer.lock().unwrap();
+
+    let event = Arc::new(FeedEvent {
+      cursor: Cursor {
+        height: event.block_height(),
+        sequence: event_id,
+      },
+      event,
+      script_pubkeys,
+    });
+
+    buffer.next = event_id + 1;
+
+    buffer.events.push_back(event.clone());
+
+    while buffer.events.len() > self.capacity {
+      let expired = buffer.events.pop_front().unwrap();
+      buffer.first = expired.cursor.sequence + 1;
+    }
+
+    // sending only fails if there are no subscribers
+    self.sender.send(event).ok();
+  }
+
+  fn subscribe(
+    &self,
+    index: &Index,
+    cursor: Option<Cursor>,
+  ) -> Result<Result<Subscription, ResumeError>> {
+    let buffer = self.buffer.lock().unwrap();
+
+    let receiver = self.sender.subscribe();
+
+    let backlog = match cursor {
+      Some(cursor) => match self.resume(index, cursor, buffer)? {
+        Ok(backlog) => backlog,
+        Err(err) => return Ok(Err(err)),
+      },
+      None => Vec::new(),
+    };
+
+    Ok(Ok((backlog, receiver)))
+  }
+
+  fn after(
+    &self,
+    index: &Index,
+    cursor: Cursor,
+  ) -> Result<Result<Vec<Arc<FeedEvent>>, ResumeError>> {
+    self.resume(index, cursor, self.buffer.lock().unwrap())
+  }
+
+  fn resume(
+    &self,
+    index: &Index,
+    cursor: Cursor,
+    buffer: MutexGuard<Buffer>,
+  ) -> Result<Result<Vec<Arc<FeedEvent>>, ResumeError>> {
+    let first = buffer.first;
+
+    let buffered = match buffer.after(cursor) {
+      Ok(buffered) => buffered,
+      Err(err) => return Ok(Err(err)),
+    };
+
+    drop(buffer);
+
+    if cursor.sequence.saturating_add(1) >= first {
+      return Ok(Ok(buffered));
+    }
+
+    // read the events between the cursor and the buffer from the outbox,
+    // starting with the cursor's own event to check that it matches
+    let stored = index.events_from(
+      cursor.sequence,
+      usize::try_from(first - cursor.sequence).unwrap_or(usize::MAX),
+    )?;
+
+    match stored.first() {
+      Some((event_id, event)) if *event_id == cursor.sequence => {
+        if event.block_height() != cursor.height {
+          return Ok(Err(ResumeError::Mismatch(cursor)));
+        }
+      }
+      _ => return Ok(Err(ResumeError::Expired(cursor))),
+    }
+
+    let mut backlog = Vec::new();
+
+    let mut transactions = HashMap::new();
+
+    for (event_id, event) in stored.into_iter().skip(1) {
+      if event_id >= first {
+        break;
+      }
+
+      let script_pubkeys = Self::script_pubkeys(index, &event, &mut transactions)?;
+
+      backlog.push(Arc::new(FeedEvent {
+        cursor: Cursor {
+          height: event.block_height(),
+          sequence: event_id,
+        },
+        event,
+        script_pubkeys,
+      }));
+    }
+
+    backlog.extend(buffered);
+
+    Ok(Ok(backlog))
+  }
+
+  pub(crate) fn stream(
+    self: Arc<Self>,
+    index: Arc<Index>,
+    filter: EventFilter,
+    cursor: Option<Cursor>,
+  ) -> Result<Result<Sse<ReceiverStream<Result<sse::Event, Infallible>>>, ResumeError>> {
+    let (backlog, receiver) = match self.subscribe(&index, cursor)? {
+      Ok(subscription) => subscription,
+      Err(err) => return Ok(Err(err)),
+    };
+
+    let (sender, stream) = mpsc::channel(Self::CHANNEL_CAPACITY);
+
+    tokio::spawn(async move {
+      if let Err(err) = self
+        .forward(index, filter, cursor, backlog, receiver, sender)
+        .await
+      {
+        log::warn!("event feed subscriber disconnected: {err}");
+      }
+    });
+
+    Ok(Ok(
+      Sse::new(ReceiverStream::new(stream)).keep_alive(KeepAlive::default()),
+    ))
+  }
+
+  async fn forward(
+    &self,
+    index: Arc<Index>,
+    filter: EventFilter,
+    mut last: Option<Cursor>,
+    backlog: Vec<Arc<FeedEvent>>,
+    mut receiver: broadcast::Receiver<Arc<FeedEvent>>,
+    sender: mpsc::Sender<Result<sse::Event, Infallible>>,
+  ) -> Result {
+    let mut pending = VecDeque::from(backlog);
+
+    loop {
+      let event = match pending.pop_front() {
+        Some(event) => event,
+        None => match receiver.recv().await {
+          Ok(event) => event,
+          Err(broadcast::error::RecvError::Lagged(_)) => {
+            let Some(cursor) = last else {
+              bail!("subscriber lagged before receiving any events");
+            };
+
+            pending.extend(
+              task::block_in_place(|| self.after(&index, cursor))?
+                .map_err(|err| anyhow!("{err}"))?,
+            );
+
+            continue;
+          }
+          Err(broadcast::error::RecvError::Closed) => return Ok(()),
+        },
+      };
+
+      if last.is_some_and(|last| event.cursor.sequence <= last.sequence) {
+        continue;
+      }
+
+      last = Some(event.cursor);
+
+      if !task::block_in_place(|| filter.matches(&index, &event))? {
+        continue;
+      }
+
+      let data = serde_json::to_string(&EventMessage::new(
+        event.cursor.sequence,
+        event.event.clone(),
+      ))?;
+
+      if sender
+        .send(Ok(
+          sse::Event::default()
+            .id(event.cursor.to_string())
+            .data(data),
+        ))
+        .await
+        .is_err()
+      {
+        return Ok(());
+      }
+    }
+  }
+}
+
+#[derive(Default, Debug)]
+pub(crate) struct EventFilter {
+  pub(crate) parent: Option<(InscriptionId, u32)>,
+  pub(crate) rune: Option<RuneId>,
+  pub(crate) script_pubkey: Option<ScriptBuf>,
+  pub(crate) types: Option<BTreeSet<String>>,
+}
+
+impl EventFilter {
+  pub(crate) fn matches(&self, index: &Index, feed_event: &FeedEvent) -> Result<bool> {
+    let event = &feed_event.event;
+
+    if let Some(types) = &self.types {
+      if !types.contains(event.name()) {
+        return Ok(false);
+      }
+    }
+
+    // block, rollback, and mempool confirmation and eviction events are
+    // needed to interpret the events that match the remaining filters, so
+    // they are always included
+    if matches!(
+      event,
+      Event::BlockCommitted { .. }
+        | Event::BlockRetracted { .. }
+        | Event::BlockStarted { .. }
+        | Event::BlocksRolledBack { .. }
+        | Event::PendingTransactionConfirmed { .. }
+        | Event::PendingTransactionEvicted { .. }
+    ) {
+      return Ok(true);
+    }
+
+    if let Some(rune) = self.rune {
+      if event.rune_id() != Some(rune) {
+        return Ok(false);
+      }
+    }
+
+    if let Some((parent, parent_sequence_number)) = self.parent {
+      let matches = match event {
+        Event::InscriptionCreated {
+          parent_inscription_ids,
+          ..
+        }
+        | Event::PendingInscriptionCreated {
+          parent_inscription_ids,
+          ..
+        } => parent_inscription_ids.contains(&parent),
+        Event::InscriptionTransferred { inscription_id, .. }
+        | Event::PendingInscriptionTransferred { inscription_id, .. } => index
+          .get_inscription_entry(*inscription_id)?
+          .is_some_and(|entry| entry.parents.contains(&parent_sequence_number)),
+        _ => false,
+      };
+
+      if !matches {
+        return Ok(false);
+      }
+    }
+
+    if let Some(script_pubkey) = &self.script_pubkey {
+      if !feed_event.script_pubkeys.contains(script_pubkey) {
+        return Ok(false);
+      }
+    }
+
+    Ok(true)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use {super::*, crate::index::testing::Context};
+
+  fn etched(block_height: u32) -> Event {
+    Event::RuneEtched {
+      block_height,
+      rune_id: RuneId {
+        block: block_height.into(),
+        tx: 1,
+      },
+      txid: Txid::all_zeros(),
+    }
+  }
+
+  fn feed_event(event: Event) -> FeedEvent {
+    FeedEvent {
+      cursor: Cursor {
+        height: event.block_height(),
+        sequence: 0,
+      },
+      event,
+      script_pubkeys: Vec::new(),
+    }
+  }
+
+  fn sequences(events: &[Arc<FeedEvent>]) -> Vec<u64> {
+    events.iter().map(|event| event.cursor.sequence).collect()
+  }
+
+  #[test]
+  fn cursor_from_str() {
+    assert_eq!(
+      "840000:12".parse::<Cursor>().unwrap(),
+      Cursor {
+        height: 840000,
+        sequence: 12,
+      }
+    );
+
+    assert_eq!(
+      Cursor {
+        height: 840000,
+        sequence: 12,
+      }
+      .to_string(),
+      "840000:12"
+    );
+
+    assert_eq!(
+      "840000".parse::<Cursor>().unwrap_err().to_string(),
+      "invalid cursor `840000`, expected `<HEIGHT>:<SEQUENCE>`"
+    );
+
+    assert_eq!(
+      "foo:12".parse::<Cursor>().unwrap_err().to_string(),
+      "invalid cursor height `foo`"
+    );
+
+    assert_eq!(
+      "840000:foo".parse::<Cursor>().unwrap_err().to_string(),
+      "invalid cursor sequence `foo`"
+    );
+  }
+
+  #[test]
+  fn feed_resumes_after_cursor() {
+    let context = Context::builder().build();
+
+    let feed = EventFeed::with_capacity(&context.index, 3, EventFeed::RETENTION).unwrap();
+
+    for sequence in 0..5 {
+      feed.push(
+        sequence,
+        etched(u32::try_from(sequence).unwrap() + 10),
+        Vec::new(),
+      );
+    }
+
+    let (backlog, _receiver) = feed
+      .subscribe(
+        &context.index,
+        Some(Cursor {
+          height: 12,
+          sequence: 2,
+        }),
+      )
+      .unwrap()
+      .unwrap();
+
+    assert_eq!(sequences(&backlog), [3, 4]);
+
+    let (backlog, _receiver) = feed
+      .subscribe(
+        &context.index,
+        Some(Cursor {
+          height: 11,
+          sequence: 1,
+        }),
+      )
+      .unwrap()
+      .unwrap();
+
+    assert_eq!(sequences(&backlog), [2, 3, 4]);
+
+    let (backlog, _receiver) = feed.subscribe(&context.index, None).unwrap().unwrap();
+
+    assert!(backlog.is_empty());
+  }
+
+  #[test]
+  fn feed_rejects_unavailable_cursors() {
+    let context = Context::builder().build();
+
+    let feed = EventFeed::with_capacity(&context.index, 3, EventFeed::RETENTION).unwrap();
+
+    for sequence in 0..5 {
+      feed.push(
+        sequence,
+        etched(u32::try_from(sequence).unwrap() + 10),
+        Vec::new(),
+      );
+    }
+
+    let expired = Cursor {
+      height: 10,
+      sequence: 0,
+    };
+
+    assert_eq!(
+      feed
+        .subscribe(&context.index, Some(expired))
+        .unwrap()
+        .unwrap_err(),
+      ResumeError::Expired(expired)
+    );
+
+    let ahead = Cursor {
+      height: 15,
+      sequence: 5,
+    };
+
+    assert_eq!(
+      feed
+        .subscribe(&context.index, Some(ahead))
+        .unwrap()
+        .unwrap_err(),
+      ResumeError::Ahead(ahead)
+    );
+
+    let mismatch = Cursor {
+      height: 20,
+      sequence: 3,
+    };
+
+    assert_eq!(
+      feed
+        .subscribe(&context.index, Some(mismatch))
+        .unwrap()
+        .unwrap_err(),
+      ResumeError::Mismatch(mismatch)
+    );
+  }
+
+  #[test]
+  fn subscribers_receive_new_events() {
+    let context = Context::builder().build();
+
+    let feed = EventFeed::new(&context.index).unwrap();
+
+    let (_backlog, mut receiver) = feed.subscribe(&context.index, None).unwrap().unwrap();
+
+    feed.push(0, etched(1), Vec::new());
+
+    assert_eq!(
+      receiver.try_recv().unwrap().as_ref(),
+      &FeedEvent {
+        cursor: Cursor {
+          height: 1,
+          sequence: 0,
+        },
+        event: etched(1),
+        script_pubkeys: Vec::new(),
+      }
+    );
+  }
+
+  #[test]
+  fn ingest_reads_outbox() {
+    let context = Context::builder().event_outbox().build();
+
+    let feed = EventFeed::new(&context.index).unwrap();
+
+    context.mine_blocks(1);
+
+    feed.ingest(&context.index).unwrap();
+
+    assert_eq!(context.index.event_outbox_len().unwrap(), 4);
+
+    let (backlog, _receiver) = feed
+      .subscribe(
+        &context.index,
+        Some(Cursor {
+          height: 0,
+          sequence: 0,
+        }),
+      )
+      .unwrap()
+      .unwrap();
+
+    assert_eq!(sequences(&backlog), [1, 2, 3]);
+
+    assert!(matches!(
+      backlog[1].event,
+      Event::BlockStarted {
+        block_height: 1,
+        ..
+      }
+    ));
+  }
+
+  #[test]
+  fn feed_resumes_from_outbox() {
+    let context = Context::builder().event_outbox().build();
+
+    let feed = EventFeed::with_capacity(&context.index, 2, 4).unwrap();
+
+    context.mine_blocks(2);
+
+    feed.ingest(&context.index).unwrap();
+
+    assert_eq!(context.index.event_outbox_len().unwrap(), 4);
+
+    let (event_id, event) = context.index.events_from(0, 1).unwrap().remove(0);
+
+    assert_eq!(event_id, 2);
+
+    let cursor = Cursor {
+      height: event.block_height(),
+      sequence: event_id,
+    };
+
+    let (backlog, _receiver) = feed
+      .subscribe(&context.index, Some(cursor))
+      .unwrap()
+      .unwrap();
+
+    assert_eq!(sequences(&backlog), [3, 4, 5]);
+
+    let expired = Cursor {
+      height: 0,
+      sequence: 1,
+    };
+
+    assert_eq!(
+      feed
+        .subscribe(&context.index, Some(expired))
+        .unwrap()
+        .unwrap_err(),
+      ResumeError::Expired(expired)
+    );
+
+    let mismatch = Cursor {
+      height: cursor.height + 1,
+      sequence: 2,
+    };
+
+    assert_eq!(
+      feed
+        .subscribe(&context.index, Some(mismatch))
+        .unwrap()
+        .unwrap_err(),
+      ResumeError::Mismatch(mismatch)
+    );
+  }
+
+  #[test]
+  fn feed_buffers_recent_events_after_restart() {
+    let context = Context::builder().event_outbox().build();
+
+    context.mine_blocks(2);
+
+    let feed = EventFeed::with_capacity(&context.index, 2, 4).unwrap();
+
+    feed.ingest(&context.index).unwrap();
+
+    assert_eq!(feed.buffer.lock().unwrap().first, 4);
+
+    assert_eq!(context.index.event_outbox_len().unwrap(), 4);
+
+    let (event_id, event) = context.index.events_from(0, 1).unwrap().remove(0);
+
+    let (backlog, _receiver) = feed
+      .subscribe(
+        &context.index,
+        Some(Cursor {
+          height: event.block_height(),
+          sequence: event_id,
+        }),
+      )
+      .unwrap()
+      .unwrap();
+
+    assert_eq!(sequences(&backlog), [3, 4, 5]);
+  }
+
+  #[test]
+  fn filters() {
+    let context = Context::builder().build();
+
+    let block = Event::BlockCommitted {
+      block_hash: BlockHash::all_zeros(),
+      block_height: 1,
+      counts: default(),
+    };
+
+    let rune_filter = EventFilter {
+      rune: Some(RuneId { block: 1, tx: 1 }),
+      ..default()
+    };
+
+    assert!(rune_filter
+      .matches(&context.index, &feed_event(etched(1)))
+      .unwrap());
+    assert!(!rune_filter
+      .matches(&context.index, &feed_event(etched(2)))
+      .unwrap());
+    assert!(rune_filter
+      .matches(&context.index, &feed_event(block.clone()))
+      .unwrap());
+
+    let type_filter = EventFilter {
+      types: Some(["BlockCommitted".into()].into()),
+      ..default()
+    };
+
+    assert!(type_filter
+      .matches(&context.index, &feed_event(block))
+      .unwrap());
+    assert!(!type_filter
+      .matches(&context.index, &feed_event(etched(1)))
+      .unwrap());
+
+    let parent = InscriptionId {
+      txid: Txid::all_zeros(),
+      index: 7,
+    };
+
+    let parent_filter = EventFilter {
+      parent: Some((parent, 0)),
+      ..default()
+    };
+
+    let created = |parent_inscription_ids| Event::InscriptionCreated {
+      block_height: 1,
+      charms: 0,
+      details: None,
+      inscription_id: InscriptionId {
+        txid: Txid::all_zeros(),
+        index: 8,
+      },
+      location: None,
+      parent_inscription_ids,
+      sequence_number: 1,
+    };
+
+    assert!(parent_filter
+      .matches(&context.index, &feed_event(created(vec![parent])))
+      .unwrap());
+    assert!(!parent_filter
+      .matches(&context.index, &feed_event(created(Vec::new())))
+      .unwrap());
+    assert!(!parent_filter
+      .matches(&context.index, &feed_event(etched(1)))
+      .unwrap());
+  }
+
+  #[test]
+  fn address_filter_matches_resolved_outputs() {
+    let context = Context::builder()
+      .event_outbox()
+      .arg("--mempool-events")
+      .build();
+
+    let feed = EventFeed::new(&context.index).unwrap();
+
+    context.mine_blocks(1);
+
+    let script_pubkey = ScriptBuf::new_v0_p2wpkh(&WPubkeyHash::all_zeros());
+
+    let txid = context.core.broadcast_tx(TransactionTemplate {
+      inputs: &[(1, 0, 0, inscription("text/plain", "foo").to_witness())],
+      ..default()
+    });
+
+    context.index.update().unwrap();
+
+    context.mine_blocks(1);
+
+    context.core.broadcast_tx(TransactionTemplate {
+      inputs: &[(2, 1, 0, Default::default())],
+      p2tr: true,
+      ..default()
+    });
+
+    context.index.update().unwrap();
+
+    feed.ingest(&context.index).unwrap();
+
+    let (backlog, _receiver) = feed
+      .subscribe(
+        &context.index,
+        Some(Cursor {
+          height: 0,
+          sequence: 0,
+        }),
+      )
+      .unwrap()
+      .unwrap();
+
+    let address_filter = EventFilter {
+      script_pubkey: Some(script_pubkey.clone()),
+      ..default()
+    };
+
+    let matched = |filter: &EventFilter| {
+      backlog
+        .iter()
+        .filter(|event| filter.matches(&context.index, event).unwrap())
+        .map(|event| event.event.name())
+        .filter(|name| !name.starts_with("Block") && !name.starts_with("PendingTransaction"))
+        .collect::<Vec<&str>>()
+    };
+
+    assert_eq!(
+      matched(&address_filter),
+      ["PendingInscriptionCreated", "InscriptionCreated"],
+    );
+
+    let created = backlog
+      .iter()
+      .find(|event| matches!(event.event, Event::InscriptionCreated { .. }))
+      .unwrap();
+
+    assert_eq!(created.script_pubkeys, [script_pubkey]);
+
+    let transferred = backlog
+      .iter()
+      .find(|event| matches!(event.event, Event::PendingInscriptionTransferred { .. }))
+      .unwrap();
+
+    assert_eq!(transferred.script_pubkeys.len(), 1);
+    assert!(transferred.script_pubkeys[0].is_v1_p2tr());
+
+    assert_eq!(
+      matched(&EventFilter {
+        script_pubkey: Some(transferred.script_pubkeys[0].clone()),
+        ..default()
+      }),
+      ["PendingInscriptionTransferred"],
+    );
+
+    assert!(matches!(
+      created.event,
+      Event::InscriptionCreated {
+        inscription_id: InscriptionId { txid: id, .. },
+        ..
+      } if id == txid
+    ));
+  }
+}
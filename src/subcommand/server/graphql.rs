@@ -0,0 +1,872 @@
+use {
+  super::*,
+  crate::index::entry::InscriptionEntry,
+  async_graphql::{Context, EmptyMutation, EmptySubscription, Object, SimpleObject},
+};
+
+const MAX_LIMIT: usize = 100;
+
+type GraphqlResult<T> = async_graphql::Result<T>;
+
+pub(super) type Schema = async_graphql::Schema<Query, EmptyMutation, EmptySubscription>;
+
+pub(super) fn schema(chain: Chain, index: Arc<Index>, max_depth: usize, max_cost: usize) -> Schema {
+  Schema::build(Query, EmptyMutation, EmptySubscription)
+    .data(chain)
+    .data(index)
+    .limit_depth(max_depth)
+    .limit_complexity(max_cost)
+    .finish()
+}
+
+// index reads block, so resolvers that use the index wrap their bodies in
+// `task::block_in_place`
+fn index<'a>(ctx: &Context<'a>) -> &'a Index {
+  ctx.data_unchecked::<Arc<Index>>()
+}
+
+fn paginate<T>(items: Vec<T>, limit: usize, page: usize) -> GraphqlResult<Vec<T>> {
+  check_limit(limit)?;
+
+  Ok(
+    items
+      .into_iter()
+      .skip(page.saturating_mul(limit))
+      .take(limit)
+      .collect(),
+  )
+}
+
+fn check_limit(limit: usize) -> GraphqlResult<()> {
+  if limit > MAX_LIMIT {
+    return Err(format!("limit must be at most {MAX_LIMIT}").into());
+  }
+
+  Ok(())
+}
+
+fn require_address_index(index: &Index) -> GraphqlResult<()> {
+  if !index.has_address_index() {
+    return Err("this server has no address index".into());
+  }
+
+  Ok(())
+}
+
+fn require_rune_index(index: &Index) -> GraphqlResult<()> {
+  if !index.has_rune_index() {
+    return Err("this server has no rune index".into());
+  }
+
+  Ok(())
+}
+
+pub(super) struct Query;
+
+#[Object]
+impl Query {
+  /// Look up an inscription by ID, number, or sat name.
+  async fn inscription(
+    &self,
+    ctx: &Context<'_>,
+    query: String,
+  ) -> GraphqlResult<Option<Inscription>> {
+    task::block_in_place(|| {
+      let query = query
+        .parse::<query::Inscription>()
+        .map_err(|err| err.to_string())?;
+
+      Inscription::load(index(ctx), query)
+    })
+  }
+
+  /// Most recent inscriptions, newest first.
+  #[graphql(complexity = "limit.min(MAX_LIMIT).saturating_mul(child_complexity)")]
+  async fn inscriptions(
+    &self,
+    ctx: &Context<'_>,
+    #[graphql(default = 10)] limit: usize,
+    #[graphql(default)] page: usize,
+  ) -> GraphqlResult<Vec<Inscription>> {
+    task::block_in_place(|| {
+      check_limit(limit)?;
+
+      let index = index(ctx);
+
+      let (ids, _) = index.get_inscriptions_paginated(
+        u32::try_from(limit).unwrap(),
+        u32::try_from(page).map_err(|err| err.to_string())?,
+      )?;
+
+      Inscription::load_all(index, ids)
+    })
+  }
+
+  /// Look up a sat by number, name, decimal, degree, or percentile.
+  async fn sat(&self, sat: String) -> GraphqlResult<Sat> {
+    Ok(Sat(
+      sat
+        .parse::<ordinals::Sat>()
+        .map_err(|err| err.to_string())?,
+    ))
+  }
+
+  async fn output(&self, ctx: &Context<'_>, outpoint: String) -> GraphqlResult<Option<Output>> {
+    task::block_in_place(|| {
+      let outpoint = outpoint
+        .parse::<OutPoint>()
+        .map_err(|err| format!("invalid outpoint: {err}"))?;
+
+      Output::load(index(ctx), outpoint)
+    })
+  }
+
+  /// Look up a rune by name, ID, or number.
+  async fn rune(&self, ctx: &Context<'_>, query: String) -> GraphqlResult<Option<Rune>> {
+    task::block_in_place(|| {
+      let index = index(ctx);
+
+      require_rune_index(index)?;
+
+      let rune = match query
+        .parse::<query::Rune>()
+        .map_err(|err| err.to_string())?
+      {
+        query::Rune::Spaced(spaced_rune) => Some(spaced_rune.rune),
+        query::Rune::Id(rune_id) => index.get_rune_by_id(rune_id)?,
+        query::Rune::Number(number) => index.get_rune_by_number(usize::try_from(number)?)?,
+      };
+
+      match rune {
+        Some(rune) => Rune::load(index, rune),
+        None => Ok(None),
+      }
+    })
+  }
+
+  /// Runes in order of etching.
+  #[graphql(complexity = "limit.min(MAX_LIMIT).saturating_mul(child_complexity)")]
+  async fn runes(
+    &self,
+    ctx: &Context<'_>,
+    #[graphql(default = 10)] limit: usize,
+    #[graphql(default)] page: usize,
+  ) -> GraphqlResult<Vec<Rune>> {
+    task::block_in_place(|| {
+      check_limit(limit)?;
+
+      let index = index(ctx);
+
+      require_rune_index(index)?;
+
+      let mut runes = Vec::new();
+
+      for (_, entry) in index.runes_paginated(limit, page)?.0 {
+        runes.extend(Rune::load(index, entry.spaced_rune.rune)?);
+      }
+
+      Ok(runes)
+    })
+  }
+
+  /// Look up a block by height or hash.
+  async fn block(&self, ctx: &Context<'_>, query: String) -> GraphqlResult<Option<Block>> {
+    task::block_in_place(|| {
+      let index = index(ctx);
+
+      let (height, hash) = match query
+        .parse::<query::Block>()
+        .map_err(|err| err.to_string())?
+      {
+        query::Block::Height(height) => match index.block_hash(Some(height))? {
+          Some(hash) => (height, hash),
+          None => return Ok(None),
+        },
+        query::Block::Hash(hash) => match index.block_header_info(hash)? {
+          Some(info) => (u32::try_from(info.height)?, hash),
+          None => return Ok(None),
+        },
+      };
+
+      Ok(Some(Block { hash, height }))
+    })
+  }
+
+  /// Height of the latest indexed block.
+  async fn block_height(&self, ctx: &Context<'_>) -> GraphqlResult<Option<u32>> {
+    task::block_in_place(|| Ok(index(ctx).block_height()?.map(|height| height.n())))
+  }
+
+  async fn address(&self, ctx: &Context<'_>, address: String) -> GraphqlResult<Address> {
+    task::block_in_place(|| {
+      let index = index(ctx);
+
+      require_address_index(index)?;
+
+      let address = address
+        .parse::<bitcoin::Address<NetworkUnchecked>>()
+        .map_err(|err| format!("invalid address: {err}"))?
+        .require_network(ctx.data_unchecked::<Chain>().network())
+        .map_err(|err| format!("invalid address: {err}"))?;
+
+      Ok(Address(address))
+    })
+  }
+}
+
+pub(super) struct Inscription {
+  entry: InscriptionEntry,
+  info: api::Inscription,
+  metaprotocol: Option<String>,
+}
+
+impl Inscription {
+  fn load(index: &Index, query: query::Inscription) -> GraphqlResult<Option<Self>> {
+    let Some((info, _, inscription)) = index.inscription_info(query, None)? else {
+      return Ok(None);
+    };
+
+    let Some(entry) = index.get_inscription_entry(info.id)? else {
+      return Ok(None);
+    };
+
+    Ok(Some(Self {
+      entry,
+      info,
+      metaprotocol: inscription.metaprotocol().map(str::to_string),
+    }))
+  }
+
+  fn load_all(index: &Index, ids: Vec<InscriptionId>) -> GraphqlResult<Vec<Self>> {
+    let mut inscriptions = Vec::new();
+
+    for id in ids {
+      if let Some(inscription) = Self::load(index, query::Inscription::Id(id))? {
+        inscriptions.push(inscription);
+      }
+    }
+
+    Ok(inscriptions)
+  }
+}
+
+#[Object]
+impl Inscription {
+  async fn id(&self) -> String {
+    self.info.id.to_string()
+  }
+
+  async fn number(&self) -> i32 {
+    self.info.number
+  }
+
+  async fn sequence_number(&self) -> u32 {
+    self.entry.sequence_number
+  }
+
+  async fn address(&self) -> Option<&str> {
+    self.info.address.as_deref()
+  }
+
+  async fn charms(&self) -> Vec<String> {
+    self
+      .info
+      .charms
+      .iter()
+      .map(|charm| charm.to_string())
+      .collect()
+  }
+
+  async fn content_length(&self) -> Option<usize> {
+    self.info.content_length
+  }
+
+  async fn content_type(&self) -> Option<&str> {
+    self.info.content_type.as_deref()
+  }
+
+  async fn effective_content_type(&self) -> Option<&str> {
+    self.info.effective_content_type.as_deref()
+  }
+
+  async fn fee(&self) -> u64 {
+    self.info.fee
+  }
+
+  async fn height(&self) -> u32 {
+    self.info.height
+  }
+
+  async fn metaprotocol(&self) -> Option<&str> {
+    self.metaprotocol.as_deref()
+  }
+
+  async fn satpoint(&self) -> String {
+    self.info.satpoint.to_string()
+  }
+
+  async fn timestamp(&self) -> i64 {
+    self.info.timestamp
+  }
+
+  async fn value(&self) -> Option<u64> {
+    self.info.value
+  }
+
+  async fn sat(&self) -> Option<Sat> {
+    self.info.sat.map(Sat)
+  }
+
+  /// Output currently holding the inscription, if any.
+  async fn output(&self, ctx: &Context<'_>) -> GraphqlResult<Option<Output>> {
+    task::block_in_place(|| {
+      let outpoint = self.info.satpoint.outpoint;
+
+      if outpoint == OutPoint::null() || outpoint == unbound_outpoint() {
+        return Ok(None);
+      }
+
+      Output::load(index(ctx), outpoint)
+    })
+  }
+
+  /// Rune etched by the inscription's transaction, if any.
+  async fn rune(&self, ctx: &Context<'_>) -> GraphqlResult<Option<Rune>> {
+    task::block_in_place(|| match self.info.rune {
+      Some(spaced_rune) => Rune::load(index(ctx), spaced_rune.rune),
+      None => Ok(None),
+    })
+  }
+
+  #[graphql(complexity = "limit.min(MAX_LIMIT).saturating_mul(child_complexity)")]
+  async fn parents(
+    &self,
+    ctx: &Context<'_>,
+    #[graphql(default = 10)] limit: usize,
+    #[graphql(default)] page: usize,
+  ) -> GraphqlResult<Vec<Inscription>> {
+    task::block_in_place(|| {
+      let index = index(ctx);
+
+      let sequence_numbers = paginate(self.entry.parents.clone(), limit, page)?;
+
+      if sequence_numbers.is_empty() {
+        return Ok(Vec::new());
+      }
+
+      let (ids, _) = index.get_parents_by_sequence_number_paginated(sequence_numbers, 0)?;
+
+      Inscription::load_all(index, ids)
+    })
+  }
+
+  #[graphql(complexity = "limit.min(MAX_LIMIT).saturating_mul(child_complexity)")]
+  async fn children(
+    &self,
+    ctx: &Context<'_>,
+    #[graphql(default = 10)] limit: usize,
+    #[graphql(default)] page: usize,
+  ) -> GraphqlResult<Vec<Inscription>> {
+    task::block_in_place(|| {
+      check_limit(limit)?;
+
+      let index = index(ctx);
+
+      let (ids, _) =
+        index.get_children_by_sequence_number_paginated(self.entry.sequence_number, limit, page)?;
+
+      Inscription::load_all(index, ids)
+    })
+  }
+}
+
+pub(super) struct Sat(ordinals::Sat);
+
+#[Object]
+impl Sat {
+  async fn number(&self) -> u64 {
+    self.0.n()
+  }
+
+  async fn name(&self) -> String {
+    self.0.name()
+  }
+
+  async fn decimal(&self) -> String {
+    self.0.decimal().to_string()
+  }
+
+  async fn degree(&self) -> String {
+    self.0.degree().to_string()
+  }
+
+  async fn percentile(&self) -> String {
+    self.0.percentile()
+  }
+
+  async fn rarity(&self) -> String {
+    self.0.rarity().to_string()
+  }
+
+  async fn charms(&self) -> Vec<String> {
+    Charm::charms(self.0.charms())
+      .into_iter()
+      .map(|charm| charm.to_string())
+      .collect()
+  }
+
+  async fn block(&self) -> u32 {
+    self.0.height().0
+  }
+
+  async fn cycle(&self) -> u32 {
+    self.0.cycle()
+  }
+
+  async fn epoch(&self) -> u32 {
+    self.0.epoch().0
+  }
+
+  async fn period(&self) -> u32 {
+    self.0.period()
+  }
+
+  async fn offset(&self) -> u64 {
+    self.0.third()
+  }
+
+  async fn timestamp(&self, ctx: &Context<'_>) -> GraphqlResult<i64> {
+    task::block_in_place(|| {
+      Ok(
+        index(ctx)
+          .block_time(self.0.height())?
+          .timestamp()
+          .timestamp(),
+      )
+    })
+  }
+
+  /// Location of the sat, if it is rare or inscribed and known to the index.
+  async fn satpoint(&self, ctx: &Context<'_>) -> GraphqlResult<Option<String>> {
+    task::block_in_place(|| {
+      let index = index(ctx);
+
+      let satpoint = match index.rare_sat_satpoint(self.0)? {
+        Some(satpoint) => Some(satpoint),
+        None => match index
+          .get_inscription_ids_by_sat_paginated(self.0, 1, 0)?
+          .0
+          .first()
+        {
+          Some(id) => index.get_inscription_satpoint_by_id(*id)?,
+          None => None,
+        },
+      };
+
+      Ok(satpoint.map(|satpoint| satpoint.to_string()))
+    })
+  }
+
+  #[graphql(complexity = "limit.min(MAX_LIMIT).saturating_mul(child_complexity)")]
+  async fn inscriptions(
+    &self,
+    ctx: &Context<'_>,
+    #[graphql(default = 10)] limit: usize,
+    #[graphql(default)] page: usize,
+  ) -> GraphqlResult<Vec<Inscription>> {
+    task::block_in_place(|| {
+      check_limit(limit)?;
+
+      let index = index(ctx);
+
+      let (ids, _) = index.get_inscription_ids_by_sat_paginated(
+        self.0,
+        u64::try_from(limit).unwrap(),
+        u64::try_from(page)?,
+      )?;
+
+      Inscription::load_all(index, ids)
+    })
+  }
+}
+
+#[derive(SimpleObject)]
+pub(super) struct SatRange {
+  start: u64,
+  end: u64,
+}
+
+pub(super) struct Output {
+  info: api::Output,
+  outpoint: OutPoint,
+}
+
+impl Output {
+  fn load(index: &Index, outpoint: OutPoint) -> GraphqlResult<Option<Self>> {
+    Ok(
+      index
+        .get_output_info(outpoint)?
+        .map(|(info, _)| Self { info, outpoint }),
+    )
+  }
+}
+
+#[Object]
+impl Output {
+  async fn outpoint(&self) -> String {
+    self.outpoint.to_string()
+  }
+
+  async fn address(&self) -> Option<String> {
+    self
+      .info
+      .address
+      .as_ref()
+      .map(|address| address.clone().assume_checked().to_string())
+  }
+
+  async fn indexed(&self) -> bool {
+    self.info.indexed
+  }
+
+  async fn script_pubkey(&self) -> &str {
+    &self.info.script_pubkey
+  }
+
+  async fn spent(&self) -> bool {
+    self.info.spent
+  }
+
+  async fn transaction(&self) -> &str {
+    &self.info.transaction
+  }
+
+  async fn value(&self) -> u64 {
+    self.info.value
+  }
+
+  /// Sat ranges in the output. Only available with `--index-sats`.
+  async fn sat_ranges(&self) -> Option<Vec<SatRange>> {
+    self.info.sat_ranges.as_ref().map(|ranges| {
+      ranges
+        .iter()
+        .map(|(start, end)| SatRange {
+          start: *start,
+          end: *end,
+        })
+        .collect()
+    })
+  }
+
+  #[graphql(complexity = "limit.min(MAX_LIMIT).saturating_mul(child_complexity)")]
+  async fn inscriptions(
+    &self,
+    ctx: &Context<'_>,
+    #[graphql(default = 10)] limit: usize,
+    #[graphql(default)] page: usize,
+  ) -> GraphqlResult<Vec<Inscription>> {
+    task::block_in_place(|| {
+      Inscription::load_all(
+        index(ctx),
+        paginate(self.info.inscriptions.clone(), limit, page)?,
+      )
+    })
+  }
+
+  #[graphql(complexity = "limit.min(MAX_LIMIT).saturating_mul(child_complexity)")]
+  async fn runes(
+    &self,
+    #[graphql(default = 10)] limit: usize,
+    #[graphql(default)] page: usize,
+  ) -> GraphqlResult<Vec<RuneBalance>> {
+    paginate(
+      self
+        .info
+        .runes
+        .iter()
+        .map(|(spaced_rune, pile)| RuneBalance {
+          pile: *pile,
+          spaced_rune: *spaced_rune,
+        })
+        .collect(),
+      limit,
+      page,
+    )
+  }
+}
+
+pub(super) struct RuneBalance {
+  pile: Pile,
+  spaced_rune: SpacedRune,
+}
+
+#[Object]
+impl RuneBalance {
+  /// Balance in the rune's smallest unit, as a decimal string.
+  async fn amount(&self) -> String {
+    self.pile.amount.to_string()
+  }
+
+  /// Balance including divisibility and symbol.
+  async fn display(&self) -> String {
+    self.pile.to_string()
+  }
+
+  async fn rune(&self, ctx: &Context<'_>) -> GraphqlResult<Option<Rune>> {
+    task::block_in_place(|| Rune::load(index(ctx), self.spaced_rune.rune))
+  }
+}
+
+pub(super) struct Rune {
+  entry: RuneEntry,
+  id: RuneId,
+  mintable: bool,
+  parent: Option<InscriptionId>,
+}
+
+impl Rune {
+  fn load(index: &Index, rune: ordinals::Rune) -> GraphqlResult<Option<Self>> {
+    require_rune_index(index)?;
+
+    let Some((id, entry, parent)) = index.rune(rune)? else {
+      return Ok(None);
+    };
+
+    let height = index.block_height()?.unwrap_or(Height(0));
+
+    Ok(Some(Self {
+      mintable: entry.mintable((height.n() + 1).into()).is_ok(),
+      entry,
+      id,
+      parent,
+    }))
+  }
+}
+
+#[Object]
+impl Rune {
+  async fn id(&self) -> String {
+    self.id.to_string()
+  }
+
+  async fn name(&self) -> String {
+    self.entry.spaced_rune.to_string()
+  }
+
+  async fn number(&self) -> u64 {
+    self.entry.number
+  }
+
+  async fn block(&self) -> u64 {
+    self.entry.block
+  }
+
+  async fn burned(&self) -> String {
+    self.entry.burned.to_string()
+  }
+
+  async fn divisibility(&self) -> u8 {
+    self.entry.divisibility
+  }
+
+  async fn etching(&self) -> String {
+    self.entry.etching.to_string()
+  }
+
+  async fn mints(&self) -> String {
+    self.entry.mints.to_string()
+  }
+
+  async fn mintable(&self) -> bool {
+    self.mintable
+  }
+
+  async fn premine(&self) -> String {
+    self.entry.premine.to_string()
+  }
+
+  async fn supply(&self) -> String {
+    self.entry.supply().to_string()
+  }
+
+  async fn symbol(&self) -> Option<String> {
+    self.entry.symbol.map(|symbol| symbol.to_string())
+  }
+
+  async fn timestamp(&self) -> u64 {
+    self.entry.timestamp
+  }
+
+  async fn turbo(&self) -> bool {
+    self.entry.turbo
+  }
+
+  async fn holders(&self, ctx: &Context<'_>) -> GraphqlResult<u64> {
+    task::block_in_place(|| Ok(index(ctx).get_rune_holder_count(self.id)?))
+  }
+
+  async fn parent(&self, ctx: &Context<'_>) -> GraphqlResult<Option<Inscription>> {
+    task::block_in_place(|| match self.parent {
+      Some(parent) => Inscription::load(index(ctx), query::Inscription::Id(parent)),
+      None => Ok(None),
+    })
+  }
+}
+
+pub(super) struct Block {
+  hash: BlockHash,
+  height: u32,
+}
+
+#[Object]
+impl Block {
+  async fn hash(&self) -> String {
+    self.hash.to_string()
+  }
+
+  async fn height(&self) -> u32 {
+    self.height
+  }
+
+  async fn timestamp(&self, ctx: &Context<'_>) -> GraphqlResult<Option<u32>> {
+    task::block_in_place(|| {
+      Ok(
+        index(ctx)
+          .block_header(self.hash)?
+          .map(|header| header.time),
+      )
+    })
+  }
+
+  async fn previous_blockhash(&self, ctx: &Context<'_>) -> GraphqlResult<Option<String>> {
+    task::block_in_place(|| {
+      Ok(
+        index(ctx)
+          .block_header(self.hash)?
+          .map(|header| header.prev_blockhash.to_string()),
+      )
+    })
+  }
+
+  #[graphql(complexity = "limit.min(MAX_LIMIT).saturating_mul(child_complexity)")]
+  async fn inscriptions(
+    &self,
+    ctx: &Context<'_>,
+    #[graphql(default = 10)] limit: usize,
+    #[graphql(default)] page: usize,
+  ) -> GraphqlResult<Vec<Inscription>> {
+    task::block_in_place(|| {
+      let index = index(ctx);
+
+      Inscription::load_all(
+        index,
+        paginate(index.get_inscriptions_in_block(self.height)?, limit, page)?,
+      )
+    })
+  }
+
+  #[graphql(complexity = "limit.min(MAX_LIMIT).saturating_mul(child_complexity)")]
+  async fn runes(
+    &self,
+    ctx: &Context<'_>,
+    #[graphql(default = 10)] limit: usize,
+    #[graphql(default)] page: usize,
+  ) -> GraphqlResult<Vec<Rune>> {
+    task::block_in_place(|| {
+      let index = index(ctx);
+
+      require_rune_index(index)?;
+
+      let mut runes = Vec::new();
+
+      for spaced_rune in paginate(index.get_runes_in_block(self.height.into())?, limit, page)? {
+        runes.extend(Rune::load(index, spaced_rune.rune)?);
+      }
+
+      Ok(runes)
+    })
+  }
+}
+
+pub(super) struct Address(bitcoin::Address);
+
+#[Object]
+impl Address {
+  async fn address(&self) -> String {
+    self.0.to_string()
+  }
+
+  async fn sat_balance(&self, ctx: &Context<'_>) -> GraphqlResult<u64> {
+    task::block_in_place(|| {
+      let index = index(ctx);
+      Ok(index.get_sat_balances_for_outputs(&index.get_address_info(&self.0)?)?)
+    })
+  }
+
+  #[graphql(complexity = "limit.min(MAX_LIMIT).saturating_mul(child_complexity)")]
+  async fn outputs(
+    &self,
+    ctx: &Context<'_>,
+    #[graphql(default = 10)] limit: usize,
+    #[graphql(default)] page: usize,
+  ) -> GraphqlResult<Vec<Output>> {
+    task::block_in_place(|| {
+      let index = index(ctx);
+
+      let mut outpoints = index.get_address_info(&self.0)?;
+
+      outpoints.sort();
+
+      let mut outputs = Vec::new();
+
+      for outpoint in paginate(outpoints, limit, page)? {
+        outputs.extend(Output::load(index, outpoint)?);
+      }
+
+      Ok(outputs)
+    })
+  }
+
+  #[graphql(complexity = "limit.min(MAX_LIMIT).saturating_mul(child_complexity)")]
+  async fn inscriptions(
+    &self,
+    ctx: &Context<'_>,
+    #[graphql(default = 10)] limit: usize,
+    #[graphql(default)] page: usize,
+  ) -> GraphqlResult<Vec<Inscription>> {
+    task::block_in_place(|| {
+      check_limit(limit)?;
+
+      let index = index(ctx);
+
+      let (ids, _) = index.get_inscriptions_for_address_paginated(&self.0, limit, page)?;
+
+      Inscription::load_all(index, ids)
+    })
+  }
+
+  #[graphql(complexity = "limit.min(MAX_LIMIT).saturating_mul(child_complexity)")]
+  async fn rune_balances(
+    &self,
+    ctx: &Context<'_>,
+    #[graphql(default = 10)] limit: usize,
+    #[graphql(default)] page: usize,
+  ) -> GraphqlResult<Vec<RuneBalance>> {
+    task::block_in_place(|| {
+      check_limit(limit)?;
+
+      let index = index(ctx);
+
+      require_rune_index(index)?;
+
+      Ok(
+        index
+          .get_rune_balances_for_address_paginated(&self.0, limit, page)?
+          .0
+          .into_iter()
+          .map(|(spaced_rune, pile)| RuneBalance { pile, spaced_rune })
+          .collect(),
+      )
+    })
+  }
+}
@@ -11,8 +11,8 @@ pub(crate) fn run(wallet: Wallet) -> SubcommandResult {
   let runic_utxos = wallet.get_runic_outputs()?;
 
   let runic_utxos = unspent_outputs
-    .iter()
-    .filter_map(|(output, _)| {
+    .keys()
+    .filter_map(|output| {
       if runic_utxos.contains(output) {
         let rune_balances = wallet.get_runes_balances_in_output(output).ok()?;
         let mut runes = BTreeMap::new();
@@ -23,6 +23,7 @@ pub(crate) use {
   range::RangeHtml,
   rare::RareTxt,
   sat::SatHtml,
+  text_search::TextSearchHtml,
 };
 
 pub use {
@@ -52,6 +53,7 @@ pub mod rune;
 pub mod runes;
 pub mod sat;
 pub mod status;
+mod text_search;
 pub mod transaction;
 
 #[derive(Boilerplate)]
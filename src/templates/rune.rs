@@ -3,6 +3,7 @@ use super::*;
 #[derive(Boilerplate, Debug, PartialEq, Serialize, Deserialize)]
 pub struct RuneHtml {
   pub entry: RuneEntry,
+  pub holders: u64,
   pub id: RuneId,
   pub mintable: bool,
   pub parent: Option<InscriptionId>,
@@ -44,6 +45,7 @@ mod tests {
           timestamp: 0,
           turbo: true,
         },
+        holders: 3,
         id: RuneId { block: 10, tx: 9 },
         mintable: true,
         parent: Some(InscriptionId {
@@ -93,6 +95,8 @@ mod tests {
   <dd>0.12%</dd>
   <dt>burned</dt>
   <dd>123456789.123456789\u{A0}%</dd>
+  <dt>holders</dt>
+  <dd>3</dd>
   <dt>divisibility</dt>
   <dd>9</dd>
   <dt>symbol</dt>
@@ -129,6 +133,7 @@ mod tests {
           timestamp: 0,
           turbo: false,
         },
+        holders: 0,
         id: RuneId { block: 10, tx: 9 },
         mintable: false,
         parent: None,
@@ -163,6 +168,7 @@ mod tests {
           timestamp: 0,
           turbo: false,
         },
+        holders: 0,
         id: RuneId { block: 10, tx: 9 },
         mintable: false,
         parent: None,
@@ -202,6 +208,7 @@ mod tests {
           timestamp: 0,
           turbo: false,
         },
+        holders: 0,
         id: RuneId { block: 10, tx: 9 },
         mintable: false,
         parent: None,
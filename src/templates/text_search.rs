@@ -0,0 +1,68 @@
+use super::*;
+
+#[derive(Boilerplate)]
+pub(crate) struct TextSearchHtml {
+  pub(crate) query: String,
+  pub(crate) results: Vec<api::TextSearchResult>,
+  pub(crate) prev: Option<usize>,
+  pub(crate) next: Option<usize>,
+}
+
+impl PageContent for TextSearchHtml {
+  fn title(&self) -> String {
+    format!("Search results for “{}”", self.query)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn results() {
+    assert_regex_match!(
+      TextSearchHtml {
+        query: "foo & bar".into(),
+        results: vec![api::TextSearchResult {
+          id: inscription_id(1),
+          number: 7,
+          score: 1.5,
+          snippet: "<b>foo</b>".into(),
+        }],
+        prev: Some(0),
+        next: None,
+      },
+      "
+        <h1>Search results for “foo &amp; bar”</h1>
+        <ol class=text-search>
+          <li>
+            <a href=/inscription/1{64}i1>Inscription 7</a>
+            <p>&lt;b&gt;foo&lt;/b&gt;</p>
+          </li>
+        </ol>
+        <div class=center>
+          <a class=prev href=\"/text-search\\?query=foo%20%26%20bar&page=0\">prev</a>
+        next
+        </div>
+      "
+      .unindent()
+    );
+  }
+
+  #[test]
+  fn no_results() {
+    assert_regex_match!(
+      TextSearchHtml {
+        query: "foo".into(),
+        results: Vec::new(),
+        prev: None,
+        next: None,
+      },
+      "
+        <h1>Search results for “foo”</h1>
+        <h3>No inscriptions found</h3>
+      "
+      .unindent()
+    );
+  }
+}
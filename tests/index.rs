@@ -90,3 +90,306 @@ fn export_inscription_number_to_id_tsv() {
     &ord::Object::InscriptionId(inscription),
   );
 }
+
+#[test]
+fn publish_requires_kafka_brokers() {
+  let core = mockcore::spawn();
+
+  CommandBuilder::new("index publish")
+    .core(&core)
+    .expected_stderr("error: no Kafka brokers configured, set brokers with `--kafka-brokers`\n")
+    .expected_exit_code(1)
+    .run_and_extract_stdout();
+}
+
+fn updated_index(core: &mockcore::Handle, args: &str) -> (TempDir, PathBuf) {
+  let tempdir = TempDir::new().unwrap();
+
+  let index_path = tempdir.path().join("index.redb");
+
+  CommandBuilder::new(format!(
+    "--index {} {args} index update",
+    index_path.display()
+  ))
+  .core(core)
+  .run_and_extract_stdout();
+
+  (tempdir, index_path)
+}
+
+fn replayed_events(stdout: &str) -> Vec<Event> {
+  stdout
+    .lines()
+    .map(|line| serde_json::from_str::<EventMessage>(line).unwrap().event)
+    .collect()
+}
+
+#[test]
+fn events_replays_block_range() {
+  let core = mockcore::spawn();
+  core.mine_blocks(3);
+
+  let (_tempdir, index_path) = updated_index(&core, "");
+
+  let events = replayed_events(
+    &CommandBuilder::new(format!(
+      "--index {} index events --from-height 1 --to-height 2",
+      index_path.display()
+    ))
+    .core(&core)
+    .stdout_regex(".*")
+    .run_and_extract_stdout(),
+  );
+
+  assert_eq!(
+    events
+      .iter()
+      .map(|event| match event {
+        Event::BlockStarted { block_height, .. } => ("started", *block_height),
+        Event::BlockCommitted { block_height, .. } => ("committed", *block_height),
+        _ => panic!("unexpected event {event:?}"),
+      })
+      .collect::<Vec<(&str, u32)>>(),
+    [
+      ("started", 1),
+      ("committed", 1),
+      ("started", 2),
+      ("committed", 2)
+    ],
+  );
+}
+
+#[test]
+fn events_replays_inscriptions() {
+  let core = mockcore::spawn();
+  let ord = TestServer::spawn(&core);
+
+  create_wallet(&core, &ord);
+
+  let (inscription_id, _reveal) = inscribe(&core, &ord);
+
+  let height = core.height();
+
+  let (_tempdir, index_path) = updated_index(&core, "--index-transfers");
+
+  let events = replayed_events(
+    &CommandBuilder::new(format!(
+      "--index {} --index-transfers index events --from-height {height} --to-height {height}",
+      index_path.display()
+    ))
+    .core(&core)
+    .stdout_regex(".*")
+    .run_and_extract_stdout(),
+  );
+
+  assert!(matches!(
+    events.as_slice(),
+    [
+      Event::BlockStarted { .. },
+      Event::InscriptionCreated {
+        inscription_id: id,
+        ..
+      },
+      Event::BlockCommitted { .. },
+    ] if *id == inscription_id
+  ));
+}
+
+#[test]
+fn events_can_be_sent_to_event_file() {
+  let core = mockcore::spawn();
+  core.mine_blocks(1);
+
+  let (_tempdir, index_path) = updated_index(&core, "");
+
+  let messages = CommandBuilder::new(format!(
+    "--index {} --event-sink file --event-file events.ndjson index events --from-height 1 --publish",
+    index_path.display()
+  ))
+  .core(&core)
+  .run_and_extract_file("events.ndjson")
+  .lines()
+  .map(|line| serde_json::from_str(line).unwrap())
+  .collect::<Vec<EventMessage>>();
+
+  assert_eq!(
+    messages
+      .iter()
+      .map(|message| (message.event_id, message.schema_version))
+      .collect::<Vec<(u64, u32)>>(),
+    [
+      (0, EventMessage::SCHEMA_VERSION),
+      (1, EventMessage::SCHEMA_VERSION)
+    ],
+  );
+}
+
+#[test]
+fn schema_prints_event_schemas() {
+  assert_eq!(
+    CommandBuilder::new("index schema")
+      .stdout_regex(".*")
+      .run_and_extract_stdout(),
+    fs::read_to_string("schema/event.schema.json").unwrap(),
+  );
+
+  assert_eq!(
+    CommandBuilder::new("index schema --protobuf")
+      .stdout_regex(".*")
+      .run_and_extract_stdout(),
+    fs::read_to_string("schema/event.proto").unwrap(),
+  );
+}
+
+#[test]
+fn events_requires_ordered_range() {
+  let core = mockcore::spawn();
+  core.mine_blocks(3);
+
+  let (_tempdir, index_path) = updated_index(&core, "");
+
+  CommandBuilder::new(format!(
+    "--index {} index events --from-height 2 --to-height 1",
+    index_path.display()
+  ))
+  .core(&core)
+  .expected_stderr("error: `--from-height` must not be greater than `--to-height`\n")
+  .expected_exit_code(1)
+  .run_and_extract_stdout();
+}
+
+#[test]
+fn events_requires_blocks_in_range() {
+  let core = mockcore::spawn();
+  core.mine_blocks(1);
+
+  let (_tempdir, index_path) = updated_index(&core, "");
+
+  CommandBuilder::new(format!(
+    "--index {} index events --from-height 0 --to-height 5",
+    index_path.display()
+  ))
+  .core(&core)
+  .expected_stderr("error: cannot replay events up to height 5, index is at height 1\n")
+  .expected_exit_code(1)
+  .run_and_extract_stdout();
+}
+
+#[test]
+fn snapshot_create_and_restore() {
+  let core = mockcore::spawn();
+  core.mine_blocks(3);
+
+  let tempdir = TempDir::new().unwrap();
+
+  let snapshot = tempdir.path().join("snapshot");
+
+  let created = CommandBuilder::new(format!(
+    "--index {} index snapshot create {}",
+    tempdir.path().join("index.redb").display(),
+    snapshot.display(),
+  ))
+  .core(&core)
+  .run_and_deserialize_output::<ord::index::snapshot::Manifest>();
+
+  assert_eq!(created.height, 3);
+  assert_eq!(created.block_hash, core.state().hashes[3]);
+  assert_eq!(created.chain, Chain::Mainnet);
+
+  let restored_index = tempdir.path().join("restored.redb");
+
+  let restored = CommandBuilder::new(format!(
+    "--index {} index snapshot restore {}",
+    restored_index.display(),
+    snapshot.display(),
+  ))
+  .core(&core)
+  .run_and_deserialize_output::<ord::index::snapshot::Manifest>();
+
+  assert_eq!(restored, created);
+
+  assert_eq!(
+    fs::read(&restored_index).unwrap(),
+    fs::read(tempdir.path().join("index.redb")).unwrap(),
+  );
+
+  CommandBuilder::new(format!(
+    "--index {} index snapshot restore {}",
+    restored_index.display(),
+    snapshot.display(),
+  ))
+  .core(&core)
+  .stderr_regex("error: index `.*restored.redb` already exists, pass `--force` to overwrite it\n")
+  .expected_exit_code(1)
+  .run_and_extract_stdout();
+}
+
+#[test]
+fn verify_reports_no_discrepancies() {
+  let core = mockcore::spawn();
+  core.mine_blocks(3);
+
+  let tempdir = TempDir::new().unwrap();
+
+  let index_path = tempdir.path().join("index.redb");
+
+  CommandBuilder::new(format!(
+    "--index {} --index-sats index update",
+    index_path.display()
+  ))
+  .core(&core)
+  .run_and_extract_stdout();
+
+  let report = CommandBuilder::new(format!(
+    "--index {} --index-sats index verify",
+    index_path.display()
+  ))
+  .core(&core)
+  .run_and_deserialize_output::<ord::index::verify::Report>();
+
+  assert_eq!(report.height, Some(3));
+  assert!(report.discrepancies.is_empty());
+  assert_eq!(report.skipped, [ord::index::verify::Check::RuneSupply]);
+}
+
+#[test]
+fn verify_fails_with_discrepancies() {
+  let core = mockcore::spawn();
+  core.mine_blocks(1);
+
+  let (_tempdir, index_path) = updated_index(&core, "--index-sats");
+
+  {
+    let database = redb::Database::open(&index_path).unwrap();
+
+    let wtx = database.begin_write().unwrap();
+
+    let mut range = Vec::new();
+    range.extend_from_slice(&[0; 6]);
+    range.extend_from_slice(&(COIN_VALUE << 3).to_le_bytes()[..5]);
+
+    wtx
+      .open_table(redb::TableDefinition::<&[u8; 36], &[u8]>::new(
+        "OUTPOINT_TO_SAT_RANGES",
+      ))
+      .unwrap()
+      .insert(&[0xff; 36], range.as_slice())
+      .unwrap();
+
+    wtx.commit().unwrap();
+  }
+
+  let report = CommandBuilder::new(format!(
+    "--index {} --index-sats index verify",
+    index_path.display()
+  ))
+  .core(&core)
+  .expected_stderr("error: index has 1 discrepancy\n")
+  .expected_exit_code(1)
+  .run_and_deserialize_output::<ord::index::verify::Report>();
+
+  assert!(matches!(
+    report.discrepancies.as_slice(),
+    [ord::index::verify::Discrepancy::OverlappingSatRanges { .. }]
+  ));
+}
@@ -555,6 +555,7 @@ fn get_runes() {
         timestamp: 10,
         turbo: false,
       },
+      holders: 1,
       id: RuneId { block: 10, tx: 1 },
       mintable: false,
       parent: Some(InscriptionId {
@@ -754,3 +755,174 @@ fn get_decode_tx() {
     }
   );
 }
+
+#[test]
+fn address_inscriptions_and_runes() {
+  let core = mockcore::builder().network(Network::Regtest).build();
+
+  let ord = TestServer::spawn_with_server_args(
+    &core,
+    &["--index-runes", "--index-addresses", "--regtest"],
+    &[],
+  );
+
+  create_wallet(&core, &ord);
+
+  core.mine_blocks(3);
+
+  let etched = etch(&core, &ord, Rune(RUNE));
+
+  let inscription = etched.output.inscriptions[0].clone();
+
+  let address = inscription.destination.assume_checked();
+
+  pretty_assert_eq!(
+    serde_json::from_str::<api::AddressInscriptions>(
+      &ord
+        .json_request(format!("/address/{address}/inscriptions"))
+        .text()
+        .unwrap()
+    )
+    .unwrap(),
+    api::AddressInscriptions {
+      ids: vec![inscription.id],
+      more: false,
+      page: 0,
+    }
+  );
+
+  let address = etched
+    .output
+    .rune
+    .unwrap()
+    .destination
+    .unwrap()
+    .assume_checked();
+
+  pretty_assert_eq!(
+    serde_json::from_str::<api::AddressRunes>(
+      &ord
+        .json_request(format!("/address/{address}/runes"))
+        .text()
+        .unwrap()
+    )
+    .unwrap(),
+    api::AddressRunes {
+      balances: vec![(
+        SpacedRune {
+          rune: Rune(RUNE),
+          spacers: 0
+        },
+        Pile {
+          amount: 1000,
+          divisibility: 0,
+          symbol: Some('¢'),
+        }
+      )],
+      more: false,
+      page: 0,
+    }
+  );
+
+  pretty_assert_eq!(
+    serde_json::from_str::<api::AddressRunes>(
+      &ord
+        .json_request(format!("/address/{address}/runes/1"))
+        .text()
+        .unwrap()
+    )
+    .unwrap(),
+    api::AddressRunes {
+      balances: Vec::new(),
+      more: false,
+      page: 1,
+    }
+  );
+}
+
+#[test]
+fn address_inscriptions_require_address_index() {
+  let core = mockcore::builder().network(Network::Regtest).build();
+
+  let ord = TestServer::spawn_with_server_args(&core, &["--regtest"], &[]);
+
+  let response =
+    ord.json_request("/address/bcrt1qs758ursh4q9z627kt3pp5yysm78ddny6txaqgw/inscriptions");
+
+  assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+#[test]
+fn address_transactions() {
+  let core = mockcore::builder().network(Network::Regtest).build();
+
+  let ord = TestServer::spawn_with_server_args(
+    &core,
+    &["--index-runes", "--index-address-history", "--regtest"],
+    &[],
+  );
+
+  create_wallet(&core, &ord);
+
+  core.mine_blocks(3);
+
+  let etched = etch(&core, &ord, Rune(RUNE));
+
+  let address = etched
+    .output
+    .rune
+    .unwrap()
+    .destination
+    .unwrap()
+    .assume_checked();
+
+  let transactions = serde_json::from_str::<api::AddressTransactions>(
+    &ord
+      .json_request(format!("/address/{address}/transactions"))
+      .text()
+      .unwrap(),
+  )
+  .unwrap();
+
+  assert!(!transactions.more);
+  assert_eq!(transactions.page, 0);
+  assert_eq!(transactions.transactions.len(), 1);
+
+  let transaction = &transactions.transactions[0];
+
+  assert_eq!(transaction.txid, etched.output.reveal);
+  assert_eq!(transaction.direction, api::Direction::Incoming);
+  assert_eq!(transaction.sent, 0);
+
+  pretty_assert_eq!(
+    transaction.runes,
+    vec![api::AddressRuneDelta {
+      received: Pile {
+        amount: 1000,
+        divisibility: 0,
+        symbol: Some('¢'),
+      },
+      rune: SpacedRune {
+        rune: Rune(RUNE),
+        spacers: 0
+      },
+      sent: Pile {
+        amount: 0,
+        divisibility: 0,
+        symbol: Some('¢'),
+      },
+    }]
+  );
+}
+
+#[test]
+fn address_transactions_require_address_history_index() {
+  let core = mockcore::builder().network(Network::Regtest).build();
+
+  let ord = TestServer::spawn_with_server_args(&core, &["--index-addresses", "--regtest"], &[]);
+
+  let response =
+    ord.json_request("/address/bcrt1qs758ursh4q9z627kt3pp5yysm78ddny6txaqgw/transactions");
+
+  assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
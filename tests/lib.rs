@@ -12,8 +12,14 @@ use {
   executable_path::executable_path,
   mockcore::TransactionTemplate,
   ord::{
-    api, chain::Chain, decimal::Decimal, outgoing::Outgoing, subcommand::runes::RuneInfo,
-    wallet::batch, InscriptionId, RuneEntry,
+    api,
+    chain::Chain,
+    decimal::Decimal,
+    index::event::{Event, EventMessage},
+    outgoing::Outgoing,
+    subcommand::runes::RuneInfo,
+    wallet::batch,
+    InscriptionId, RuneEntry,
   },
   ordinals::{
     Artifact, Charm, Edict, Pile, Rarity, Rune, RuneId, Runestone, Sat, SatPoint, SpacedRune,
@@ -187,7 +193,7 @@ fn batch(core: &mockcore::Handle, ord: &TestServer, batchfile: batch::File) -> E
       .ord(ord);
 
   for inscription in &batchfile.inscriptions {
-    builder = builder.write(&inscription.file.clone().unwrap(), "inscription");
+    builder = builder.write(inscription.file.clone().unwrap(), "inscription");
   }
 
   let mut spawn = builder.spawn();
@@ -350,6 +356,8 @@ fn batch(core: &mockcore::Handle, ord: &TestServer, batchfile: batch::File) -> E
   <dd>.*</dd>
   <dt>burned</dt>
   <dd>0 {symbol}</dd>
+  <dt>holders</dt>
+  <dd>\d+</dd>
   <dt>divisibility</dt>
   <dd>{divisibility}</dd>
   <dt>symbol</dt>
@@ -11,28 +11,53 @@ fn default() {
   "bitcoin_rpc_password": null,
   "bitcoin_rpc_url": "127.0.0.1:8332",
   "bitcoin_rpc_username": null,
+  "block_source": "rpc",
+  "block_source_fixture": null,
   "chain": "mainnet",
+  "chain_tip_distance": 21,
   "commit_interval": 5000,
   "config": null,
   "config_dir": null,
   "cookie_file": ".*\.cookie",
   "data_dir": ".*",
+  "event_body_limit": null,
+  "event_encoding": "json",
+  "event_file": null,
+  "event_sink": "kafka",
+  "event_webhook_url": null,
+  "fast_sync": false,
   "first_inscription_height": 767430,
   "height_limit": null,
   "hidden": \[\],
   "http_port": null,
   "index": ".*index\.redb",
+  "index_address_history": false,
   "index_addresses": false,
   "index_cache_size": \d+,
+  "index_content": false,
+  "index_full_text": false,
+  "index_metaprotocols": \[\],
   "index_runes": false,
   "index_sats": false,
+  "index_search": false,
   "index_spent_sats": false,
   "index_transactions": false,
+  "index_transfers": false,
   "integration_test": false,
+  "kafka_acks": "all",
+  "kafka_brokers": null,
+  "kafka_key": "id",
+  "kafka_topic": "ord",
+  "max_savepoints": 2,
+  "mempool_events": false,
   "no_index_inscriptions": false,
+  "reorg_rollback": null,
+  "rich_events": false,
+  "savepoint_interval": 10,
   "server_password": null,
   "server_url": null,
-  "server_username": null
+  "server_username": null,
+  "undo_log_depth": 100
 \}
 "#,
     )
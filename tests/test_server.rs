@@ -2,7 +2,7 @@ use {
   super::*,
   axum_server::Handle,
   bitcoincore_rpc::{Auth, Client, RpcApi},
-  ord::{parse_ord_server_args, Index},
+  ord::parse_ord_server_args,
   reqwest::blocking::Response,
 };
 
@@ -50,7 +50,7 @@ impl TestServer {
       ord_server_args.join(" "),
     ));
 
-    let index = Arc::new(Index::open(&settings).unwrap());
+    let index = Arc::new(server.open_index(&settings).unwrap());
     let ord_server_handle = Handle::new();
 
     {